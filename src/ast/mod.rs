@@ -4,5 +4,5 @@ pub mod environment;
 
 // Re-export pour faciliter l'accès : use crate::ast::{Value, Instruction, ...}
 pub use value::{Value, InstanceData};
-pub use nodes::{Expression, Instruction, ClassDefinition, Statement};
-pub use environment::{Environment, NativeFn};
+pub use nodes::{Expression, Instruction, ClassDefinition, EnumDefinition, EnumVariant, Statement, MatchArm, Pattern};
+pub use environment::NativeFn;
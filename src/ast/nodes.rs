@@ -17,14 +17,14 @@ pub struct ClassProperty {
     pub name: String,
     pub visibility: Visibility,
     pub is_static: bool,
-    pub getter: Option<(Vec<(String, Option<String>)>, Vec<Statement>)>, 
-    pub setter: Option<(Vec<(String, Option<String>)>, Vec<Statement>)>,
+    pub getter: Option<(Params, Vec<Statement>)>, 
+    pub setter: Option<(Params, Vec<Statement>)>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct InterfaceMethod {
     pub name: String,
-    pub params: Vec<(String, Option<String>)>
+    pub params: Params
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -39,7 +39,8 @@ pub struct ClassDefinition {
     pub parent: Option<String>,
     pub interfaces: Vec<String>,
 
-    pub methods: HashMap<String, (Vec<(String, Option<String>)>, Vec<Statement>, bool, bool)>,
+    // (paramètres, corps, static, final, async)
+    pub methods: HashMap<String, (Params, Vec<Statement>, bool, bool, bool)>,
     pub fields: Vec<ClassField>,
     pub properties: Vec<ClassProperty>,
 
@@ -47,14 +48,48 @@ pub struct ClassDefinition {
     pub is_final: bool
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub name: String,
+    pub type_annot: Option<String>,
+    // Valeur utilisée quand l'argument est absent (évaluée à chaque appel)
+    pub default: Option<Expression>,
+    // ...rest : reçoit les arguments positionnels en trop dans une liste
+    pub is_rest: bool
+}
+
+impl Param {
+    pub fn new(name: String, type_annot: Option<String>) -> Self {
+        Param { name, type_annot, default: None, is_rest: false }
+    }
+}
+
+pub type Params = Vec<Param>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumVariant {
+    pub name: String,
+    // Noms des données portées, ex: Rect(w, h) ; vide pour une variante simple
+    pub fields: Vec<String>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumDefinition {
+    pub name: String,
+    pub variants: Vec<EnumVariant>,
+    // Méthodes appelables sur chaque variante (`this` = la variante)
+    pub methods: HashMap<String, (Params, Vec<Statement>)>
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     Literal(Value),
     Variable(String),
     Function {
-        params: Vec<(String, Option<String>)>,
+        params: Params,
         ret_type: Option<String>,
-        body: Vec<Statement>
+        body: Vec<Statement>,
+        is_async: bool
     },
 
     // Arithmetic
@@ -92,7 +127,43 @@ pub enum Expression {
     List(Vec<Expression>),
     Dict(Vec<(String, Expression)>),
     SuperCall(String, Vec<Expression>),
-    Range(Box<Expression>, Box<Expression>)
+    Range(Box<Expression>, Box<Expression>),
+    Index(Box<Expression>, Box<Expression>),
+    // x[start:end] : une borne absente vaut Literal(Null)
+    Slice(Box<Expression>, Box<Expression>, Box<Expression>),
+    Match(Box<Expression>, Vec<MatchArm>),
+    // Arguments d'appel uniquement : f(timeout: 5) et f(...args)
+    NamedArg(String, Box<Expression>),
+    Spread(Box<Expression>),
+    // await tache : suspend la tâche courante jusqu'au résultat
+    Await(Box<Expression>)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Expression
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Pattern {
+    // _
+    Wildcard,
+    // x : capture la valeur
+    Binding(String),
+    // 42, "abc", true, null
+    Literal(Value),
+    // 1..10 (borne de fin exclue)
+    Range(Value, Value),
+    // [a, b, ...rest] : rest vaut Some("_") pour un `...` anonyme
+    List(Vec<Pattern>, Option<String>),
+    // {name: n} : fonctionne aussi sur les champs d'une instance
+    Dict(Vec<(String, Pattern)>),
+    // Point(x, y) : arguments dans l'ordre des paramètres de `init`
+    Class(Expression, Vec<Pattern>),
+    // Color.Red, Config.MAX : comparé avec ==
+    Value(Expression)
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -109,18 +180,27 @@ pub enum Instruction {
         body: Vec<Statement>
     },
     Return(Expression),
+    // Suspend le générateur en cours et produit une valeur
+    Yield(Expression),
     ExpressionStatement(Expression),
     Function {
         name: String,
-        params: Vec<(String, Option<String>)>,
+        params: Params,
         ret_type: Option<String>,
-        body: Vec<Statement>
+        body: Vec<Statement>,
+        is_async: bool
     },
     Input(String, Expression),
     Class(ClassDefinition),
     SetAttr(Box<Expression>, String, Expression),
-    Enum(String, Vec<String>),
-    Import(String),
+    SetIndex(Box<Expression>, Expression, Expression),
+    Enum(EnumDefinition),
+    // import "x" ; import "x" as X
+    Import(String, Option<String>),
+    // from "x" import a, b
+    ImportFrom(String, Vec<String>),
+    // Déclaration visible des fichiers qui importent le module
+    Export(Box<Statement>),
     TryCatch {
         try_body: Vec<Statement>,
         error_var: String,
@@ -4,15 +4,51 @@ use std::rc::Rc;
 use std::cell::RefCell;
 use serde::{Deserialize, Serialize};
 
-use crate::ast::Environment;
 use crate::chunk::Chunk;
+use crate::ast::environment::NativeFn;
+use crate::vm::builtins::BuiltinType;
+use crate::vm::scheduler::TaskData;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct FunctionData {
+    // Nom affiché dans les erreurs d'arité ("Point.move" pour une méthode, vide pour une lambda anonyme)
+    pub name: String,
     pub params: Vec<(String, Option<String>)>,
+    // Les `required` premiers paramètres sont obligatoires, les suivants ont une valeur par défaut
+    pub required: usize,
+    // Le dernier paramètre est un `...rest`
+    pub variadic: bool,
     pub ret_type: Option<String>,
-    pub chunk: Chunk,
-    pub env: Option<Rc<RefCell<Environment>>>, // SharedEnv
+    // Le corps contient un `yield` : l'appel renvoie un générateur au lieu d'exécuter la fonction
+    pub is_generator: bool,
+    // Fonction `async` : l'appel renvoie une tâche, exécutée par la boucle d'événements
+    pub is_async: bool,
+    // Partagé entre toutes les closures créées à partir de la même fonction
+    pub chunk: Rc<Chunk>,
+    // Variables des fonctions englobantes utilisées par le corps (résolues à la compilation)
+    pub captures: Vec<Capture>,
+    // Upvalues de la closure, dans l'ordre de `captures` (vide tant que MakeClosure n'est pas passé)
+    pub upvalues: Vec<Rc<RefCell<Upvalue>>>,
+}
+
+/// Origine d'une variable capturée, vue depuis la fonction qui crée la closure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capture {
+    // Slot local de la frame englobante
+    Local(usize),
+    // Variable liée par un `match` en cours : (niveau, slot)
+    Match(usize, usize),
+    // Upvalue de la closure englobante
+    Upvalue(usize),
+}
+
+/// Variable capturée par une closure. Tant que sa frame est vivante, elle désigne
+/// un slot de la pile (toutes les closures voient et modifient la même variable) ;
+/// quand la frame se termine, la valeur est recopiée dans l'upvalue.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Upvalue {
+    Open(usize),
+    Closed(Value),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -57,33 +93,303 @@ pub struct ClassData {
     pub interfaces_names: Vec<String>,
 
     pub visibilities: HashMap<String, Visibility>,
+
+    // Forme des instances qui n'ont encore aucun champ (racine de l'arbre des formes de la classe)
+    pub shape: Rc<Shape>,
+}
+
+// Au-delà, une instance garde sa propre forme, agrandie sur place (champs ajoutés dynamiquement)
+const SHAPE_SHARED_MAX: usize = 64;
+
+/// Disposition des champs d'une instance : nom -> position dans `InstanceData.slots`.
+/// Les instances qui reçoivent les mêmes champs dans le même ordre partagent la même forme,
+/// ce qui permet aux caches de la VM de retenir une position plutôt que de hacher le nom.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Shape {
+    names: Vec<String>,
+    slots: HashMap<String, usize>,
+    // Forme obtenue en ajoutant un champ
+    transitions: RefCell<HashMap<String, Rc<Shape>>>,
+}
+
+impl Shape {
+    pub fn root() -> Rc<Shape> {
+        Rc::new(Shape::default())
+    }
+
+    pub fn slot(&self, name: &str) -> Option<usize> {
+        self.slots.get(name).copied()
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Forme partagée obtenue en ajoutant `name`, si une instance l'a déjà prise.
+    pub fn transition(&self, name: &str) -> Option<Rc<Shape>> {
+        self.transitions.borrow().get(name).cloned()
+    }
+
+    fn push(&mut self, name: &str) {
+        self.slots.insert(name.to_string(), self.names.len());
+        self.names.push(name.to_string());
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct InstanceData {
-    pub class: Rc<ClassData>, 
-    pub fields: HashMap<String, Value>,
+    pub class: Rc<ClassData>,
+    pub shape: Rc<Shape>,
+    // Valeurs des champs, dans l'ordre de `shape`
+    pub slots: Vec<Value>,
+}
+
+impl InstanceData {
+    pub fn new(class: Rc<ClassData>) -> Self {
+        let shape = class.shape.clone();
+        InstanceData { class, shape, slots: Vec::new() }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.shape.slot(name).and_then(|slot| self.slots.get(slot))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Écrit un champ (en l'ajoutant si besoin) et renvoie sa position.
+    pub fn set(&mut self, name: &str, value: Value) -> usize {
+        let slot = match self.shape.slot(name) {
+            Some(slot) => slot,
+            None => {
+                self.add_field(name);
+                self.shape.len() - 1
+            }
+        };
+        if slot >= self.slots.len() {
+            self.slots.resize(slot + 1, Value::Null);
+        }
+        self.slots[slot] = value;
+        slot
+    }
+
+    fn add_field(&mut self, name: &str) {
+        if self.shape.len() < SHAPE_SHARED_MAX {
+            let next = self.shape.transitions.borrow_mut()
+                .entry(name.to_string())
+                .or_insert_with(|| {
+                    let mut next = Shape { names: self.shape.names.clone(), slots: self.shape.slots.clone(), ..Shape::default() };
+                    next.push(name);
+                    Rc::new(next)
+                })
+                .clone();
+            self.shape = next;
+        } else {
+            // Copiée une seule fois (si partagée), puis agrandie sur place : les positions
+            // existantes ne bougent pas, les caches qui pointent sur cette forme restent justes
+            let shape = Rc::make_mut(&mut self.shape);
+            shape.transitions.get_mut().clear();
+            shape.push(name);
+        }
+    }
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumData {
+    pub name: String,
+    // (nom, champs) dans l'ordre de déclaration : la position est l'ordinal de la variante
+    pub variants: Vec<(String, Vec<String>)>,
+    pub methods: HashMap<String, Value>,
+}
+
+#[derive(Debug, Clone)]
+pub struct VariantData {
+    pub enum_data: Rc<EnumData>,
+    pub index: usize,
+    // Vide pour une variante simple, ou pour un constructeur (`Shape.Circle` pas encore appelé)
+    pub values: Vec<Value>,
+}
+
+impl VariantData {
+    pub fn name(&self) -> &str {
+        &self.enum_data.variants[self.index].0
+    }
+
+    pub fn fields(&self) -> &[String] {
+        &self.enum_data.variants[self.index].1
+    }
+
+    pub fn is_constructor(&self) -> bool {
+        self.values.is_empty() && !self.fields().is_empty()
+    }
+
+    /// Valeur d'une donnée portée par son nom (`c.r` pour `Circle(r)`).
+    pub fn get(&self, field: &str) -> Option<&Value> {
+        self.fields().iter().position(|f| f == field).and_then(|i| self.values.get(i))
+    }
+}
+
+// Égalité structurelle : même enum (par identité), même variante, mêmes données
+impl PartialEq for VariantData {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.enum_data, &other.enum_data) && self.index == other.index && self.values == other.values
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneratorState {
+    Suspended,
+    Running,
+    Done,
+}
+
+/// Arguments nommés qu'une fonction variadique n'a liés à aucun paramètre : ils terminent sa liste
+/// `...rest`, et `f(...rest)` les repasse avec leurs noms tant que la liste n'a pas changé de taille.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamedRest {
+    pub list: Rc<RefCell<Vec<Value>>>,
+    pub len: usize,
+    pub names: Vec<String>,
+}
+
+/// Frame suspendue d'une fonction qui contient `yield`, reprise à chaque `next()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratorData {
+    pub function: Rc<FunctionData>,
+    pub ip: usize,
+    // Paramètres, locales et temporaires de la frame (le slot 0 est le premier paramètre)
+    pub stack: Vec<Value>,
+    pub class_context: Option<Rc<ClassData>>,
+    // Positions relatives au début de `stack`
+    pub match_bases: Vec<usize>,
+    // try en cours dans le générateur : (adresse du catch, hauteur relative de la pile)
+    pub handlers: Vec<(usize, usize)>,
+    pub missing_args: u64,
+    pub named_rest: Option<NamedRest>,
+    // Upvalues qui désignaient la frame, fermées pendant la suspension : (slot relatif, upvalue)
+    pub upvalues: Vec<(usize, Rc<RefCell<Upvalue>>)>,
+    pub state: GeneratorState,
+}
+
+/// Itérateur paresseux : parcours d'une séquence, objet utilisateur ou adaptateur branché sur une autre source.
+/// Les sources des adaptateurs sont ouvertes par la VM au premier `next()`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IteratorData {
+    // Itérable quelconque (`iter(x)`), pas encore ouvert
+    Source(Value),
+    // Liste, chaîne, octets, range ou objet avec len()/at() : élément à l'index courant
+    Sequence(Value, usize),
+    // Objet utilisateur dont next() renvoie null à la fin
+    Object(Value),
+    Map(Value, Value),
+    Filter(Value, Value),
+    Take(Value, usize),
+    Zip(Vec<Value>),
+    Enumerate(Value, i64),
+}
+
+/// Méthode native d'un type de base liée à sa valeur : `var push = list.push`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundMethod {
+    pub receiver: Value,
+    pub ty: BuiltinType,
+    pub id: usize, // Index dans la table de méthodes du type
+}
+
+impl BoundMethod {
+    pub fn name(&self) -> &'static str {
+        self.ty.method(self.id).name
+    }
+}
+
+/// Fonction native résolue une seule fois : l'appel passe par le pointeur, sans toucher au registre.
+/// `min_args` est vérifié par la VM avant l'appel ; la native reste seule juge du reste de ses arguments
+/// (nombre maximal, types), et les natives des plugins, qui n'en déclarent pas, de tous.
+#[derive(Debug, Clone)]
+pub struct NativeData {
+    pub name: Rc<str>,
+    pub func: NativeFn,
+    pub min_args: Option<usize>,
+}
+
+// Deux natives sont égales si elles portent le même nom (comparer des pointeurs de fonction n'est pas fiable)
+impl PartialEq for NativeData {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+// Bornes d'un intervalle `start..end` (fin exclue), parcouru de `step` en `step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeData {
+    pub start: i64,
+    pub end: i64,
+    pub step: i64,
+}
+
+// Les chaînes sont immuables : Rc<str> les partage au lieu de les recopier à chaque lecture
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
     Integer(i64),
     Float(f64),
-    String(String),
+    String(Rc<str>),
     Boolean(bool),
     List(Rc<RefCell<Vec<Value>>>),
     Dict(Rc<RefCell<HashMap<String, Value>>>),
-    Enum(Rc<HashMap<String, Value>>),
+    Enum(Rc<EnumData>),
+    Variant(Rc<VariantData>),
     Function(Rc<FunctionData>), 
     Class(Rc<ClassData>),
     Instance(Rc<RefCell<InstanceData>>),
     Interface(Rc<InterfaceData>),
-    Native(String),
-    Range(i64, i64, i64),
+    Native(Rc<NativeData>),
+    Range(Rc<RangeData>),
     Bytes(Rc<RefCell<Vec<u8>>>),
+    Generator(Rc<RefCell<GeneratorData>>),
+    Iterator(Rc<RefCell<IteratorData>>),
+    Task(Rc<RefCell<TaskData>>),
+    BoundMethod(Rc<BoundMethod>),
     Null
 }
 
+// Une Value tient en 24 octets (étiquette + pointeur large) : tout contenu plus gros passe par un Rc
+const _: () = assert!(std::mem::size_of::<Value>() == 24);
+
+/// Profondeur maximale des valeurs imbriquées affichées ou converties en JSON.
+pub const MAX_NESTING: usize = 512;
+
+thread_local! {
+    // Conteneurs en cours d'affichage, du plus externe au plus interne
+    static DISPLAYING: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
+
+// Affiche le contenu d'une liste, d'un dictionnaire ou d'une variante avec `body`,
+// ou `cut` si le conteneur est déjà en cours d'affichage (cycle) ou trop profond
+fn display_nested(f: &mut fmt::Formatter<'_>, ptr: *const (), cut: &str, body: impl FnOnce(&mut fmt::Formatter<'_>) -> fmt::Result) -> fmt::Result {
+    let entered = DISPLAYING.with_borrow_mut(|open| {
+        if open.len() >= MAX_NESTING || open.contains(&ptr) {
+            return false;
+        }
+        open.push(ptr);
+        true
+    });
+    if !entered {
+        return write!(f, "{}", cut);
+    }
+    let result = body(f);
+    DISPLAYING.with_borrow_mut(|open| open.pop());
+    result
+}
+
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -92,27 +398,45 @@ impl fmt::Display for Value {
             Value::String(s) => write!(f, "{}", s),
             Value::Boolean(b) => write!(f, "{}", b),
             Value::Null => write!(f, "null"),
-            Value::List(l) => {
+            Value::List(l) => display_nested(f, Rc::as_ptr(l) as *const (), "[...]", |f| {
                 write!(f, "[")?;
                 for (i, v) in l.borrow().iter().enumerate() {
                     if i > 0 { write!(f, ", ")?; }
                     write!(f, "{}", v)?;
                 }
                 write!(f, "]")
-            },
-            Value::Dict(d) => {
+            }),
+            Value::Dict(d) => display_nested(f, Rc::as_ptr(d) as *const (), "{...}", |f| {
                 write!(f, "{{")?;
                 for (i, (k, v)) in d.borrow().iter().enumerate() {
                     if i > 0 { write!(f, ", ")?; }
                     write!(f, "{}: {}", k, v)?;
                 }
                 write!(f, "}}")
+            }),
+            Value::Enum(e) => write!(f, "<Enum {}>", e.name),
+            Value::Variant(v) if v.is_constructor() => {
+                write!(f, "<Variant {}.{}({})>", v.enum_data.name, v.name(), v.fields().join(", "))
             },
-            Value::Enum(e) => {
-                write!(f, "<Enum ({} variants)>", e.len())
+            Value::Variant(v) => {
+                write!(f, "{}.{}", v.enum_data.name, v.name())?;
+                if v.values.is_empty() {
+                    return Ok(());
+                }
+                display_nested(f, Rc::as_ptr(v) as *const (), "(...)", |f| {
+                    write!(f, "(")?;
+                    for (i, val) in v.values.iter().enumerate() {
+                        if i > 0 { write!(f, ", ")?; }
+                        write!(f, "{}", val)?;
+                    }
+                    write!(f, ")")
+                })
             },
             Value::Function(rc_fn) => {
-                 let p_str: Vec<String> = rc_fn.params.iter().map(|p| p.0.clone()).collect();
+                 let mut p_str: Vec<String> = rc_fn.params.iter().map(|p| p.0.clone()).collect();
+                 if let (true, Some(rest)) = (rc_fn.variadic, p_str.last_mut()) {
+                     rest.insert_str(0, "...");
+                 }
                  write!(f, "<Function({})>", p_str.join(", "))
             },
             Value::Class { 0: rc_class } => write!(f, "<Class {}>", rc_class.name),
@@ -122,19 +446,72 @@ impl fmt::Display for Value {
                 write!(f, "<Instance of {}>", borrow.class.name)
             },
             Value::Interface(interface) => write!(f, "<Interface {}>", interface.name),
-            Value::Native(name) => write!(f, "<Native Fn {}>", name),
-            Value::Range(s, e, step) => write!(f, "{}..{} (step {})", s, e, step),
+            Value::Native(native) => write!(f, "<Native Fn {}>", native.name),
+            Value::BoundMethod(m) => write!(f, "<Method {}.{}>", m.receiver.type_name(), m.name()),
+            Value::Range(r) => write!(f, "{}..{} (step {})", r.start, r.end, r.step),
             Value::Bytes(b) => write!(f, "<Bytes size={}>", b.borrow().len()),
+            Value::Generator(g) => write!(f, "<Generator {}>", g.borrow().function.name),
+            Value::Iterator(_) => write!(f, "<Iterator>"),
+            Value::Task(t) => write!(f, "<Task {}>", t.borrow().name),
         }
     }
 }
 
 impl Value {
+    pub fn range(start: i64, end: i64, step: i64) -> Value {
+        Value::Range(Rc::new(RangeData { start, end, step }))
+    }
+
+    /// Nom du type tel que renvoyé par `typeof` ("int", "list"... ou le nom de la classe d'une instance).
+    pub fn type_name(&self) -> String {
+        match self {
+            Value::Integer(_) => "int".to_string(),
+            Value::Float(_) => "float".to_string(),
+            Value::String(_) => "string".to_string(),
+            Value::Boolean(_) => "bool".to_string(),
+            Value::Null => "null".to_string(),
+            Value::List(_) => "list".to_string(),
+            Value::Dict(_) => "dict".to_string(),
+            Value::Enum(_) => "enum".to_string(),
+            Value::Variant(v) => v.enum_data.name.clone(),
+            Value::Range(_) => "range".to_string(),
+            Value::Function(..) => "function".to_string(),
+            Value::Class { .. } => "class".to_string(),
+            Value::Interface(_) => "interface".to_string(),
+            Value::Bytes(_) => "bytes".to_string(),
+            Value::Generator(_) => "generator".to_string(),
+            Value::Iterator(_) => "iterator".to_string(),
+            Value::Task(_) => "task".to_string(),
+            Value::Instance(i) => i.borrow().class.name.clone(),
+            Value::Native(_) | Value::BoundMethod(_) => "function".to_string()
+        }
+    }
+
+    /// Égalité de l'opérateur `==` : une variante simple est aussi égale à son ordinal
+    /// (compatibilité avec les enums qui n'étaient que des entiers : `Status.Idle == 0`).
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Variant(v), Value::Integer(i)) | (Value::Integer(i), Value::Variant(v)) if v.fields().is_empty() => v.index as i64 == *i,
+            _ => self == other,
+        }
+    }
+
+    /// Opérande des opérateurs arithmétiques et de comparaison : une variante simple
+    /// vaut son ordinal (`Color.Red + 1`, `Status.Idle < Status.Running`).
+    pub fn numeric(self) -> Value {
+        match self {
+            Value::Variant(v) if v.fields().is_empty() => Value::Integer(v.index as i64),
+            other => other,
+        }
+    }
+
     pub fn as_int(&self) -> Result<i64, String> {
         match self {
             Value::Integer(i) => Ok(*i),
             Value::Float(f) => Ok(*f as i64),
             Value::String(s) => s.trim().parse::<i64>().map_err(|_| "Cannot parse string to int".into()),
+            // Ordinal d'une variante simple (Status.Running -> 1)
+            Value::Variant(v) if v.fields().is_empty() => Ok(v.index as i64),
             _ => Err(format!("Cannot convert {:?} to int", self))
         }
     }
@@ -149,7 +526,7 @@ impl Value {
 
     pub fn as_str(&self) -> Result<String, String> {
         match self {
-            Value::String(s) => Ok(s.clone()),
+            Value::String(s) => Ok(s.to_string()),
             _ => Err(format!("Expected string, got {:?}", self))
         }
     }
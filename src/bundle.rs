@@ -0,0 +1,346 @@
+//! Exécutables autonomes (`aegis build`) : une copie de l'exécutable aegis suivie d'un bundle.
+//!
+//! Le bundle contient le programme principal (format `.aegc`), chaque module qu'il importe,
+//! directement ou non (identité du module -> bytecode relogeable, voir bytecode::load_module),
+//! les imports résolus à la construction et éventuellement des plugins natifs. Il se termine par sa longueur (u64) et `AEGISBND` :
+//! au démarrage, aegis lit la fin de son propre exécutable pour savoir s'il porte un programme.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::ast::Value;
+use crate::bytecode;
+use crate::chunk::Chunk;
+use crate::opcode::OpCode;
+use crate::vm::compiler::GlobalNames;
+
+const TRAILER_MAGIC: &[u8; 8] = b"AEGISBND";
+const TRAILER_LEN: u64 = 16;
+
+pub struct Bundle {
+    pub program: Vec<u8>,
+    pub modules: HashMap<String, Vec<u8>>,
+    // (module qui importe, chemin écrit dans l'import) -> module importé ; "" désigne le programme principal
+    links: HashMap<(String, String), String>,
+    pub plugins: Vec<(String, Vec<u8>)>, // Nom du fichier de la bibliothèque, contenu
+}
+
+impl Bundle {
+    /// Compile tous les modules importés par `chunk`, le programme du fichier `file`, en les cherchant
+    /// comme le ferait la VM (voir crate::imports), et enregistre le programme avec la table des globales
+    /// complétée par ces modules.
+    pub fn new(file: &Path, chunk: &Chunk, globals: GlobalNames) -> Result<Self, String> {
+        // Modules dont les imports restent à résoudre : identité, dossier, chemins importés
+        let dir = file.parent().map(|dir| if dir.as_os_str().is_empty() { Path::new(".") } else { dir });
+        let root = dir.map(crate::imports::project_root);
+        let mut pending = vec![(String::new(), dir.map(Path::to_path_buf), imports_of(chunk))];
+        let mut chunks = BTreeMap::new();
+        let mut links = HashMap::new();
+
+        while let Some((importer, dir, paths)) = pending.pop() {
+            for path in paths {
+                let module = crate::imports::resolve(&path, dir.as_deref(), root.as_deref())?;
+                links.insert((importer.clone(), path), module.id.clone());
+                if chunks.contains_key(&module.id) {
+                    continue;
+                }
+                let compiled = crate::vm::compile_module(&module, globals.clone())?;
+                pending.push((module.id.clone(), module.dir, imports_of(&compiled)));
+                chunks.insert(module.id, compiled);
+            }
+        }
+
+        let globals = globals.borrow();
+        let mut modules = HashMap::new();
+        for (id, module) in chunks {
+            modules.insert(id, bytecode::save(&module, &globals)?);
+        }
+        Ok(Bundle { program: bytecode::save(chunk, &globals)?, modules, links, plugins: Vec::new() })
+    }
+
+    /// Module importé par `path` depuis le module `importer` : identité et bytecode.
+    pub fn module(&self, importer: &str, path: &str) -> Option<(&str, &[u8])> {
+        let id = self.links.get(&(importer.to_string(), path.to_string()))?;
+        Some((id, self.modules.get(id)?))
+    }
+
+    /// Embarque une bibliothèque native, chargée au démarrage de l'exécutable.
+    pub fn add_plugin(&mut self, path: &Path) -> Result<(), String> {
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned())
+            .ok_or_else(|| format!("Invalid plugin path: {}", path.display()))?;
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read plugin {}: {}", path.display(), e))?;
+        self.plugins.push((name, bytes));
+        Ok(())
+    }
+
+    /// Écrit une copie de l'exécutable courant suivie du bundle.
+    pub fn write_executable(&self, output: &Path) -> Result<(), String> {
+        let exe = std::env::current_exe().map_err(|e| format!("Unable to locate the aegis executable: {}", e))?;
+        let mut bytes = std::fs::read(&exe).map_err(|e| format!("Unable to read {}: {}", exe.display(), e))?;
+
+        let payload = self.encode();
+        bytes.extend_from_slice(&payload);
+        bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(TRAILER_MAGIC);
+
+        std::fs::write(output, bytes).map_err(|e| format!("Unable to write {}: {}", output.display(), e))?;
+        let permissions = std::fs::metadata(&exe).map_err(|e| e.to_string())?.permissions();
+        std::fs::set_permissions(output, permissions).map_err(|e| e.to_string())
+    }
+
+    /// Écrit les plugins embarqués dans un dossier privé à l'utilisateur (un fichier par contenu) pour pouvoir
+    /// les charger. Un fichier déjà présent n'est chargé que si son contenu correspond au plugin embarqué.
+    pub fn extract_plugins(&self) -> Result<Vec<PathBuf>, String> {
+        if self.plugins.is_empty() {
+            return Ok(Vec::new());
+        }
+        let dir = plugin_dir()?;
+
+        let mut paths = Vec::new();
+        for (name, bytes) in &self.plugins {
+            let digest = Sha256::digest(bytes);
+            let hash: String = digest.iter().take(8).map(|b| format!("{:02x}", b)).collect();
+            let path = dir.join(format!("{}-{}", hash, name));
+            if !path.exists() {
+                write_plugin(&dir, &path, name, bytes)?;
+            }
+            // Vérifié même juste après l'écriture : un autre processus a pu renommer son fichier à la place du nôtre
+            let on_disk = std::fs::read(&path).map_err(|e| format!("Unable to read {}: {}", path.display(), e))?;
+            if Sha256::digest(&on_disk) != digest {
+                return Err(format!("Plugin {} does not match the embedded copy, refusing to load it", path.display()));
+            }
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        put(&mut out, &self.program);
+
+        let mut modules: Vec<_> = self.modules.iter().collect();
+        modules.sort();
+        out.extend_from_slice(&(modules.len() as u64).to_le_bytes());
+        for (path, bytes) in modules {
+            put(&mut out, path.as_bytes());
+            put(&mut out, bytes);
+        }
+
+        let mut links: Vec<_> = self.links.iter().collect();
+        links.sort();
+        out.extend_from_slice(&(links.len() as u64).to_le_bytes());
+        for ((importer, path), id) in links {
+            put(&mut out, importer.as_bytes());
+            put(&mut out, path.as_bytes());
+            put(&mut out, id.as_bytes());
+        }
+
+        out.extend_from_slice(&(self.plugins.len() as u64).to_le_bytes());
+        for (name, bytes) in &self.plugins {
+            put(&mut out, name.as_bytes());
+            put(&mut out, bytes);
+        }
+        out
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Self, String> {
+        let mut r = Cursor { bytes, pos: 0 };
+        let program = r.blob()?.to_vec();
+
+        let mut modules = HashMap::new();
+        for _ in 0..r.u64()? {
+            let path = r.string()?;
+            modules.insert(path, r.blob()?.to_vec());
+        }
+
+        let mut links = HashMap::new();
+        for _ in 0..r.u64()? {
+            let importer = r.string()?;
+            let path = r.string()?;
+            links.insert((importer, path), r.string()?);
+        }
+
+        let mut plugins = Vec::new();
+        for _ in 0..r.u64()? {
+            let name = r.string()?;
+            plugins.push((name, r.blob()?.to_vec()));
+        }
+        Ok(Bundle { program, modules, links, plugins })
+    }
+}
+
+// Dossier des plugins extraits : $XDG_RUNTIME_DIR/aegis-plugins, sinon <cache utilisateur>/aegis/plugins.
+// Jamais le dossier temporaire partagé, où un autre utilisateur pourrait déposer une bibliothèque à notre place.
+fn plugin_dir() -> Result<PathBuf, String> {
+    let dir = dirs::runtime_dir().map(|dir| dir.join("aegis-plugins"))
+        .or_else(|| dirs::cache_dir().map(|dir| dir.join("aegis").join("plugins")))
+        .ok_or("Unable to find a private directory to extract plugins")?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        std::fs::DirBuilder::new().recursive(true).mode(0o700).create(&dir)
+            .map_err(|e| format!("Unable to create {}: {}", dir.display(), e))?;
+        // Un lien symbolique pourrait pointer vers un dossier d'un autre utilisateur
+        let meta = std::fs::symlink_metadata(&dir).map_err(|e| format!("Unable to read {}: {}", dir.display(), e))?;
+        if !meta.is_dir() {
+            return Err(format!("{} is not a directory", dir.display()));
+        }
+        // Seul le propriétaire peut changer les droits : l'échec signale un dossier qui n'est pas à nous
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+            .map_err(|e| format!("Unable to make {} private: {}", dir.display(), e))?;
+    }
+    #[cfg(not(unix))]
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Unable to create {}: {}", dir.display(), e))?;
+
+    Ok(dir)
+}
+
+// Écrit à côté puis renomme, comme le cache des modules. Le fichier temporaire est créé par nous seuls
+// (create_new) ; si le renommage échoue, c'est qu'un autre processus a extrait le même plugin.
+fn write_plugin(dir: &Path, path: &Path, name: &str, bytes: &[u8]) -> Result<(), String> {
+    use std::io::Write;
+
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.subsec_nanos());
+    let tmp = dir.join(format!("{}-{}.{}.tmp", std::process::id(), nanos, name));
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o700);
+
+    let written = options.open(&tmp).and_then(|mut file| file.write_all(bytes));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(format!("Unable to write {}: {}", tmp.display(), e));
+    }
+    if let Err(e) = std::fs::rename(&tmp, path) {
+        let _ = std::fs::remove_file(&tmp);
+        if !path.exists() {
+            return Err(format!("Unable to write {}: {}", path.display(), e));
+        }
+    }
+    Ok(())
+}
+
+/// Bundle porté par l'exécutable courant, s'il a été produit par `aegis build`.
+pub fn embedded() -> Result<Option<Bundle>, String> {
+    let Ok(exe) = std::env::current_exe() else { return Ok(None) };
+    let Ok(mut file) = File::open(&exe) else { return Ok(None) };
+    let size = file.metadata().map_err(|e| e.to_string())?.len();
+    if size < TRAILER_LEN {
+        return Ok(None);
+    }
+
+    // Seule la fin du fichier est lue tant qu'on ne sait pas s'il porte un bundle
+    let mut trailer = [0u8; TRAILER_LEN as usize];
+    file.seek(SeekFrom::Start(size - TRAILER_LEN)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut trailer).map_err(|e| e.to_string())?;
+    if !trailer.ends_with(TRAILER_MAGIC) {
+        return Ok(None);
+    }
+    let len = u64::from_le_bytes(trailer[..8].try_into().unwrap());
+    if len > size - TRAILER_LEN {
+        return Err("Corrupt executable: the embedded program is truncated".into());
+    }
+
+    let mut payload = vec![0; len as usize];
+    file.seek(SeekFrom::Start(size - TRAILER_LEN - len)).map_err(|e| e.to_string())?;
+    file.read_exact(&mut payload).map_err(|e| e.to_string())?;
+    Bundle::decode(&payload).map(Some)
+}
+
+fn put(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+    out.extend_from_slice(bytes);
+}
+
+struct Cursor<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn u64(&mut self) -> Result<u64, String> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len())
+            .ok_or("Corrupt executable: the embedded program is truncated")?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn blob(&mut self) -> Result<&'a [u8], String> {
+        let len = usize::try_from(self.u64()?).map_err(|e| e.to_string())?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let bytes = self.blob()?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| "Corrupt executable: invalid UTF-8 string".to_string())
+    }
+}
+
+fn imports_of(chunk: &Chunk) -> Vec<String> {
+    let mut paths = Vec::new();
+    imports(chunk, &mut paths);
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+// Chemins des `import` d'un chunk et des fonctions portées par ses constantes
+fn imports(chunk: &Chunk, out: &mut Vec<String>) {
+    let code = &chunk.code;
+    let mut at = 0;
+    let mut width = 1;
+    while at < code.len() {
+        let Ok(op) = OpCode::try_from(code[at]) else { return };
+        at += 1;
+        if matches!(op, OpCode::Wide) {
+            width = 4;
+            continue;
+        }
+
+        let operands = if op.is_jump() { 2 } else { op.operand_count() * width };
+        let Some(bytes) = code.get(at..at + operands) else { return };
+        if matches!(op, OpCode::Import) {
+            // Le premier opérande est le chemin importé
+            let idx = bytes[..width].iter().fold(0, |acc, &b| (acc << 8) | b as usize);
+            if let Some(Value::String(path)) = chunk.constants.get(idx) {
+                out.push(path.to_string());
+            }
+        }
+        at += operands;
+        width = 1;
+    }
+
+    for constant in &chunk.constants {
+        constant_imports(constant, out);
+    }
+}
+
+fn constant_imports(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Function(f) => imports(&f.chunk, out),
+        Value::Class(c) => {
+            let properties = c.properties.values().chain(c.static_properties.values())
+                .flat_map(|p| p.getter.iter().chain(p.setter.iter()));
+            for member in c.methods.values().chain(c.fields.values()).chain(c.static_methods.values()).chain(properties) {
+                constant_imports(member, out);
+            }
+            for init in c.static_fields.borrow().values() {
+                constant_imports(init, out);
+            }
+        }
+        Value::Enum(e) => e.methods.values().for_each(|m| constant_imports(m, out)),
+        _ => {}
+    }
+}
@@ -0,0 +1,690 @@
+//! Format binaire `.aegc` : un chunk compilé, relu sans repasser par le lexer, le parser et le compilateur.
+//!
+//! En-tête : `AEGC`, version du format (u16), version d'aegis. Suivent la table des globales
+//! (noms dans l'ordre des ids) puis le chunk principal. Les constantes (fonctions imbriquées,
+//! classes, interfaces, enums) sont écrites récursivement avec leur propre chunk.
+//! Les caches des sites d'accès ne sont pas écrits, seulement leur nombre : ils repartent vides.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use sha2::{Digest, Sha256};
+
+use crate::ast::value::{Capture, ClassData, EnumData, FunctionData, InterfaceData, PropertyData, Shape, Visibility};
+use crate::ast::Value;
+use crate::chunk::Chunk;
+use crate::opcode::OpCode;
+use crate::vm::compiler::GlobalTable;
+use crate::vm::verifier;
+
+const MAGIC: &[u8; 4] = b"AEGC";
+
+// À changer dès que l'encodage d'une valeur change. Un changement d'opcodes est couvert
+// par la version d'aegis, écrite elle aussi dans l'en-tête.
+pub const FORMAT_VERSION: u16 = 2;
+
+pub const EXTENSION: &str = "aegc";
+
+const TAG_NULL: u8 = 0;
+const TAG_INTEGER: u8 = 1;
+const TAG_FLOAT: u8 = 2;
+const TAG_STRING: u8 = 3;
+const TAG_BOOLEAN: u8 = 4;
+const TAG_LIST: u8 = 5;
+const TAG_FUNCTION: u8 = 6;
+const TAG_CLASS: u8 = 7;
+const TAG_INTERFACE: u8 = 8;
+const TAG_ENUM: u8 = 9;
+
+/// Le fichier commence-t-il par l'en-tête `.aegc` ?
+pub fn is_bytecode(bytes: &[u8]) -> bool {
+    bytes.starts_with(MAGIC)
+}
+
+/// Encode un chunk et la table des globales que son bytecode désigne.
+pub fn save(chunk: &Chunk, globals: &GlobalTable) -> Result<Vec<u8>, String> {
+    let mut w = Writer { out: Vec::new() };
+    w.out.extend_from_slice(MAGIC);
+    w.out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    w.string(env!("CARGO_PKG_VERSION"));
+
+    w.size(globals.len());
+    for name in globals.names() {
+        w.string(name);
+    }
+    w.chunk(chunk)?;
+    Ok(w.out)
+}
+
+/// Relit un programme : les ids de globales sont ceux de la table enregistrée.
+/// Le bytecode est vérifié avant d'être rendu (voir vm::verifier).
+pub fn load_program(bytes: &[u8]) -> Result<(Chunk, GlobalTable), String> {
+    let mut r = Reader::new(bytes)?;
+    let names = r.names()?;
+    let chunk = r.chunk()?;
+    r.finish()?;
+    verifier::verify(&chunk, names.len())?;
+    Ok((chunk, GlobalTable::from_names(names)))
+}
+
+/// Relit un module et relie son bytecode à la table de la VM qui l'importe.
+/// Le module doit avoir été compilé avec `Compiler.relocatable` (ids de globales sur quatre octets).
+pub fn load_module(bytes: &[u8], globals: &mut GlobalTable) -> Result<Chunk, String> {
+    // Les noms sont résolus dans une copie de la table, qui ne remplace l'originale qu'une fois
+    // le bytecode vérifié : un fichier corrompu ne laisse aucune globale derrière lui
+    let mut scratch = globals.clone();
+    let chunk = {
+        let mut r = Reader::new(bytes)?;
+        let names = r.names()?;
+        r.relink = Some((names, &mut scratch));
+        let chunk = r.chunk()?;
+        r.finish()?;
+        chunk
+    };
+    verifier::verify(&chunk, scratch.len())?;
+    *globals = scratch;
+    Ok(chunk)
+}
+
+// Dossier du cache des modules importés : $AEGIS_CACHE_DIR, sinon <cache utilisateur>/aegis/modules
+fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("AEGIS_CACHE_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    dirs::cache_dir().map(|dir| dir.join("aegis").join("modules"))
+}
+
+// Le nom du fichier dépend du source et de la version : un module modifié ou une autre version d'aegis
+// ne retrouve jamais un ancien bytecode
+fn cache_path(id: &str, source: &str) -> Option<PathBuf> {
+    let mut hasher = Sha256::new();
+    hasher.update(env!("CARGO_PKG_VERSION"));
+    hasher.update(FORMAT_VERSION.to_le_bytes());
+    // Les globales d'un module sont préfixées par son identité : le même source ailleurs donne un autre bytecode
+    hasher.update(id);
+    hasher.update([0]);
+    hasher.update(source);
+    let key: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+    cache_dir().map(|dir| dir.join(format!("{}.{}", key, EXTENSION)))
+}
+
+/// Bytecode du module `id` déjà compilé depuis ce source, relié à `globals`.
+/// Un fichier absent ou illisible est traité comme un module jamais compilé.
+pub fn cached_module(id: &str, source: &str, globals: &mut GlobalTable) -> Option<Chunk> {
+    let bytes = std::fs::read(cache_path(id, source)?).ok()?;
+    load_module(&bytes, globals).ok()
+}
+
+/// Enregistre le bytecode d'un module. Le cache n'est qu'une accélération : une écriture impossible est ignorée.
+pub fn cache_module(id: &str, source: &str, chunk: &Chunk, globals: &GlobalTable) {
+    let (Some(path), Ok(bytes)) = (cache_path(id, source), save(chunk, globals)) else {
+        return;
+    };
+    let Some(dir) = path.parent() else { return };
+    if std::fs::create_dir_all(dir).is_err() {
+        return;
+    }
+    // Écrit à côté puis renommé : un autre processus ne lit jamais un fichier à moitié écrit
+    let tmp = path.with_extension(format!("{}.{}", std::process::id(), EXTENSION));
+    if std::fs::write(&tmp, bytes).is_ok() && std::fs::rename(&tmp, &path).is_err() {
+        let _ = std::fs::remove_file(&tmp);
+    }
+}
+
+struct Writer {
+    out: Vec<u8>,
+}
+
+impl Writer {
+    fn byte(&mut self, b: u8) {
+        self.out.push(b);
+    }
+
+    fn bool(&mut self, b: bool) {
+        self.byte(b as u8);
+    }
+
+    // Entier non signé en LEB128 : un octet pour les petites valeurs (index, compteurs, lignes)
+    fn size(&mut self, mut n: usize) {
+        loop {
+            let b = (n & 0x7f) as u8;
+            n >>= 7;
+            if n == 0 {
+                self.byte(b);
+                return;
+            }
+            self.byte(b | 0x80);
+        }
+    }
+
+    fn string(&mut self, s: &str) {
+        self.size(s.len());
+        self.out.extend_from_slice(s.as_bytes());
+    }
+
+    fn opt_string(&mut self, s: &Option<String>) {
+        self.bool(s.is_some());
+        if let Some(s) = s {
+            self.string(s);
+        }
+    }
+
+    fn chunk(&mut self, chunk: &Chunk) -> Result<(), String> {
+        self.size(chunk.code.len());
+        self.out.extend_from_slice(&chunk.code);
+        for &line in &chunk.lines {
+            self.size(line);
+        }
+
+        self.size(chunk.constants.len());
+        for constant in &chunk.constants {
+            self.value(constant)?;
+        }
+
+        let mut locals: Vec<_> = chunk.locals_map.iter().collect();
+        locals.sort();
+        self.size(locals.len());
+        for (slot, name) in locals {
+            self.size(*slot);
+            self.string(name);
+        }
+
+        self.size(chunk.caches.len());
+        Ok(())
+    }
+
+    fn value(&mut self, value: &Value) -> Result<(), String> {
+        match value {
+            Value::Null => self.byte(TAG_NULL),
+            Value::Integer(i) => {
+                self.byte(TAG_INTEGER);
+                self.out.extend_from_slice(&i.to_le_bytes());
+            }
+            Value::Float(f) => {
+                self.byte(TAG_FLOAT);
+                self.out.extend_from_slice(&f.to_bits().to_le_bytes());
+            }
+            Value::String(s) => {
+                self.byte(TAG_STRING);
+                self.string(s);
+            }
+            Value::Boolean(b) => {
+                self.byte(TAG_BOOLEAN);
+                self.bool(*b);
+            }
+            Value::List(items) => {
+                self.byte(TAG_LIST);
+                let items = items.borrow();
+                self.size(items.len());
+                for item in items.iter() {
+                    self.value(item)?;
+                }
+            }
+            Value::Function(f) => {
+                self.byte(TAG_FUNCTION);
+                self.function(f)?;
+            }
+            Value::Class(c) => {
+                self.byte(TAG_CLASS);
+                self.class(c)?;
+            }
+            Value::Interface(i) => {
+                self.byte(TAG_INTERFACE);
+                self.string(&i.name);
+                let mut methods: Vec<_> = i.methods.iter().collect();
+                methods.sort();
+                self.size(methods.len());
+                for (name, arity) in methods {
+                    self.string(name);
+                    self.size(*arity);
+                }
+            }
+            Value::Enum(e) => {
+                self.byte(TAG_ENUM);
+                self.string(&e.name);
+                self.size(e.variants.len());
+                for (name, fields) in &e.variants {
+                    self.string(name);
+                    self.strings(fields.iter());
+                }
+                self.values(&e.methods)?;
+            }
+            other => return Err(format!("A constant of type {} cannot be saved as bytecode", other.type_name())),
+        }
+        Ok(())
+    }
+
+    fn function(&mut self, f: &FunctionData) -> Result<(), String> {
+        self.string(&f.name);
+        self.size(f.params.len());
+        for (name, ty) in &f.params {
+            self.string(name);
+            self.opt_string(ty);
+        }
+        self.size(f.required);
+        self.bool(f.variadic);
+        self.opt_string(&f.ret_type);
+        self.bool(f.is_generator);
+        self.bool(f.is_async);
+
+        self.size(f.captures.len());
+        for capture in &f.captures {
+            match *capture {
+                Capture::Local(slot) => {
+                    self.byte(0);
+                    self.size(slot);
+                }
+                Capture::Match(level, slot) => {
+                    self.byte(1);
+                    self.size(level);
+                    self.size(slot);
+                }
+                Capture::Upvalue(idx) => {
+                    self.byte(2);
+                    self.size(idx);
+                }
+            }
+        }
+        self.chunk(&f.chunk)
+    }
+
+    fn class(&mut self, c: &ClassData) -> Result<(), String> {
+        self.string(&c.name);
+        self.opt_string(&c.parent);
+
+        self.values(&c.methods)?;
+        self.values(&c.fields)?;
+        self.types(&c.field_types);
+        self.properties(&c.properties)?;
+
+        self.values(&c.static_methods)?;
+        // Le compilateur y range les initialiseurs des champs statiques (voir OpCode::Class)
+        self.values(&c.static_fields.borrow())?;
+        self.types(&c.static_field_types);
+        self.properties(&c.static_properties)?;
+
+        self.bool(c.is_final);
+        let mut final_methods: Vec<_> = c.final_methods.iter().collect();
+        final_methods.sort();
+        self.strings(final_methods.into_iter());
+        self.strings(c.interfaces_names.iter());
+
+        let mut visibilities: Vec<_> = c.visibilities.iter().collect();
+        visibilities.sort_by(|a, b| a.0.cmp(b.0));
+        self.size(visibilities.len());
+        for (name, visibility) in visibilities {
+            self.string(name);
+            self.byte(match visibility {
+                Visibility::Public => 0,
+                Visibility::Protected => 1,
+                Visibility::Private => 2,
+            });
+        }
+        Ok(())
+    }
+
+    fn strings<'s>(&mut self, strings: impl ExactSizeIterator<Item = &'s String>) {
+        self.size(strings.len());
+        for s in strings {
+            self.string(s);
+        }
+    }
+
+    // Les tables sont écrites triées par nom : un même source donne toujours le même fichier
+    fn values(&mut self, map: &HashMap<String, Value>) -> Result<(), String> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        self.size(entries.len());
+        for (name, value) in entries {
+            self.string(name);
+            self.value(value)?;
+        }
+        Ok(())
+    }
+
+    fn types(&mut self, map: &HashMap<String, String>) {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort();
+        self.size(entries.len());
+        for (name, ty) in entries {
+            self.string(name);
+            self.string(ty);
+        }
+    }
+
+    fn properties(&mut self, map: &HashMap<String, PropertyData>) -> Result<(), String> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        self.size(entries.len());
+        for (name, prop) in entries {
+            self.string(name);
+            for accessor in [&prop.getter, &prop.setter] {
+                self.bool(accessor.is_some());
+                if let Some(f) = accessor {
+                    self.value(f)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    // Module : noms des globales du fichier, et table de la VM à laquelle le bytecode est relié
+    relink: Option<(Vec<String>, &'a mut GlobalTable)>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Result<Self, String> {
+        if !is_bytecode(bytes) {
+            return Err("Not an Aegis bytecode file".into());
+        }
+        let mut r = Reader { bytes, pos: MAGIC.len(), relink: None };
+
+        let format = u16::from_le_bytes([r.byte()?, r.byte()?]);
+        if format != FORMAT_VERSION {
+            return Err(format!("Bytecode format {} is not supported (expected {})", format, FORMAT_VERSION));
+        }
+        let version = r.string()?;
+        if version != env!("CARGO_PKG_VERSION") {
+            return Err(format!("Bytecode compiled by aegis {}, this is aegis {}: compile it again", version, env!("CARGO_PKG_VERSION")));
+        }
+        Ok(r)
+    }
+
+    fn finish(&self) -> Result<(), String> {
+        if self.pos != self.bytes.len() {
+            return Err("Corrupt bytecode: unexpected data after the main chunk".into());
+        }
+        Ok(())
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.bytes.len())
+            .ok_or("Corrupt bytecode: unexpected end of file")?;
+        let slice = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> Result<bool, String> {
+        match self.byte()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(format!("Corrupt bytecode: invalid boolean {}", b)),
+        }
+    }
+
+    fn size(&mut self) -> Result<usize, String> {
+        let mut n: usize = 0;
+        let mut shift = 0;
+        loop {
+            let b = self.byte()?;
+            if shift >= usize::BITS {
+                return Err("Corrupt bytecode: integer too large".into());
+            }
+            n |= ((b & 0x7f) as usize) << shift;
+            if b & 0x80 == 0 {
+                return Ok(n);
+            }
+            shift += 7;
+        }
+    }
+
+    // Longueur d'une liste : bornée par ce qui reste à lire, pour ne pas réserver une mémoire démesurée
+    fn count(&mut self) -> Result<usize, String> {
+        let n = self.size()?;
+        if n > self.bytes.len() - self.pos {
+            return Err("Corrupt bytecode: unexpected end of file".into());
+        }
+        Ok(n)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.size()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| "Corrupt bytecode: invalid UTF-8 string".to_string())
+    }
+
+    fn opt_string(&mut self) -> Result<Option<String>, String> {
+        Ok(if self.bool()? { Some(self.string()?) } else { None })
+    }
+
+    fn names(&mut self) -> Result<Vec<String>, String> {
+        let count = self.count()?;
+        (0..count).map(|_| self.string()).collect()
+    }
+
+    fn chunk(&mut self) -> Result<Chunk, String> {
+        let mut chunk = Chunk::new();
+        let len = self.size()?;
+        chunk.code = self.take(len)?.to_vec();
+        chunk.lines = (0..len).map(|_| self.size()).collect::<Result<_, _>>()?;
+        if let Some((names, globals)) = &mut self.relink {
+            relink(&mut chunk.code, names, globals)?;
+        }
+
+        let count = self.count()?;
+        for _ in 0..count {
+            let value = self.value()?;
+            chunk.constants.push(value);
+        }
+
+        let count = self.count()?;
+        for _ in 0..count {
+            let slot = self.size()?;
+            let name = self.string()?;
+            chunk.locals_map.insert(slot, name);
+        }
+
+        let caches = self.size()?;
+        for _ in 0..caches {
+            chunk.add_cache();
+        }
+        Ok(chunk)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        Ok(match self.byte()? {
+            TAG_NULL => Value::Null,
+            TAG_INTEGER => Value::Integer(i64::from_le_bytes(self.take(8)?.try_into().unwrap())),
+            TAG_FLOAT => Value::Float(f64::from_bits(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))),
+            TAG_STRING => Value::String(self.string()?.into()),
+            TAG_BOOLEAN => Value::Boolean(self.bool()?),
+            TAG_LIST => {
+                let count = self.count()?;
+                let items = (0..count).map(|_| self.value()).collect::<Result<Vec<_>, _>>()?;
+                Value::List(Rc::new(RefCell::new(items)))
+            }
+            TAG_FUNCTION => Value::Function(Rc::new(self.function()?)),
+            TAG_CLASS => Value::Class(Rc::new(self.class()?)),
+            TAG_INTERFACE => {
+                let name = self.string()?;
+                let count = self.count()?;
+                let mut methods = HashMap::new();
+                for _ in 0..count {
+                    let method = self.string()?;
+                    methods.insert(method, self.size()?);
+                }
+                Value::Interface(Rc::new(InterfaceData { name, methods }))
+            }
+            TAG_ENUM => {
+                let name = self.string()?;
+                let count = self.count()?;
+                let mut variants = Vec::new();
+                for _ in 0..count {
+                    let variant = self.string()?;
+                    variants.push((variant, self.strings()?));
+                }
+                let methods = self.values()?;
+                Value::Enum(Rc::new(EnumData { name, variants, methods }))
+            }
+            tag => return Err(format!("Corrupt bytecode: unknown constant tag {}", tag)),
+        })
+    }
+
+    fn function(&mut self) -> Result<FunctionData, String> {
+        let name = self.string()?;
+        let count = self.count()?;
+        let mut params = Vec::new();
+        for _ in 0..count {
+            let param = self.string()?;
+            params.push((param, self.opt_string()?));
+        }
+        let required = self.size()?;
+        let variadic = self.bool()?;
+        let ret_type = self.opt_string()?;
+        let is_generator = self.bool()?;
+        let is_async = self.bool()?;
+
+        let count = self.count()?;
+        let mut captures = Vec::new();
+        for _ in 0..count {
+            captures.push(match self.byte()? {
+                0 => Capture::Local(self.size()?),
+                1 => Capture::Match(self.size()?, self.size()?),
+                2 => Capture::Upvalue(self.size()?),
+                tag => return Err(format!("Corrupt bytecode: unknown capture tag {}", tag)),
+            });
+        }
+
+        let chunk = Rc::new(self.chunk()?);
+        Ok(FunctionData { name, params, required, variadic, ret_type, is_generator, is_async, chunk, captures, upvalues: Vec::new() })
+    }
+
+    fn class(&mut self) -> Result<ClassData, String> {
+        let name = self.string()?;
+        let parent = self.opt_string()?;
+
+        let methods = self.values()?;
+        let fields = self.values()?;
+        let field_types = self.types()?;
+        let properties = self.properties()?;
+
+        let static_methods = self.values()?;
+        let static_fields = RefCell::new(self.values()?);
+        let static_field_types = self.types()?;
+        let static_properties = self.properties()?;
+
+        let is_final = self.bool()?;
+        let final_methods: HashSet<String> = self.strings()?.into_iter().collect();
+        let interfaces_names = self.strings()?;
+
+        let count = self.count()?;
+        let mut visibilities = HashMap::new();
+        for _ in 0..count {
+            let member = self.string()?;
+            let visibility = match self.byte()? {
+                0 => Visibility::Public,
+                1 => Visibility::Protected,
+                2 => Visibility::Private,
+                b => return Err(format!("Corrupt bytecode: unknown visibility {}", b)),
+            };
+            visibilities.insert(member, visibility);
+        }
+
+        Ok(ClassData {
+            name,
+            parent,
+            parent_ref: None, // Résolu par OpCode::Class, comme pour une classe fraîchement compilée
+            methods,
+            fields,
+            field_types,
+            properties,
+            static_methods,
+            static_fields,
+            static_field_types,
+            static_properties,
+            is_final,
+            final_methods,
+            interfaces: Vec::new(),
+            interfaces_names,
+            visibilities,
+            shape: Shape::root(),
+        })
+    }
+
+    fn strings(&mut self) -> Result<Vec<String>, String> {
+        let count = self.count()?;
+        (0..count).map(|_| self.string()).collect()
+    }
+
+    fn values(&mut self) -> Result<HashMap<String, Value>, String> {
+        let count = self.count()?;
+        let mut map = HashMap::new();
+        for _ in 0..count {
+            let name = self.string()?;
+            map.insert(name, self.value()?);
+        }
+        Ok(map)
+    }
+
+    fn types(&mut self) -> Result<HashMap<String, String>, String> {
+        let count = self.count()?;
+        let mut map = HashMap::new();
+        for _ in 0..count {
+            let name = self.string()?;
+            map.insert(name, self.string()?);
+        }
+        Ok(map)
+    }
+
+    fn properties(&mut self) -> Result<HashMap<String, PropertyData>, String> {
+        let count = self.count()?;
+        let mut map = HashMap::new();
+        for _ in 0..count {
+            let name = self.string()?;
+            let getter = if self.bool()? { Some(self.value()?) } else { None };
+            let setter = if self.bool()? { Some(self.value()?) } else { None };
+            map.insert(name, PropertyData { getter, setter });
+        }
+        Ok(map)
+    }
+}
+
+// Remplace les ids de globales du fichier (GET_GLOBAL, SET_GLOBAL, GET_FREE_GLOBAL) par ceux de `globals`
+fn relink(code: &mut [u8], names: &[String], globals: &mut GlobalTable) -> Result<(), String> {
+    let mut at = 0;
+    while at < code.len() {
+        let mut width = 1;
+        if code[at] == OpCode::Wide as u8 {
+            width = 4;
+            at += 1;
+        }
+        let op = code.get(at).and_then(|&b| OpCode::try_from(b).ok())
+            .ok_or_else(|| format!("Corrupt bytecode: invalid opcode at {}", at))?;
+        let operands = at + 1;
+        at = operands + if op.is_jump() { 2 } else { op.operand_count() * width };
+        if at > code.len() {
+            return Err("Corrupt bytecode: truncated instruction".into());
+        }
+
+        if !matches!(op, OpCode::GetGlobal | OpCode::SetGlobal | OpCode::GetFreeGlobal) {
+            continue;
+        }
+        let old = if width == 1 {
+            code[operands] as usize
+        } else {
+            u32::from_be_bytes(code[operands..at].try_into().unwrap()) as usize
+        };
+        let name = names.get(old).ok_or_else(|| format!("Corrupt bytecode: unknown global {}", old))?;
+        let id = globals.resolve(name);
+        if width == 4 {
+            let id = u32::try_from(id).map_err(|_| "Too many globals".to_string())?;
+            code[operands..at].copy_from_slice(&id.to_be_bytes());
+        } else if id <= u8::MAX as usize {
+            code[operands] = id as u8;
+        } else {
+            return Err("Bytecode was not compiled as a relocatable module".into());
+        }
+    }
+    Ok(())
+}
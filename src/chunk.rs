@@ -1,13 +1,92 @@
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::rc::Weak;
 
+use crate::ast::value::{ClassData, FunctionData, Shape};
 use crate::ast::Value;
+use crate::vm::builtins::BuiltinType;
+
+/// Cache d'un site d'accès (GET_ATTR, SET_ATTR, METHOD), rempli par la VM au premier passage.
+/// Il ne retient qu'un seul cas (monomorphique) et ne garde rien en vie.
+#[derive(Debug, Clone, Default)]
+pub enum InlineCache {
+    #[default]
+    Empty,
+    // Champ à la position `slot` des instances de forme `shape`
+    Field {
+        shape: Weak<Shape>,
+        context: Option<Weak<ClassData>>, // Classe de l'appelant, pour laquelle check_access a réussi
+        slot: usize,
+    },
+    // Ajout d'un champ : les instances de forme `from` passent à `to` (le champ va à la fin)
+    Transition {
+        from: Weak<Shape>,
+        to: Weak<Shape>,
+        context: Option<Weak<ClassData>>,
+    },
+    // Méthode `method` de `owner`, trouvée à partir des instances de `class`
+    Method {
+        class: Weak<ClassData>,
+        context: Option<Weak<ClassData>>,
+        method: Weak<FunctionData>,
+        owner: Weak<ClassData>,
+    },
+    // Méthode native `id` d'un type de base (voir vm::builtins)
+    Builtin {
+        ty: BuiltinType,
+        id: usize,
+    },
+}
+
+/// Caches des sites d'accès d'un chunk. Ils ne font pas partie du bytecode :
+/// une copie du chunk repart avec des caches vides, et deux chunks se comparent sans eux.
+#[derive(Default)]
+pub struct InlineCaches(RefCell<Vec<InlineCache>>);
+
+impl InlineCaches {
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, idx: usize) -> std::cell::Ref<'_, InlineCache> {
+        std::cell::Ref::map(self.0.borrow(), |caches| &caches[idx])
+    }
+
+    pub fn set(&self, idx: usize, cache: InlineCache) {
+        self.0.borrow_mut()[idx] = cache;
+    }
+}
+
+impl Clone for InlineCaches {
+    fn clone(&self) -> Self {
+        InlineCaches(RefCell::new(vec![InlineCache::Empty; self.len()]))
+    }
+}
+
+impl PartialEq for InlineCaches {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl fmt::Debug for InlineCaches {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "InlineCaches({})", self.len())
+    }
+}
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Chunk {
     pub code: Vec<u8>,
     pub constants: Vec<Value>,
-    pub locals_map: HashMap<u8, String>,
+    pub locals_map: HashMap<usize, String>,
     pub lines: Vec<usize>,
+    pub caches: InlineCaches,
 }
 
 impl Chunk {
@@ -17,6 +96,7 @@ impl Chunk {
             constants: Vec::new(),
             locals_map: HashMap::new(),
             lines: Vec::new(),
+            caches: InlineCaches::default(),
         }
     }
 
@@ -25,8 +105,15 @@ impl Chunk {
         self.lines.push(line);
     }
 
-    pub fn add_constant(&mut self, value: Value) -> u8 {
+    pub fn add_constant(&mut self, value: Value) -> usize {
         self.constants.push(value);
-        (self.constants.len() - 1) as u8
+        self.constants.len() - 1
+    }
+
+    // Réserve un cache pour un nouveau site d'accès et renvoie son index (opérande de l'instruction)
+    pub fn add_cache(&mut self) -> usize {
+        let caches = self.caches.0.get_mut();
+        caches.push(InlineCache::Empty);
+        caches.len() - 1
     }
 }
\ No newline at end of file
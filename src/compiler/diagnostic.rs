@@ -0,0 +1,91 @@
+use std::fmt;
+
+/// Plage d'octets dans le fichier source (début inclus, fin exclue).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// Erreur de compilation localisée (lexer ou parser).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, line: usize, column: usize, span: Span) -> Self {
+        Diagnostic {
+            message: message.into(),
+            file: None,
+            line,
+            column,
+            span,
+        }
+    }
+
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    /// Affiche l'erreur avec un extrait du code source et un curseur sous le token fautif.
+    ///
+    /// ```text
+    /// error: Unterminated string
+    ///  --> main.aeg:3:7
+    ///   |
+    /// 3 | print "hello
+    ///   |       ^^^^^^
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let mut out = format!("error: {}\n", self.message);
+
+        let file = self.file.as_deref().unwrap_or("<input>");
+        let line_no = self.line.to_string();
+        let gutter = " ".repeat(line_no.len());
+        out.push_str(&format!("{}--> {}:{}:{}\n", gutter, file, self.line, self.column));
+
+        let Some(text) = source.lines().nth(self.line.saturating_sub(1)) else {
+            return out;
+        };
+
+        // Le curseur s'arrête en fin de ligne pour les tokens multi-lignes
+        let prefix: String = text
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let remaining = text.chars().count().saturating_sub(self.column.saturating_sub(1));
+        let width = source
+            .get(self.span.start..self.span.end.min(source.len()))
+            .map(|s| s.chars().count())
+            .unwrap_or(1)
+            .min(remaining)
+            .max(1);
+
+        out.push_str(&format!("{} |\n", gutter));
+        out.push_str(&format!("{} | {}\n", line_no, text));
+        out.push_str(&format!("{} | {}{}", gutter, prefix, "^".repeat(width)));
+        out
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{}:{}:{}: {}", file, self.line, self.column, self.message),
+            None => write!(f, "{} (Line {}, Column {})", self.message, self.line, self.column),
+        }
+    }
+}
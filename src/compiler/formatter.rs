@@ -0,0 +1,288 @@
+use super::diagnostic::{Diagnostic, Span};
+use super::lexer::{Lexer, Token, TokenKind, Trivia, TriviaKind};
+
+const INDENT: &str = "    ";
+
+/// Reformate un fichier `.aeg` selon le guide de style (indentation, espaces, lignes vides).
+/// Les commentaires, le shebang et les chaînes `...` sont conservés tels quels.
+/// Le fichier doit être syntaxiquement valide.
+pub fn format_source(source: &str) -> Result<String, Vec<Diagnostic>> {
+    super::compile(source)?;
+
+    let (tokens, trivia) = lex(source).map_err(|e| vec![e])?;
+    let items = collect_items(source, &tokens, &trivia);
+    let formatted = Formatter::new(source).run(&items);
+
+    // Garde-fou : le formateur ne doit changer que les blancs
+    let (new_tokens, new_trivia) = lex(&formatted).map_err(|e| vec![e])?;
+    let same_tokens = tokens.len() == new_tokens.len()
+        && tokens.iter().zip(&new_tokens).all(|(a, b)| a.kind == b.kind);
+    if !same_tokens || trivia.len() != new_trivia.len() {
+        return Err(vec![Diagnostic::new("Internal formatter error: the output would change the program", 1, 1, Span::default())]);
+    }
+
+    Ok(formatted)
+}
+
+fn lex(source: &str) -> Result<(Vec<Token>, Vec<Trivia>), Diagnostic> {
+    let mut lexer = Lexer::new(source).with_trivia();
+    let tokens = lexer.tokenize()?;
+    Ok((tokens, lexer.take_trivia()))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Token(TokenKind),
+    Trivia(TriviaKind),
+}
+
+struct Item<'a> {
+    piece: Piece,
+    text: &'a str,
+    span: Span,
+}
+
+impl Item<'_> {
+    fn kind(&self) -> Option<&TokenKind> {
+        match &self.piece {
+            Piece::Token(kind) => Some(kind),
+            Piece::Trivia(_) => None,
+        }
+    }
+
+    fn is(&self, kind: TokenKind) -> bool {
+        self.kind() == Some(&kind)
+    }
+
+    fn is_comment(&self) -> bool {
+        matches!(self.piece, Piece::Trivia(TriviaKind::LineComment | TriviaKind::BlockComment))
+    }
+
+    fn is_closer(&self) -> bool {
+        matches!(self.kind(), Some(TokenKind::RBrace | TokenKind::RParen | TokenKind::RBracket))
+    }
+
+    fn is_opener(&self) -> bool {
+        matches!(self.kind(), Some(TokenKind::LBrace | TokenKind::LParen | TokenKind::LBracket))
+    }
+
+    // Fin d'opérande : un '-' qui suit est binaire, un '(' est un appel
+    fn ends_operand(&self) -> bool {
+        match &self.piece {
+            Piece::Trivia(kind) => *kind == TriviaKind::Template,
+            Piece::Token(kind) => matches!(kind,
+                TokenKind::Identifier(_) | TokenKind::StringLiteral(_) | TokenKind::Integer(_) | TokenKind::Float(_)
+                | TokenKind::True | TokenKind::False | TokenKind::Null
+                | TokenKind::RParen | TokenKind::RBracket | TokenKind::RBrace
+                | TokenKind::PlusPlus | TokenKind::MinusMinus),
+        }
+    }
+
+    // Opérateur binaire en fin de ligne : la ligne suivante est une continuation
+    fn continues_line(&self) -> bool {
+        matches!(self.kind(), Some(
+            TokenKind::Plus | TokenKind::Minus | TokenKind::Star | TokenKind::Slash | TokenKind::Percent
+            | TokenKind::Eq | TokenKind::EqEq | TokenKind::Neq | TokenKind::Lt | TokenKind::Gt
+            | TokenKind::LtEq | TokenKind::GtEq | TokenKind::And | TokenKind::Or
+            | TokenKind::PlusEq | TokenKind::MinusEq | TokenKind::StarEq | TokenKind::SlashEq
+            | TokenKind::BitAnd | TokenKind::BitOr | TokenKind::BitXor | TokenKind::ShiftLeft | TokenKind::ShiftRight
+            | TokenKind::Question | TokenKind::DoubleQuestion | TokenKind::Arrow))
+    }
+}
+
+// Tokens et trivia fusionnés dans l'ordre du source ; le contenu des chaînes `...` disparaît
+// derrière l'élément Template qui les recouvre.
+fn collect_items<'a>(source: &'a str, tokens: &[Token], trivia: &[Trivia]) -> Vec<Item<'a>> {
+    let templates: Vec<Span> = trivia.iter()
+        .filter(|t| t.kind == TriviaKind::Template)
+        .map(|t| t.span)
+        .collect();
+    let inside_template = |span: Span| templates.iter().any(|t| span.start >= t.start && span.start < t.end && span != *t);
+
+    let mut items: Vec<Item> = tokens.iter()
+        .filter(|t| t.kind != TokenKind::EOF && !inside_template(t.span))
+        .map(|t| Item { piece: Piece::Token(t.kind.clone()), text: &source[t.span.start..t.span.end], span: t.span })
+        .chain(trivia.iter()
+            .filter(|t| !inside_template(t.span))
+            .map(|t| Item { piece: Piece::Trivia(t.kind), text: source[t.span.start..t.span.end].trim_end(), span: t.span }))
+        .collect();
+    items.sort_by_key(|item| item.span.start);
+    items
+}
+
+struct Open {
+    // Indentation de la ligne qui a ouvert le bloc
+    indent: usize,
+    is_switch: bool,
+    in_case: bool,
+    // '[' d'un accès x[i] ou x[a:b] (et non d'une liste)
+    is_index: bool,
+    // '?' en attente de leur ':' à ce niveau d'imbrication
+    ternaries: usize,
+}
+
+struct Formatter<'a> {
+    source: &'a str,
+    out: String,
+    line: String,
+    stack: Vec<Open>,
+    top_ternaries: usize,
+    pending_switch: Option<usize>,
+    unary_minus: bool,
+    slice_colon: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &'a str) -> Self {
+        Formatter {
+            source,
+            out: String::new(),
+            line: String::new(),
+            stack: Vec::new(),
+            top_ternaries: 0,
+            pending_switch: None,
+            unary_minus: false,
+            slice_colon: false,
+        }
+    }
+
+    fn run(mut self, items: &[Item]) -> String {
+        let mut prev: Option<&Item> = None;
+
+        for item in items {
+            match prev {
+                None => self.start_line(item, None),
+                Some(p) => {
+                    let gap = &self.source[p.span.end..item.span.start];
+                    let newlines = gap.matches('\n').count();
+                    if newlines > 0 && !Self::joins_previous_line(p, item) {
+                        self.flush_line();
+                        if newlines > 1 && !p.is_opener() && !item.is_closer() {
+                            self.out.push('\n');
+                        }
+                        self.start_line(item, Some(p));
+                    } else if self.space_before(p, item) {
+                        self.line.push(' ');
+                    }
+                }
+            }
+            self.emit(item, prev);
+            prev = Some(item);
+        }
+
+        self.flush_line();
+        self.out
+    }
+
+    // Accolade ouvrante remontée sur la ligne précédente (style K&R), `} else` et `} catch` sur une ligne
+    fn joins_previous_line(prev: &Item, item: &Item) -> bool {
+        match item.kind() {
+            Some(TokenKind::LBrace) => matches!(prev.kind(), Some(TokenKind::RParen | TokenKind::Identifier(_) | TokenKind::Else | TokenKind::Try)),
+            Some(TokenKind::Else | TokenKind::Catch) => prev.is(TokenKind::RBrace),
+            _ => false,
+        }
+    }
+
+    fn flush_line(&mut self) {
+        let line = self.line.trim_end();
+        if !line.is_empty() || !self.out.is_empty() {
+            self.out.push_str(line);
+            self.out.push('\n');
+        }
+        self.line.clear();
+    }
+
+    fn start_line(&mut self, item: &Item, prev: Option<&Item>) {
+        let mut indent = match self.stack.last_mut() {
+            None => 0,
+            Some(open) if item.is_closer() => open.indent,
+            Some(open) if open.is_switch => {
+                if item.is(TokenKind::Case) || item.is(TokenKind::Default) {
+                    open.in_case = true;
+                    open.indent + 1
+                } else if open.in_case {
+                    open.indent + 2
+                } else {
+                    open.indent + 1
+                }
+            },
+            Some(open) => open.indent + 1,
+        };
+
+        // Ligne de continuation : chaînage `.methode()` ou opérateur en fin de ligne précédente
+        if item.is(TokenKind::Dot) || prev.is_some_and(|p| p.continues_line()) {
+            indent += 1;
+        }
+
+        self.line = INDENT.repeat(indent);
+    }
+
+    fn current_indent(&self) -> usize {
+        self.line.len() - self.line.trim_start_matches(' ').len()
+    }
+
+    fn ternaries(&mut self) -> &mut usize {
+        match self.stack.last_mut() {
+            Some(open) => &mut open.ternaries,
+            None => &mut self.top_ternaries,
+        }
+    }
+
+    fn emit(&mut self, item: &Item, prev: Option<&Item>) {
+        self.line.push_str(item.text);
+
+        let Some(kind) = item.kind() else { return };
+        match kind {
+            TokenKind::LBrace | TokenKind::LParen | TokenKind::LBracket => {
+                let is_switch = kind == &TokenKind::LBrace && self.pending_switch == Some(self.stack.len());
+                if is_switch {
+                    self.pending_switch = None;
+                }
+                let is_index = kind == &TokenKind::LBracket && prev.is_some_and(|p| p.ends_operand());
+                let indent = self.current_indent() / INDENT.len();
+                self.stack.push(Open { indent, is_switch, in_case: false, is_index, ternaries: 0 });
+            },
+            TokenKind::RBrace | TokenKind::RParen | TokenKind::RBracket => {
+                self.stack.pop();
+            },
+            TokenKind::Switch => self.pending_switch = Some(self.stack.len()),
+            TokenKind::Question => *self.ternaries() += 1,
+            TokenKind::Colon => {
+                let pending = self.ternaries();
+                let in_ternary = *pending > 0;
+                *pending = pending.saturating_sub(1);
+                self.slice_colon = !in_ternary && self.stack.last().is_some_and(|open| open.is_index);
+            },
+            TokenKind::Minus => self.unary_minus = !prev.is_some_and(|p| p.ends_operand()),
+            _ => {},
+        }
+    }
+
+    fn space_before(&mut self, prev: &Item, item: &Item) -> bool {
+        if prev.is_comment() || item.is_comment() {
+            return true;
+        }
+        let (Some(p), Some(c)) = (prev.kind(), item.kind()) else {
+            // Chaîne `...` : traitée comme un littéral
+            return !matches!(prev.kind(), Some(TokenKind::LParen | TokenKind::LBracket | TokenKind::Dot))
+                && !matches!(item.kind(), Some(TokenKind::RParen | TokenKind::RBracket | TokenKind::Comma | TokenKind::Dot));
+        };
+
+        match (p, c) {
+            (_, TokenKind::RParen | TokenKind::RBracket | TokenKind::Comma | TokenKind::Dot) => false,
+            (TokenKind::LBrace, TokenKind::RBrace) => false,
+            (_, TokenKind::DotDot) | (TokenKind::DotDot, _) => false,
+            (TokenKind::LParen | TokenKind::LBracket | TokenKind::Dot | TokenKind::At | TokenKind::Bang | TokenKind::Ellipsis, _) => false,
+            (TokenKind::Minus, _) if self.unary_minus => false,
+            (TokenKind::Colon, _) if self.slice_colon => false,
+            // ':' du ternaire entouré d'espaces, collé ailleurs (dictionnaire, type, case, tranche)
+            (_, TokenKind::Colon) => *self.ternaries() > 0,
+            (_, TokenKind::PlusPlus | TokenKind::MinusMinus) => !prev.ends_operand(),
+            // Appel, lambda `func(` et accès `x[i]`
+            (TokenKind::Func, TokenKind::LParen) => false,
+            (TokenKind::RBrace, TokenKind::LParen | TokenKind::LBracket) => true,
+            (_, TokenKind::LParen | TokenKind::LBracket) => !prev.ends_operand(),
+            _ => true,
+        }
+    }
+}
@@ -1,13 +1,16 @@
 use std::iter::Peekable;
 use std::str::Chars;
 
+use super::diagnostic::{Diagnostic, Span};
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
     True, False, Null,
     Try, Catch, Throw,
-    Var, If, Else, While, Func, Return, Print, Input, 
+    Var, If, Else, While, Func, Return, Yield, Print, Input, 
+    Async, Await,
     Class, New, Extends, Enum,
-    Import, Break, Continue, Switch, Case, Default,
+    Import, Export, Break, Continue, Switch, Case, Default,
     Identifier(String), StringLiteral(String), Integer(i64), Float(f64),
     Plus, Minus, Star, Slash, Percent,
     Eq, EqEq, Neq, Lt, Gt, LtEq, GtEq,
@@ -34,71 +37,203 @@ pub enum TokenKind {
     Static,
     Final,
     Prop,
-    Interface, Implements
+    Interface, Implements,
+    Match,
+    FatArrow, // =>
+    Ellipsis, // ...
+}
+
+impl TokenKind {
+    /// Texte source du token pour les mots-clés et la ponctuation.
+    pub fn symbol(&self) -> Option<&'static str> {
+        use TokenKind::*;
+        let s = match self {
+            True => "true", False => "false", Null => "null",
+            Try => "try", Catch => "catch", Throw => "throw",
+            Var => "var", If => "if", Else => "else", While => "while", Func => "func",
+            Return => "return", Yield => "yield", Print => "print", Input => "input",
+            Async => "async", Await => "await",
+            Class => "class", New => "new", Extends => "extends", Enum => "enum",
+            Import => "import", Export => "export", Break => "break", Continue => "continue",
+            Switch => "switch", Case => "case", Default => "default",
+            Plus => "+", Minus => "-", Star => "*", Slash => "/", Percent => "%",
+            Eq => "=", EqEq => "==", Neq => "!=", Lt => "<", Gt => ">", LtEq => "<=", GtEq => ">=",
+            And => "&&", Or => "||", Bang => "!",
+            LParen => "(", RParen => ")", LBrace => "{", RBrace => "}", LBracket => "[", RBracket => "]",
+            Comma => ",", Dot => ".", Colon => ":",
+            PlusEq => "+=", MinusEq => "-=", StarEq => "*=", SlashEq => "/=",
+            PlusPlus => "++", MinusMinus => "--",
+            Namespace => "namespace",
+            BitAnd => "&", BitOr => "|", BitXor => "^", ShiftLeft => "<<", ShiftRight => ">>",
+            At => "@", Arrow => "->", Super => "super",
+            Question => "?", DoubleQuestion => "??",
+            Const => "const", ForEach => "foreach", In => "in", DotDot => "..",
+            Public => "public", Protected => "protected", Private => "private",
+            Static => "static", Final => "final", Prop => "prop",
+            Interface => "interface", Implements => "implements",
+            Match => "match", FatArrow => "=>", Ellipsis => "...",
+            Identifier(_) | StringLiteral(_) | Integer(_) | Float(_) | EOF => return None,
+        };
+        Some(s)
+    }
+
+    /// Description lisible utilisée dans les messages d'erreur ("'{'", "identifier 'x'"...).
+    pub fn describe(&self) -> String {
+        match self {
+            TokenKind::Identifier(n) => format!("identifier '{}'", n),
+            TokenKind::StringLiteral(_) => "string literal".to_string(),
+            TokenKind::Integer(n) => format!("number {}", n),
+            TokenKind::Float(f) => format!("number {}", f),
+            TokenKind::EOF => "end of file".to_string(),
+            other => format!("'{}'", other.symbol().unwrap_or("?")),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
-    pub line: usize
+    pub line: usize,
+    pub column: usize,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriviaKind {
+    Shebang,
+    LineComment,
+    BlockComment,
+    /// Chaîne `...` complète : le formateur la recopie telle quelle
+    Template,
+}
+
+/// Texte ignoré par le parser mais conservé pour le formateur (`aegis fmt`).
+#[derive(Debug, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub line: usize,
+    pub span: Span,
+}
+
+// Position dans le source : (ligne, colonne, offset en octets)
+#[derive(Debug, Clone, Copy)]
+struct Mark {
+    line: usize,
+    column: usize,
+    offset: usize,
 }
 
 pub struct Lexer<'a> {
     chars: Peekable<Chars<'a>>,
-    line: usize
+    line: usize,
+    column: usize,
+    offset: usize,
+    start: Mark,
+    // Collecte des commentaires, uniquement en mode `with_trivia`
+    trivia: Option<Vec<Trivia>>,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
         Lexer { 
             chars: input.chars().peekable(),
-            line: 1 
+            line: 1,
+            column: 1,
+            offset: 0,
+            start: Mark { line: 1, column: 1, offset: 0 },
+            trivia: None,
         }
     }
 
+    /// Conserve les commentaires, le shebang et les chaînes `...` (voir `take_trivia`).
+    pub fn with_trivia(mut self) -> Self {
+        self.trivia = Some(Vec::new());
+        self
+    }
+
+    pub fn take_trivia(&mut self) -> Vec<Trivia> {
+        self.trivia.take().unwrap_or_default()
+    }
+
+    fn push_trivia(&mut self, kind: TriviaKind, start: Mark) {
+        let span = Span::new(start.offset, self.offset);
+        if let Some(trivia) = &mut self.trivia {
+            trivia.push(Trivia { kind, line: start.line, span });
+        }
+    }
+
+    // Avance d'un caractère en tenant à jour ligne, colonne et offset
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+
+    fn mark(&self) -> Mark {
+        Mark { line: self.line, column: self.column, offset: self.offset }
+    }
+
+    fn token_at(&self, kind: TokenKind, start: Mark) -> Token {
+        Token {
+            kind,
+            line: start.line,
+            column: start.column,
+            span: Span::new(start.offset, self.offset),
+        }
+    }
+
+    fn error_at(&self, message: impl Into<String>, start: Mark) -> Diagnostic {
+        Diagnostic::new(message, start.line, start.column, Span::new(start.offset, self.offset))
+    }
+
     fn add_token(&self, tokens: &mut Vec<Token>, kind: TokenKind) {
-        tokens.push(Token { kind, line: self.line });
+        tokens.push(self.token_at(kind, self.start));
     }
 
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, Diagnostic> {
         self.handle_shebang();
 
         let mut tokens = Vec::new();
         while self.chars.peek().is_some() {
             // On utilise scan_token pour lire le prochain élément
-            if let Err(e) = self.scan_token(&mut tokens) {
-                // En cas d'erreur (ex: string non fermée), on panic pour l'instant
-                // Idéalement, il faudrait retourner un Result<Vec<Token>, String>
-                panic!("Lexer error: {}", e);
-            }
+            self.scan_token(&mut tokens)?;
         }
+        self.start = self.mark();
         self.add_token(&mut tokens, TokenKind::EOF);
-        tokens
+        Ok(tokens)
     }
 
     // Extrait la logique de lecture d'un token unique pour pouvoir la réutiliser
-    fn scan_token(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
+    fn scan_token(&mut self, tokens: &mut Vec<Token>) -> Result<(), Diagnostic> {
+        self.start = self.mark();
         if let Some(&c) = self.chars.peek() {
             match c {
                 '\n' => {
-                    self.line += 1;
-                    self.chars.next();
+                    self.bump();
                 }
-                ' ' | '\t' | '\r' => { self.chars.next(); }
+                ' ' | '\t' | '\r' => { self.bump(); }
                 '/' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'/') = self.chars.peek() {
                         while let Some(&c) = self.chars.peek() {
                             if c == '\n' { break; }
-                            self.chars.next();
+                            self.bump();
                         }
+                        self.push_trivia(TriviaKind::LineComment, self.start);
                     }
                     else if let Some(&'*') = self.chars.peek() {
-                        self.chars.next(); // Consomme '*'
+                        self.bump(); // Consomme '*'
                         self.skip_multiline_comment()?;
+                        self.push_trivia(TriviaKind::BlockComment, self.start);
                     }
                     else if let Some(&'=') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::SlashEq);
                     } 
                     else { 
@@ -106,51 +241,56 @@ impl<'a> Lexer<'a> {
                     }
                 }
                 '{' => {
-                    self.add_token(tokens, TokenKind::LBrace);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::LBrace); 
                 }
                 '}' => { 
-                    self.add_token(tokens, TokenKind::RBrace);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::RBrace); 
                 }
                 '(' => {
-                    self.add_token(tokens, TokenKind::LParen);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::LParen); 
                 }
                 ')' => {
-                    self.add_token(tokens, TokenKind::RParen);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::RParen); 
                 }
                 '[' => { 
-                    self.add_token(tokens, TokenKind::LBracket);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::LBracket); 
                 }
                 ']' => { 
-                    self.add_token(tokens, TokenKind::RBracket);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::RBracket); 
                 }
                 ',' => {
-                    self.add_token(tokens, TokenKind::Comma);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::Comma); 
                 }
                 '.' => { 
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'.') = self.chars.peek() {
-                        self.chars.next();
-                        self.add_token(tokens, TokenKind::DotDot);
+                        self.bump();
+                        if let Some(&'.') = self.chars.peek() {
+                            self.bump();
+                            self.add_token(tokens, TokenKind::Ellipsis);
+                        } else {
+                            self.add_token(tokens, TokenKind::DotDot);
+                        }
                     }
                     else {
                         self.add_token(tokens, TokenKind::Dot);
                     }
                 }
                 ':' => {
-                    self.add_token(tokens, TokenKind::Colon);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::Colon); 
                 }
                 '?' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'?') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::DoubleQuestion);
                     }
                     else {
@@ -158,13 +298,13 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 '+' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'=') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::PlusEq);
                     } 
                     else if let Some(&'+') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::PlusPlus);
                     } 
                     else {
@@ -172,17 +312,17 @@ impl<'a> Lexer<'a> {
                     }
                 }
                 '-' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'=') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::MinusEq);
                     } 
                     else if let Some(&'-') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::MinusMinus);
                     } 
                     else if let Some(&'>') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::Arrow);
                     }
                     else {
@@ -190,9 +330,9 @@ impl<'a> Lexer<'a> {
                     }
                 }
                 '*' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'=') = self.chars.peek() {
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::StarEq);
                     } 
                     else {
@@ -200,27 +340,31 @@ impl<'a> Lexer<'a> {
                     }
                 }
                 '%' => { 
-                    self.add_token(tokens, TokenKind::Percent);
-                    self.chars.next(); 
+                    self.bump();
+                    self.add_token(tokens, TokenKind::Percent); 
                 }
                 '=' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'=') = self.chars.peek() { 
-                        self.chars.next(); 
+                        self.bump(); 
                         self.add_token(tokens, TokenKind::EqEq);
                     } 
+                    else if let Some(&'>') = self.chars.peek() {
+                        self.bump();
+                        self.add_token(tokens, TokenKind::FatArrow);
+                    }
                     else { 
                         self.add_token(tokens, TokenKind::Eq);
                     }
                 }
                 '<' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'=') = self.chars.peek() { 
-                        self.chars.next(); 
+                        self.bump(); 
                         self.add_token(tokens, TokenKind::LtEq);
                     }
                     else if let Some(&'<') = self.chars.peek() { 
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::ShiftLeft);
                     }
                     else { 
@@ -228,13 +372,13 @@ impl<'a> Lexer<'a> {
                     }
                 }
                 '>' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'=') = self.chars.peek() { 
-                        self.chars.next(); 
+                        self.bump(); 
                         self.add_token(tokens, TokenKind::GtEq);
                     }
                     else if let Some(&'>') = self.chars.peek() { 
-                        self.chars.next();
+                        self.bump();
                         self.add_token(tokens, TokenKind::ShiftRight);
                     }
                     else { 
@@ -242,9 +386,9 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 '&' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'&') = self.chars.peek() { 
-                        self.chars.next(); 
+                        self.bump(); 
                         self.add_token(tokens, TokenKind::And);
                     }
                     else {
@@ -252,9 +396,9 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 '|' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'|') = self.chars.peek() { 
-                        self.chars.next(); 
+                        self.bump(); 
                         self.add_token(tokens, TokenKind::Or);
                     }
                     else {
@@ -262,13 +406,13 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 '^' => {
-                    self.chars.next();
+                    self.bump();
                     self.add_token(tokens, TokenKind::BitXor);
                 },
                 '!' => {
-                    self.chars.next();
+                    self.bump();
                     if let Some(&'=') = self.chars.peek() { 
-                        self.chars.next(); 
+                        self.bump(); 
                         self.add_token(tokens, TokenKind::Neq);
                     }
                     else { 
@@ -276,16 +420,19 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 '@' => {
-                    self.chars.next();
+                    self.bump();
                     self.add_token(tokens, TokenKind::At);
                 }
                 '"' => {
-                    let token = self.read_string();
+                    let token = self.read_string()?;
                     tokens.push(token);
                 },
                 '`' => {
-                    self.chars.next(); // On consomme le backtick d'ouverture
+                    // self.start est modifié pendant la lecture des interpolations
+                    let start = self.start;
+                    self.bump(); // On consomme le backtick d'ouverture
                     self.read_multiline_string(tokens)?;
+                    self.push_trivia(TriviaKind::Template, start);
                 },
                 c if c.is_digit(10) => {
                     let token = self.read_number();
@@ -295,28 +442,29 @@ impl<'a> Lexer<'a> {
                     let token = self.read_identifier();
                     tokens.push(token);
                 },
-                _ => return Err(format!("Unexpected char '{}' at line {}", c, self.line)),
+                _ => {
+                    self.bump();
+                    return Err(self.error_at(format!("Unexpected character '{}'", c), self.start));
+                }
             }
         }
         Ok(())
     }
 
-    fn read_string(&mut self) -> Token {
-        self.chars.next(); // On consomme le guillemet ouvrant "
+    fn read_string(&mut self) -> Result<Token, Diagnostic> {
+        let start = self.mark();
+        self.bump(); // On consomme le guillemet ouvrant "
         let mut s = String::new();
         
         while let Some(&c) = self.chars.peek() {
             match c {
                 '"' => { 
-                    self.chars.next(); // On consomme le guillemet fermant "
-                    return Token {
-                        kind: TokenKind::StringLiteral(s), 
-                        line: self.line
-                    };
+                    self.bump(); // On consomme le guillemet fermant "
+                    return Ok(self.token_at(TokenKind::StringLiteral(s), start));
                 },
                 '\\' => {
-                    self.chars.next(); // On consomme le \
-                    if let Some(escaped) = self.chars.next() {
+                    self.bump(); // On consomme le \
+                    if let Some(escaped) = self.bump() {
                         match escaped {
                             'n' => s.push('\n'),
                             'r' => s.push('\r'),
@@ -328,19 +476,20 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 _ => {
-                    s.push(self.chars.next().unwrap());
+                    s.push(self.bump().unwrap());
                 }
             }
         }
-        panic!("Unterminated string at line {}", self.line);
+        Err(self.error_at("Unterminated string", start))
     }
 
     fn read_number(&mut self) -> Token {
+        let start = self.mark();
         let mut s = String::new();
         let mut has_dot = false;
         while let Some(&c) = self.chars.peek() {
             if c.is_digit(10) { 
-                s.push(self.chars.next().unwrap()); 
+                s.push(self.bump().unwrap()); 
             } 
             else if c == '.' && !has_dot {
                 let mut lookahead = self.chars.clone();
@@ -353,7 +502,7 @@ impl<'a> Lexer<'a> {
                 }
 
                 has_dot = true; 
-                s.push(self.chars.next().unwrap()); 
+                s.push(self.bump().unwrap()); 
             } 
             else { 
                 break; 
@@ -367,15 +516,16 @@ impl<'a> Lexer<'a> {
             TokenKind::Integer(s.parse().unwrap_or(0))
         };
 
-        Token { kind, line: self.line }
+        self.token_at(kind, start)
     }
 
     fn read_identifier(&mut self) -> Token {
+        let start = self.mark();
         let mut s = String::new();
 
         while let Some(&c) = self.chars.peek() {
             if c.is_alphanumeric() || c == '_' { 
-                s.push(self.chars.next().unwrap()); 
+                s.push(self.bump().unwrap()); 
             } 
             else { 
                 break; 
@@ -389,14 +539,19 @@ impl<'a> Lexer<'a> {
             "while" => TokenKind::While,
             "func" => TokenKind::Func, 
             "return" => TokenKind::Return, 
+            "yield" => TokenKind::Yield,
+            "async" => TokenKind::Async,
+            "await" => TokenKind::Await,
             "print" => TokenKind::Print,
             "input" => TokenKind::Input, 
             "class" => TokenKind::Class, 
             "new" => TokenKind::New, 
             "extends" => TokenKind::Extends,
             "import" => TokenKind::Import, 
+            "export" => TokenKind::Export,
             "break" => TokenKind::Break, 
             "switch" => TokenKind::Switch, 
+            "match" => TokenKind::Match,
             "case" => TokenKind::Case, 
             "default" => TokenKind::Default,
             "true" => TokenKind::True,
@@ -423,7 +578,7 @@ impl<'a> Lexer<'a> {
             _ => TokenKind::Identifier(s),
         };
 
-        Token { kind, line: self.line }
+        self.token_at(kind, start)
     }
 
     fn handle_shebang(&mut self) {
@@ -432,69 +587,72 @@ impl<'a> Lexer<'a> {
         if let Some('#') = lookahead.next() {
             if let Some('!') = lookahead.next() {
                 // C'est un shebang ! On consomme la vraie ligne.
+                let start = self.mark();
                 while let Some(&c) = self.chars.peek() {
                     if c == '\n' { break; } 
-                    self.chars.next();
+                    self.bump();
                 }
+                self.push_trivia(TriviaKind::Shebang, start);
             }
         }
     }
 
-    fn skip_multiline_comment(&mut self) -> Result<(), String> {
-        while let Some(c) = self.chars.next() {
-            if c == '*' {
-                if let Some('/') = self.chars.peek() {
-                    self.chars.next(); // Consomme '/'
-                    return Ok(()); // Fin du commentaire
-                }
-            } else if c == '\n' {
-                self.line += 1;
+    fn skip_multiline_comment(&mut self) -> Result<(), Diagnostic> {
+        let start = self.start;
+        while let Some(c) = self.bump() {
+            if c == '*' && self.chars.peek() == Some(&'/') {
+                self.bump(); // Consomme '/'
+                return Ok(()); // Fin du commentaire
             }
         }
         
-        Err(format!("Unterminated block comment at line {}", self.line))
+        Err(self.error_at("Unterminated block comment", start))
     }
 
-    fn read_multiline_string(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
+    fn read_multiline_string(&mut self, tokens: &mut Vec<Token>) -> Result<(), Diagnostic> {
+        let start = self.start;
+        let mut segment = start;
         let mut string_content = String::new();
         
         while let Some(&c) = self.chars.peek() {
             match c {
                 '`' => { // Fin de la chaîne
-                    self.chars.next();
-                    self.add_token(tokens, TokenKind::StringLiteral(string_content));
+                    self.bump();
+                    tokens.push(self.token_at(TokenKind::StringLiteral(string_content), segment));
                     return Ok(());
                 },
                 '\n' => { // Saut de ligne autorisé
-                    self.chars.next();
+                    self.bump();
                     string_content.push('\n');
-                    self.line += 1;
                 },
                 '$' => { 
-                    self.chars.next();
+                    self.bump();
                     if let Some('{') = self.chars.peek() {
                         // C'est une interpolation ${...}
-                        self.chars.next(); // Mange '{'
+                        self.bump(); // Mange '{'
                         
                         // 1. On push ce qu'on a lu jusqu'ici
-                        self.add_token(tokens, TokenKind::StringLiteral(string_content.clone()));
+                        tokens.push(self.token_at(TokenKind::StringLiteral(string_content.clone()), segment));
                         string_content.clear();
                         
                         // 2. On ajoute un '+'
+                        self.start = self.mark();
                         self.add_token(tokens, TokenKind::Plus);
                         
                         // 3. On lit l'expression intérieure
                         self.read_interpolated_expression(tokens)?;
                         
                         // 4. Au retour, on ajoute un autre '+'
+                        self.start = self.mark();
                         self.add_token(tokens, TokenKind::Plus);
+                        segment = self.mark();
                     } else {
                         string_content.push('$');
                     }
                 },
                 '\\' => { 
-                    self.chars.next();
-                    if let Some(escaped) = self.chars.next() {
+                    self.bump();
+                    if let Some(escaped) = self.bump() {
                         match escaped {
                             'n' => string_content.push('\n'),
                             't' => string_content.push('\t'),
@@ -506,27 +664,29 @@ impl<'a> Lexer<'a> {
                     }
                 },
                 _ => {
-                    self.chars.next();
+                    self.bump();
                     string_content.push(c);
                 }
             }
         }
 
-        Err(format!("Unterminated string literal starting at line {}", self.line))
+        Err(self.error_at("Unterminated string literal", start))
     }
 
     // NOUVELLE MÉTHODE : Lit une expression à l'intérieur de ${...}
-    fn read_interpolated_expression(&mut self, tokens: &mut Vec<Token>) -> Result<(), String> {
+    fn read_interpolated_expression(&mut self, tokens: &mut Vec<Token>) -> Result<(), Diagnostic> {
+        let open = self.mark();
         let mut balance = 1; // On a déjà consommé le '{' ouvrant
 
         while balance > 0 {
             if self.chars.peek().is_none() {
-                return Err("Unclosed string interpolation".to_string());
+                return Err(self.error_at("Unclosed string interpolation", open));
             }
 
             // Gestion manuelle des accolades pour l'imbrication
+            self.start = self.mark();
             if let Some(&'}') = self.chars.peek() {
-                self.chars.next();
+                self.bump();
                 balance -= 1;
                 if balance == 0 {
                     return Ok(()); // Fin de l'interpolation
@@ -536,7 +696,7 @@ impl<'a> Lexer<'a> {
             }
             
             if let Some(&'{') = self.chars.peek() {
-                self.chars.next();
+                self.bump();
                 balance += 1;
                 self.add_token(tokens, TokenKind::LBrace);
                 continue;
@@ -1,13 +1,18 @@
+pub mod diagnostic;
+pub mod formatter;
 pub mod lexer;
 pub mod parser;
 
-use serde_json::Value as JsonValue;
+use crate::ast::Statement;
+pub use diagnostic::{Diagnostic, Span};
 use lexer::Lexer;
 use parser::Parser;
 
-pub fn compile(source: &str) -> Result<JsonValue, String> {
+/// Compile le source en AST. Renvoie toutes les erreurs de syntaxe trouvées
+/// (une erreur du lexer arrête l'analyse immédiatement).
+pub fn compile(source: &str) -> Result<Vec<Statement>, Vec<Diagnostic>> {
     let mut lexer = Lexer::new(source);
-    let tokens = lexer.tokenize();
+    let tokens = lexer.tokenize().map_err(|e| vec![e])?;
     let mut parser = Parser::new(tokens);
     parser.parse()
 }
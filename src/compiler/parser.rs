@@ -1,22 +1,107 @@
+use std::collections::HashMap;
+
+use super::diagnostic::Diagnostic;
 use super::lexer::{ Token, TokenKind };
-use serde_json::{json, Value};
+use crate::ast::nodes::{ClassField, ClassProperty, InterfaceDefinition, InterfaceMethod, Param, Params};
+use crate::ast::value::Visibility;
+use crate::ast::{ClassDefinition, EnumDefinition, EnumVariant, Expression, Instruction, MatchArm, Pattern, Statement, Value};
+
+type BinaryOp = fn(Box<Expression>, Box<Expression>) -> Expression;
 
 pub struct Parser {
     tokens: Vec<Token>,
     pos: usize,
+    errors: Vec<Diagnostic>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, pos: 0 }
+        Parser { tokens, pos: 0, errors: Vec::new() }
     }
 
-    pub fn parse(&mut self) -> Result<Value, String> {
+    /// Parse tout le fichier. En cas d'erreur, le parser se resynchronise
+    /// au début de l'instruction suivante et renvoie toutes les erreurs rencontrées.
+    pub fn parse(&mut self) -> Result<Vec<Statement>, Vec<Diagnostic>> {
         let mut instructions = Vec::new();
         while !self.is_at_end() {
-            instructions.push(self.parse_statement()?);
+            // `export` n'a de sens qu'au niveau du module
+            let stmt = if self.check(&TokenKind::Export) {
+                self.recover(Parser::parse_export)
+            } else {
+                self.parse_statement_or_recover()
+            };
+            match stmt {
+                Some(stmt) => instructions.push(stmt),
+                // Une '}' orpheline ici ferme le bloc de l'instruction en erreur
+                None => { self.match_token(TokenKind::RBrace); }
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(instructions)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    // --- Error recovery ---
+
+    fn parse_statement_or_recover(&mut self) -> Option<Statement> {
+        self.recover(Parser::parse_statement)
+    }
+
+    fn recover(&mut self, parse: fn(&mut Parser) -> Result<Statement, Diagnostic>) -> Option<Statement> {
+        let start = self.pos;
+        match parse(self) {
+            Ok(stmt) => Some(stmt),
+            Err(e) => {
+                self.errors.push(e);
+                // On avance d'au moins un token pour ne pas boucler sur la même erreur
+                // (un '{' sera sauté en entier par skip_to_boundary)
+                if self.pos == start && !self.check(&TokenKind::LBrace) {
+                    self.advance();
+                }
+                self.skip_to_boundary(Parser::at_statement_boundary);
+                None
+            }
+        }
+    }
+
+    // Mode panique : on saute les tokens jusqu'à une frontière (début d'instruction
+    // ou de membre, ou la '}' du bloc englobant). Les blocs { ... } rencontrés
+    // en route sont sautés en entier.
+    fn skip_to_boundary(&mut self, at_boundary: fn(&Parser) -> bool) {
+        let mut depth = 0usize;
+        while !self.is_at_end() {
+            match self.peek() {
+                TokenKind::LBrace => depth += 1,
+                TokenKind::RBrace if depth == 0 => return,
+                TokenKind::RBrace => depth -= 1,
+                _ if depth == 0 && at_boundary(self) => return,
+                _ => {}
+            }
+            self.advance();
+        }
+    }
+
+    fn at_statement_boundary(&self) -> bool {
+        match self.peek() {
+            TokenKind::Var | TokenKind::Const | TokenKind::Class | TokenKind::Final
+            | TokenKind::Enum | TokenKind::Interface | TokenKind::Namespace
+            | TokenKind::Import | TokenKind::Export | TokenKind::If | TokenKind::While | TokenKind::ForEach
+            | TokenKind::Return | TokenKind::Yield | TokenKind::Try | TokenKind::Switch | TokenKind::Print
+            | TokenKind::At => true,
+            // 'func' commence une instruction seulement s'il est suivi d'un nom (sinon c'est une lambda)
+            TokenKind::Func => matches!(self.peek_next(), TokenKind::Identifier(_)),
+            TokenKind::Async => matches!(self.peek_next(), TokenKind::Func),
+            _ => self.at_from_import(),
         }
-        Ok(json!(instructions))
+    }
+
+    fn at_member_boundary(&self) -> bool {
+        matches!(self.peek(),
+            TokenKind::Func | TokenKind::Async | TokenKind::Var | TokenKind::Prop | TokenKind::Static | TokenKind::Final
+            | TokenKind::Public | TokenKind::Private | TokenKind::Protected)
     }
 
     // --- Helpers ---
@@ -25,6 +110,11 @@ impl Parser {
         &self.tokens[self.pos].kind
     }
 
+    fn peek_next(&self) -> &TokenKind {
+        let idx = (self.pos + 1).min(self.tokens.len() - 1);
+        &self.tokens[idx].kind
+    }
+
     fn current_line(&self) -> usize {
         if self.is_at_end() {
             if !self.tokens.is_empty() {
@@ -62,17 +152,47 @@ impl Parser {
         self.peek() == &TokenKind::EOF
     }
 
-    fn consume(&mut self, expected: TokenKind, msg: &str) -> Result<&Token, String> {
+    // `context` complète le message : "Expected '{' before block, found 'print'"
+    fn consume(&mut self, expected: TokenKind, context: &str) -> Result<&Token, Diagnostic> {
         if self.check(&expected) {
             Ok(self.advance())
         } else {
-            Err(format!("{} (Line {})", msg, self.current_line()))
+            let place = if context.is_empty() { String::new() } else { format!(" {}", context) };
+            Err(self.error_at_current(format!("Expected {}{}, found {}", expected.describe(), place, self.peek().describe())))
         }
     }
 
+    fn expect_identifier(&mut self, what: &str) -> Result<String, Diagnostic> {
+        if let TokenKind::Identifier(name) = self.peek() {
+            let name = name.clone();
+            self.advance();
+            Ok(name)
+        } else {
+            Err(self.error_at_current(format!("Expected {}, found {}", what, self.peek().describe())))
+        }
+    }
+
+    fn error_at(&self, token: &Token, msg: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(msg, token.line, token.column, token.span)
+    }
+
+    // Erreur sur le token qui n'a pas encore été consommé
+    fn error_at_current(&self, msg: impl Into<String>) -> Diagnostic {
+        self.error_at(&self.tokens[self.pos], msg)
+    }
+
+    // Erreur sur le dernier token consommé (ex: après un advance())
+    fn error_at_previous(&self, msg: impl Into<String>) -> Diagnostic {
+        self.error_at(&self.tokens[self.pos.saturating_sub(1)], msg)
+    }
+
+    fn stmt(kind: Instruction, line: usize) -> Statement {
+        Statement { kind, line }
+    }
+
     // --- Statements ---
 
-    fn parse_statement(&mut self) -> Result<Value, String> {
+    fn parse_statement(&mut self) -> Result<Statement, Diagnostic> {
         match self.peek() {
             TokenKind::At => self.parse_decorated_function(),
             TokenKind::Var => self.parse_var(),
@@ -80,21 +200,26 @@ impl Parser {
             TokenKind::If => self.parse_if(),
             TokenKind::While => self.parse_while(),
             TokenKind::Func => self.parse_func(),
+            // async func nom() {...} ; `async func() {...}` reste une expression (lambda)
+            TokenKind::Async if self.tokens.get(self.pos + 2).is_some_and(|t| matches!(t.kind, TokenKind::Identifier(_))) => self.parse_func(),
             TokenKind::Class | TokenKind::Final => self.parse_class(),
             TokenKind::Enum => self.parse_enum(),
             TokenKind::Return => self.parse_return(),
+            TokenKind::Yield => self.parse_yield(),
             TokenKind::Input => self.parse_input(),
-            TokenKind::Break => { 
+            TokenKind::Break => {
                 let line = self.current_line();
-                self.advance(); 
-                Ok(json!(["break", line])) 
+                self.advance();
+                Ok(Self::stmt(Instruction::Break, line))
             },
             TokenKind::Continue => {
                 let line = self.current_line();
-                self.advance(); 
-                Ok(json!(["continue", line])) 
+                self.advance();
+                Ok(Self::stmt(Instruction::Continue, line))
             },
             TokenKind::Import => self.parse_import(),
+            TokenKind::Export => Err(self.error_at_current("'export' is only allowed at the top level of a module")),
+            TokenKind::Identifier(_) if self.at_from_import() => self.parse_from_import(),
             TokenKind::Try => self.parse_try(),
             TokenKind::Throw => self.parse_throw(),
             TokenKind::Switch => self.parse_switch(),
@@ -102,945 +227,970 @@ impl Parser {
             TokenKind::Const => self.parse_const(),
             TokenKind::ForEach => self.parse_foreach(),
             TokenKind::Interface => self.parse_interface(),
-            
+
             // --- GESTION DES EXPRESSIONS ET ASSIGNATIONS ---
-            TokenKind::Identifier(_) | TokenKind::Super | TokenKind::LParen => {
+            TokenKind::Identifier(_) | TokenKind::Super | TokenKind::LParen | TokenKind::Match | TokenKind::Await => {
                 let line = self.current_line();
+                let target_start = self.pos;
                 // 1. On parse la partie gauche (ex: "x", "obj.prop", "list.at(0)")
                 let expr = self.parse_expression()?;
 
-                match self.peek() {
+                // Sucre des assignations composées : x += v  =>  x = x + v
+                let compound: Option<BinaryOp> = match self.peek() {
+                    TokenKind::PlusEq | TokenKind::PlusPlus => Some(Expression::Add),
+                    TokenKind::MinusEq | TokenKind::MinusMinus => Some(Expression::Sub),
+                    TokenKind::StarEq => Some(Expression::Mul),
+                    TokenKind::SlashEq => Some(Expression::Div),
+                    _ => None,
+                };
+
+                match (self.peek(), compound) {
                     // Assignation standard (=)
-                    TokenKind::Eq => {
+                    (TokenKind::Eq, _) => {
                         self.advance();
                         let value = self.parse_expression()?;
-                        return self.convert_to_assignment(line, expr, value);
-                    },
-                    
-                    // Assignations composées (+=, -=, *=, /=)
-                    TokenKind::PlusEq => {
-                        self.advance();
-                        let value = self.parse_expression()?;
-                        // Sucre : x = x + val
-                        let operation = json!(["+", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
-                    },
-                    TokenKind::MinusEq => {
-                        self.advance();
-                        let value = self.parse_expression()?;
-                        // Sucre : x = x - val
-                        let operation = json!(["-", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
-                    },
-                    TokenKind::StarEq => {
-                        self.advance();
-                        let value = self.parse_expression()?;
-                        // Sucre : x = x * val
-                        let operation = json!(["*", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
-                    },
-                    TokenKind::SlashEq => {
-                        self.advance();
-                        let value = self.parse_expression()?;
-                        // Sucre : x = x / val
-                        let operation = json!(["/", expr.clone(), value]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        self.convert_to_assignment(line, target_start, expr, value)
                     },
 
                     // Incrémentation / Décrémentation (++, --)
-                    TokenKind::PlusPlus => {
+                    (TokenKind::PlusPlus | TokenKind::MinusMinus, Some(op)) => {
                         self.advance();
-                        let one = json!(1);
-                        // Sucre : x = x + 1
-                        let operation = json!(["+", expr.clone(), one]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        let one = Expression::Literal(Value::Integer(1));
+                        let operation = op(Box::new(expr.clone()), Box::new(one));
+                        self.convert_to_assignment(line, target_start, expr, operation)
                     },
-                    TokenKind::MinusMinus => {
+
+                    // Assignations composées (+=, -=, *=, /=)
+                    (_, Some(op)) => {
                         self.advance();
-                        let one = json!(1);
-                        // Sucre : x = x - 1
-                        let operation = json!(["-", expr.clone(), one]);
-                        return self.convert_to_assignment(line, expr, operation);
+                        let value = self.parse_expression()?;
+                        let operation = op(Box::new(expr.clone()), Box::new(value));
+                        self.convert_to_assignment(line, target_start, expr, operation)
                     },
 
                     // Expression seule (appel de fonction, etc.)
-                    _ => {
-                        // Hack pour ajouter la ligne aux appels pour les stacktraces
-                        if let Some(arr) = expr.as_array() {
-                            let mut new_arr = arr.clone();
-                            if !new_arr.is_empty() {
-                                if let Some(cmd) = new_arr[0].as_str() {
-                                    if cmd == "call" || cmd == "call_method" || cmd == "super_call" {
-                                        // On insère le numéro de ligne à l'index 1 si pas déjà fait
-                                        // (Dépend de ta structure JSON, mais ton code précédent faisait ça)
-                                        if new_arr.len() > 1 && !new_arr[1].is_u64() {
-                                             new_arr.insert(1, json!(line));
-                                        }
-                                        return Ok(Value::Array(new_arr));
-                                    }
-                                }
-                            }
-                        }
-                        Ok(expr) 
+                    _ => match expr {
+                        Expression::Call(..) | Expression::CallMethod(..) | Expression::SuperCall(..) | Expression::Match(..) | Expression::Await(..) => {
+                            Ok(Self::stmt(Instruction::ExpressionStatement(expr), line))
+                        },
+                        _ => Err(self.error_at(&self.tokens[target_start], "Expected an assignment or a call, found an unused expression")),
                     }
                 }
             },
-            
-            _ => Err(format!("Unexpected token at start of statement: {:?} (Line {})", self.peek(), self.current_line())),
+
+            other => Err(self.error_at_current(format!("Expected statement, found {}", other.describe()))),
         }
     }
 
-    fn convert_to_assignment(&self, line: usize, target: Value, value: Value) -> Result<Value, String> {
-        if let Some(arr) = target.as_array() {
-            let cmd = arr[0].as_str().unwrap_or("");
-            
-            if cmd == "get" {
-                let name = &arr[1];
-                return Ok(json!(["set", line, name, null, value]));
-            }
-            if cmd == "get_attr" {
-                let obj = &arr[1];
-                let attr = &arr[2];
-                return Ok(json!(["set_attr", line, obj, attr, value]));
-            }
+    fn convert_to_assignment(&self, line: usize, target_start: usize, target: Expression, value: Expression) -> Result<Statement, Diagnostic> {
+        match target {
+            Expression::Variable(name) => Ok(Self::stmt(Instruction::Set(name, None, value), line)),
+            Expression::GetAttr(obj, attr) => Ok(Self::stmt(Instruction::SetAttr(obj, attr, value), line)),
+            Expression::Index(obj, key) => Ok(Self::stmt(Instruction::SetIndex(obj, *key, value), line)),
+            _ => Err(self.error_at(&self.tokens[target_start], "Invalid assignment target")),
         }
-        Err(format!("Invalid assignment target (Line {})", line))
     }
 
-    fn parse_block(&mut self) -> Result<Value, String> {
-        self.consume(TokenKind::LBrace, "Expect '{' before block")?;
+    fn parse_block(&mut self) -> Result<Vec<Statement>, Diagnostic> {
+        self.consume(TokenKind::LBrace, "before block")?;
         let mut block = Vec::new();
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
-            block.push(self.parse_statement()?);
+            if let Some(stmt) = self.parse_statement_or_recover() {
+                block.push(stmt);
+            }
         }
-        self.consume(TokenKind::RBrace, "Expect '}' after block")?;
-        Ok(json!(block))
+        self.consume(TokenKind::RBrace, "after block")?;
+        Ok(block)
     }
 
-    fn parse_var(&mut self) -> Result<Value, String> {
+    fn parse_var(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
-        self.advance(); 
+        self.advance();
 
         if self.match_token(TokenKind::LBracket) {
             let mut vars = Vec::new();
             if !self.check(&TokenKind::RBracket) {
                 loop {
-                    if let TokenKind::Identifier(n) = &self.advance().kind {
-                        vars.push(n.clone());
-                    } else {
-                        return Err(format!("Expect variable name in destructuring (Line {})", line));
-                    }
+                    vars.push(self.expect_identifier("variable name in destructuring pattern")?);
                     if !self.match_token(TokenKind::Comma) { break; }
                 }
             }
-            self.consume(TokenKind::RBracket, "Expect ']'")?;
-            self.consume(TokenKind::Eq, "Expect '='")?;
-            
+            self.consume(TokenKind::RBracket, "to close destructuring pattern")?;
+            self.consume(TokenKind::Eq, "after destructuring pattern")?;
+
             let expr = self.parse_expression()?;
-            
+
             let mut instructions = Vec::new();
-            let temp_name = format!("__destruct_temp_{}", vars.len()); 
-            
-            instructions.push(json!(["set", line, temp_name, null, expr]));
-            
-            for (i, var_name) in vars.iter().enumerate() {
-                let access = json!([
-                    "call_method", 
-                    ["get", temp_name], 
-                    "at", 
-                    [json!(i as i64)]
-                ]);
-                instructions.push(json!(["set", line, var_name, null, access]));
+            let temp_name = format!("__destruct_temp_{}", vars.len());
+
+            instructions.push(Self::stmt(Instruction::Set(temp_name.clone(), None, expr), line));
+
+            for (i, var_name) in vars.into_iter().enumerate() {
+                let access = Expression::CallMethod(
+                    Box::new(Expression::Variable(temp_name.clone())),
+                    "at".to_string(),
+                    vec![Expression::Literal(Value::Integer(i as i64))]
+                );
+                instructions.push(Self::stmt(Instruction::Set(var_name, None, access), line));
             }
-            
-            return Ok(json!(["if", line, json!(true), instructions]));
+
+            // Bloc "if (true)" pour regrouper les affectations en une seule instruction
+            return Ok(Self::stmt(Instruction::If {
+                condition: Expression::Literal(Value::Boolean(true)),
+                body: instructions,
+                else_body: vec![]
+            }, line));
         }
 
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Expect var name".into()); };
-        let type_annot = self.parse_type_annotation()?; 
-        let expr = if self.match_token(TokenKind::Eq) { self.parse_expression()? } else { json!(null) };
-        
-        Ok(json!(["set", line, name, type_annot, expr]))
+        let name = self.expect_identifier("variable name")?;
+        let type_annot = self.parse_type_annotation()?;
+        let expr = if self.match_token(TokenKind::Eq) { self.parse_expression()? } else { Expression::Literal(Value::Null) };
+
+        Ok(Self::stmt(Instruction::Set(name, type_annot, expr), line))
     }
 
-    fn parse_type_annotation(&mut self) -> Result<Option<String>, String> {
+    fn parse_type_annotation(&mut self) -> Result<Option<String>, Diagnostic> {
         if self.match_token(TokenKind::Colon) {
-            if let TokenKind::Identifier(t) = &self.advance().kind {
-                Ok(Some(t.clone()))
-            } else {
-                Err(format!("Expect type name after ':' (Line {})", self.current_line()))
-            }
+            Ok(Some(self.expect_identifier("type name after ':'")?))
         } else {
             Ok(None)
         }
     }
 
-    fn parse_print(&mut self) -> Result<Value, String> {
+    fn parse_print(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
         let expr = self.parse_expression()?;
-        Ok(json!(["print", line, expr]))
+        Ok(Self::stmt(Instruction::Print(expr), line))
     }
 
-    fn parse_return(&mut self) -> Result<Value, String> {
+    fn parse_return(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
         let expr = self.parse_expression()?;
-        Ok(json!(["return", line, expr]))
+        Ok(Self::stmt(Instruction::Return(expr), line))
     }
 
-    fn parse_input(&mut self) -> Result<Value, String> {
+    fn parse_yield(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Expect name".into()); };
+        let expr = self.parse_expression()?;
+        Ok(Self::stmt(Instruction::Yield(expr), line))
+    }
+
+    fn parse_input(&mut self) -> Result<Statement, Diagnostic> {
+        let line = self.current_line();
+        self.advance();
+        let name = self.expect_identifier("variable name")?;
         let prompt = self.parse_expression()?;
-        Ok(json!(["input", line, name, prompt]))
+        Ok(Self::stmt(Instruction::Input(name, prompt), line))
     }
 
-    fn parse_import(&mut self) -> Result<Value, String> {
+    fn parse_import(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
-        let path = match &self.advance().kind {
+        let path = match self.peek() {
             TokenKind::StringLiteral(s) => s.clone(),
-            _ => return Err("Expect path".into()),
+            other => return Err(self.error_at_current(format!("Expected module path string after 'import', found {}", other.describe()))),
         };
-        Ok(json!(["import", line, path]))
+        self.advance();
+
+        // import "x" as X : `as` n'est un mot-clé qu'ici
+        let alias = if matches!(self.peek(), TokenKind::Identifier(s) if s == "as") {
+            self.advance();
+            Some(self.expect_identifier("module name after 'as'")?)
+        } else {
+            None
+        };
+        Ok(Self::stmt(Instruction::Import(path, alias), line))
+    }
+
+    // `from` n'est un mot-clé qu'en début d'instruction, suivi du chemin d'un module
+    fn at_from_import(&self) -> bool {
+        matches!(self.peek(), TokenKind::Identifier(s) if s == "from") && matches!(self.peek_next(), TokenKind::StringLiteral(_))
     }
 
-    fn parse_try(&mut self) -> Result<Value, String> {
+    fn parse_from_import(&mut self) -> Result<Statement, Diagnostic> {
+        let line = self.current_line();
+        self.advance(); // from
+        let path = match self.peek() {
+            TokenKind::StringLiteral(s) => s.clone(),
+            other => return Err(self.error_at_current(format!("Expected module path string after 'from', found {}", other.describe()))),
+        };
+        self.advance();
+        self.consume(TokenKind::Import, "after module path")?;
+
+        let mut names = Vec::new();
+        loop {
+            let name = self.expect_identifier("name to import")?;
+            if names.contains(&name) {
+                return Err(self.error_at_previous(format!("'{}' is imported twice", name)));
+            }
+            names.push(name);
+            if !self.match_token(TokenKind::Comma) { break; }
+        }
+        Ok(Self::stmt(Instruction::ImportFrom(path, names), line))
+    }
+
+    fn parse_export(&mut self) -> Result<Statement, Diagnostic> {
+        let line = self.current_line();
+        self.advance(); // export
+        match self.peek() {
+            TokenKind::Var if matches!(self.peek_next(), TokenKind::LBracket) => {
+                Err(self.error_at_current("Destructuring declarations cannot be exported"))
+            },
+            TokenKind::Var | TokenKind::Const | TokenKind::Func | TokenKind::Async | TokenKind::At
+            | TokenKind::Class | TokenKind::Final | TokenKind::Enum | TokenKind::Namespace | TokenKind::Interface => {
+                let declaration = self.parse_statement()?;
+                Ok(Self::stmt(Instruction::Export(Box::new(declaration)), line))
+            },
+            other => Err(self.error_at_current(format!("Expected a declaration after 'export', found {}", other.describe()))),
+        }
+    }
+
+    fn parse_try(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
         let try_body = self.parse_block()?;
-        self.consume(TokenKind::Catch, "Expect catch")?;
-        self.consume(TokenKind::LParen, "(")?;
-        let err_var = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Expect error var".into()); };
-        self.consume(TokenKind::RParen, ")")?;
+        self.consume(TokenKind::Catch, "after try block")?;
+        self.consume(TokenKind::LParen, "after 'catch'")?;
+        let error_var = self.expect_identifier("error variable name")?;
+        self.consume(TokenKind::RParen, "after catch variable")?;
         let catch_body = self.parse_block()?;
-        Ok(json!(["try", line, try_body, err_var, catch_body]))
+        Ok(Self::stmt(Instruction::TryCatch { try_body, error_var, catch_body }, line))
     }
 
-    fn parse_throw(&mut self) -> Result<Value, String> {
+    fn parse_throw(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance(); // Consomme 'throw'
         let expr = self.parse_expression()?;
-        Ok(json!(["throw", line, expr]))
+        Ok(Self::stmt(Instruction::Throw(expr), line))
     }
 
-    fn parse_switch(&mut self) -> Result<Value, String> {
+    fn parse_switch(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
-        self.consume(TokenKind::LParen, "(")?;
-        let val = self.parse_expression()?;
-        self.consume(TokenKind::RParen, ")")?;
-        self.consume(TokenKind::LBrace, "{")?;
-        
+        self.consume(TokenKind::LParen, "after 'switch'")?;
+        let value = self.parse_expression()?;
+        self.consume(TokenKind::RParen, "after switch value")?;
+        self.consume(TokenKind::LBrace, "before switch cases")?;
+
         let mut cases = Vec::new();
         let mut default = Vec::new();
-        
+
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
             if self.match_token(TokenKind::Case) {
                 let c_val = self.parse_expression()?;
-                self.consume(TokenKind::Colon, ":")?;
+                self.consume(TokenKind::Colon, "after case value")?;
                 let mut body = Vec::new();
-                while !self.check(&TokenKind::Case) && !self.check(&TokenKind::Default) && !self.check(&TokenKind::RBrace) {
-                    body.push(self.parse_statement()?);
+                while !self.check(&TokenKind::Case) && !self.check(&TokenKind::Default) && !self.check(&TokenKind::RBrace) && !self.is_at_end() {
+                    if let Some(stmt) = self.parse_statement_or_recover() {
+                        body.push(stmt);
+                    }
                 }
-                cases.push(json!([c_val, body]));
+                cases.push((c_val, body));
             } else if self.match_token(TokenKind::Default) {
-                self.consume(TokenKind::Colon, ":")?;
-                while !self.check(&TokenKind::Case) && !self.check(&TokenKind::Default) && !self.check(&TokenKind::RBrace) {
-                    default.push(self.parse_statement()?);
+                self.consume(TokenKind::Colon, "after 'default'")?;
+                while !self.check(&TokenKind::Case) && !self.check(&TokenKind::Default) && !self.check(&TokenKind::RBrace) && !self.is_at_end() {
+                    if let Some(stmt) = self.parse_statement_or_recover() {
+                        default.push(stmt);
+                    }
                 }
             } else {
-                return Err("Unexpected in switch".into());
+                return Err(self.error_at_current(format!("Expected 'case' or 'default', found {}", self.peek().describe())));
             }
         }
-        self.consume(TokenKind::RBrace, "}")?;
-        
-        Ok(json!(["switch", line, val, cases, default]))
+        self.consume(TokenKind::RBrace, "after switch cases")?;
+
+        Ok(Self::stmt(Instruction::Switch { value, cases, default }, line))
     }
 
-    fn parse_namespace(&mut self) -> Result<Value, String> {
+    fn parse_namespace(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Ns Name".into()); };
+        let name = self.expect_identifier("namespace name")?;
         let body = self.parse_block()?;
-        Ok(json!(["namespace", line, name, body]))
+        Ok(Self::stmt(Instruction::Namespace { name, body }, line))
     }
 
-    fn parse_const(&mut self) -> Result<Value, String> {
+    fn parse_const(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance(); // Eat 'const'
-        
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { 
-            n.clone() 
-        } else { 
-            return Err("Expect constant name".into()); 
-        };
+
+        let name = self.expect_identifier("constant name")?;
 
         // Typage graduel optionnel (const PI: float = ...)
         // On consomme le type mais on l'ignore pour l'instant (ou on l'utilise pour check)
-        let _type_annot = self.parse_type_annotation()?; 
+        let _type_annot = self.parse_type_annotation()?;
+
+        self.consume(TokenKind::Eq, "after constant name")?;
 
-        self.consume(TokenKind::Eq, "Expect '=' after constant name")?;
-        
         let expr = self.parse_expression()?;
-        
-        // JSON: ["const", line, name, expr]
-        Ok(json!(["const", line, name, expr]))
+
+        Ok(Self::stmt(Instruction::Const(name, expr), line))
     }
 
-    fn parse_foreach(&mut self) -> Result<Value, String> {
+    fn parse_foreach(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance(); // Eat 'foreach'
-        
-        self.consume(TokenKind::LParen, "Expect '(' after 'foreach'")?;
-        
+
+        self.consume(TokenKind::LParen, "after 'foreach'")?;
+
         // Nom de la variable (ex: "elem")
-        let var_name = if let TokenKind::Identifier(n) = &self.advance().kind {
-            n.clone()
-        } else {
-            return Err("Expect variable name in foreach".into());
-        };
-        
-        self.consume(TokenKind::In, "Expect 'in' after variable name")?;
-        
+        let var_name = self.expect_identifier("loop variable name")?;
+
+        self.consume(TokenKind::In, "after loop variable")?;
+
         // L'expression itérable (ex: "mylist" ou "[1, 2]")
         let iterable = self.parse_expression()?;
-        
-        self.consume(TokenKind::RParen, "Expect ')' after loop header")?;
-        
+
+        self.consume(TokenKind::RParen, "after loop header")?;
+
         // Le corps
         let body = self.parse_block()?;
-        
-        // JSON: ["foreach", line, var_name, iterable, body]
-        Ok(json!(["foreach", line, var_name, iterable, body]))
+
+        Ok(Self::stmt(Instruction::ForEach(var_name, iterable, body), line))
     }
 
-    fn parse_interface(&mut self) -> Result<Value, String> {
+    fn parse_interface(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
-        self.consume(TokenKind::Interface, "Expect 'interface'")?;
-        
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Interface Name".into()); };
-        
-        self.consume(TokenKind::LBrace, "{")?;
-        
+        self.consume(TokenKind::Interface, "")?;
+
+        let name = self.expect_identifier("interface name")?;
+
+        self.consume(TokenKind::LBrace, "before interface body")?;
+
         let mut methods = Vec::new();
 
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
             // Syntaxe : func nom(args); (Pas de corps !)
             // Ou juste : nom(args);
-            
+
             // On accepte 'func' optionnel pour cohérence
-            self.match_token(TokenKind::Func); 
-            
-            let m_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Method Name".into()); };
-            
-            let m_params = self.parse_params_list()?;
-            
-            // On stocke juste [name, params]
-            methods.push(json!([m_name, m_params]));
+            self.match_token(TokenKind::Func);
+
+            let m_name = self.expect_identifier("method name")?;
+            let params = self.parse_params_list()?;
+
+            methods.push(InterfaceMethod { name: m_name, params });
         }
-        
-        self.consume(TokenKind::RBrace, "}")?;
-        
-        // JSON: ["interface", line, name, methods]
-        Ok(json!(["interface", line, name, methods]))
+
+        self.consume(TokenKind::RBrace, "after interface body")?;
+
+        Ok(Self::stmt(Instruction::Interface(InterfaceDefinition { name, methods }), line))
     }
 
-    fn parse_decorated_function(&mut self) -> Result<Value, String> {
+    fn parse_decorated_function(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance(); // @
-        let deco_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Deco Name".into()); };
-        self.consume(TokenKind::Func, "Func")?;
-        let func_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Func Name".into()); };
-        
-        self.consume(TokenKind::LParen, "(")?;
-        let mut params = Vec::new();
-        if !self.check(&TokenKind::RParen) {
-            loop {
-                if let TokenKind::Identifier(p) = &self.advance().kind { params.push(p.clone()); }
-                if !self.match_token(TokenKind::Comma) { break; }
-            }
-        }
-        self.consume(TokenKind::RParen, ")")?;
+        let deco_name = self.expect_identifier("decorator name")?;
+        let is_async = self.match_token(TokenKind::Async);
+        self.consume(TokenKind::Func, "after decorator")?;
+        let func_name = self.expect_identifier("function name")?;
+        let params = self.parse_params_list()?;
         let body = self.parse_block()?;
-        
-        let lambda = json!(["lambda", params, body]);
-        let deco_var = json!(["get", deco_name]);
-        let call = json!(["call", deco_var, [lambda]]);
-        
-        Ok(json!(["set", line, func_name, null, call]))
+
+        // @deco func f() {...}  =>  f = deco(func() {...})
+        let lambda = Expression::Function { params, ret_type: None, body, is_async };
+        let call = Expression::Call(Box::new(Expression::Variable(deco_name)), vec![lambda]);
+
+        Ok(Self::stmt(Instruction::Set(func_name, None, call), line))
     }
 
-    fn parse_params_list(&mut self) -> Result<Value, String> {
-        self.consume(TokenKind::LParen, "(")?;
-        let mut params = Vec::new();
+    // (a, b: int, c = 1, ...rest) : les paramètres qui suivent une valeur par défaut
+    // doivent en avoir une, et `...rest` ferme la liste.
+    fn parse_params_list(&mut self) -> Result<Params, Diagnostic> {
+        self.consume(TokenKind::LParen, "before parameters")?;
+        let mut params: Params = Vec::new();
         if !self.check(&TokenKind::RParen) {
             loop {
-                if let TokenKind::Identifier(p) = &self.advance().kind {
-                    let p_name = p.clone();
-                    let p_type = self.parse_type_annotation()?;
-                    params.push(json!([p_name, p_type]));
+                let is_rest = self.match_token(TokenKind::Ellipsis);
+                let p_name = self.expect_identifier("parameter name")?;
+                if params.iter().any(|p| p.name == p_name) {
+                    return Err(self.error_at_previous(format!("Duplicate parameter '{}'", p_name)));
+                }
+                let mut param = Param::new(p_name, self.parse_type_annotation()?);
+                param.is_rest = is_rest;
+
+                if self.match_token(TokenKind::Eq) {
+                    if is_rest {
+                        return Err(self.error_at_previous("A rest parameter cannot have a default value"));
+                    }
+                    param.default = Some(self.parse_expression()?);
+                } else if !is_rest && params.iter().any(|p| p.default.is_some()) {
+                    return Err(self.error_at_previous(format!("Parameter '{}' needs a default value because it follows one", param.name)));
+                }
+                params.push(param);
+
+                if is_rest && !self.check(&TokenKind::RParen) {
+                    return Err(self.error_at_current("The '...' rest parameter must be the last parameter"));
                 }
                 if !self.match_token(TokenKind::Comma) { break; }
             }
         }
-        self.consume(TokenKind::RParen, ")")?;
-        Ok(json!(params))
+        self.consume(TokenKind::RParen, "after parameters")?;
+        Ok(params)
     }
 
-    fn parse_if(&mut self) -> Result<Value, String> {
+    fn parse_if(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
-        self.consume(TokenKind::LParen, "(")?;
-        let cond = self.parse_expression()?;
-        self.consume(TokenKind::RParen, ")")?;
-        let true_blk = self.parse_block()?;
-        let mut false_blk = json!([]);
-        
+        self.consume(TokenKind::LParen, "after 'if'")?;
+        let condition = self.parse_expression()?;
+        self.consume(TokenKind::RParen, "after condition")?;
+        let body = self.parse_block()?;
+        let mut else_body = Vec::new();
+
         if self.match_token(TokenKind::Else) {
             if self.check(&TokenKind::If) {
-                false_blk = json!([self.parse_if()?]);
+                else_body = vec![self.parse_if()?];
             } else {
-                false_blk = self.parse_block()?;
+                else_body = self.parse_block()?;
             }
         }
-        
-        if false_blk.as_array().unwrap().is_empty() {
-            Ok(json!(["if", line, cond, true_blk]))
-        } else {
-            Ok(json!(["if", line, cond, true_blk, false_blk]))
-        }
+
+        Ok(Self::stmt(Instruction::If { condition, body, else_body }, line))
     }
 
-    fn parse_while(&mut self) -> Result<Value, String> {
+    fn parse_while(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance();
-        self.consume(TokenKind::LParen, "(")?;
-        let cond = self.parse_expression()?;
-        self.consume(TokenKind::RParen, ")")?;
+        self.consume(TokenKind::LParen, "after 'while'")?;
+        let condition = self.parse_expression()?;
+        self.consume(TokenKind::RParen, "after condition")?;
         let body = self.parse_block()?;
-        Ok(json!(["while", line, cond, body]))
+        Ok(Self::stmt(Instruction::While { condition, body }, line))
     }
 
     // Parses a property block: prop name { get { ... } set(v) { ... } }
-    fn parse_property(&mut self, vis: &str, is_static: bool) -> Result<Value, String> {
+    fn parse_property(&mut self, visibility: Visibility, is_static: bool) -> Result<ClassProperty, Diagnostic> {
         // 1. Name
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Prop Name".into()); };
-        
-        self.consume(TokenKind::LBrace, "Expect '{' after property name")?;
-        
-        let mut getter = Value::Null;
-        let mut setter = Value::Null;
-        
+        let name = self.expect_identifier("property name")?;
+
+        self.consume(TokenKind::LBrace, "after property name")?;
+
+        let mut getter = None;
+        let mut setter = None;
+
         // Loop until '}'
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
-            let token = self.advance();
-            
-            // Check for identifiers "get" or "set"
-            if let TokenKind::Identifier(method_type) = &token.kind {
-                if method_type == "get" {
-                    // Syntax: get { body }
-                    // Note: No parenthesis for getter definition in modern syntax usually
-                    if self.check(&TokenKind::LParen) {
-                        return Err("Getters should not have parameters list ()".into());
-                    }
-                    let body = self.parse_block()?;
-                    // Store as [params, body] where params is empty
-                    getter = json!([ [], body ]);
-                } 
-                else if method_type == "set" {
-                    // Syntax: set(val) { body }
-                    let params = self.parse_params_list()?;
-                    let body = self.parse_block()?;
-                    setter = json!([ params, body ]);
-                } 
-                else {
-                    return Err(format!("Expected 'get' or 'set' inside property block, found '{}'", method_type));
+            let accessor = match self.peek() {
+                TokenKind::Identifier(n) if n == "get" || n == "set" => n.clone(),
+                other => return Err(self.error_at_current(format!("Expected 'get' or 'set' in property block, found {}", other.describe()))),
+            };
+            self.advance();
+
+            if accessor == "get" {
+                // Syntax: get { body }
+                // Note: No parenthesis for getter definition in modern syntax usually
+                if self.check(&TokenKind::LParen) {
+                    return Err(self.error_at_current("Getters should not have parameters list ()"));
                 }
-            } else {
-                return Err("Expected 'get' or 'set' identifier inside property block".into());
+                let body = self.parse_block()?;
+                getter = Some((vec![], body));
+            }
+            else {
+                // Syntax: set(val) { body }
+                let params = self.parse_params_list()?;
+                let body = self.parse_block()?;
+                setter = Some((params, body));
             }
         }
-        
-        self.consume(TokenKind::RBrace, "Expect '}' after property body")?;
-        
-        // JSON structure: ["prop", name, vis, is_static, getter, setter]
-        Ok(json!(["prop", name, vis, is_static, getter, setter]))
+
+        self.consume(TokenKind::RBrace, "after property body")?;
+
+        Ok(ClassProperty { name, visibility, is_static, getter, setter })
     }
 
-    fn parse_class(&mut self) -> Result<Value, String> {
+    fn parse_class(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
-        let is_class_final = self.match_token(TokenKind::Final);
+        let is_final = self.match_token(TokenKind::Final);
+
+        self.consume(TokenKind::Class, "")?;
 
-        self.consume(TokenKind::Class, "Expect 'class'")?;
-        
         // 1. Nom de la classe
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { 
-            n.clone() 
-        } else { 
-            return Err("Expect Class Name".into()); 
-        };
-        
+        let name = self.expect_identifier("class name")?;
+
         // 2. Extends (Parent)
-        let mut parent = Value::Null;
+        let mut parent = None;
         if self.match_token(TokenKind::Extends) {
-            if let TokenKind::Identifier(n) = &self.advance().kind { 
-                parent = json!(n); 
-            } else {
-                return Err("Expect Parent Class Name".into());
-            }
+            parent = Some(self.expect_identifier("parent class name after 'extends'")?);
         }
 
         // 3. Implements (Interfaces)
         let mut interfaces = Vec::new();
         if self.match_token(TokenKind::Implements) {
             loop {
-                if let TokenKind::Identifier(n) = &self.advance().kind {
-                    interfaces.push(json!(n));
-                } else {
-                    return Err("Expected interface name after 'implements'".into());
-                }
+                interfaces.push(self.expect_identifier("interface name after 'implements'")?);
 
                 if !self.match_token(TokenKind::Comma) {
                     break;
                 }
             }
         }
-        
-        self.consume(TokenKind::LBrace, "Expect '{' before class body")?;
-        
-        // Structures de stockage pour le JSON final
-        let mut methods = serde_json::Map::new();
-        let mut visibilities = serde_json::Map::new(); // Map<Nom, "public"|"private"|"protected">
-        let mut fields = Vec::new(); // Liste de ["field", nom, visibilité, valeur_defaut]
+
+        self.consume(TokenKind::LBrace, "before class body")?;
+
+        let mut class = ClassDefinition {
+            name,
+            parent,
+            interfaces,
+            methods: HashMap::new(),
+            fields: Vec::new(),
+            properties: Vec::new(),
+            visibilities: HashMap::new(),
+            is_final,
+        };
 
         while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
-            
-            // 1. Visibilité
-            let vis_str = if self.match_token(TokenKind::Public) { "public" }
-                     else if self.match_token(TokenKind::Private) { "private" }
-                     else if self.match_token(TokenKind::Protected) { "protected" }
-                     else { "public" };
-
-            // 2. Static & Final ?
-            let is_static = self.match_token(TokenKind::Static);
-            let is_final_method = self.match_token(TokenKind::Final);
-
-            // 3. Analyse du membre
-            
-            // Cas Méthode explicite 'func'
-            if self.match_token(TokenKind::Func) {
-                let m_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Method Name".into()); };
-                let m_params = self.parse_params_list()?;
-                let m_body = self.parse_block()?;
-                
-                methods.insert(m_name.clone(), json!([m_params, m_body, is_static, is_final_method]));
-                visibilities.insert(m_name, json!(vis_str));
-            }
-            else if self.match_token(TokenKind::Prop) {
-                // On délègue au helper
-                let prop_json = self.parse_property(vis_str, is_static)?;
-                
-                // On stocke ça dans 'fields' temporairement pour le JSON de sortie.
-                // Le Loader fera le tri grâce au tag "prop".
-                fields.push(prop_json.clone());
-                visibilities.insert(prop_json[1].as_str().unwrap().to_string(), json!(vis_str));
-            }
-            // Cas Champ explicite 'var'
-            else if self.match_token(TokenKind::Var) {
-                let f_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Field Name".into()); };
-                let type_annot = self.parse_type_annotation()?;
-                let default_val = if self.match_token(TokenKind::Eq) { self.parse_expression()? } else { json!(null) };
-                
-                // JSON Field: ["field", name, vis, default_val, is_static] <--- Ajout à la fin
-                fields.push(json!(["field", f_name.clone(), vis_str, default_val, is_static, type_annot]));
-                visibilities.insert(f_name, json!(vis_str));
+            let start = self.pos;
+            if let Err(e) = self.parse_class_member(&mut class) {
+                self.errors.push(e);
+                if self.pos == start && !self.check(&TokenKind::LBrace) {
+                    self.advance();
+                }
+                self.skip_to_boundary(Parser::at_member_boundary);
             }
-            // Cas Implicite (identifiant...)
-            else {
-                let member_name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Member name".into()); };
+        }
 
-                if self.check(&TokenKind::LParen) {
-                    // Méthode
-                    let m_params = self.parse_params_list()?;
-                    let m_body = self.parse_block()?;
-                    methods.insert(member_name.clone(), json!([m_params, m_body, is_static]));
-                    visibilities.insert(member_name, json!(vis_str));
-                } else {
-                    // Champ
-                    let type_annot = self.parse_type_annotation()?;
-                    let default_val = if self.match_token(TokenKind::Eq) { self.parse_expression()? } else { json!(null) };
-                    fields.push(json!(["field", member_name.clone(), vis_str, default_val, is_static, type_annot]));
-                    visibilities.insert(member_name, json!(vis_str));
-                }
+        self.consume(TokenKind::RBrace, "after class body")?;
+
+        Ok(Self::stmt(Instruction::Class(class), line))
+    }
+
+    fn parse_class_member(&mut self, class: &mut ClassDefinition) -> Result<(), Diagnostic> {
+        // 1. Visibilité
+        let visibility = if self.match_token(TokenKind::Public) { Visibility::Public }
+                 else if self.match_token(TokenKind::Private) { Visibility::Private }
+                 else if self.match_token(TokenKind::Protected) { Visibility::Protected }
+                 else { Visibility::Public };
+
+        // 2. Static, Final & Async ?
+        let is_static = self.match_token(TokenKind::Static);
+        let is_final_method = self.match_token(TokenKind::Final);
+        let is_async = self.match_token(TokenKind::Async);
+
+        // 3. Analyse du membre
+
+        // Cas Méthode explicite 'func'
+        if self.match_token(TokenKind::Func) {
+            let m_name = self.expect_identifier("method name")?;
+            let m_params = self.parse_params_list()?;
+            let m_body = self.parse_block()?;
+
+            class.methods.insert(m_name.clone(), (m_params, m_body, is_static, is_final_method, is_async));
+            class.visibilities.insert(m_name, visibility);
+        }
+        else if is_async {
+            // async nom(...) : seule une méthode peut être async
+            let m_name = self.expect_identifier("method name")?;
+            let m_params = self.parse_params_list()?;
+            let m_body = self.parse_block()?;
+            class.methods.insert(m_name.clone(), (m_params, m_body, is_static, is_final_method, true));
+            class.visibilities.insert(m_name, visibility);
+        }
+        else if self.match_token(TokenKind::Prop) {
+            let prop = self.parse_property(visibility, is_static)?;
+            class.visibilities.insert(prop.name.clone(), visibility);
+            class.properties.push(prop);
+        }
+        // Cas Champ explicite 'var'
+        else if self.match_token(TokenKind::Var) {
+            let f_name = self.expect_identifier("field name")?;
+            self.parse_field(class, f_name, visibility, is_static)?;
+        }
+        // Cas Implicite (identifiant...)
+        else {
+            let member_name = self.expect_identifier("member name")?;
+
+            if self.check(&TokenKind::LParen) {
+                // Méthode
+                let m_params = self.parse_params_list()?;
+                let m_body = self.parse_block()?;
+                class.methods.insert(member_name.clone(), (m_params, m_body, is_static, false, false));
+                class.visibilities.insert(member_name, visibility);
+            } else {
+                // Champ
+                self.parse_field(class, member_name, visibility, is_static)?;
             }
         }
-        
-        self.consume(TokenKind::RBrace, "Expect '}' after class body")?;
-        
-        // FORMAT JSON DE SORTIE (v0.3.0)
-        // ["class", line, name, methods, parent, fields, visibilities, is_final]
-        
-        let result = if parent.is_null() {
-            json!(["class", line, name, methods, null, fields, visibilities, is_class_final, interfaces])
-        } else {
-            json!(["class", line, name, methods, parent, fields, visibilities, is_class_final, interfaces])
-        };
 
-        Ok(result)
+        Ok(())
+    }
+
+    fn parse_field(&mut self, class: &mut ClassDefinition, name: String, visibility: Visibility, is_static: bool) -> Result<(), Diagnostic> {
+        let type_annot = self.parse_type_annotation()?;
+        let default_value = if self.match_token(TokenKind::Eq) { self.parse_expression()? } else { Expression::Literal(Value::Null) };
+
+        class.fields.push(ClassField { name: name.clone(), visibility, default_value, is_static, type_annot });
+        class.visibilities.insert(name, visibility);
+        Ok(())
     }
 
-    fn parse_enum(&mut self) -> Result<Value, String> {
+    fn parse_enum(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
         self.advance(); // Eat 'enum'
-        
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { 
-            n.clone() 
-        } else { 
-            return Err("Expect Enum Name".into()); 
-        };
 
-        self.consume(TokenKind::LBrace, "Expect '{'")?;
-        
-        let mut variants = Vec::new();
-        if !self.check(&TokenKind::RBrace) {
-            loop {
-                if let TokenKind::Identifier(v) = &self.advance().kind {
-                    variants.push(json!(v));
-                } else {
-                    return Err("Expect enum variant name".into());
+        let name = self.expect_identifier("enum name")?;
+
+        self.consume(TokenKind::LBrace, "before enum variants")?;
+
+        let mut def = EnumDefinition { name, variants: Vec::new(), methods: HashMap::new() };
+        while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
+            // Méthode : func nom(params) { ... }
+            if self.match_token(TokenKind::Func) {
+                let name_token = self.tokens[self.pos].clone();
+                let m_name = self.expect_identifier("method name")?;
+                let m_params = self.parse_params_list()?;
+                let m_body = self.parse_block()?;
+                if def.methods.insert(m_name.clone(), (m_params, m_body)).is_some() {
+                    return Err(self.error_at(&name_token, format!("Duplicate method '{}' in enum '{}'", m_name, def.name)));
                 }
-                
-                // Virgule optionnelle pour le dernier élément ?
-                if !self.match_token(TokenKind::Comma) { 
-                    break; 
+                continue;
+            }
+
+            // Variante, avec ses données éventuelles : Rect(w, h)
+            let name_token = self.tokens[self.pos].clone();
+            let v_name = self.expect_identifier("enum variant name")?;
+            if def.variants.iter().any(|v| v.name == v_name) {
+                return Err(self.error_at(&name_token, format!("Duplicate variant '{}' in enum '{}'", v_name, def.name)));
+            }
+
+            let mut fields = Vec::new();
+            if self.match_token(TokenKind::LParen) {
+                if !self.check(&TokenKind::RParen) {
+                    loop {
+                        let field_token = self.tokens[self.pos].clone();
+                        let field = self.expect_identifier("variant field name")?;
+                        if fields.contains(&field) {
+                            return Err(self.error_at(&field_token, format!("Duplicate field '{}' in variant '{}'", field, v_name)));
+                        }
+                        fields.push(field);
+                        if !self.match_token(TokenKind::Comma) {
+                            break;
+                        }
+                    }
                 }
+                self.consume(TokenKind::RParen, "after variant fields")?;
+            }
+            def.variants.push(EnumVariant { name: v_name, fields });
+
+            // Virgule optionnelle après la dernière variante (ou avant les méthodes)
+            if !self.match_token(TokenKind::Comma) && !self.check(&TokenKind::RBrace) && !self.check(&TokenKind::Func) {
+                return Err(self.error_at_current("Expected ',' between enum variants"));
             }
         }
-        
-        self.consume(TokenKind::RBrace, "Expect '}'")?;
-        
-        // JSON: ["enum", line, name, [variants...]]
-        Ok(json!(["enum", line, name, variants]))
+
+        self.consume(TokenKind::RBrace, "after enum variants")?;
+
+        Ok(Self::stmt(Instruction::Enum(def), line))
     }
 
-    fn parse_func(&mut self) -> Result<Value, String> {
+    fn parse_func(&mut self) -> Result<Statement, Diagnostic> {
         let line = self.current_line();
-        self.advance();
-        let name = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Func Name".into()); };
-        
+        let is_async = self.match_token(TokenKind::Async);
+        self.consume(TokenKind::Func, "after 'async'")?;
+        let name = self.expect_identifier("function name")?;
+
         let params = self.parse_params_list()?;
-        
-        let mut ret_type = Value::Null;
+
+        let mut ret_type = None;
         if self.match_token(TokenKind::Arrow) {
-             if let TokenKind::Identifier(t) = &self.advance().kind {
-                 ret_type = json!(t);
-             }
+             ret_type = Some(self.expect_identifier("return type after '->'")?);
         }
         let body = self.parse_block()?;
-        
-        Ok(json!(["function", line, name, params, ret_type, body]))
+
+        Ok(Self::stmt(Instruction::Function { name, params, ret_type, body, is_async }, line))
     }
 
     // --- Expression Parsing ---
 
-    fn parse_expression(&mut self) -> Result<Value, String> {
+    fn parse_expression(&mut self) -> Result<Expression, Diagnostic> {
         self.parse_ternary()
     }
 
-    fn parse_ternary(&mut self) -> Result<Value, String> {
+    fn parse_ternary(&mut self) -> Result<Expression, Diagnostic> {
         // On commence par parser le niveau inférieur (OR, AND...)
         let mut expr = self.parse_null_coalescing()?;
 
         // Si on rencontre '?', c'est un ternaire
         if self.match_token(TokenKind::Question) {
             let true_branch = self.parse_expression()?; // Récursif pour permettre l'imbrication
-            self.consume(TokenKind::Colon, "Expect ':' in ternary operator")?;
+            self.consume(TokenKind::Colon, "in ternary expression")?;
             let false_branch = self.parse_ternary()?;   // Associativité à droite
 
-            // Format JSON : ["?", condition, true_expr, false_expr]
-            expr = json!(["?", expr, true_branch, false_branch]);
+            expr = Expression::Ternary(Box::new(expr), Box::new(true_branch), Box::new(false_branch));
         }
 
         Ok(expr)
     }
 
-    fn parse_null_coalescing(&mut self) -> Result<Value, String> {
+    fn parse_null_coalescing(&mut self) -> Result<Expression, Diagnostic> {
         let mut expr = self.parse_logical_or()?;
 
         while self.match_token(TokenKind::DoubleQuestion) {
             let right = self.parse_logical_or()?;
-
-            let line = self.current_line();
-            expr = json!(["??", line, expr, right]);
+            expr = Expression::NullCoalescing(Box::new(expr), Box::new(right));
         }
 
         Ok(expr)
     }
 
-    fn parse_logical_or(&mut self) -> Result<Value, String> {
+    fn parse_logical_or(&mut self) -> Result<Expression, Diagnostic> {
         let mut left = self.parse_logical_and()?;
         while self.match_token(TokenKind::Or) {
             let right = self.parse_logical_and()?;
-            left = json!(["||", left, right]);
+            left = Expression::Or(Box::new(left), Box::new(right));
         }
         Ok(left)
     }
 
-    fn parse_logical_and(&mut self) -> Result<Value, String> {
+    fn parse_logical_and(&mut self) -> Result<Expression, Diagnostic> {
         let mut left = self.parse_equality()?;
         while self.match_token(TokenKind::And) {
             let right = self.parse_equality()?;
-            left = json!(["&&", left, right]);
+            left = Expression::And(Box::new(left), Box::new(right));
         }
         Ok(left)
     }
 
-    fn parse_equality(&mut self) -> Result<Value, String> {
+    fn parse_equality(&mut self) -> Result<Expression, Diagnostic> {
         let mut left = self.parse_relational()?;
         while let TokenKind::EqEq | TokenKind::Neq = self.peek() {
-            let op = match self.advance().kind {
-                TokenKind::EqEq => "==",
-                TokenKind::Neq => "!=",
+            let op: BinaryOp = match self.advance().kind {
+                TokenKind::EqEq => Expression::Equal,
+                TokenKind::Neq => Expression::NotEqual,
                 _ => unreachable!()
             };
             let right = self.parse_relational()?;
-            left = json!([op, left, right]);
+            left = op(Box::new(left), Box::new(right));
         }
         Ok(left)
     }
 
-    fn parse_relational(&mut self) -> Result<Value, String> {
+    fn parse_relational(&mut self) -> Result<Expression, Diagnostic> {
         let mut left = self.parse_bitwise()?;
         while let TokenKind::Lt | TokenKind::Gt | TokenKind::LtEq | TokenKind::GtEq = self.peek() {
-             let op = match self.advance().kind {
-                TokenKind::Lt => "<",
-                TokenKind::Gt => ">",
-                TokenKind::LtEq => "<=",
-                TokenKind::GtEq => ">=",
+            let op: BinaryOp = match self.advance().kind {
+                TokenKind::Lt => Expression::LessThan,
+                TokenKind::Gt => Expression::GreaterThan,
+                TokenKind::LtEq => Expression::LessEqual,
+                TokenKind::GtEq => Expression::GreaterEqual,
                 _ => unreachable!(),
             };
             let right = self.parse_bitwise()?;
-            left = json!([op, left, right]);
+            left = op(Box::new(left), Box::new(right));
         }
         Ok(left)
     }
 
-    fn parse_bitwise(&mut self) -> Result<Value, String> {
+    fn parse_bitwise(&mut self) -> Result<Expression, Diagnostic> {
         let mut left = self.parse_range()?;
         while let TokenKind::BitAnd | TokenKind::BitOr | TokenKind::BitXor | TokenKind::ShiftLeft | TokenKind::ShiftRight = self.peek() {
-            let op = match self.advance().kind {
-                TokenKind::BitAnd => "&",
-                TokenKind::BitOr => "|",
-                TokenKind::BitXor => "^",
-                TokenKind::ShiftLeft => "<<",
-                TokenKind::ShiftRight => ">>",
+            let op: BinaryOp = match self.advance().kind {
+                TokenKind::BitAnd => Expression::BitAnd,
+                TokenKind::BitOr => Expression::BitOr,
+                TokenKind::BitXor => Expression::BitXor,
+                TokenKind::ShiftLeft => Expression::ShiftLeft,
+                TokenKind::ShiftRight => Expression::ShiftRight,
                 _ => unreachable!()
             };
             let right = self.parse_range()?;
-            left = json!([op, left, right]);
+            left = op(Box::new(left), Box::new(right));
         }
         Ok(left)
     }
 
-    fn parse_range(&mut self) -> Result<Value, String> {
+    fn parse_range(&mut self) -> Result<Expression, Diagnostic> {
         // On parse la partie gauche (ex: 1+1)
         let left = self.parse_additive()?;
 
         // Si on voit '..', c'est un Range
         if self.match_token(TokenKind::DotDot) {
             let right = self.parse_additive()?;
-            let line = self.current_line();
-            // JSON: ["range", line, left, right]
-            return Ok(json!(["range", line, left, right]));
+            return Ok(Expression::Range(Box::new(left), Box::new(right)));
         }
 
         Ok(left)
     }
 
-    fn parse_additive(&mut self) -> Result<Value, String> {
+    fn parse_additive(&mut self) -> Result<Expression, Diagnostic> {
         let mut left = self.parse_multiplicative()?;
         while let TokenKind::Plus | TokenKind::Minus = self.peek() {
-            let op = match self.advance().kind {
-                TokenKind::Plus => "+",
-                TokenKind::Minus => "-",
+            let op: BinaryOp = match self.advance().kind {
+                TokenKind::Plus => Expression::Add,
+                TokenKind::Minus => Expression::Sub,
                 _ => unreachable!()
             };
             let right = self.parse_multiplicative()?;
-            left = json!([op, left, right]);
+            left = op(Box::new(left), Box::new(right));
         }
         Ok(left)
     }
 
-    fn parse_multiplicative(&mut self) -> Result<Value, String> {
+    fn parse_multiplicative(&mut self) -> Result<Expression, Diagnostic> {
         let mut left = self.parse_unary()?;
         while let TokenKind::Star | TokenKind::Slash | TokenKind::Percent = self.peek() {
-            let op = match self.advance().kind {
-                TokenKind::Star => "*",
-                TokenKind::Slash => "/",
-                TokenKind::Percent => "%",
+            let op: BinaryOp = match self.advance().kind {
+                TokenKind::Star => Expression::Mul,
+                TokenKind::Slash => Expression::Div,
+                TokenKind::Percent => Expression::Modulo,
                 _ => unreachable!()
             };
             let right = self.parse_unary()?;
-            left = json!([op, left, right]);
+            left = op(Box::new(left), Box::new(right));
         }
         Ok(left)
     }
 
-    fn parse_unary(&mut self) -> Result<Value, String> {
+    fn parse_unary(&mut self) -> Result<Expression, Diagnostic> {
         if self.match_token(TokenKind::Bang) {
             let right = self.parse_unary()?;
-            return Ok(json!(["!", right]));
+            return Ok(Expression::Not(Box::new(right)));
         }
         if self.match_token(TokenKind::Minus) {
+            // -x  =>  0 - x
+            let right = self.parse_unary()?;
+            return Ok(Expression::Sub(Box::new(Expression::Literal(Value::Integer(0))), Box::new(right)));
+        }
+        if self.match_token(TokenKind::Await) {
             let right = self.parse_unary()?;
-            return Ok(json!(["-", json!(0), right]));
+            return Ok(Expression::Await(Box::new(right)));
         }
         self.parse_primary()
     }
 
-    fn parse_primary(&mut self) -> Result<Value, String> {
+    // Arguments d'appel jusqu'à ')' (virgule finale tolérée)
+    // Arguments d'appel : positionnels, puis nommés (`timeout: 5`) ; `...liste` déplie une liste
+    fn parse_arguments(&mut self) -> Result<Vec<Expression>, Diagnostic> {
+        let mut args = Vec::new();
+        let mut named = false;
+        if !self.check(&TokenKind::RParen) {
+            loop {
+                if let (TokenKind::Identifier(name), TokenKind::Colon) = (self.peek(), self.peek_next()) {
+                    let name = name.clone();
+                    if args.iter().any(|a| matches!(a, Expression::NamedArg(n, _) if *n == name)) {
+                        return Err(self.error_at_current(format!("Duplicate named argument '{}'", name)));
+                    }
+                    self.advance();
+                    self.advance();
+                    args.push(Expression::NamedArg(name, Box::new(self.parse_expression()?)));
+                    named = true;
+                } else if named {
+                    return Err(self.error_at_current("Positional arguments cannot follow named arguments"));
+                } else if self.match_token(TokenKind::Ellipsis) {
+                    args.push(Expression::Spread(Box::new(self.parse_expression()?)));
+                } else {
+                    args.push(self.parse_expression()?);
+                }
+                if !self.match_token(TokenKind::Comma) { break; }
+                if self.check(&TokenKind::RParen) { break; }
+            }
+        }
+        self.consume(TokenKind::RParen, "after arguments")?;
+        Ok(args)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression, Diagnostic> {
         let mut expr = match self.peek() {
-            TokenKind::Integer(n) => { let v = *n; self.advance(); json!(v) },
-            TokenKind::Float(f) => { let v = *f; self.advance(); json!(v) },
-            TokenKind::StringLiteral(s) => { 
-                let raw = s.clone(); 
-                self.advance(); 
+            TokenKind::Integer(n) => { let v = *n; self.advance(); Expression::Literal(Value::Integer(v)) },
+            TokenKind::Float(f) => { let v = *f; self.advance(); Expression::Literal(Value::Float(v)) },
+            TokenKind::StringLiteral(s) => {
+                let raw = s.clone();
+                self.advance();
                 if raw.contains("${") { return self.parse_interpolated_string(&raw); }
-                json!(raw) 
+                Expression::Literal(Value::String(raw.into()))
             },
-            TokenKind::True => { self.advance(); json!(true) },
-            TokenKind::False => { self.advance(); json!(false) },
-            TokenKind::Null => { self.advance(); json!(null) },
-            TokenKind::Identifier(name) => { let n = name.clone(); self.advance(); json!(["get", n]) },
-            TokenKind::Func => {
-                self.advance();
-                self.consume(TokenKind::LParen, "(")?;
-                let mut params = Vec::new();
-                if !self.check(&TokenKind::RParen) {
-                    loop {
-                        if let TokenKind::Identifier(p) = &self.advance().kind { params.push(p.clone()); }
-                        if !self.match_token(TokenKind::Comma) { break; }
-                    }
-                }
-                self.consume(TokenKind::RParen, ")")?;
+            TokenKind::True => { self.advance(); Expression::Literal(Value::Boolean(true)) },
+            TokenKind::False => { self.advance(); Expression::Literal(Value::Boolean(false)) },
+            TokenKind::Null => { self.advance(); Expression::Literal(Value::Null) },
+            TokenKind::Identifier(name) => { let n = name.clone(); self.advance(); Expression::Variable(n) },
+            TokenKind::Func | TokenKind::Async => {
+                let is_async = self.match_token(TokenKind::Async);
+                self.consume(TokenKind::Func, "after 'async'")?;
+                let params = self.parse_params_list()?;
                 let body = self.parse_block()?;
-                json!(["lambda", params, body])
+                Expression::Function { params, ret_type: None, body, is_async }
             },
             TokenKind::LParen => {
                 self.advance();
                 let e = self.parse_expression()?;
-                self.consume(TokenKind::RParen, ")")?;
+                self.consume(TokenKind::RParen, "after expression")?;
                 e
             },
             TokenKind::LBracket => {
                 self.advance();
                 let mut els = Vec::new();
                 if !self.check(&TokenKind::RBracket) {
-                    loop { 
-                        els.push(self.parse_expression()?); 
-                        if !self.match_token(TokenKind::Comma) { break; } 
+                    loop {
+                        els.push(self.parse_expression()?);
+                        if !self.match_token(TokenKind::Comma) { break; }
                         if self.check(&TokenKind::RBracket) { break; }
                     }
                 }
-                self.consume(TokenKind::RBracket, "]")?;
-                let mut ast = vec![json!("make_list")];
-                ast.extend(els);
-                json!(ast)
+                self.consume(TokenKind::RBracket, "after list elements")?;
+                Expression::List(els)
             },
             TokenKind::LBrace => {
                 self.advance();
                 let mut entries = Vec::new();
                 if !self.check(&TokenKind::RBrace) {
                     loop {
-                        let key = match &self.advance().kind {
+                        let key = match self.peek() {
                             TokenKind::StringLiteral(s) => s.clone(),
                             TokenKind::Identifier(s) => s.clone(),
-                            _ => return Err("Dict Key".into())
+                            other => return Err(self.error_at_current(format!("Expected dictionary key, found {}", other.describe())))
                         };
-                        self.consume(TokenKind::Colon, ":")?;
+                        self.advance();
+                        self.consume(TokenKind::Colon, "after dictionary key")?;
                         let val = self.parse_expression()?;
-                        entries.push(json!([key, val]));
+                        entries.push((key, val));
                         if !self.match_token(TokenKind::Comma) { break; }
                         if self.check(&TokenKind::RBrace) { break; }
                     }
                 }
-                self.consume(TokenKind::RBrace, "}")?;
-                let mut ast = vec![json!("make_dict")];
-                ast.extend(entries);
-                json!(ast)
+                self.consume(TokenKind::RBrace, "after dictionary entries")?;
+                Expression::Dict(entries)
             },
             TokenKind::New => {
                 self.advance();
-                let mut expr = if let TokenKind::Identifier(n) = &self.advance().kind { json!(["get", n.clone()]) } else { return Err("Class".into()); };
+                let mut class_expr = Expression::Variable(self.expect_identifier("class name after 'new'")?);
                 while self.match_token(TokenKind::Dot) {
-                    if let TokenKind::Identifier(m) = &self.advance().kind { expr = json!(["get_attr", expr, m.clone()]); }
+                    class_expr = Expression::GetAttr(Box::new(class_expr), self.expect_identifier("member name")?);
                 }
-                self.consume(TokenKind::LParen, "(")?;
-                let mut args = Vec::new();
-                if !self.check(&TokenKind::RParen) {
-                    loop { args.push(self.parse_expression()?); if !self.match_token(TokenKind::Comma) { break; } }
-                }
-                self.consume(TokenKind::RParen, ")")?;
-                let mut new_cmd = vec![json!("new"), expr];
-                new_cmd.extend(args);
-                json!(new_cmd)
+                self.consume(TokenKind::LParen, "after class name")?;
+                let args = self.parse_arguments()?;
+                Expression::New(Box::new(class_expr), args)
             },
+            TokenKind::Match => self.parse_match()?,
             TokenKind::Super => {
                 self.advance(); // Consomme 'super'
-                self.consume(TokenKind::Dot, "Expect '.' after super")?;
-                
-                let method_name = if let TokenKind::Identifier(n) = &self.advance().kind {
-                    n.clone()
-                } else {
-                    return Err("Expect superclass method name".into());
-                };
+                self.consume(TokenKind::Dot, "after 'super'")?;
 
-                self.consume(TokenKind::LParen, "Expect '(' after method name")?;
-                
-                let mut args = Vec::new();
-                if !self.check(&TokenKind::RParen) {
-                    loop {
-                        args.push(self.parse_expression()?);
-                        if !self.match_token(TokenKind::Comma) { break; }
-                    }
-                }
-                self.consume(TokenKind::RParen, "Expect ')' after arguments")?;
+                let method_name = self.expect_identifier("method name after 'super.'")?;
 
-                // On génère le format JSON attendu par le Loader
-                json!(["super_call", method_name, args])
+                self.consume(TokenKind::LParen, "after method name")?;
+                let args = self.parse_arguments()?;
+
+                Expression::SuperCall(method_name, args)
             },
-            _ => return Err(format!("Unexpected token: {:?} at line {}", self.peek(), self.current_line()))
+            other => return Err(self.error_at_current(format!("Expected expression, found {}", other.describe())))
         };
 
         loop {
             if self.match_token(TokenKind::LParen) {
-                let mut args = Vec::new();
-                if !self.check(&TokenKind::RParen) {
-                    loop { 
-                        args.push(self.parse_expression()?); 
-                        if !self.match_token(TokenKind::Comma) { break; }
-                        if self.check(&TokenKind::RParen) {
-                            break;
-                        }
-                    }
-                }
-                self.consume(TokenKind::RParen, ")")?;
-                expr = json!(["call", expr, args]);
+                let args = self.parse_arguments()?;
+                expr = Expression::Call(Box::new(expr), args);
             } else if self.match_token(TokenKind::Dot) {
-                let member = if let TokenKind::Identifier(n) = &self.advance().kind { n.clone() } else { return Err("Member".into()); };
+                let member = self.expect_identifier("member name")?;
                 if self.match_token(TokenKind::LParen) {
-                    let mut args = Vec::new();
-                    if !self.check(&TokenKind::RParen) {
-                        loop { args.push(self.parse_expression()?); if !self.match_token(TokenKind::Comma) { break; } }
-                    }
-                    self.consume(TokenKind::RParen, ")")?;
-                    expr = json!(["call_method", expr, member, args]);
+                    let args = self.parse_arguments()?;
+                    expr = Expression::CallMethod(Box::new(expr), member, args);
                 } else {
-                    expr = json!(["get_attr", expr, member]);
+                    expr = Expression::GetAttr(Box::new(expr), member);
                 }
+            } else if self.match_token(TokenKind::LBracket) {
+                expr = self.parse_subscript(expr)?;
             } else {
                 break;
             }
@@ -1048,75 +1198,289 @@ impl Parser {
         Ok(expr)
     }
 
-    fn parse_interpolated_string(&self, source: &str) -> Result<Value, String> {
+    // Après '[' : x[i] ou x[start:end] (chaque borne est optionnelle)
+    fn parse_subscript(&mut self, target: Expression) -> Result<Expression, Diagnostic> {
+        let null = || Box::new(Expression::Literal(Value::Null));
+        let start = if self.match_token(TokenKind::Colon) {
+            None
+        } else {
+            let index = self.parse_expression()?;
+            if !self.match_token(TokenKind::Colon) {
+                self.consume(TokenKind::RBracket, "after index")?;
+                return Ok(Expression::Index(Box::new(target), Box::new(index)));
+            }
+            Some(index)
+        };
+
+        let end = if self.check(&TokenKind::RBracket) { None } else { Some(self.parse_expression()?) };
+        self.consume(TokenKind::RBracket, "after slice")?;
+        Ok(Expression::Slice(
+            Box::new(target),
+            start.map(Box::new).unwrap_or_else(null),
+            end.map(Box::new).unwrap_or_else(null),
+        ))
+    }
+
+    // match (valeur) { motif [if garde] => expression, ... }
+    // Les bras sont séparés par une virgule ou un retour à la ligne.
+    fn parse_match(&mut self) -> Result<Expression, Diagnostic> {
+        self.advance(); // Consomme 'match'
+        self.consume(TokenKind::LParen, "after 'match'")?;
+        let subject = self.parse_expression()?;
+        self.consume(TokenKind::RParen, "after match value")?;
+        self.consume(TokenKind::LBrace, "before match arms")?;
+
+        let mut arms = Vec::new();
+        while !self.check(&TokenKind::RBrace) && !self.is_at_end() {
+            let start = self.pos;
+            let pattern = self.parse_pattern()?;
+
+            let mut bindings = Vec::new();
+            Self::pattern_bindings(&pattern, &mut bindings);
+            for (i, name) in bindings.iter().enumerate() {
+                if bindings[..i].contains(name) {
+                    return Err(self.error_at(&self.tokens[start], format!("Duplicate binding '{}' in pattern", name)));
+                }
+            }
+
+            let guard = if self.match_token(TokenKind::If) { Some(self.parse_expression()?) } else { None };
+            self.consume(TokenKind::FatArrow, "after match pattern")?;
+            let body = self.parse_expression()?;
+            arms.push(MatchArm { pattern, guard, body });
+
+            if !self.match_token(TokenKind::Comma)
+                && !self.check(&TokenKind::RBrace)
+                && self.tokens[self.pos].line == self.tokens[self.pos - 1].line {
+                return Err(self.error_at_current(format!("Expected ',' or a new line between match arms, found {}", self.peek().describe())));
+            }
+        }
+        self.consume(TokenKind::RBrace, "after match arms")?;
+
+        if arms.is_empty() {
+            return Err(self.error_at_previous("A match needs at least one arm"));
+        }
+        Ok(Expression::Match(Box::new(subject), arms))
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, Diagnostic> {
+        match self.peek().clone() {
+            TokenKind::Identifier(name) => {
+                self.advance();
+                if name == "_" {
+                    return Ok(Pattern::Wildcard);
+                }
+
+                // Chemin pointé : Color.Red, Geo.Point...
+                let mut path = Expression::Variable(name.clone());
+                let mut dotted = false;
+                while self.match_token(TokenKind::Dot) {
+                    path = Expression::GetAttr(Box::new(path), self.expect_identifier("member name")?);
+                    dotted = true;
+                }
+
+                if self.match_token(TokenKind::LParen) {
+                    let mut args = Vec::new();
+                    if !self.check(&TokenKind::RParen) {
+                        loop {
+                            args.push(self.parse_pattern()?);
+                            if !self.match_token(TokenKind::Comma) { break; }
+                            if self.check(&TokenKind::RParen) { break; }
+                        }
+                    }
+                    self.consume(TokenKind::RParen, "after class pattern arguments")?;
+                    Ok(Pattern::Class(path, args))
+                } else if dotted {
+                    Ok(Pattern::Value(path))
+                } else {
+                    Ok(Pattern::Binding(name))
+                }
+            },
+            TokenKind::LBracket => {
+                self.advance();
+                let mut items = Vec::new();
+                let mut rest = None;
+                if !self.check(&TokenKind::RBracket) {
+                    loop {
+                        if self.match_token(TokenKind::Ellipsis) {
+                            rest = Some(match self.peek() {
+                                TokenKind::Identifier(name) => { let n = name.clone(); self.advance(); n },
+                                _ => "_".to_string(),
+                            });
+                            self.match_token(TokenKind::Comma);
+                            if !self.check(&TokenKind::RBracket) {
+                                return Err(self.error_at_current("The '...' rest pattern must be the last element of a list pattern"));
+                            }
+                            break;
+                        }
+                        items.push(self.parse_pattern()?);
+                        if !self.match_token(TokenKind::Comma) { break; }
+                        if self.check(&TokenKind::RBracket) { break; }
+                    }
+                }
+                self.consume(TokenKind::RBracket, "after list pattern")?;
+                Ok(Pattern::List(items, rest))
+            },
+            TokenKind::LBrace => {
+                self.advance();
+                let mut entries = Vec::new();
+                if !self.check(&TokenKind::RBrace) {
+                    loop {
+                        let (key, shorthand) = match self.peek() {
+                            TokenKind::Identifier(s) => (s.clone(), true),
+                            TokenKind::StringLiteral(s) => (s.clone(), false),
+                            other => return Err(self.error_at_current(format!("Expected dictionary key in pattern, found {}", other.describe()))),
+                        };
+                        self.advance();
+                        // {name} est un raccourci pour {name: name}
+                        let pattern = if self.match_token(TokenKind::Colon) {
+                            self.parse_pattern()?
+                        } else if shorthand {
+                            Pattern::Binding(key.clone())
+                        } else {
+                            return Err(self.error_at_current(format!("Expected ':' after dictionary key in pattern, found {}", self.peek().describe())));
+                        };
+                        entries.push((key, pattern));
+                        if !self.match_token(TokenKind::Comma) { break; }
+                        if self.check(&TokenKind::RBrace) { break; }
+                    }
+                }
+                self.consume(TokenKind::RBrace, "after dictionary pattern")?;
+                Ok(Pattern::Dict(entries))
+            },
+            _ => {
+                let value = self.parse_pattern_literal()?;
+                if self.match_token(TokenKind::DotDot) {
+                    let end = self.parse_pattern_literal()?;
+                    let is_number = |v: &Value| matches!(v, Value::Integer(_) | Value::Float(_));
+                    if !is_number(&value) || !is_number(&end) {
+                        return Err(self.error_at_previous("Range patterns only accept numbers"));
+                    }
+                    return Ok(Pattern::Range(value, end));
+                }
+                Ok(Pattern::Literal(value))
+            }
+        }
+    }
+
+    fn parse_pattern_literal(&mut self) -> Result<Value, Diagnostic> {
+        let negative = self.match_token(TokenKind::Minus);
+        let value = match self.peek() {
+            TokenKind::Integer(n) => Value::Integer(if negative { -n } else { *n }),
+            TokenKind::Float(f) => Value::Float(if negative { -f } else { *f }),
+            TokenKind::StringLiteral(s) if !negative => {
+                if s.contains("${") {
+                    return Err(self.error_at_current("String interpolation is not allowed in patterns"));
+                }
+                Value::String(s.as_str().into())
+            },
+            TokenKind::True if !negative => Value::Boolean(true),
+            TokenKind::False if !negative => Value::Boolean(false),
+            TokenKind::Null if !negative => Value::Null,
+            other => return Err(self.error_at_current(format!("Expected pattern, found {}", other.describe()))),
+        };
+        self.advance();
+        Ok(value)
+    }
+
+    fn pattern_bindings(pattern: &Pattern, names: &mut Vec<String>) {
+        match pattern {
+            Pattern::Binding(name) => names.push(name.clone()),
+            Pattern::List(items, rest) => {
+                for item in items {
+                    Self::pattern_bindings(item, names);
+                }
+                if let Some(name) = rest.as_ref().filter(|n| *n != "_") {
+                    names.push(name.clone());
+                }
+            },
+            Pattern::Dict(entries) => {
+                for (_, item) in entries {
+                    Self::pattern_bindings(item, names);
+                }
+            },
+            Pattern::Class(_, args) => {
+                for arg in args {
+                    Self::pattern_bindings(arg, names);
+                }
+            },
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range(..) | Pattern::Value(_) => {},
+        }
+    }
+
+    fn parse_interpolated_string(&self, source: &str) -> Result<Expression, Diagnostic> {
         let mut parts = Vec::new();
         let mut current_text = String::new();
         let mut chars = source.chars().peekable();
 
         while let Some(c) = chars.next() {
-            if c == '$' {
-                if let Some(&'{') = chars.peek() {
-                    chars.next(); // Eat '{'
-                    
-                    if !current_text.is_empty() {
-                        parts.push(json!(current_text.clone()));
-                        current_text.clear();
-                    }
+            if c == '$' && chars.peek() == Some(&'{') {
+                chars.next(); // Eat '{'
 
-                    // Extraction intelligente
-                    let mut code_snippet = String::new();
-                    let mut format_specifier = String::new();
-                    let mut brace_count = 1;
-                    let mut found_colon = false;
-                    
-                    while let Some(code_char) = chars.next() {
-                        if code_char == '}' {
-                            brace_count -= 1;
-                            if brace_count == 0 { break; }
-                        } else if code_char == '{' {
-                            brace_count += 1;
-                        }
+                if !current_text.is_empty() {
+                    parts.push(Expression::Literal(Value::String(std::mem::take(&mut current_text).into())));
+                }
 
-                        // Si on trouve ':' et qu'on est au niveau 1
-                        if code_char == ':' && brace_count == 1 && !found_colon {
-                            found_colon = true;
-                            continue;
-                        }
+                // Extraction intelligente
+                let mut code_snippet = String::new();
+                let mut format_specifier = String::new();
+                let mut brace_count = 1;
+                let mut found_colon = false;
+
+                for code_char in chars.by_ref() {
+                    if code_char == '}' {
+                        brace_count -= 1;
+                        if brace_count == 0 { break; }
+                    } else if code_char == '{' {
+                        brace_count += 1;
+                    }
 
-                        if found_colon {
-                            format_specifier.push(code_char);
-                        } else {
-                            code_snippet.push(code_char);
-                        }
+                    // Si on trouve ':' et qu'on est au niveau 1
+                    if code_char == ':' && brace_count == 1 && !found_colon {
+                        found_colon = true;
+                        continue;
                     }
-                    
-                    if brace_count > 0 { return Err("Unterminated interpolation".into()); }
-
-                    // Compilation du snippet
-                    let mut sub_lexer = super::lexer::Lexer::new(&code_snippet);
-                    let sub_tokens = sub_lexer.tokenize();
-                    let mut sub_parser = Parser::new(sub_tokens);
-                    let expr = sub_parser.parse_expression()?;
-                    
-                    if !format_specifier.is_empty() {
-                        let fmt_call = json!(["call", ["get", "fmt"], [expr, json!(format_specifier)]]);
-                        parts.push(fmt_call);
+
+                    if found_colon {
+                        format_specifier.push(code_char);
                     } else {
-                        parts.push(expr);
+                        code_snippet.push(code_char);
                     }
-                    
-                    continue;
                 }
+
+                if brace_count > 0 { return Err(self.error_at_previous("Unterminated interpolation")); }
+
+                // Compilation du snippet
+                // Les positions du sous-lexer sont relatives au snippet : on rattache
+                // les erreurs au littéral chaîne lui-même
+                let mut sub_lexer = super::lexer::Lexer::new(&code_snippet);
+                let sub_tokens = sub_lexer.tokenize()
+                    .map_err(|e| self.error_at_previous(e.message))?;
+                let mut sub_parser = Parser::new(sub_tokens);
+                let expr = sub_parser.parse_expression()
+                    .map_err(|e| self.error_at_previous(e.message))?;
+
+                if !format_specifier.is_empty() {
+                    parts.push(Expression::Call(
+                        Box::new(Expression::Variable("fmt".to_string())),
+                        vec![expr, Expression::Literal(Value::String(format_specifier.into()))]
+                    ));
+                } else {
+                    parts.push(expr);
+                }
+
+                continue;
             }
             current_text.push(c);
         }
-        
-        if !current_text.is_empty() { parts.push(json!(current_text)); }
-        if parts.is_empty() { return Ok(json!("")); }
-        
-        let mut final_expr = parts[0].clone();
-        for i in 1..parts.len() {
-            final_expr = json!(["+", final_expr, parts[i]]);
+
+        if !current_text.is_empty() { parts.push(Expression::Literal(Value::String(current_text.into()))); }
+
+        let mut parts = parts.into_iter();
+        let Some(mut final_expr) = parts.next() else {
+            return Ok(Expression::Literal(Value::String("".into())));
+        };
+        for part in parts {
+            final_expr = Expression::Add(Box::new(final_expr), Box::new(part));
         }
 
         Ok(final_expr)
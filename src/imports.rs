@@ -0,0 +1,100 @@
+//! Résolution des `import "..."`, dans l'ordre :
+//! 1. relatif au dossier du fichier qui importe (le dossier courant pour le REPL) ;
+//! 2. les dossiers de `AEGIS_PATH` (séparés comme PATH) ;
+//! 3. `packages/<chemin>` à la racine du projet, et `packages/<nom>/<nom>.aeg` pour un nom seul ;
+//! 4. la stdlib embarquée dans l'exécutable (`import "json"`, ou l'ancien `import "stdlib/json.aeg"`).
+//!
+//! Un chemin sans extension reçoit `.aeg` : `import "lib/utils"` trouve `lib/utils.aeg`.
+//! La racine du projet est le premier dossier contenant `aegis.toml` en remontant depuis le script principal
+//! (voir `project_root`) : elle ne dépend pas du dossier courant.
+
+use std::path::{Path, PathBuf};
+
+use crate::stdlib::StdLibAsset;
+
+/// Module trouvé pour un import.
+pub struct Module {
+    /// Identité du module : chemin canonique du fichier, ou `stdlib/<fichier>` pour la stdlib embarquée.
+    /// Deux imports qui désignent le même fichier ont la même identité.
+    pub id: String,
+    /// Nom affiché dans les erreurs (le chemin tel qu'il a été trouvé).
+    pub name: String,
+    /// Dossier où chercher les imports relatifs du module (aucun pour la stdlib embarquée).
+    pub dir: Option<PathBuf>,
+    pub source: String,
+}
+
+/// Trouve le module désigné par `path`, importé depuis un fichier du dossier `dir`
+/// (aucun dossier : un module de la stdlib embarquée) d'un projet dont `root` est la racine.
+pub fn resolve(path: &str, dir: Option<&Path>, root: Option<&Path>) -> Result<Module, String> {
+    if let Some(file) = find_file(path, dir, root) {
+        let source = std::fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to import '{}': {}", path, e))?;
+        let id = std::fs::canonicalize(&file).unwrap_or_else(|_| file.clone());
+        return Ok(Module {
+            id: id.display().to_string(),
+            name: file.display().to_string(),
+            dir: file.parent().map(Path::to_path_buf),
+            source,
+        });
+    }
+
+    if let Some((name, source)) = stdlib_source(path) {
+        let id = format!("stdlib/{}", name);
+        return Ok(Module { id: id.clone(), name: id, dir: None, source });
+    }
+    Err(format!(
+        "Failed to import '{}': module not found (searched next to the importing file, AEGIS_PATH, packages/ and the standard library)",
+        path
+    ))
+}
+
+/// Fichier désigné par `path` sur le disque (étapes 1 à 3), sans la stdlib embarquée.
+pub fn find_file(path: &str, dir: Option<&Path>, root: Option<&Path>) -> Option<PathBuf> {
+    let file = with_extension(path);
+    let file = Path::new(&file);
+    if file.is_absolute() {
+        return file.is_file().then(|| file.to_path_buf());
+    }
+
+    let mut candidates: Vec<PathBuf> = dir.into_iter().map(|dir| dir.join(file)).collect();
+    if let Some(search_path) = std::env::var_os("AEGIS_PATH") {
+        candidates.extend(std::env::split_paths(&search_path).map(|dir| dir.join(file)));
+    }
+    if let Some(packages) = root.map(|root| root.join("packages")) {
+        candidates.push(packages.join(file));
+        if is_bare_name(path) {
+            candidates.push(packages.join(path).join(format!("{}.aeg", path)));
+        }
+    }
+    candidates.into_iter().find(|candidate| candidate.is_file())
+}
+
+/// Racine du projet d'un script du dossier `dir` : le premier dossier qui contient `aegis.toml`
+/// en remontant depuis `dir`, sinon `dir` lui-même.
+pub fn project_root(dir: &Path) -> PathBuf {
+    let dir = std::fs::canonicalize(dir).unwrap_or_else(|_| dir.to_path_buf());
+    let root = dir.ancestors().find(|ancestor| ancestor.join("aegis.toml").is_file());
+    root.unwrap_or(&dir).to_path_buf()
+}
+
+/// Module de la stdlib embarquée : nom du fichier et source.
+pub fn stdlib_source(path: &str) -> Option<(String, String)> {
+    let name = with_extension(path.strip_prefix("stdlib/").unwrap_or(path));
+    let asset = StdLibAsset::get(&name)?;
+    let source = String::from_utf8(asset.data.into_owned()).ok()?;
+    Some((name, source))
+}
+
+fn with_extension(path: &str) -> String {
+    if Path::new(path).extension().is_some() {
+        path.to_string()
+    } else {
+        format!("{}.aeg", path)
+    }
+}
+
+// `import "sqlite"` : ni dossier ni extension
+fn is_bare_name(path: &str) -> bool {
+    !path.is_empty() && !path.contains(['/', '\\', '.'])
+}
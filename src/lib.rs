@@ -1,11 +1,17 @@
 pub mod ast;
 pub mod compiler;
 pub mod loader;
+pub mod validator;
+pub mod unparser;
+pub mod lsp;
 pub mod native;
 pub mod plugins;
 pub mod stdlib;
 pub mod vm;
 pub mod chunk;
+pub mod bytecode;
+pub mod bundle;
+pub mod imports;
 pub mod opcode;
 pub mod package_manager;
 
@@ -1,12 +1,48 @@
-use serde_json::Value as JsonValue;
-use crate::ast::{ClassDefinition, Expression, Instruction, Statement, Value, nodes::{ClassField, ClassProperty, InterfaceDefinition, InterfaceMethod}, value::Visibility};
+use serde_json::{json, Value as JsonValue};
+use crate::ast::{ClassDefinition, EnumDefinition, EnumVariant, Expression, Instruction, MatchArm, Pattern, Statement, Value, nodes::{ClassField, ClassProperty, InterfaceDefinition, InterfaceMethod, Param, Params}, value::Visibility};
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
+/// Version du format d'échange JSON de l'AST (voir aegis-docs/src/advanced/json_ast.md).
+pub const AST_FORMAT_VERSION: u64 = 1;
+
+/// Charge un programme exporté : `{"version": 1, "body": [...]}`.
+/// Un tableau nu (format historique, sans en-tête) est toujours accepté.
+pub fn parse_program(program_json: &JsonValue) -> Result<Vec<Statement>, String> {
+    if program_json.is_array() {
+        return parse_block(program_json);
+    }
+    let version = program_json.get("version").and_then(|v| v.as_u64()).ok_or("AST: missing 'version' header")?;
+    if version != AST_FORMAT_VERSION {
+        return Err(format!("AST: unsupported format version {} (supported: {})", version, AST_FORMAT_VERSION));
+    }
+    parse_block(program_json.get("body").ok_or("AST: missing 'body'")?)
+}
+
 pub fn parse_block(block_json: &JsonValue) -> Result<Vec<Statement>, String> {
     let array = block_json.as_array().ok_or("Block must be a JSON array")?;
     array.iter().map(|instr| parse_statement_json(instr)).collect()
 }
 
+// Paramètres : [nom, type|null, défaut|null, rest] (les deux derniers sont optionnels) ou un nom seul
+fn parse_params(params_json: &JsonValue) -> Result<Params, String> {
+    let mut params = Vec::new();
+    for p in params_json.as_array().ok_or("Invalid params array")? {
+        if let Some(s) = p.as_str() {
+            params.push(Param::new(s.to_string(), None));
+        } else if let Some(row) = p.as_array() {
+            let n = row.first().and_then(|n| n.as_str()).ok_or("Invalid parameter name")?.to_string();
+            let t = row.get(1).and_then(|t| t.as_str()).map(|s| s.to_string());
+            let mut param = Param::new(n, t);
+            if let Some(default) = row.get(2).filter(|d| !d.is_null()) {
+                param.default = Some(parse_expression(default)?);
+            }
+            param.is_rest = row.get(3).and_then(|r| r.as_bool()).unwrap_or(false);
+            params.push(param);
+        }
+    }
+    Ok(params)
+}
+
 fn parse_visibility(v: &str) -> Visibility {
     match v {
         "private" => Visibility::Private,
@@ -22,7 +58,7 @@ fn json_to_value(json: &JsonValue) -> Result<Value, String> {
             else if n.is_f64() { Ok(Value::Float(n.as_f64().unwrap())) }
             else { Ok(Value::Integer(n.as_i64().unwrap_or(0))) }
         },
-        JsonValue::String(s) => Ok(Value::String(s.clone())),
+        JsonValue::String(s) => Ok(Value::String(s.as_str().into())),
         JsonValue::Bool(b) => Ok(Value::Boolean(*b)),
         JsonValue::Null => Ok(Value::Null),
         JsonValue::Array(arr) => {
@@ -115,23 +151,35 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
                     Ok(Expression::New(Box::new(class_name_expr), args))
                 },
                 "get_attr" => Ok(Expression::GetAttr(Box::new(parse_expression(&array[1])?), array[2].as_str().ok_or("Attr")?.to_string())),
+                "index" => Ok(Expression::Index(Box::new(parse_expression(&array[1])?), Box::new(parse_expression(&array[2])?))),
+                "slice" => {
+                    // ["slice", OBJ, START, END] : null pour une borne absente
+                    let obj = parse_expression(&array[1])?;
+                    let start = parse_expression(&array[2])?;
+                    let end = parse_expression(&array[3])?;
+                    Ok(Expression::Slice(Box::new(obj), Box::new(start), Box::new(end)))
+                },
+                "match" => {
+                    // ["match", SUBJECT, [[PATTERN, GUARD|null, BODY], ...]]
+                    let subject = parse_expression(&array[1])?;
+                    let mut arms = Vec::new();
+                    for arm in array[2].as_array().ok_or("Match: arms array missing")? {
+                        let arm = arm.as_array().ok_or("Match arm array")?;
+                        let guard = if arm[1].is_null() { None } else { Some(parse_expression(&arm[1])?) };
+                        arms.push(MatchArm { pattern: parse_pattern(&arm[0])?, guard, body: parse_expression(&arm[2])? });
+                    }
+                    Ok(Expression::Match(Box::new(subject), arms))
+                },
                 
                 // --- Fonctions ---
+                // ["lambda", params, block] ou ["lambda", params, block, true] pour une lambda async
                 "lambda" => {
-                    let params_json = array[1].as_array().ok_or("Params array")?;
-                    let mut params = Vec::new();
-                    for p in params_json {
-                        if let Some(name) = p.as_str() {
-                            params.push((name.to_string(), None));
-                        } else if let Some(pair) = p.as_array() {
-                            let name = pair[0].as_str().unwrap().to_string();
-                            let typ = pair[1].as_str().map(|s| s.to_string());
-                            params.push((name, typ));
-                        }
-                    }
+                    let params = parse_params(&array[1])?;
                     let body = parse_block(&array[2])?;
-                    Ok(Expression::Function { params, ret_type: None, body })
+                    let is_async = array.get(3).and_then(|v| v.as_bool()).unwrap_or(false);
+                    Ok(Expression::Function { params, ret_type: None, body, is_async })
                 },
+                "await" => Ok(Expression::Await(Box::new(parse_expression(&array[1])?))),
 
                 // --- GESTION ROBUSTE DES APPELS (AVEC OU SANS LIGNE) ---
                 
@@ -172,6 +220,13 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
                     Ok(Expression::SuperCall(method, args))
                 },
 
+                // Arguments d'appel : ["named", nom, expr] et ["spread", expr]
+                "named" => {
+                    let name = array[1].as_str().ok_or("Named argument: name missing")?.to_string();
+                    Ok(Expression::NamedArg(name, Box::new(parse_expression(&array[2])?)))
+                },
+                "spread" => Ok(Expression::Spread(Box::new(parse_expression(&array[1])?))),
+
                 "range" => {
                     let start = parse_expression(&array[2])?;
                     let end = parse_expression(&array[3])?;
@@ -207,6 +262,38 @@ pub fn parse_expression(json_expr: &JsonValue) -> Result<Expression, String> {
     }
 }
 
+// ["_"], ["bind", name], ["lit", value], ["range", start, end], ["list", [items], rest|null],
+// ["dict", [[key, pattern], ...]], ["class", class_expr, [patterns]], ["value", expr]
+pub fn parse_pattern(json: &JsonValue) -> Result<Pattern, String> {
+    let array = json.as_array().ok_or("Pattern array")?;
+    let kind = array.first().and_then(|k| k.as_str()).ok_or("Pattern kind")?;
+    let patterns = |json: &JsonValue| -> Result<Vec<Pattern>, String> {
+        json.as_array().ok_or("Pattern list")?.iter().map(parse_pattern).collect()
+    };
+
+    match kind {
+        "_" => Ok(Pattern::Wildcard),
+        "bind" => Ok(Pattern::Binding(array[1].as_str().ok_or("Binding name")?.to_string())),
+        "lit" => Ok(Pattern::Literal(json_to_value(&array[1])?)),
+        "range" => Ok(Pattern::Range(json_to_value(&array[1])?, json_to_value(&array[2])?)),
+        "list" => {
+            let rest = array[2].as_str().map(|s| s.to_string());
+            Ok(Pattern::List(patterns(&array[1])?, rest))
+        },
+        "dict" => {
+            let mut entries = Vec::new();
+            for entry in array[1].as_array().ok_or("Dict pattern entries")? {
+                let pair = entry.as_array().ok_or("Dict pattern entry")?;
+                entries.push((pair[0].as_str().ok_or("Dict pattern key")?.to_string(), parse_pattern(&pair[1])?));
+            }
+            Ok(Pattern::Dict(entries))
+        },
+        "class" => Ok(Pattern::Class(parse_expression(&array[1])?, patterns(&array[2])?)),
+        "value" => Ok(Pattern::Value(parse_expression(&array[1])?)),
+        other => Err(format!("Unknown pattern '{}'", other)),
+    }
+}
+
 pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String> {
     let array = json_instr.as_array().ok_or("Instruction must be array")?;
     let command = array[0].as_str().ok_or("Command must be string")?;
@@ -227,6 +314,12 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
             let val = parse_expression(&array[4])?;
             Ok(Instruction::SetAttr(Box::new(obj), attr, val))
         },
+        "set_index" => {
+            let obj = parse_expression(&array[2])?;
+            let key = parse_expression(&array[3])?;
+            let val = parse_expression(&array[4])?;
+            Ok(Instruction::SetIndex(Box::new(obj), key, val))
+        },
         "print" => Ok(Instruction::Print(parse_expression(&array[2])?)),
         "input" => {
             let var = array[2].as_str().unwrap().to_string();
@@ -243,28 +336,23 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
         "while" => Ok(Instruction::While { condition: parse_expression(&array[2])?, body: parse_block(&array[3])? }),
         
         "return" => Ok(Instruction::Return(parse_expression(&array[2])?)),
+        "yield" => Ok(Instruction::Yield(parse_expression(&array[2])?)),
         
         "call" | "call_method" | "super_call" => {
             // Ici, parse_expression va gérer le format imbriqué
             Ok(Instruction::ExpressionStatement(parse_expression(json_instr)?))
         },
+
+        // Expression quelconque évaluée pour ses effets : ["expr", line, expr]
+        "expr" => Ok(Instruction::ExpressionStatement(parse_expression(&array[2])?)),
         
         "function" => {
             let name = array[2].as_str().unwrap().to_string();
-            let params_json = array[3].as_array().unwrap();
-            let mut params = Vec::new();
-            for p in params_json {
-                if let Some(s) = p.as_str() {
-                    params.push((s.to_string(), None));
-                } else if let Some(pair) = p.as_array() {
-                    let n = pair[0].as_str().unwrap().to_string();
-                    let t = pair[1].as_str().map(|s| s.to_string());
-                    params.push((n, t));
-                }
-            }
+            let params = parse_params(&array[3])?;
             let ret_type = array[4].as_str().map(|s| s.to_string());
             let body = parse_block(&array[5])?;
-            Ok(Instruction::Function { name, params, ret_type, body })
+            let is_async = array.get(6).and_then(|v| v.as_bool()).unwrap_or(false);
+            Ok(Instruction::Function { name, params, ret_type, body, is_async })
         },
 
         "interface" => {
@@ -282,22 +370,8 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                 // Index 0 : Le nom de la méthode
                 let m_name = m_data[0].as_str().ok_or("Invalid method name")?.to_string();
                 
-                // Index 1 : Les paramètres (C'est ici que tu avais l'erreur "Invalid params array")
-                let params_json = m_data[1].as_array().ok_or("Invalid params array")?;
-                
-                let mut params = Vec::new();
-                for p in params_json {
-                    // Chaque paramètre est un tableau [nom, type] ou [nom, null]
-                    let p_arr = p.as_array().ok_or("Invalid param structure")?;
-                    let p_name = p_arr[0].as_str().unwrap().to_string();
-                    
-                    let p_type = if p_arr.len() > 1 && !p_arr[1].is_null() {
-                        Some(p_arr[1].as_str().unwrap().to_string())
-                    } else {
-                        None
-                    };
-                    params.push((p_name, p_type));
-                }
+                // Index 1 : Les paramètres, chacun [nom, type] ou [nom, null]
+                let params = parse_params(&m_data[1])?;
                 
                 methods.push(InterfaceMethod { name: m_name, params });
             }
@@ -320,17 +394,7 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                 // JSON attendu : [params, body, is_static]
                 
                 // A. Params
-                let params_arr = m_arr[0].as_array().ok_or("Invalid params array")?;
-                let mut params = Vec::new();
-                for p in params_arr {
-                    if let Some(p_row) = p.as_array() {
-                         let p_name = p_row[0].as_str().unwrap().to_string();
-                         let p_type = if p_row.len() > 1 && !p_row[1].is_null() {
-                             Some(p_row[1].as_str().unwrap().to_string())
-                         } else { None };
-                         params.push((p_name, p_type));
-                    }
-                }
+                let params = parse_params(&m_arr[0])?;
                 
                 // B. Body
                 let body = parse_block(&m_arr[1])?;
@@ -344,9 +408,11 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                 let is_final = if m_arr.len() > 3 {
                     m_arr[3].as_bool().unwrap_or(false)
                 } else { false };
+
+                let is_async = m_arr.get(4).and_then(|v| v.as_bool()).unwrap_or(false);
                 
-                // On insère le tuple (params, body, is_static)
-                methods.insert(m_name.clone(), (params, body, is_static, is_final));
+                // On insère le tuple (params, body, is_static, is_final, is_async)
+                methods.insert(m_name.clone(), (params, body, is_static, is_final, is_async));
             }
 
             // 2. Parsing du Parent
@@ -407,17 +473,8 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
                             // Parsing Setter
                             let setter_data = if !m_data[5].is_null() {
                                 let s_arr = m_data[5].as_array().unwrap();
-                                let params_json = s_arr[0].as_array().unwrap();
-                                
                                 // On parse les params du setter (ex: [val])
-                                let mut params = Vec::new();
-                                for p in params_json {
-                                    if let Some(p_row) = p.as_array() {
-                                         let p_name = p_row[0].as_str().unwrap().to_string();
-                                         let p_type = if p_row.len() > 1 && !p_row[1].is_null() { Some(p_row[1].as_str().unwrap().to_string()) } else { None };
-                                         params.push((p_name, p_type));
-                                    }
-                                }
+                                let params = parse_params(&s_arr[0])?;
                                 let body = parse_block(&s_arr[1])?;
                                 Some((params, body))
                             } else { None };
@@ -468,17 +525,49 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
         },
 
         "enum" => {
-            let name = array[2].as_str().unwrap().to_string();
-            let variants_arr = array[3].as_array().unwrap();
-            
-            let variants: Vec<String> = variants_arr.iter()
-                .map(|v| v.as_str().unwrap().to_string())
-                .collect();
-                
-            Ok(Instruction::Enum(name, variants))
+            // ["enum", line, name, variants, methods?] : une variante est "Nom" ou ["Nom", [champs]]
+            let name = array[2].as_str().ok_or("Invalid enum name")?.to_string();
+            let mut variants = Vec::new();
+            for v in array[3].as_array().ok_or("Invalid enum variants")? {
+                let variant = match v {
+                    JsonValue::String(v_name) => EnumVariant { name: v_name.clone(), fields: Vec::new() },
+                    JsonValue::Array(pair) => {
+                        let v_name = pair.first().and_then(|n| n.as_str()).ok_or("Invalid variant name")?.to_string();
+                        let fields = pair.get(1).and_then(|f| f.as_array()).ok_or("Invalid variant fields")?
+                            .iter()
+                            .map(|f| f.as_str().map(|f| f.to_string()).ok_or("Invalid variant field"))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        EnumVariant { name: v_name, fields }
+                    },
+                    _ => return Err("Invalid enum variant".into()),
+                };
+                variants.push(variant);
+            }
+
+            let mut methods = HashMap::new();
+            if let Some(methods_json) = array.get(4) {
+                for (m_name, m_data) in methods_json.as_object().ok_or("Invalid enum methods object")? {
+                    let m_arr = m_data.as_array().ok_or("Invalid method array")?;
+                    methods.insert(m_name.clone(), (parse_params(&m_arr[0])?, parse_block(&m_arr[1])?));
+                }
+            }
+
+            Ok(Instruction::Enum(EnumDefinition { name, variants, methods }))
         },
         
-        "import" => Ok(Instruction::Import(array[2].as_str().unwrap().to_string())),
+        "import" => {
+            let alias = array.get(3).and_then(|a| a.as_str()).map(|a| a.to_string());
+            Ok(Instruction::Import(array[2].as_str().unwrap().to_string(), alias))
+        },
+
+        "from_import" => {
+            let names = array[3].as_array().ok_or("Invalid import name list")?
+                .iter().map(|n| n.as_str().map(|n| n.to_string()).ok_or("Invalid import name"))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Instruction::ImportFrom(array[2].as_str().unwrap().to_string(), names))
+        },
+
+        "export" => Ok(Instruction::Export(Box::new(parse_statement_json(&array[2])?))),
         
         "switch" => {
             let val = parse_expression(&array[2])?;
@@ -532,3 +621,221 @@ pub fn parse_statement_json(json_instr: &JsonValue) -> Result<Statement, String>
 
     Ok(Statement { kind: instruction, line })
 }
+
+// --- Export AST -> JSON ---
+// Format inverse de parse_program : program_to_json(ast) relu par parse_program redonne le même AST.
+
+pub fn program_to_json(program: &[Statement]) -> JsonValue {
+    json!({ "version": AST_FORMAT_VERSION, "body": block_to_json(program) })
+}
+
+pub fn block_to_json(block: &[Statement]) -> JsonValue {
+    JsonValue::Array(block.iter().map(statement_to_json).collect())
+}
+
+fn visibility_to_json(v: &Visibility) -> &'static str {
+    match v {
+        Visibility::Public => "public",
+        Visibility::Protected => "protected",
+        Visibility::Private => "private",
+    }
+}
+
+fn params_to_json(params: &[Param]) -> JsonValue {
+    JsonValue::Array(params.iter().map(|p| {
+        if p.default.is_none() && !p.is_rest {
+            json!([p.name, p.type_annot])
+        } else {
+            json!([p.name, p.type_annot, p.default.as_ref().map(expression_to_json), p.is_rest])
+        }
+    }).collect())
+}
+
+fn value_to_json(value: &Value) -> JsonValue {
+    match value {
+        Value::Integer(i) => json!(i),
+        Value::Float(f) => json!(f),
+        Value::String(s) => json!(&**s),
+        Value::Boolean(b) => json!(b),
+        Value::List(list) => JsonValue::Array(list.borrow().iter().map(value_to_json).collect()),
+        Value::Dict(dict) => JsonValue::Object(dict.borrow().iter().map(|(k, v)| (k.clone(), value_to_json(v))).collect()),
+        // Les autres valeurs n'existent qu'à l'exécution
+        _ => JsonValue::Null,
+    }
+}
+
+fn exprs_to_json(exprs: &[Expression]) -> Vec<JsonValue> {
+    exprs.iter().map(expression_to_json).collect()
+}
+
+fn binary(op: &str, left: &Expression, right: &Expression) -> JsonValue {
+    json!([op, expression_to_json(left), expression_to_json(right)])
+}
+
+pub fn expression_to_json(expr: &Expression) -> JsonValue {
+    match expr {
+        Expression::Literal(v) => value_to_json(v),
+        Expression::Variable(name) => json!(["get", name]),
+        Expression::Function { params, body, is_async: false, .. } => json!(["lambda", params_to_json(params), block_to_json(body)]),
+        Expression::Function { params, body, .. } => json!(["lambda", params_to_json(params), block_to_json(body), true]),
+
+        Expression::Add(l, r) => binary("+", l, r),
+        Expression::Sub(l, r) => binary("-", l, r),
+        Expression::Mul(l, r) => binary("*", l, r),
+        Expression::Div(l, r) => binary("/", l, r),
+        Expression::Modulo(l, r) => binary("%", l, r),
+
+        Expression::Equal(l, r) => binary("==", l, r),
+        Expression::NotEqual(l, r) => binary("!=", l, r),
+        Expression::LessThan(l, r) => binary("<", l, r),
+        Expression::GreaterThan(l, r) => binary(">", l, r),
+        Expression::LessEqual(l, r) => binary("<=", l, r),
+        Expression::GreaterEqual(l, r) => binary(">=", l, r),
+
+        Expression::And(l, r) => binary("&&", l, r),
+        Expression::Or(l, r) => binary("||", l, r),
+        Expression::Not(e) => json!(["!", expression_to_json(e)]),
+        Expression::Ternary(c, t, f) => json!(["?", expression_to_json(c), expression_to_json(t), expression_to_json(f)]),
+        // La ligne (index 1) est ignorée par le loader
+        Expression::NullCoalescing(l, r) => json!(["??", 0, expression_to_json(l), expression_to_json(r)]),
+        Expression::Range(l, r) => json!(["range", 0, expression_to_json(l), expression_to_json(r)]),
+
+        Expression::BitAnd(l, r) => binary("&", l, r),
+        Expression::BitOr(l, r) => binary("|", l, r),
+        Expression::BitXor(l, r) => binary("^", l, r),
+        Expression::ShiftLeft(l, r) => binary("<<", l, r),
+        Expression::ShiftRight(l, r) => binary(">>", l, r),
+
+        Expression::Call(target, args) => json!(["call", expression_to_json(target), exprs_to_json(args)]),
+        Expression::CallMethod(obj, name, args) => json!(["call_method", expression_to_json(obj), name, exprs_to_json(args)]),
+        Expression::SuperCall(name, args) => json!(["super_call", name, exprs_to_json(args)]),
+        Expression::New(class, args) => {
+            let mut arr = vec![json!("new"), expression_to_json(class)];
+            arr.extend(exprs_to_json(args));
+            JsonValue::Array(arr)
+        },
+        Expression::GetAttr(obj, name) => json!(["get_attr", expression_to_json(obj), name]),
+        Expression::Index(obj, key) => json!(["index", expression_to_json(obj), expression_to_json(key)]),
+        Expression::Slice(obj, start, end) => json!(["slice", expression_to_json(obj), expression_to_json(start), expression_to_json(end)]),
+        Expression::List(items) => {
+            let mut arr = vec![json!("make_list")];
+            arr.extend(exprs_to_json(items));
+            JsonValue::Array(arr)
+        },
+        Expression::Dict(entries) => {
+            let mut arr = vec![json!("make_dict")];
+            arr.extend(entries.iter().map(|(k, v)| json!([k, expression_to_json(v)])));
+            JsonValue::Array(arr)
+        },
+        Expression::Match(subject, arms) => {
+            let arms: Vec<JsonValue> = arms.iter()
+                .map(|arm| json!([pattern_to_json(&arm.pattern), arm.guard.as_ref().map(expression_to_json), expression_to_json(&arm.body)]))
+                .collect();
+            json!(["match", expression_to_json(subject), arms])
+        },
+        Expression::NamedArg(name, value) => json!(["named", name, expression_to_json(value)]),
+        Expression::Spread(list) => json!(["spread", expression_to_json(list)]),
+        Expression::Await(task) => json!(["await", expression_to_json(task)]),
+    }
+}
+
+fn pattern_to_json(pattern: &Pattern) -> JsonValue {
+    let patterns = |items: &[Pattern]| JsonValue::Array(items.iter().map(pattern_to_json).collect());
+    match pattern {
+        Pattern::Wildcard => json!(["_"]),
+        Pattern::Binding(name) => json!(["bind", name]),
+        Pattern::Literal(v) => json!(["lit", value_to_json(v)]),
+        Pattern::Range(start, end) => json!(["range", value_to_json(start), value_to_json(end)]),
+        Pattern::List(items, rest) => json!(["list", patterns(items), rest]),
+        Pattern::Dict(entries) => {
+            let entries: Vec<JsonValue> = entries.iter().map(|(k, p)| json!([k, pattern_to_json(p)])).collect();
+            json!(["dict", entries])
+        },
+        Pattern::Class(class, args) => json!(["class", expression_to_json(class), patterns(args)]),
+        Pattern::Value(expr) => json!(["value", expression_to_json(expr)]),
+    }
+}
+
+fn enum_to_json(line: usize, def: &EnumDefinition) -> JsonValue {
+    let variants: Vec<JsonValue> = def.variants.iter()
+        .map(|v| if v.fields.is_empty() { json!(v.name) } else { json!([v.name, v.fields]) })
+        .collect();
+    if def.methods.is_empty() {
+        return json!(["enum", line, def.name, variants]);
+    }
+    let methods: serde_json::Map<String, JsonValue> = def.methods.iter()
+        .map(|(name, (params, body))| (name.clone(), json!([params_to_json(params), block_to_json(body)])))
+        .collect();
+    json!(["enum", line, def.name, variants, methods])
+}
+
+fn class_to_json(line: usize, class: &ClassDefinition) -> JsonValue {
+    let methods: serde_json::Map<String, JsonValue> = class.methods.iter()
+        .map(|(name, (params, body, is_static, is_final, is_async))| {
+            let mut method = json!([params_to_json(params), block_to_json(body), is_static, is_final]);
+            if *is_async {
+                method.as_array_mut().unwrap().push(json!(true));
+            }
+            (name.clone(), method)
+        })
+        .collect();
+
+    let mut members: Vec<JsonValue> = class.fields.iter()
+        .map(|f| json!(["field", f.name, visibility_to_json(&f.visibility), expression_to_json(&f.default_value), f.is_static, f.type_annot]))
+        .collect();
+    members.extend(class.properties.iter().map(|p| {
+        let getter = p.getter.as_ref().map(|(_, body)| json!([[], block_to_json(body)]));
+        let setter = p.setter.as_ref().map(|(params, body)| json!([params_to_json(params), block_to_json(body)]));
+        json!(["prop", p.name, visibility_to_json(&p.visibility), p.is_static, getter, setter])
+    }));
+
+    let visibilities: serde_json::Map<String, JsonValue> = class.visibilities.iter()
+        .map(|(name, v)| (name.clone(), json!(visibility_to_json(v))))
+        .collect();
+
+    json!(["class", line, class.name, methods, class.parent, members, visibilities, class.is_final, class.interfaces])
+}
+
+pub fn statement_to_json(stmt: &Statement) -> JsonValue {
+    let line = stmt.line;
+    match &stmt.kind {
+        Instruction::Set(name, typ, expr) => json!(["set", line, name, typ, expression_to_json(expr)]),
+        Instruction::SetAttr(obj, attr, expr) => json!(["set_attr", line, expression_to_json(obj), attr, expression_to_json(expr)]),
+        Instruction::SetIndex(obj, key, expr) => json!(["set_index", line, expression_to_json(obj), expression_to_json(key), expression_to_json(expr)]),
+        Instruction::Print(expr) => json!(["print", line, expression_to_json(expr)]),
+        Instruction::Input(name, prompt) => json!(["input", line, name, expression_to_json(prompt)]),
+        Instruction::If { condition, body, else_body } => json!(["if", line, expression_to_json(condition), block_to_json(body), block_to_json(else_body)]),
+        Instruction::While { condition, body } => json!(["while", line, expression_to_json(condition), block_to_json(body)]),
+        Instruction::Return(expr) => json!(["return", line, expression_to_json(expr)]),
+        Instruction::Yield(expr) => json!(["yield", line, expression_to_json(expr)]),
+        Instruction::ExpressionStatement(expr) => match expr {
+            Expression::Call(target, args) => json!(["call", line, expression_to_json(target), exprs_to_json(args)]),
+            Expression::CallMethod(obj, name, args) => json!(["call_method", line, expression_to_json(obj), name, exprs_to_json(args)]),
+            Expression::SuperCall(name, args) => json!(["super_call", line, name, exprs_to_json(args)]),
+            _ => json!(["expr", line, expression_to_json(expr)]),
+        },
+        Instruction::Function { name, params, ret_type, body, is_async: false } => json!(["function", line, name, params_to_json(params), ret_type, block_to_json(body)]),
+        Instruction::Function { name, params, ret_type, body, .. } => json!(["function", line, name, params_to_json(params), ret_type, block_to_json(body), true]),
+        Instruction::Interface(def) => {
+            let methods: Vec<JsonValue> = def.methods.iter().map(|m| json!([m.name, params_to_json(&m.params)])).collect();
+            json!(["interface", line, def.name, methods])
+        },
+        Instruction::Class(class) => class_to_json(line, class),
+        Instruction::Enum(def) => enum_to_json(line, def),
+        Instruction::Import(path, None) => json!(["import", line, path]),
+        Instruction::Import(path, Some(alias)) => json!(["import", line, path, alias]),
+        Instruction::ImportFrom(path, names) => json!(["from_import", line, path, names]),
+        Instruction::Export(declaration) => json!(["export", line, statement_to_json(declaration)]),
+        Instruction::Switch { value, cases, default } => {
+            let cases: Vec<JsonValue> = cases.iter().map(|(c, body)| json!([expression_to_json(c), block_to_json(body)])).collect();
+            json!(["switch", line, expression_to_json(value), cases, block_to_json(default)])
+        },
+        Instruction::TryCatch { try_body, error_var, catch_body } => json!(["try", line, block_to_json(try_body), error_var, block_to_json(catch_body)]),
+        Instruction::Throw(expr) => json!(["throw", line, expression_to_json(expr)]),
+        Instruction::Namespace { name, body } => json!(["namespace", line, name, block_to_json(body)]),
+        Instruction::Break => json!(["break", line]),
+        Instruction::Continue => json!(["continue", line]),
+        Instruction::Const(name, expr) => json!(["const", line, name, expression_to_json(expr)]),
+        Instruction::ForEach(var, iterable, body) => json!(["foreach", line, var, expression_to_json(iterable), block_to_json(body)]),
+    }
+}
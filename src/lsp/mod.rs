@@ -0,0 +1,499 @@
+mod symbols;
+
+use crate::compiler::{self, Span};
+use crate::native;
+use crate::imports;
+use serde_json::{json, Value as JsonValue};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use symbols::{Outline, Symbol, SymbolKind};
+use url::Url;
+
+// Codes d'erreur JSON-RPC
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+const KEYWORDS: &[&str] = &[
+    "var", "const", "func", "return", "yield", "async", "await", "if", "else", "while", "foreach", "in", "switch", "case", "default", "match",
+    "break", "continue", "try", "catch", "throw", "class", "extends", "implements", "interface", "new",
+    "super", "this", "namespace", "enum", "import", "export", "print", "input", "public", "protected", "private",
+    "static", "final", "prop", "true", "false", "null",
+];
+
+/// Lance le serveur de langage sur stdin/stdout (JSON-RPC avec en-têtes Content-Length).
+/// Rien d'autre ne doit être écrit sur stdout : les logs vont sur stderr.
+pub fn run() -> Result<(), String> {
+    let stdin = io::stdin();
+    let mut reader = stdin.lock();
+    let mut stdout = io::stdout();
+    let mut server = Server::new();
+
+    while let Some(body) = read_message(&mut reader)? {
+        // Un message illisible reçoit une erreur sans id ; le serveur passe au suivant
+        let message: JsonValue = match serde_json::from_slice(&body) {
+            Ok(message) => message,
+            Err(e) => {
+                let error = json!({ "code": PARSE_ERROR, "message": format!("Invalid JSON-RPC message: {}", e) });
+                write_message(&mut stdout, &json!({ "jsonrpc": "2.0", "id": null, "error": error }))?;
+                continue;
+            },
+        };
+        let method = message.get("method").and_then(|m| m.as_str()).unwrap_or("");
+        let params = message.get("params").cloned().unwrap_or(JsonValue::Null);
+
+        if method == "exit" {
+            break;
+        }
+
+        let result = server.handle(method, &params);
+
+        // Une requête (avec id) attend une réponse, une notification non
+        if let Some(id) = message.get("id") {
+            let response = match result {
+                Ok(value) => json!({ "jsonrpc": "2.0", "id": id, "result": value }),
+                Err((code, msg)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": msg } }),
+            };
+            write_message(&mut stdout, &response)?;
+        }
+
+        for notification in server.outgoing.drain(..) {
+            write_message(&mut stdout, &notification)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Lit le corps du prochain message : None à la fin de stdin, une erreur si les en-têtes sont illisibles
+fn read_message(reader: &mut impl BufRead) -> Result<Option<Vec<u8>>, String> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        let read = reader.read_line(&mut header).map_err(|e| e.to_string())?;
+        if read == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = Some(value.trim().parse::<usize>().map_err(|e| format!("Invalid Content-Length: {}", e))?);
+        }
+    }
+
+    let length = length.ok_or("Missing Content-Length header")?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    Ok(Some(body))
+}
+
+fn write_message(out: &mut impl Write, message: &JsonValue) -> Result<(), String> {
+    let body = message.to_string();
+    write!(out, "Content-Length: {}\r\n\r\n{}", body.len(), body).map_err(|e| e.to_string())?;
+    out.flush().map_err(|e| e.to_string())
+}
+
+struct Document {
+    text: String,
+    // Dernier plan valide : conservé tant que le fichier ne passe pas le lexer
+    outline: Outline,
+}
+
+// Fichier dont on connaît les déclarations : le document ouvert ou un fichier importé
+struct Module {
+    // None pour la stdlib embarquée dans l'exécutable
+    uri: Option<String>,
+    text: String,
+    outline: Outline,
+}
+
+struct Server {
+    root: Option<PathBuf>,
+    documents: HashMap<String, Document>,
+    natives: Vec<String>,
+    outgoing: Vec<JsonValue>,
+}
+
+type HandlerResult = Result<JsonValue, (i64, String)>;
+
+impl Server {
+    fn new() -> Self {
+        Server {
+            root: None,
+            documents: HashMap::new(),
+            natives: native::get_all_names(),
+            outgoing: Vec::new(),
+        }
+    }
+
+    fn handle(&mut self, method: &str, params: &JsonValue) -> HandlerResult {
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "initialized" => Ok(JsonValue::Null),
+            "shutdown" => Ok(JsonValue::Null),
+            "textDocument/didOpen" => {
+                let uri = text_document_uri(params)?;
+                let text = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                self.update(uri, text);
+                Ok(JsonValue::Null)
+            },
+            "textDocument/didChange" => {
+                let uri = text_document_uri(params)?;
+                // Synchronisation complète : le dernier changement contient tout le texte
+                let text = params["contentChanges"].as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                    .ok_or((INVALID_PARAMS, "Missing contentChanges".to_string()))?;
+                self.update(uri, text.to_string());
+                Ok(JsonValue::Null)
+            },
+            "textDocument/didClose" => {
+                let uri = text_document_uri(params)?;
+                self.documents.remove(&uri);
+                self.outgoing.push(publish_diagnostics(&uri, Vec::new()));
+                Ok(JsonValue::Null)
+            },
+            "textDocument/completion" => self.with_position(params, Self::completion),
+            "textDocument/hover" => self.with_position(params, Self::hover),
+            "textDocument/definition" => self.with_position(params, Self::definition),
+            _ if method.starts_with("$/") => Ok(JsonValue::Null),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        }
+    }
+
+    fn initialize(&mut self, params: &JsonValue) -> JsonValue {
+        self.root = params["rootUri"].as_str()
+            .and_then(uri_to_path)
+            .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": 1,
+                "completionProvider": { "triggerCharacters": ["."] },
+                "hoverProvider": true,
+                "definitionProvider": true,
+            },
+            "serverInfo": { "name": "aegis", "version": env!("CARGO_PKG_VERSION") },
+        })
+    }
+
+    fn update(&mut self, uri: String, text: String) {
+        let diagnostics = match compiler::compile(&text) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.iter().map(|d| json!({
+                "range": range(&text, d.span),
+                "severity": 1,
+                "source": "aegis",
+                "message": d.message,
+            })).collect(),
+        };
+        self.outgoing.push(publish_diagnostics(&uri, diagnostics));
+
+        let outline = match (symbols::outline(&text), self.documents.remove(&uri)) {
+            (Some(outline), _) => outline,
+            (None, Some(previous)) => previous.outline,
+            (None, None) => Outline { symbols: Vec::new(), imports: Vec::new() },
+        };
+        self.documents.insert(uri, Document { text, outline });
+    }
+
+    fn with_position(&self, params: &JsonValue, handler: fn(&Self, &str, usize) -> JsonValue) -> HandlerResult {
+        let uri = text_document_uri(params)?;
+        let Some(document) = self.documents.get(&uri) else {
+            return Ok(JsonValue::Null);
+        };
+        let line = params["position"]["line"].as_u64().unwrap_or(0) as usize;
+        let character = params["position"]["character"].as_u64().unwrap_or(0) as usize;
+        let offset = offset_at(&document.text, line, character);
+        Ok(handler(self, &uri, offset))
+    }
+
+    // Le document puis, récursivement, les fichiers qu'il importe
+    fn modules(&self, uri: &str) -> Vec<Module> {
+        let mut modules = Vec::new();
+        let mut visited = HashSet::new();
+        if let Some(document) = self.documents.get(uri) {
+            visited.insert(uri.to_string());
+            modules.push(Module { uri: Some(uri.to_string()), text: document.text.clone(), outline: document.outline.clone() });
+        }
+
+        let mut i = 0;
+        while i < modules.len() {
+            let dir = modules[i].uri.as_deref().and_then(uri_to_path).and_then(|p| p.parent().map(Path::to_path_buf));
+            let imports: Vec<String> = modules[i].outline.imports.iter().map(|(path, _)| path.clone()).collect();
+            for path in imports {
+                if let Some(module) = self.load_import(dir.as_deref(), &path, &mut visited) {
+                    modules.push(module);
+                }
+            }
+            i += 1;
+        }
+        modules
+    }
+
+    fn load_import(&self, dir: Option<&Path>, path: &str, visited: &mut HashSet<String>) -> Option<Module> {
+        if let Some(file) = self.resolve_import(dir, path) {
+            let uri = Url::from_file_path(&file).ok()?.to_string();
+            if !visited.insert(uri.clone()) {
+                return None;
+            }
+            // Un fichier ouvert dans l'éditeur fait foi, même non sauvegardé
+            let text = match self.documents.get(&uri) {
+                Some(document) => document.text.clone(),
+                None => std::fs::read_to_string(&file).ok()?,
+            };
+            let outline = symbols::outline(&text)?;
+            return Some(Module { uri: Some(uri), text, outline });
+        }
+
+        // Stdlib absente du disque : celle embarquée dans l'exécutable
+        let (name, text) = imports::stdlib_source(path)?;
+        if !visited.insert(format!("stdlib/{}", name)) {
+            return None;
+        }
+        let outline = symbols::outline(&text)?;
+        Some(Module { uri: None, text, outline })
+    }
+
+    // Comme la VM (voir crate::imports) ; un document sans fichier importe depuis la racine du projet
+    fn resolve_import(&self, dir: Option<&Path>, path: &str) -> Option<PathBuf> {
+        let dir = dir.or(self.root.as_deref());
+        imports::find_file(path, dir, dir.map(imports::project_root).as_deref())
+    }
+
+    fn completion(&self, uri: &str, offset: usize) -> JsonValue {
+        let modules = self.modules(uri);
+        let Some(current) = modules.first() else { return json!([]) };
+        let (start, _) = word_bounds(&current.text, offset);
+        let qualifier = qualifier(&current.text, start);
+
+        let symbols: Vec<&Symbol> = match &qualifier {
+            Some(qualifier) => {
+                let members: Vec<&Symbol> = all_symbols(&modules)
+                    .filter(|s| s.container.as_deref() == Some(qualifier.as_str()))
+                    .collect();
+                if members.is_empty() {
+                    // Objet dont on ne connaît pas le type (this, variable...) : tous les membres de classe
+                    all_symbols(&modules)
+                        .filter(|s| matches!(s.kind, SymbolKind::Method | SymbolKind::Field | SymbolKind::Property))
+                        .collect()
+                } else {
+                    members
+                }
+            },
+            // Tout ce qui est déclaré hors classe/namespace dans le document, le premier niveau des imports
+            None => current.outline.symbols.iter()
+                .filter(|s| s.container.is_none())
+                .chain(modules.iter().skip(1)
+                    .flat_map(|m| m.outline.symbols.iter())
+                    .filter(|s| s.container.is_none() && s.global))
+                .collect(),
+        };
+
+        let mut seen = HashSet::new();
+        let mut items: Vec<JsonValue> = symbols.into_iter()
+            .filter(|s| seen.insert(s.name.clone()))
+            .map(|s| json!({ "label": s.name, "kind": completion_kind(s.kind), "detail": s.detail }))
+            .collect();
+
+        if qualifier.is_none() {
+            for keyword in KEYWORDS {
+                items.push(json!({ "label": keyword, "kind": 14 }));
+            }
+            for name in &self.natives {
+                if seen.insert(name.clone()) {
+                    items.push(json!({ "label": name, "kind": 3, "detail": "native function" }));
+                }
+            }
+        }
+        json!(items)
+    }
+
+    fn hover(&self, uri: &str, offset: usize) -> JsonValue {
+        let modules = self.modules(uri);
+        let Some(current) = modules.first() else { return JsonValue::Null };
+        let (start, end) = word_bounds(&current.text, offset);
+        if start == end {
+            return JsonValue::Null;
+        }
+        let name = &current.text[start..end];
+
+        let contents = match find_symbol(&modules, name, qualifier(&current.text, start), start) {
+            Some((_, symbol)) => {
+                let mut value = format!("```aegis\n{}\n```", symbol.detail);
+                if let Some(container) = &symbol.container {
+                    value.push_str(&format!("\n\nin {} `{}`", container_label(&modules, container), container));
+                }
+                value
+            },
+            None if self.natives.iter().any(|n| n == name) => format!("```aegis\n{}(...)\n```\n\nnative function", name),
+            None => return JsonValue::Null,
+        };
+
+        json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": range(&current.text, Span::new(start, end)),
+        })
+    }
+
+    fn definition(&self, uri: &str, offset: usize) -> JsonValue {
+        let modules = self.modules(uri);
+        let Some(current) = modules.first() else { return JsonValue::Null };
+
+        // Sur le chemin d'un import : ouvre le fichier importé
+        if let Some((path, _)) = current.outline.imports.iter().find(|(_, span)| span.start <= offset && offset < span.end) {
+            let dir = uri_to_path(uri).and_then(|p| p.parent().map(Path::to_path_buf));
+            return self.resolve_import(dir.as_deref(), path)
+                .and_then(|file| Url::from_file_path(file).ok())
+                .map(|target| json!({ "uri": target.to_string(), "range": range("", Span::default()) }))
+                .unwrap_or(JsonValue::Null);
+        }
+
+        let (start, end) = word_bounds(&current.text, offset);
+        if start == end {
+            return JsonValue::Null;
+        }
+        let name = &current.text[start..end];
+        match find_symbol(&modules, name, qualifier(&current.text, start), start) {
+            Some((module, symbol)) => match &module.uri {
+                Some(target) => json!({ "uri": target, "range": range(&module.text, symbol.span) }),
+                None => JsonValue::Null,
+            },
+            None => JsonValue::Null,
+        }
+    }
+}
+
+fn all_symbols(modules: &[Module]) -> impl Iterator<Item = &Symbol> {
+    modules.iter().flat_map(|m| m.outline.symbols.iter())
+}
+
+// `X.nom` : membre de X ; sinon la déclaration la plus proche avant le curseur, puis les imports
+fn find_symbol<'a>(modules: &'a [Module], name: &str, qualifier: Option<String>, offset: usize) -> Option<(&'a Module, &'a Symbol)> {
+    let mut candidates = modules.iter()
+        .flat_map(|m| m.outline.symbols.iter().map(move |s| (m, s)))
+        .filter(|(_, s)| s.name == name);
+
+    if let Some(qualifier) = qualifier
+        && let Some(found) = candidates.clone().find(|(_, s)| s.container.as_deref() == Some(qualifier.as_str())) {
+        return Some(found);
+    }
+
+    let current = modules.first()?;
+    let local: Vec<&Symbol> = current.outline.symbols.iter().filter(|s| s.name == name).collect();
+    let before = local.iter().rev().find(|s| s.span.start <= offset);
+    if let Some(symbol) = before.or(local.first()) {
+        return Some((current, symbol));
+    }
+
+    candidates.find(|(_, s)| s.global)
+}
+
+fn container_label(modules: &[Module], container: &str) -> &'static str {
+    let kind = all_symbols(modules)
+        .find(|s| s.name == container && matches!(s.kind, SymbolKind::Namespace | SymbolKind::Class | SymbolKind::Interface | SymbolKind::Enum))
+        .map(|s| s.kind);
+    match kind {
+        Some(SymbolKind::Namespace) => "namespace",
+        Some(SymbolKind::Interface) => "interface",
+        Some(SymbolKind::Enum) => "enum",
+        _ => "class",
+    }
+}
+
+fn completion_kind(kind: SymbolKind) -> u8 {
+    match kind {
+        SymbolKind::Method => 2,
+        SymbolKind::Function => 3,
+        SymbolKind::Field => 5,
+        SymbolKind::Variable => 6,
+        SymbolKind::Class => 7,
+        SymbolKind::Interface => 8,
+        SymbolKind::Namespace => 9,
+        SymbolKind::Property => 10,
+        SymbolKind::Enum => 13,
+        SymbolKind::EnumMember => 20,
+        SymbolKind::Constant => 21,
+    }
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<JsonValue>) -> JsonValue {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+fn text_document_uri(params: &JsonValue) -> Result<String, (i64, String)> {
+    params["textDocument"]["uri"].as_str()
+        .map(str::to_string)
+        .ok_or((INVALID_PARAMS, "Missing textDocument.uri".to_string()))
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// Identifiant qui entoure le curseur
+fn word_bounds(text: &str, offset: usize) -> (usize, usize) {
+    let start = text[..offset].char_indices().rev()
+        .take_while(|(_, c)| is_word_char(*c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+    let end = text[offset..].char_indices()
+        .find(|(_, c)| !is_word_char(*c))
+        .map_or(text.len(), |(i, _)| offset + i);
+    (start, end)
+}
+
+// `System.` devant le mot commençant à `start` : renvoie "System"
+fn qualifier(text: &str, start: usize) -> Option<String> {
+    let before = text[..start].strip_suffix('.')?;
+    let (qualifier_start, _) = word_bounds(before, before.len());
+    let qualifier = &before[qualifier_start..];
+    (!qualifier.is_empty()).then(|| qualifier.to_string())
+}
+
+// Position LSP : ligne à partir de 0, colonne en unités UTF-16
+fn offset_at(text: &str, line: usize, character: usize) -> usize {
+    let mut line_start = 0;
+    for _ in 0..line {
+        match text[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return text.len(),
+        }
+    }
+    let mut units = 0;
+    for (i, c) in text[line_start..].char_indices() {
+        if units >= character || c == '\n' {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    text.len()
+}
+
+fn position_at(text: &str, offset: usize) -> JsonValue {
+    // Le plan d'un document qui ne passe plus le lexer peut être décalé par rapport au texte
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    json!({ "line": line, "character": character })
+}
+
+fn range(text: &str, span: Span) -> JsonValue {
+    json!({ "start": position_at(text, span.start), "end": position_at(text, span.end) })
+}
@@ -0,0 +1,305 @@
+use crate::compiler::lexer::{Lexer, Token, TokenKind};
+use crate::compiler::Span;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Class,
+    Interface,
+    Namespace,
+    Enum,
+    EnumMember,
+    Variable,
+    Constant,
+    Field,
+    Property,
+}
+
+/// Déclaration trouvée dans un fichier.
+#[derive(Debug, Clone)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    // Namespace, classe, interface ou enum qui contient la déclaration
+    pub container: Option<String>,
+    // Texte de la déclaration, ex: "func add(a: int, b) -> int"
+    pub detail: String,
+    // Position du nom dans le source
+    pub span: Span,
+    // Faux pour les variables locales à une fonction
+    pub global: bool,
+}
+
+#[derive(Clone)]
+pub struct Outline {
+    pub symbols: Vec<Symbol>,
+    // Chemins des `import "..."` avec la position du littéral
+    pub imports: Vec<(String, Span)>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ScopeKind {
+    Namespace,
+    Class,
+    Interface,
+    Enum,
+    Function,
+    Block,
+}
+
+struct Scope {
+    kind: ScopeKind,
+    name: Option<String>,
+}
+
+/// Liste les déclarations d'un fichier à partir des tokens seuls : contrairement au parser,
+/// cela fonctionne aussi sur un fichier en cours d'édition qui contient des erreurs de syntaxe.
+/// Renvoie None si le lexer échoue (chaîne non terminée...).
+pub fn outline(source: &str) -> Option<Outline> {
+    let tokens = Lexer::new(source).tokenize().ok()?;
+    let mut builder = OutlineBuilder {
+        source,
+        tokens: &tokens,
+        scopes: Vec::new(),
+        pending: None,
+        parens: 0,
+        outline: Outline { symbols: Vec::new(), imports: Vec::new() },
+    };
+    builder.run();
+    Some(builder.outline)
+}
+
+struct OutlineBuilder<'a> {
+    source: &'a str,
+    tokens: &'a [Token],
+    scopes: Vec<Scope>,
+    // Portée ouverte par la prochaine '{' (corps de fonction, de classe...), et la profondeur
+    // de parenthèses où elle est attendue : le '{}' d'un paramètre `h = {}` n'est pas le corps
+    pending: Option<(Scope, usize)>,
+    parens: usize,
+    outline: Outline,
+}
+
+impl OutlineBuilder<'_> {
+    fn kind(&self, i: usize) -> &TokenKind {
+        self.tokens.get(i).map(|t| &t.kind).unwrap_or(&TokenKind::EOF)
+    }
+
+    fn identifier(&self, i: usize) -> Option<String> {
+        match self.kind(i) {
+            TokenKind::Identifier(name) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    fn expect_scope(&mut self, scope: Scope) {
+        self.pending = Some((scope, self.parens));
+    }
+
+    fn current(&self) -> Option<ScopeKind> {
+        self.scopes.last().map(|s| s.kind)
+    }
+
+    fn in_function(&self) -> bool {
+        self.scopes.iter().any(|s| s.kind == ScopeKind::Function)
+    }
+
+    fn container(&self) -> Option<String> {
+        self.scopes.iter().rev()
+            .take_while(|s| s.kind != ScopeKind::Function)
+            .find(|s| s.kind != ScopeKind::Block)
+            .and_then(|s| s.name.clone())
+    }
+
+    fn is_modifier(kind: &TokenKind) -> bool {
+        matches!(kind, TokenKind::Public | TokenKind::Private | TokenKind::Protected | TokenKind::Static | TokenKind::Final)
+    }
+
+    // Premier token de la déclaration, modificateurs (public static...) compris
+    fn declaration_start(&self, mut i: usize) -> usize {
+        while i > 0 && Self::is_modifier(self.kind(i - 1)) {
+            i -= 1;
+        }
+        i
+    }
+
+    // Texte de la déclaration sur sa première ligne, arrêté avant l'accolade du corps
+    fn detail(&self, start: usize, stop_at_brace: bool) -> String {
+        let line = self.tokens[start].line;
+        let mut end = start;
+        let mut depth = 0;
+        for (i, token) in self.tokens.iter().enumerate().skip(start) {
+            if token.line != line || token.kind == TokenKind::EOF { break; }
+            match token.kind {
+                TokenKind::LParen => depth += 1,
+                TokenKind::RParen => depth -= 1,
+                TokenKind::LBrace if stop_at_brace && depth == 0 => break,
+                _ => {}
+            }
+            end = i;
+        }
+        let text = &self.source[self.tokens[start].span.start..self.tokens[end].span.end];
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    }
+
+    // Une fonction a-t-elle un corps ? (les méthodes d'interface n'en ont pas)
+    fn body_follows(&self, mut i: usize) -> bool {
+        if *self.kind(i) != TokenKind::LParen { return false; }
+        let mut depth = 0;
+        loop {
+            match self.kind(i) {
+                TokenKind::LParen => depth += 1,
+                TokenKind::RParen => {
+                    depth -= 1;
+                    if depth == 0 { break; }
+                },
+                TokenKind::EOF => return false,
+                _ => {}
+            }
+            i += 1;
+        }
+        i += 1;
+        if *self.kind(i) == TokenKind::Arrow { i += 2; }
+        *self.kind(i) == TokenKind::LBrace
+    }
+
+    fn add(&mut self, name: String, kind: SymbolKind, name_idx: usize, detail: String) {
+        let symbol = Symbol {
+            name,
+            kind,
+            container: self.container(),
+            detail,
+            span: self.tokens[name_idx].span,
+            global: !self.in_function(),
+        };
+        self.outline.symbols.push(symbol);
+    }
+
+    // Membre implicite d'une classe (`nom(...)` ou `nom = ...`) : en début de membre seulement
+    fn at_member_start(&self, i: usize) -> bool {
+        if i == 0 { return true; }
+        let prev = self.kind(i - 1);
+        matches!(prev, TokenKind::LBrace | TokenKind::RBrace) || Self::is_modifier(prev)
+            || (self.tokens[i - 1].line < self.tokens[i].line
+                && !matches!(prev, TokenKind::Eq | TokenKind::Comma | TokenKind::LParen | TokenKind::LBracket | TokenKind::Dot | TokenKind::Colon))
+    }
+
+    fn run(&mut self) {
+        let mut i = 0;
+        while i < self.tokens.len() {
+            match self.kind(i).clone() {
+                TokenKind::Namespace | TokenKind::Class | TokenKind::Interface | TokenKind::Enum => {
+                    if let Some(name) = self.identifier(i + 1) {
+                        let (kind, scope) = match self.kind(i) {
+                            TokenKind::Namespace => (SymbolKind::Namespace, ScopeKind::Namespace),
+                            TokenKind::Class => (SymbolKind::Class, ScopeKind::Class),
+                            TokenKind::Interface => (SymbolKind::Interface, ScopeKind::Interface),
+                            _ => (SymbolKind::Enum, ScopeKind::Enum),
+                        };
+                        let start = self.declaration_start(i);
+                        let detail = self.detail(start, scope != ScopeKind::Enum);
+                        self.add(name.clone(), kind, i + 1, detail);
+                        self.expect_scope(Scope { kind: scope, name: Some(name) });
+                        i += 1;
+                    }
+                },
+                TokenKind::Func => {
+                    if let Some(name) = self.identifier(i + 1) {
+                        let kind = match self.current() {
+                            Some(ScopeKind::Class | ScopeKind::Interface | ScopeKind::Enum) => SymbolKind::Method,
+                            _ => SymbolKind::Function,
+                        };
+                        let start = self.declaration_start(i);
+                        let detail = self.detail(start, true);
+                        self.add(name.clone(), kind, i + 1, detail);
+                        if self.body_follows(i + 2) {
+                            self.expect_scope(Scope { kind: ScopeKind::Function, name: Some(name) });
+                        }
+                        i += 1;
+                    } else {
+                        // Lambda
+                        self.expect_scope(Scope { kind: ScopeKind::Function, name: None });
+                    }
+                },
+                TokenKind::Var | TokenKind::Const | TokenKind::Prop => {
+                    if let Some(name) = self.identifier(i + 1) {
+                        let in_class = self.current() == Some(ScopeKind::Class);
+                        let kind = match self.kind(i) {
+                            TokenKind::Prop => SymbolKind::Property,
+                            TokenKind::Const => SymbolKind::Constant,
+                            _ if in_class => SymbolKind::Field,
+                            _ => SymbolKind::Variable,
+                        };
+                        let start = self.declaration_start(i);
+                        let detail = self.detail(start, kind == SymbolKind::Property);
+                        self.add(name, kind, i + 1, detail);
+                        i += 1;
+                    }
+                },
+                // from "x" import a, b
+                TokenKind::Identifier(word) if word == "from" && matches!(self.kind(i + 1), TokenKind::StringLiteral(_)) => {
+                    if let TokenKind::StringLiteral(path) = self.kind(i + 1) {
+                        self.outline.imports.push((path.clone(), self.tokens[i + 1].span));
+                        i += 1;
+                    }
+                },
+                TokenKind::Identifier(name) => {
+                    match self.current() {
+                        Some(ScopeKind::Class) if self.pending.is_none() && self.at_member_start(i) => {
+                            let is_method = *self.kind(i + 1) == TokenKind::LParen;
+                            let start = self.declaration_start(i);
+                            let detail = self.detail(start, is_method);
+                            if is_method {
+                                self.add(name.clone(), SymbolKind::Method, i, detail);
+                                self.expect_scope(Scope { kind: ScopeKind::Function, name: Some(name) });
+                            } else {
+                                self.add(name, SymbolKind::Field, i, detail);
+                            }
+                        },
+                        Some(ScopeKind::Interface) if *self.kind(i + 1) == TokenKind::LParen => {
+                            let detail = self.detail(i, false);
+                            self.add(name, SymbolKind::Method, i, detail);
+                        },
+                        Some(ScopeKind::Enum) if matches!(self.kind(i - 1), TokenKind::LBrace | TokenKind::Comma) => {
+                            // Variante avec données : Rect(w, h), les champs ne sont pas des variantes
+                            let mut end = i;
+                            if *self.kind(i + 1) == TokenKind::LParen {
+                                while !matches!(self.kind(end), TokenKind::RParen | TokenKind::EOF) {
+                                    end += 1;
+                                }
+                            }
+                            let detail = self.source[self.tokens[i].span.start..self.tokens[end.min(self.tokens.len() - 1)].span.end].to_string();
+                            self.add(name, SymbolKind::EnumMember, i, detail);
+                            i = end;
+                        },
+                        _ => {},
+                    }
+                },
+                TokenKind::Import => {
+                    if let TokenKind::StringLiteral(path) = self.kind(i + 1) {
+                        self.outline.imports.push((path.clone(), self.tokens[i + 1].span));
+                        i += 1;
+                    }
+                },
+                TokenKind::LBrace => {
+                    let scope = match self.pending.take() {
+                        Some((scope, parens)) if parens == self.parens => scope,
+                        other => {
+                            self.pending = other;
+                            Scope { kind: ScopeKind::Block, name: None }
+                        },
+                    };
+                    self.scopes.push(scope);
+                },
+                TokenKind::LParen => self.parens += 1,
+                TokenKind::RParen => self.parens = self.parens.saturating_sub(1),
+                TokenKind::RBrace => {
+                    self.scopes.pop();
+                },
+                _ => {},
+            }
+            i += 1;
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use aegis_core::{compiler, loader, native, package_manager, plugins};
+use aegis_core::{bundle, bytecode, compiler, loader, lsp, native, package_manager, plugins, unparser, validator};
 use clap::{Parser, Subcommand};
 use rustyline::DefaultEditor;
 use serde::Deserialize;
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 use std::fs;
 use serde_json::Value as JsonValue;
 use std::path::Path;
+use aegis_core::ast::Statement;
 use aegis_core::vm::VM;
 
 #[derive(Parser)]
@@ -26,6 +27,14 @@ enum Commands {
         /// Affiche le bytecode généré avant l'exécution
         #[arg(long, short)]
         debug: bool,
+
+        /// Nombre maximal d'appels imbriqués avant une erreur "Stack overflow"
+        #[arg(long, default_value_t = aegis_core::vm::FRAMES_MAX)]
+        max_frames: usize,
+
+        /// Nombre maximal de valeurs sur la pile de la VM
+        #[arg(long, default_value_t = aegis_core::vm::STACK_MAX)]
+        max_stack: usize,
         
         /// Arguments à passer au script (accessibles via System.args())
         /// Ils capturent tout ce qui se trouve après le nom du fichier ou "--"
@@ -33,9 +42,69 @@ enum Commands {
         args: Vec<String>,
     },
 
+    /// Compile un script en bytecode (.aegc), exécutable ensuite avec `aegis run`
+    Compile {
+        /// Le chemin du fichier .aeg (ou d'un AST .json)
+        file: String,
+
+        /// Fichier produit (par défaut : le même nom avec l'extension .aegc)
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+
+    /// Produit un exécutable autonome : le script, ses imports et l'interpréteur dans un seul fichier
+    Build {
+        /// Le chemin du fichier .aeg (ou d'un AST .json)
+        file: String,
+
+        /// Exécutable produit (par défaut : le nom du script sans extension)
+        #[arg(long, short)]
+        output: Option<String>,
+
+        /// Embarque aussi les plugins natifs des dépendances de aegis.toml
+        #[arg(long)]
+        plugins: bool,
+    },
+
     /// Lance le mode interactif (REPL)
     Repl,
 
+    /// Affiche l'AST d'un script Aegis
+    Ast {
+        /// Le chemin du fichier .aeg
+        file: String,
+
+        /// Exporte l'AST au format JSON (relisible par `aegis run fichier.json`)
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Vérifie un AST JSON sans l'exécuter
+    Validate {
+        /// Le chemin du fichier .json
+        file: String,
+    },
+
+    /// Regénère le code source .aeg d'un AST JSON
+    Unparse {
+        /// Le chemin du fichier .json (ou .aeg)
+        file: String,
+    },
+
+    /// Formate des fichiers .aeg selon le guide de style
+    Fmt {
+        /// Fichiers ou dossiers à formater
+        #[arg(required = true)]
+        files: Vec<String>,
+
+        /// Vérifie seulement le formatage (code de sortie 1 si un fichier doit changer)
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Lance le serveur de langage (LSP) sur stdin/stdout
+    Lsp,
+
     /// [APM] Installe un paquet depuis le registre
     Add {
         /// Nom du paquet (ex: "glfw")
@@ -82,12 +151,22 @@ struct PackageInfo {
 
 // Charge les plugins natifs basés sur le fichier aegis.toml (Legacy support pour les DLLs locales)
 fn load_config() {
-    if let Ok(content) = fs::read_to_string("aegis.toml") {
+    for (name, path) in plugin_paths(Path::new(".")) {
+        if let Err(e) = plugins::load_plugin(path.to_str().unwrap()) {
+            eprintln!("   ⚠️ Warning chargement plugin '{}': {}", name, e);
+        }
+    }
+}
+
+// Bibliothèques natives des dépendances du aegis.toml de `root` : (nom du paquet, chemin du .so/.dll/.dylib)
+fn plugin_paths(root: &Path) -> Vec<(String, std::path::PathBuf)> {
+    let mut paths = Vec::new();
+    if let Ok(content) = fs::read_to_string(root.join("aegis.toml")) {
         let config: ProjectConfig = toml::from_str(&content).unwrap_or_else(|_| ProjectConfig { dependencies: None });
 
         if let Some(deps) = config.dependencies {
             for (name, _version_req) in deps {
-                let package_path = Path::new("packages").join(&name);
+                let package_path = root.join("packages").join(&name);
 
                 if !package_path.exists() {
                     // On ne crie pas si le dossier n'existe pas, car ça peut être une dépendance pure source (.aeg)
@@ -95,15 +174,14 @@ fn load_config() {
                     continue; 
                 }
 
-                // Si on trouve une librairie native, on la charge
+                // Si on trouve une librairie native, on la garde
                 if let Ok(final_path) = resolve_library_path(&package_path) {
-                    if let Err(e) = plugins::load_plugin(final_path.to_str().unwrap()) {
-                        eprintln!("   ⚠️ Warning chargement plugin '{}': {}", name, e);
-                    }
+                    paths.push((name, final_path));
                 }
             }
         }
     }
+    paths
 }
 
 // Tente de trouver un .dll/.so dans le dossier du paquet
@@ -140,22 +218,59 @@ fn resolve_library_path(path: &Path) -> Result<std::path::PathBuf, String> {
 
 fn main() -> Result<(), String> {
     native::init_registry();
-    
+
+    // Exécutable produit par `aegis build` : tous les arguments sont pour le script
+    if let Some(bundle) = bundle::embedded()? {
+        return run_bundle(bundle);
+    }
+
     // On charge les plugins natifs AVANT de lancer la VM
     load_config();
 
     let cli = Cli::parse();
 
     match &cli.command {
-        Some(Commands::Run { file, debug, args }) => {
+        Some(Commands::Run { file, debug, max_frames, max_stack, args }) => {
             // On passe les args (clonés pour ownership) à run_file
-            run_file(file, *debug, args.clone())
+            let (file, debug, limits, args) = (file.clone(), *debug, (*max_frames, *max_stack), args.clone());
+            on_vm_thread(move || run_file(&file, debug, limits, args))
         }
 
+        Some(Commands::Compile { file, output }) => compile_file(file, output.as_deref()),
+
+        Some(Commands::Build { file, output, plugins }) => build_executable(file, output.as_deref(), *plugins),
+
+        Some(Commands::Ast { file, json }) => {
+            let statements = load_statements(file)?;
+            if *json {
+                let json_ast = loader::program_to_json(&statements);
+                println!("{}", serde_json::to_string_pretty(&json_ast).map_err(|e| e.to_string())?);
+            } else {
+                println!("{:#?}", statements);
+            }
+            Ok(())
+        }
+
+        Some(Commands::Validate { file }) => {
+            load_statements(file)?;
+            println!("{}: AST valide", file);
+            Ok(())
+        }
+
+        Some(Commands::Unparse { file }) => {
+            let statements = load_statements(file)?;
+            print!("{}", unparser::unparse(&statements));
+            Ok(())
+        }
+
+        Some(Commands::Fmt { files, check }) => run_fmt(files, *check),
+
+        Some(Commands::Lsp) => lsp::run(),
+
         Some(Commands::Repl) | None => {
             println!("Aegis v2.0 - REPL");
             println!("Tapez 'exit' ou 'quit' pour quitter.");
-            run_repl();
+            on_vm_thread(run_repl);
             Ok(())
         }
 
@@ -175,24 +290,188 @@ fn main() -> Result<(), String> {
     }
 }
 
-// Nouvelle implémentation utilisant la VM v2
-fn run_file(filename: &str, debug: bool, args: Vec<String>) -> Result<(), String> {
+// Frontend : un .aeg passe par le parser, tout autre fichier est lu comme un AST JSON (validé avant chargement)
+fn load_statements(filename: &str) -> Result<Vec<Statement>, String> {
     let content = fs::read_to_string(filename)
         .map_err(|e| format!("Impossible de lire {}: {}", filename, e))?;
 
-    // 1. Frontend 
-    let json_data: JsonValue = if filename.ends_with(".aeg") {
-        compiler::compile(&content)?
+    if filename.ends_with(".aeg") {
+        match compiler::compile(&content) {
+            Ok(statements) => Ok(statements),
+            Err(errors) => {
+                for diag in &errors {
+                    eprintln!("{}\n", diag.clone().with_file(filename).render(&content));
+                }
+                eprintln!("{} erreur(s) de syntaxe dans {}", errors.len(), filename);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        let json_data: JsonValue = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+
+        let errors = validator::validate(&json_data);
+        if !errors.is_empty() {
+            for err in &errors {
+                eprintln!("{}: {}", filename, err);
+            }
+            eprintln!("{} erreur(s) dans l'AST {}", errors.len(), filename);
+            std::process::exit(1);
+        }
+
+        loader::parse_program(&json_data)
+    }
+}
+
+// Ajoute les .aeg d'un dossier (récursivement) ou le fichier lui-même
+fn collect_aeg_files(path: &Path, files: &mut Vec<std::path::PathBuf>) -> Result<(), String> {
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)
+            .map_err(|e| format!("Impossible de lire {}: {}", path.display(), e))?
+            .flatten()
+            .map(|entry| entry.path())
+            .collect();
+        entries.sort();
+        for entry in entries {
+            if entry.is_dir() || entry.extension().is_some_and(|ext| ext == "aeg") {
+                collect_aeg_files(&entry, files)?;
+            }
+        }
     } else {
-        serde_json::from_str(&content).map_err(|e| e.to_string())?
+        files.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+fn run_fmt(paths: &[String], check: bool) -> Result<(), String> {
+    let mut files = Vec::new();
+    for path in paths {
+        collect_aeg_files(Path::new(path), &mut files)?;
+    }
+
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for file in &files {
+        let name = file.display().to_string();
+        let content = fs::read_to_string(file)
+            .map_err(|e| format!("Impossible de lire {}: {}", name, e))?;
+
+        let formatted = match compiler::formatter::format_source(&content) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                for diag in errors {
+                    eprintln!("{}\n", diag.with_file(&name).render(&content));
+                }
+                failed += 1;
+                continue;
+            }
+        };
+
+        if formatted == content {
+            continue;
+        }
+        if check {
+            println!("Non formaté : {}", name);
+            unformatted += 1;
+        } else {
+            fs::write(file, formatted).map_err(|e| format!("Impossible d'écrire {}: {}", name, e))?;
+            println!("Formaté : {}", name);
+        }
+    }
+
+    if failed > 0 || unformatted > 0 {
+        if unformatted > 0 {
+            eprintln!("{} fichier(s) à formater (lancez `aegis fmt`)", unformatted);
+        }
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// Les callbacks (map, filter...) et les générateurs s'exécutent récursivement dans la VM :
+// elle tourne sur un thread dont la pile native suffit pour vm::NESTED_MAX niveaux
+const VM_THREAD_STACK: usize = 256 * 1024 * 1024;
+
+fn on_vm_thread<T: Send + 'static>(run: impl FnOnce() -> T + Send + 'static) -> T {
+    std::thread::Builder::new()
+        .stack_size(VM_THREAD_STACK)
+        .spawn(run)
+        .expect("Unable to start the VM thread")
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+fn compile_file(filename: &str, output: Option<&str>) -> Result<(), String> {
+    let statements = load_statements(filename)?;
+    let (chunk, global_names) = aegis_core::vm::compiler::Compiler::new().compile(statements)?;
+    let bytes = bytecode::save(&chunk, &global_names.borrow())?;
+
+    let output = match output {
+        Some(output) => output.to_string(),
+        None => Path::new(filename).with_extension(bytecode::EXTENSION).display().to_string(),
     };
-    
-    // 2. Loader
-    let statements = loader::parse_block(&json_data)?;
+    fs::write(&output, bytes).map_err(|e| format!("Impossible d'écrire {}: {}", output, e))?;
+    println!("Compilé : {}", output);
+    Ok(())
+}
 
-    // 3. Compilation v2
-    let compiler = aegis_core::vm::compiler::Compiler::new();
-    let (chunk, global_names) = compiler.compile(statements);
+fn build_executable(filename: &str, output: Option<&str>, with_plugins: bool) -> Result<(), String> {
+    let statements = load_statements(filename)?;
+    let (chunk, global_names) = aegis_core::vm::compiler::Compiler::new().compile(statements)?;
+    let mut bundle = bundle::Bundle::new(Path::new(filename), &chunk, global_names)?;
+    if with_plugins {
+        // Comme les imports du script : aegis.toml et packages/ sont ceux de la racine de son projet
+        let dir = Path::new(filename).parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let root = aegis_core::imports::project_root(dir);
+        if !root.join("aegis.toml").is_file() {
+            return Err(format!("--plugins : aucun aegis.toml dans {} ni dans ses dossiers parents", dir.display()));
+        }
+        for (_, path) in plugin_paths(&root) {
+            bundle.add_plugin(&path)?;
+        }
+    }
+
+    let output = match output {
+        Some(output) => output.to_string(),
+        None => {
+            let stem = Path::new(filename).with_extension("");
+            format!("{}{}", stem.display(), std::env::consts::EXE_SUFFIX)
+        }
+    };
+    bundle.write_executable(Path::new(&output))?;
+    println!("Exécutable : {} ({} module(s), {} plugin(s))", output, bundle.modules.len(), bundle.plugins.len());
+    Ok(())
+}
+
+// Programme embarqué : plugins chargés avant la VM, imports servis par le bundle
+fn run_bundle(bundle: bundle::Bundle) -> Result<(), String> {
+    for path in bundle.extract_plugins()? {
+        plugins::load_plugin(path.to_str().unwrap())?;
+    }
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    on_vm_thread(move || {
+        let (chunk, globals) = bytecode::load_program(&bundle.program)?;
+        let mut vm = VM::new(chunk, std::rc::Rc::new(std::cell::RefCell::new(globals)), args);
+        vm.set_bundle(bundle);
+        vm.run()
+    })
+}
+
+// Nouvelle implémentation utilisant la VM v2
+fn run_file(filename: &str, debug: bool, (max_frames, max_stack): (usize, usize), args: Vec<String>) -> Result<(), String> {
+    let (chunk, global_names) = if filename.ends_with(&format!(".{}", bytecode::EXTENSION)) {
+        // Bytecode déjà compilé : ni parser ni compilateur
+        let bytes = fs::read(filename).map_err(|e| format!("Impossible de lire {}: {}", filename, e))?;
+        let (chunk, globals) = bytecode::load_program(&bytes).map_err(|e| format!("{}: {}", filename, e))?;
+        (chunk, std::rc::Rc::new(std::cell::RefCell::new(globals)))
+    } else {
+        // 1. Frontend
+        let statements = load_statements(filename)?;
+
+        // 2. Compilation v2
+        let compiler = aegis_core::vm::compiler::Compiler::new();
+        compiler.compile(statements)?
+    };
 
     if debug {
         use aegis_core::vm::debug;
@@ -201,7 +480,7 @@ fn run_file(filename: &str, debug: bool, args: Vec<String>) -> Result<(), String
         println!("=================================\n");
     }
 
-    // 4. Nettoyage des arguments "--" si présents
+    // 3. Nettoyage des arguments "--" si présents
     let mut script_args = Vec::new();
     for arg in args {
         if arg != "--" {
@@ -209,14 +488,16 @@ fn run_file(filename: &str, debug: bool, args: Vec<String>) -> Result<(), String
         }
     }
 
-    // 5. Exécution VM avec les arguments
+    // 4. Exécution VM avec les arguments
     let mut vm = VM::new(chunk, global_names, script_args);
+    vm.set_limits(max_frames, max_stack);
+    vm.set_script_path(Path::new(filename));
     
     vm.run()
 }
 
 fn run_repl() {
-    let global_names = std::rc::Rc::new(std::cell::RefCell::new(HashMap::new()));
+    let global_names = std::rc::Rc::new(std::cell::RefCell::new(aegis_core::vm::compiler::GlobalTable::with_natives()));
     let empty_chunk = aegis_core::chunk::Chunk::new();
     let mut vm = VM::new(empty_chunk, global_names.clone(), vec![]);
 
@@ -233,23 +514,25 @@ fn run_repl() {
                 
                 // Pipeline v2 pour REPL
                 match compiler::compile(source) {
-                    Ok(json_ast) => {
-                        match loader::parse_block(&json_ast) {
-                            Ok(statements) => {
-                                // Important: préserver le contexte global
-                                let mut repl_compiler = aegis_core::vm::compiler::Compiler::new_with_globals(global_names.clone());
-                                repl_compiler.scope_depth = 0; 
-                                
-                                let (chunk, _) = repl_compiler.compile(statements);
-
+                    Ok(statements) => {
+                        // Important: préserver le contexte global
+                        let mut repl_compiler = aegis_core::vm::compiler::Compiler::new_with_globals(global_names.clone());
+                        repl_compiler.scope_depth = 0; 
+                        
+                        match repl_compiler.compile(statements) {
+                            Ok((chunk, _)) => {
                                 if let Err(e) = vm.execute_chunk(chunk) {
                                     println!("Runtime Error: {}", e);
                                 }
                             },
-                            Err(e) => println!("Loader Error: {}", e)
+                            Err(e) => println!("{}", e),
                         }
                     },
-                    Err(e) => println!("Syntax Error: {}", e)
+                    Err(errors) => {
+                        for e in errors {
+                            println!("{}", e.with_file("<repl>").render(source));
+                        }
+                    }
                 }
             }
             Err(error) => {
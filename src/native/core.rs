@@ -1,32 +1,64 @@
 use crate::ast::Value;
+use crate::ast::value::IteratorData;
+use crate::vm::builtins::BuiltinType;
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use super::{native, Native};
+
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("to_str".to_string(), native(to_str, 1));
+    map.insert("to_int".to_string(), native(to_int, 1));
+    map.insert("to_float".to_string(), native(to_float, 1));
+    map.insert("to_bytes".to_string(), native(to_bytes, 1));
+    map.insert("chr".to_string(), native(chr, 1));
+    map.insert("ord".to_string(), native(ord, 1));
+    map.insert("len".to_string(), native(len, 1));
+    map.insert("fmt".to_string(), native(fmt, 2));
+    map.insert("typeof".to_string(), native(type_of, 1));
+    map.insert("is_instance".to_string(), native(is_instance, 2));
+    map.insert("methods".to_string(), native(methods, 1));
+    map.insert("iter".to_string(), native(iter, 1));
+    map.insert("zip".to_string(), native(zip, 2));
+    map.insert("enumerate".to_string(), native(enumerate, 1));
+}
+
+// Les itérables sont ouverts par la VM au premier next() : une native ne peut pas appeler iter() elle-même
+fn lazy(data: IteratorData) -> Value {
+    Value::Iterator(Rc::new(RefCell::new(data)))
+}
+
+fn iter(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("iter expects 1 argument (iterable)".into()); }
+    Ok(lazy(IteratorData::Source(args[0].clone())))
+}
+
+fn zip(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() < 2 { return Err("zip expects at least 2 iterables".into()); }
+    let sources = args.into_iter().map(|a| lazy(IteratorData::Source(a))).collect();
+    Ok(lazy(IteratorData::Zip(sources)))
+}
 
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("to_str".to_string(), to_str);
-    map.insert("to_int".to_string(), to_int);
-    map.insert("to_float".to_string(), to_float);
-    map.insert("to_bytes".to_string(), to_bytes);
-    map.insert("chr".to_string(), chr);
-    map.insert("ord".to_string(), ord);
-    map.insert("len".to_string(), len);
-    map.insert("fmt".to_string(), fmt);
-    map.insert("typeof".to_string(), type_of);
-    map.insert("is_instance".to_string(), is_instance);
+fn enumerate(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("enumerate expects 1 argument (iterable)".into()); }
+    Ok(lazy(IteratorData::Enumerate(lazy(IteratorData::Source(args[0].clone())), 0)))
 }
 
 fn to_str(args: Vec<Value>) -> Result<Value, String> {
-    Ok(Value::String(format!("{}", args[0])))
+    if args.len() != 1 { return Err("to_str attend 1 argument".into()); }
+    Ok(Value::String(format!("{}", args[0]).into()))
 }
 
 fn to_int(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("to_int attend 1 argument".into()); }
     Ok(Value::Integer(args[0].as_int()?))
 }
 
 fn to_float(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("to_float attend 1 argument".into()); }
     Ok(Value::Float(args[0].as_float()?))
 }
 
 fn to_bytes(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("to_bytes attend 1 argument".into()); }
     let s = args[0].as_str()?;
     Ok(Value::Bytes(Rc::new(RefCell::new(s.as_bytes().to_vec()))))
 }
@@ -37,7 +69,7 @@ fn chr(args: Vec<Value>) -> Result<Value, String> {
     let code = args[0].as_int()?;
     // Conversion sécurisée u32 -> char
     if let Some(c) = std::char::from_u32(code as u32) {
-        Ok(Value::String(c.to_string()))
+        Ok(Value::String(c.to_string().into()))
     } else {
         Err(format!("Code caractère invalide : {}", code))
     }
@@ -56,6 +88,7 @@ fn ord(args: Vec<Value>) -> Result<Value, String> {
 }
 
 fn len(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("len attend 1 argument".into()); }
     match &args[0] {
         Value::String(s) => return Ok(Value::Integer(s.len() as i64)),
         Value::List(l) => return Ok(Value::Integer(l.borrow().len() as i64)),
@@ -79,46 +112,44 @@ fn fmt(args: Vec<Value>) -> Result<Value, String> {
         let num = match val {
             Value::Integer(i) => *i as f64,
             Value::Float(f) => *f,
-            _ => return Ok(Value::String(format!("{}", val))) // Fallback
+            _ => return Ok(Value::String(format!("{}", val).into())) // Fallback
         };
                                     
         // Astuce Rust pour précision dynamique
-        return Ok(Value::String(format!("{:.1$}", num, precision)));
+        return Ok(Value::String(format!("{:.1$}", num, precision).into()));
     } 
                                 
     // Tu peux ajouter d'autres formats ici (ex: "b" pour binaire, "x" pour hexa...)
-    Ok(Value::String(format!("{}", val)))
+    Ok(Value::String(format!("{}", val).into()))
 }
 
 fn type_of(args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 { return Err("typeof attend 1 argument".into()); }
                                 
-    // On détermine le nom du type (String)
-    let type_name = match &args[0] {
-        Value::Integer(_) => "int".to_string(),
-        Value::Float(_) => "float".to_string(),
-        Value::String(_) => "string".to_string(),
-        Value::Boolean(_) => "bool".to_string(),
-        Value::Null => "null".to_string(),
-        Value::List(_) => "list".to_string(),
-        Value::Dict(_) => "dict".to_string(),
-        Value::Enum(_) => "enum".to_string(),
-        Value::Range(_, _, _) => "range".to_string(),
-                                    
-        Value::Function(..) => "function".to_string(),
-        Value::Class { .. } => "class".to_string(),
-        Value::Interface(_) => "interface".to_string(),
-        Value::Bytes(_) => "bytes".to_string(),
-                                    
-        // Pour l'instance, on récupère le nom dynamiquement
-        Value::Instance(i) => {
-            let borrow = i.borrow();
-            borrow.class.name.clone()
-        },
-        Value::Native(_) => "function".to_string()
-    };
+    Ok(Value::String(args[0].type_name().into()))
+}
 
-    Ok(Value::String(type_name))
+// Noms des méthodes qu'on peut appeler sur la valeur : table native d'un type de base,
+// ou méthodes de la classe d'une instance et de ses parents (triées)
+fn methods(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("methods expects 1 argument (value)".into()); }
+
+    let names: Vec<String> = if let Some(ty) = BuiltinType::of(&args[0]) {
+        ty.methods().iter().map(|m| m.name.to_string()).collect()
+    } else if let Value::Instance(inst) = &args[0] {
+        let mut names = Vec::new();
+        let mut class = Some(inst.borrow().class.clone());
+        while let Some(c) = class {
+            names.extend(c.methods.keys().cloned());
+            class = c.parent_ref.clone();
+        }
+        names.sort();
+        names.dedup();
+        names
+    } else {
+        Vec::new()
+    };
+    Ok(Value::List(Rc::new(RefCell::new(names.into_iter().map(|n| Value::String(n.into())).collect()))))
 }
 
 fn is_instance(args: Vec<Value>) -> Result<Value, String> {
@@ -130,8 +161,13 @@ fn is_instance(args: Vec<Value>) -> Result<Value, String> {
     // 1. Récupération "tolérante" de la classe cible
     // Si le 2ème argument n'est pas une classe (ex: "String" qui est une fonction), 
     // on retourne 'false' au lieu de crasher. C'est le comportement de 'instanceof' en JS.
-    let target_rc = match target_class {
-        Value::Class(c) => c,
+    let target_rc = match (target_class, instance) {
+        (Value::Class(c), _) => c,
+        // Variante d'un enum : is_instance(s, Shape) ou is_instance(s, Shape.Circle)
+        (Value::Enum(e), Value::Variant(v)) => return Ok(Value::Boolean(Rc::ptr_eq(e, &v.enum_data))),
+        (Value::Variant(target), Value::Variant(v)) => {
+            return Ok(Value::Boolean(Rc::ptr_eq(&target.enum_data, &v.enum_data) && target.index == v.index));
+        },
         _ => return Ok(Value::Boolean(false)), 
     };
 
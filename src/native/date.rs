@@ -1,20 +1,22 @@
-use crate::{Value, NativeFn};
+use crate::Value;
+use super::{native, Native};
 use std::collections::HashMap;
 use chrono::Local;
 
-pub fn register(map: &mut HashMap<String, NativeFn>) {
-    map.insert("date_now".to_string(), date_now);
-    map.insert("date_format".to_string(), date_format);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("date_now".to_string(), native(date_now, 0));
+    map.insert("date_format".to_string(), native(date_format, 1));
 }
 
 fn date_now(_: Vec<Value>) -> Result<Value, String> {
     // Retourne le timestamp ISO 8601
-    Ok(Value::String(Local::now().to_rfc3339()))
+    Ok(Value::String(Local::now().to_rfc3339().into()))
 }
 
 fn date_format(args: Vec<Value>) -> Result<Value, String> {
+    if args.is_empty() { return Err("date_format attend un format".into()); }
     // args: [format_str] (utilise l'heure actuelle) ou [timestamp_iso, format_str]
     let now = Local::now();
     let fmt = args[0].as_str()?;
-    Ok(Value::String(now.format(&fmt).to_string()))
+    Ok(Value::String(now.format(&fmt).to_string().into()))
 }
@@ -1,9 +1,10 @@
 use crate::ast::Value;
 use std::collections::HashMap;
+use super::{native, Native};
 
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("http_get".to_string(), http_get);
-    map.insert("http_post".to_string(), http_post);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("http_get".to_string(), native(http_get, 1));
+    map.insert("http_post".to_string(), native(http_post, 3));
 }
 
 fn http_get(args: Vec<Value>) -> Result<Value, String> {
@@ -33,7 +34,7 @@ fn http_get(args: Vec<Value>) -> Result<Value, String> {
     let text = response.text()
         .map_err(|e| format!("Erreur lecture body: {}", e))?;
                                     
-    Ok(Value::String(text))
+    Ok(Value::String(text.into()))
 }
 
 fn http_post(args: Vec<Value>) -> Result<Value, String> {
@@ -60,5 +61,5 @@ fn http_post(args: Vec<Value>) -> Result<Value, String> {
         return Err(format!("Erreur API: {}", res.status()));
     }
                                 
-    Ok(Value::String(res.text().unwrap_or_default()))
+    Ok(Value::String(res.text().unwrap_or_default().into()))
 }
\ No newline at end of file
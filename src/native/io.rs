@@ -5,14 +5,15 @@ use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::Path;
 use std::rc::Rc;
-
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("io_read".to_string(), io_read);
-    map.insert("io_read_bytes".to_string(), io_read_bytes);
-    map.insert("io_write".to_string(), io_write);
-    map.insert("io_append".to_string(), io_append);
-    map.insert("io_exists".to_string(), io_exists);
-    map.insert("io_delete".to_string(), io_delete);
+use super::{native, Native};
+
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("io_read".to_string(), native(io_read, 1));
+    map.insert("io_read_bytes".to_string(), native(io_read_bytes, 1));
+    map.insert("io_write".to_string(), native(io_write, 2));
+    map.insert("io_append".to_string(), native(io_append, 2));
+    map.insert("io_exists".to_string(), native(io_exists, 1));
+    map.insert("io_delete".to_string(), native(io_delete, 1));
 }
 
 fn io_read(args: Vec<Value>) -> Result<Value, String> {
@@ -23,7 +24,7 @@ fn io_read(args: Vec<Value>) -> Result<Value, String> {
     let path = args[0].as_str()?;
 
     match fs::read_to_string(&path) {
-        Ok(content) => Ok(Value::String(content)),
+        Ok(content) => Ok(Value::String(content.into())),
         Err(_) => Ok(Value::Null)
     }
 }
@@ -1,9 +1,11 @@
 use crate::ast::Value;
+use crate::ast::value::MAX_NESTING;
 use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use super::{native, Native};
 
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("json_parse".to_string(), json_parse);
-    map.insert("json_stringify".to_string(), json_stringify);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("json_parse".to_string(), native(json_parse, 1));
+    map.insert("json_stringify".to_string(), native(json_stringify, 1));
 }
 
 // Conversion : serde_json::Value (Externe) -> crate::ast::Value (Interne Aegis)
@@ -15,7 +17,7 @@ fn serde_to_aegis(v: serde_json::Value) -> Value {
             if n.is_i64() { Value::Integer(n.as_i64().unwrap()) }
             else { Value::Float(n.as_f64().unwrap()) }
         },
-        serde_json::Value::String(s) => Value::String(s),
+        serde_json::Value::String(s) => Value::String(s.into()),
         serde_json::Value::Array(arr) => {
             let list = arr.into_iter().map(serde_to_aegis).collect();
             Value::List(Rc::new(RefCell::new(list)))
@@ -30,30 +32,43 @@ fn serde_to_aegis(v: serde_json::Value) -> Value {
     }
 }
 
-// Conversion inverse (pour envoyer du JSON ou stringify) : Aegis -> Serde
-// (Version simplifiée qui retourne string direct pour l'instant)
-#[allow(dead_code)]
-fn aegis_to_json_string(v: &Value) -> String {
-    match v {
-        Value::Null => "null".to_string(),
-        Value::Boolean(b) => b.to_string(),
-        Value::Integer(i) => i.to_string(),
-        Value::Float(f) => f.to_string(),
-        Value::String(s) => format!("\"{}\"", s), // Ajout des quotes pour JSON valide
-        Value::List(l) => {
-            let items: Vec<String> = l.borrow().iter().map(|i| aegis_to_json_string(i)).collect();
-            format!("[{}]", items.join(", "))
-        },
-        Value::Dict(d) => {
-            let items: Vec<String> = d.borrow().iter().map(|(k, v)| {
-                format!("\"{}\": {}", k, aegis_to_json_string(v))
-            }).collect();
-            format!("{{{}}}", items.join(", "))
-        },
-        _ => "\"unsupported\"".to_string()
+// Conversion inverse : Aegis -> Serde (pour stringify)
+// `open` contient les listes et dictionnaires en cours de conversion (détection des cycles)
+fn aegis_to_serde(v: &Value, open: &mut Vec<*const ()>) -> Result<serde_json::Value, String> {
+    let ptr = match v {
+        Value::Null => return Ok(serde_json::Value::Null),
+        Value::Boolean(b) => return Ok(serde_json::Value::Bool(*b)),
+        Value::Integer(i) => return Ok(serde_json::Value::from(*i)),
+        // NaN et l'infini n'existent pas en JSON : from_f64 renvoie None
+        Value::Float(f) => return Ok(serde_json::Number::from_f64(*f).map_or(serde_json::Value::Null, serde_json::Value::Number)),
+        Value::String(s) => return Ok(serde_json::Value::String(s.to_string())),
+        Value::List(l) => Rc::as_ptr(l) as *const (),
+        Value::Dict(d) => Rc::as_ptr(d) as *const (),
+        other => return Err(format!("Cannot convert a value of type '{}' to JSON", other.type_name())),
+    };
+
+    if open.contains(&ptr) {
+        return Err("Cannot convert a cyclic structure to JSON".into());
+    }
+    if open.len() >= MAX_NESTING {
+        return Err(format!("Cannot convert to JSON: more than {} nested levels", MAX_NESTING));
     }
-}
 
+    open.push(ptr);
+    let result = match v {
+        Value::List(l) => l.borrow().iter()
+            .map(|item| aegis_to_serde(item, open))
+            .collect::<Result<Vec<_>, _>>()
+            .map(serde_json::Value::Array),
+        Value::Dict(d) => d.borrow().iter()
+            .map(|(k, item)| Ok((k.clone(), aegis_to_serde(item, open)?)))
+            .collect::<Result<serde_json::Map<_, _>, String>>()
+            .map(serde_json::Value::Object),
+        _ => unreachable!(),
+    };
+    open.pop();
+    result
+}
 
 fn json_parse(args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
@@ -70,12 +85,11 @@ fn json_parse(args: Vec<Value>) -> Result<Value, String> {
 
 fn json_stringify(args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
-        return Err("json_parse attend une chaine".into());
+        return Err("json_stringify expects one value".into());
     }
-    let json_str = args[0].as_str()?;
-
-    let serde_val: serde_json::Value = serde_json::from_str(&json_str)
-        .map_err(|e| format!("Erreur Parsing JSON: {}", e))?;
 
-    Ok(serde_to_aegis(serde_val))
+    let serde_val = aegis_to_serde(&args[0], &mut Vec::new())?;
+    serde_json::to_string(&serde_val)
+        .map(|json| Value::String(json.into()))
+        .map_err(|e| format!("JSON error: {}", e))
 }
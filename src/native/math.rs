@@ -1,20 +1,21 @@
 use crate::ast::Value;
 use std::collections::HashMap;
+use super::{native, Native};
 
 // Enregistrement des fonctions dans la VM
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("math_abs".to_string(), abs);
-    map.insert("math_ceil".to_string(), ceil);
-    map.insert("math_floor".to_string(), floor);
-    map.insert("math_round".to_string(), round);
-    map.insert("math_sqrt".to_string(), sqrt);
-    map.insert("math_pow".to_string(), pow);
-    map.insert("math_sin".to_string(), sin);
-    map.insert("math_cos".to_string(), cos);
-    map.insert("math_tan".to_string(), tan);
-    map.insert("math_acos".to_string(), acos);
-    map.insert("math_asin".to_string(), asin);
-    map.insert("math_atan".to_string(), atan);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("math_abs".to_string(), native(abs, 1));
+    map.insert("math_ceil".to_string(), native(ceil, 1));
+    map.insert("math_floor".to_string(), native(floor, 1));
+    map.insert("math_round".to_string(), native(round, 1));
+    map.insert("math_sqrt".to_string(), native(sqrt, 1));
+    map.insert("math_pow".to_string(), native(pow, 2));
+    map.insert("math_sin".to_string(), native(sin, 1));
+    map.insert("math_cos".to_string(), native(cos, 1));
+    map.insert("math_tan".to_string(), native(tan, 1));
+    map.insert("math_acos".to_string(), native(acos, 1));
+    map.insert("math_asin".to_string(), native(asin, 1));
+    map.insert("math_atan".to_string(), native(atan, 1));
 }
 
 // Helper pour convertir Value (Int ou Float) en f64
@@ -2,7 +2,19 @@ use std::collections::HashMap;
 use std::sync::{OnceLock, RwLock};
 use crate::ast::environment::NativeFn;
 
-static REGISTRY: OnceLock<RwLock<HashMap<String, NativeFn>>> = OnceLock::new();
+static REGISTRY: OnceLock<RwLock<HashMap<String, Native>>> = OnceLock::new();
+
+/// Native du registre : la fonction et le nombre minimal d'arguments qu'elle déclare.
+/// Les plugins n'enregistrent que des pointeurs de fonction : leurs natives n'en déclarent pas.
+#[derive(Clone, Copy)]
+pub struct Native {
+    pub func: NativeFn,
+    pub min_args: Option<usize>,
+}
+
+const fn native(func: NativeFn, min_args: usize) -> Native {
+    Native { func, min_args: Some(min_args) }
+}
 
 pub fn init_registry() {
     let mut map = HashMap::new();
@@ -21,11 +33,12 @@ pub fn init_registry() {
     date::register(&mut map);
     socket::register(&mut map);
     math::register(&mut map);
+    task::register(&mut map);
 
     let _ = REGISTRY.set(RwLock::new(map));
 }
 
-pub fn find(name: &str) -> Option<NativeFn> {
+pub fn find(name: &str) -> Option<Native> {
     let register_lock = REGISTRY.get()?;
 
     let reader = register_lock.read().ok()?;
@@ -38,7 +51,7 @@ pub fn extend_registry(new_funcs: HashMap<String, NativeFn>) {
         if let Ok(mut writer) = registry_lock.write() {
             println!("[Aegis] Chargement de {} nouvelles fonctions natives...", new_funcs.len());
 
-            writer.extend(new_funcs);
+            writer.extend(new_funcs.into_iter().map(|(name, func)| (name, Native { func, min_args: None })));
         }
         else {
             eprintln!("[Aegis] Erreur : Impossible d'obtenir le verrou d'écriture sur le registre.");
@@ -49,6 +62,21 @@ pub fn extend_registry(new_funcs: HashMap<String, NativeFn>) {
     }
 }
 
+/// Couples (nom, native) triés par nom, pour installer les natives dans les globales de la VM
+pub fn entries() -> Vec<(String, Native)> {
+    if REGISTRY.get().is_none() {
+        init_registry();
+    }
+
+    let lock = REGISTRY.get().expect("Registry not initialized");
+    let reader = lock.read().expect("Registry lock poisoned");
+
+    let mut entries: Vec<(String, Native)> = reader.iter().map(|(name, native)| (name.clone(), *native)).collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+    entries
+}
+
 pub fn get_all_names() -> Vec<String> {
     // On s'assure que le registre est initialisé, sinon on le fait
     if REGISTRY.get().is_none() {
@@ -79,4 +107,4 @@ mod regex;
 mod crypto;
 mod date;
 mod socket;
-mod math;
\ No newline at end of file
+mod math;mod task;
@@ -1,30 +1,34 @@
-use crate::{Value, NativeFn};
+use crate::Value;
+use super::{native, Native};
 use std::collections::HashMap;
 use std::path::Path;
 
-pub fn register(map: &mut HashMap<String, NativeFn>) {
-    map.insert("path_join".to_string(), path_join);
-    map.insert("path_ext".to_string(), path_ext);
-    map.insert("path_exists".to_string(), path_exists);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("path_join".to_string(), native(path_join, 2));
+    map.insert("path_ext".to_string(), native(path_ext, 1));
+    map.insert("path_exists".to_string(), native(path_exists, 1));
 }
 
 fn path_join(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 { return Err("path_join attend 2 arguments".into()); }
     let p1 = args[0].as_str()?;
     let p2 = args[1].as_str()?;
     let path = Path::new(&p1).join(p2);
-    Ok(Value::String(path.to_string_lossy().to_string()))
+    Ok(Value::String(path.to_string_lossy().into()))
 }
 
 fn path_ext(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("path_ext attend 1 argument".into()); }
     let p = args[0].as_str()?;
     let path = Path::new(&p);
     match path.extension() {
-        Some(os_str) => Ok(Value::String(os_str.to_string_lossy().to_string())),
-        None => Ok(Value::String("".to_string()))
+        Some(os_str) => Ok(Value::String(os_str.to_string_lossy().into())),
+        None => Ok(Value::String("".into()))
     }
 }
 
 fn path_exists(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("path_exists attend 1 argument".into()); }
     let p = args[0].as_str()?;
     Ok(Value::Boolean(Path::new(&p).exists()))
 }
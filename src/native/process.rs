@@ -1,11 +1,14 @@
-use std::{cell::RefCell, collections::HashMap, process::Command, rc::Rc};
-use crate::{NativeFn, Value};
+use std::{cell::RefCell, collections::HashMap, process::{Command, Output}, rc::Rc, sync::mpsc, thread};
+use crate::Value;
+use super::{native, Native};
+use crate::vm::scheduler::{self, TaskData};
 
-pub fn register(map: &mut HashMap<String, NativeFn>) {
-    map.insert("proc_exec".to_string(), proc_exec);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("proc_exec".to_string(), native(proc_exec, 1));
+    map.insert("proc_spawn".to_string(), native(proc_spawn, 1));
 }
 
-fn proc_exec(args: Vec<Value>) -> Result<Value, String> {
+fn build_command(args: &[Value]) -> Result<Command, String> {
     if args.is_empty() {
         return Err("Args: command, [args_list]".into());
     }
@@ -21,19 +24,48 @@ fn proc_exec(args: Vec<Value>) -> Result<Value, String> {
         }
     }
 
-    let output = command.output().map_err(|e| format!("Exec failed: {}", e))?;
-    
-    // On retourne un Dict { "code": int, "stdout": string, "stderr": string }
+    Ok(command)
+}
+
+// On retourne un Dict { "code": int, "stdout": string, "stderr": string }
+fn output_to_value(output: Output) -> Value {
     let mut res_map = HashMap::new();
 
     let code = output.status.code().unwrap_or(-1) as i64;
     res_map.insert("code".to_string(), Value::Integer(code));
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    res_map.insert("stdout".to_string(), Value::String(stdout));
+    res_map.insert("stdout".to_string(), Value::String(stdout.into()));
 
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    res_map.insert("stderr".to_string(), Value::String(stderr));
+    res_map.insert("stderr".to_string(), Value::String(stderr.into()));
+
+    Value::Dict(Rc::new(RefCell::new(res_map)))
+}
+
+fn proc_exec(args: Vec<Value>) -> Result<Value, String> {
+    let mut command = build_command(&args)?;
+    let output = command.output().map_err(|e| format!("Exec failed: {}", e))?;
+    Ok(output_to_value(output))
+}
+
+// Version async : la commande tourne dans un thread, qui réveille la boucle d'événements quand elle se termine.
+// La tâche se termine avec le même Dict
+fn proc_spawn(args: Vec<Value>) -> Result<Value, String> {
+    let mut command = build_command(&args)?;
+    let waker = scheduler::waker()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(command.output());
+        let _ = waker.wake();
+    });
+
+    let task = TaskData::poll("proc_spawn", Box::new(move || match receiver.try_recv() {
+        Ok(Ok(output)) => Some(Ok(output_to_value(output))),
+        Ok(Err(e)) => Some(Err(format!("Exec failed: {}", e))),
+        Err(mpsc::TryRecvError::Empty) => None,
+        Err(mpsc::TryRecvError::Disconnected) => Some(Err("Exec failed: process thread stopped".into())),
+    }));
 
-    Ok(Value::Dict(Rc::new(RefCell::new(res_map))))
+    Ok(Value::Task(Rc::new(RefCell::new(task))))
 }
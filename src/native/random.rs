@@ -1,10 +1,11 @@
 use rand::Rng;
 use crate::ast::Value;
 use std::collections::HashMap;
+use super::{native, Native};
 
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("rand_int".to_string(), rand_int);
-    map.insert("rand_float".to_string(), rand_float);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("rand_int".to_string(), native(rand_int, 2));
+    map.insert("rand_float".to_string(), native(rand_float, 0));
 }
 
 fn rand_int(args: Vec<Value>) -> Result<Value, String> {
@@ -1,4 +1,5 @@
-use crate::{Value, NativeFn};
+use crate::Value;
+use super::{native, Native};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use lazy_static::lazy_static;
@@ -16,13 +17,14 @@ lazy_static! {
     });
 }
 
-pub fn register(map: &mut HashMap<String, NativeFn>) {
-    map.insert("re_new".to_string(), re_new);
-    map.insert("re_match".to_string(), re_match);
-    map.insert("re_replace".to_string(), re_replace);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("re_new".to_string(), native(re_new, 1));
+    map.insert("re_match".to_string(), native(re_match, 2));
+    map.insert("re_replace".to_string(), native(re_replace, 3));
 }
 
 fn re_new(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("re_new attend 1 argument".into()); }
     let pattern = args[0].as_str()?;
     let re = Regex::new(&pattern).map_err(|e| format!("Invalid Regex: {}", e))?;
 
@@ -35,6 +37,7 @@ fn re_new(args: Vec<Value>) -> Result<Value, String> {
 }
 
 fn re_match(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 { return Err("re_match attend 2 arguments".into()); }
     let id = args[0].as_int()? as usize;
     let text = args[1].as_str()?;
 
@@ -47,6 +50,7 @@ fn re_match(args: Vec<Value>) -> Result<Value, String> {
 }
 
 fn re_replace(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 3 { return Err("re_replace attend 3 arguments".into()); }
     let id = args[0].as_int()? as usize;
     let text = args[1].as_str()?;
     let replacement = args[2].as_str()?;
@@ -54,7 +58,7 @@ fn re_replace(args: Vec<Value>) -> Result<Value, String> {
     let state = RE_STATE.lock().unwrap();
     if let Some(re) = state.patterns.get(&id) {
         let result = re.replace_all(&text, replacement.as_str());
-        Ok(Value::String(result.to_string()))
+        Ok(Value::String(result.into()))
     } else {
         Err("Regex ID not found".into())
     }
@@ -1,13 +1,18 @@
-use crate::{Value, NativeFn};
+use crate::Value;
+use super::{native, Native};
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::sync::Mutex;
-use std::net::{TcpListener, TcpStream};
-use std::io::{Read, Write};
+use std::io::{self, ErrorKind, Read, Write};
 use lazy_static::lazy_static;
+use mio::Interest;
+use mio::net::{TcpListener, TcpStream};
+use crate::vm::scheduler::{self, TaskData};
 
 // --- STATE ---
+// Les sockets sont non bloquants et enregistrés auprès du réacteur de la boucle d'événements (voir scheduler::register) :
+// les versions async réessaient à chaque réveil de la boucle, les versions synchrones attendent le réacteur (voir blocking)
 struct SocketState {
     listeners: HashMap<usize, TcpListener>,
     streams: HashMap<usize, TcpStream>,
@@ -26,14 +31,59 @@ lazy_static! {
 }
 
 // --- REGISTER ---
-pub fn register(map: &mut HashMap<String, NativeFn>) {
-    map.insert("sock_bind".to_string(), sock_bind);
-    map.insert("sock_accept".to_string(), sock_accept);
-    map.insert("sock_connect".to_string(), sock_connect);
-    map.insert("sock_read".to_string(), sock_read);
-    map.insert("sock_read_bytes".to_string(), sock_read_bytes);
-    map.insert("sock_write".to_string(), sock_write);
-    map.insert("sock_close".to_string(), sock_close);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("sock_bind".to_string(), native(sock_bind, 2));
+    map.insert("sock_accept".to_string(), native(sock_accept, 1));
+    map.insert("sock_connect".to_string(), native(sock_connect, 2));
+    map.insert("sock_read".to_string(), native(sock_read, 2));
+    map.insert("sock_read_bytes".to_string(), native(sock_read_bytes, 2));
+    map.insert("sock_write".to_string(), native(sock_write, 2));
+    map.insert("sock_close".to_string(), native(sock_close, 1));
+    map.insert("sock_accept_async".to_string(), native(sock_accept_async, 1));
+    map.insert("sock_read_async".to_string(), native(sock_read_async, 2));
+    map.insert("sock_write_async".to_string(), native(sock_write_async, 2));
+}
+
+// --- HELPERS ---
+
+// Opération synchrone sur un socket non bloquant : on attend un événement du réacteur, puis on réessaie
+fn blocking<T>(mut op: impl FnMut() -> io::Result<T>) -> Result<T, String> {
+    loop {
+        match op() {
+            Err(e) if e.kind() == ErrorKind::WouldBlock => scheduler::wait_io(None)?,
+            Err(e) if e.kind() == ErrorKind::Interrupted => {},
+            result => return result.map_err(|e| e.to_string()),
+        }
+    }
+}
+
+// Range un stream (accepté ou connecté) dans l'état, enregistré auprès du réacteur
+fn add_stream(state: &mut SocketState, mut stream: TcpStream) -> Result<Value, String> {
+    let id = state.next_id;
+    scheduler::register(&mut stream, id, Interest::READABLE | Interest::WRITABLE)?;
+    state.streams.insert(id, stream);
+    state.next_id += 1;
+    Ok(Value::Integer(id as i64))
+}
+
+// Octets envoyés par sock_write : le texte, le buffer brut d'un Bytes, sinon la valeur convertie en texte
+fn payload(content: &Value) -> Vec<u8> {
+    match content {
+        Value::String(s) => s.as_bytes().to_vec(),
+        Value::Bytes(b) => b.borrow().clone(),
+        _ => content.to_string().into_bytes(),
+    }
+}
+
+// Écrit la suite de `data` à partir de `written` ; WouldBlock quand le socket n'accepte plus rien pour l'instant
+fn write_more(stream: &mut TcpStream, data: &[u8], written: &mut usize) -> io::Result<()> {
+    while *written < data.len() {
+        match stream.write(&data[*written..])? {
+            0 => return Err(ErrorKind::WriteZero.into()),
+            n => *written += n,
+        }
+    }
+    Ok(())
 }
 
 // --- IMPLEMENTATION ---
@@ -46,15 +96,15 @@ fn sock_bind(args: Vec<Value>) -> Result<Value, String> {
     let port = args[1].as_int()?;
     let addr = format!("{}:{}", host, port);
 
-    let listener = TcpListener::bind(&addr).map_err(|e| e.to_string())?;
-    
-    // On met le listener en mode non-bloquant ? Non, restons simple (bloquant) pour l'instant.
-    // Ou alors on laisse le script gérer ça.
+    let listener = std::net::TcpListener::bind(&addr).map_err(|e| e.to_string())?;
+    listener.set_nonblocking(true).map_err(|e| e.to_string())?;
+    let mut listener = TcpListener::from_std(listener);
     
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
     
     let id = state.next_id;
+    scheduler::register(&mut listener, id, Interest::READABLE)?;
     state.listeners.insert(id, listener);
     state.next_id += 1;
 
@@ -63,6 +113,7 @@ fn sock_bind(args: Vec<Value>) -> Result<Value, String> {
 
 // 2. SERVEUR : Accepter une connexion (BLOQUANT)
 fn sock_accept(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("sock_accept attend 1 argument".into()); }
     let id = args[0].as_int()? as usize;
     
     let mut guard = STATE.lock().unwrap();
@@ -70,38 +121,29 @@ fn sock_accept(args: Vec<Value>) -> Result<Value, String> {
     
     let listener = state.listeners.get(&id).ok_or("Invalid Listener ID")?;
     
-    match listener.accept() {
-        Ok((stream, _addr)) => {
-            // On a une nouvelle connexion (Stream)
-            let stream_id = state.next_id;
-            state.streams.insert(stream_id, stream);
-            state.next_id += 1;
-            Ok(Value::Integer(stream_id as i64))
-        },
-        Err(e) => Err(e.to_string())
-    }
+    // On a une nouvelle connexion (Stream)
+    let (stream, _addr) = blocking(|| listener.accept())?;
+    add_stream(state, stream)
 }
 
 // 3. CLIENT : Se connecter
 fn sock_connect(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 { return Err("sock_connect attend 2 arguments".into()); }
     let host = args[0].as_str()?;
     let port = args[1].as_int()?;
     let addr = format!("{}:{}", host, port);
 
-    let stream = TcpStream::connect(&addr).map_err(|e| e.to_string())?;
+    // La connexion elle-même est établie en mode bloquant, le socket ne l'est plus ensuite
+    let stream = std::net::TcpStream::connect(&addr).map_err(|e| e.to_string())?;
+    stream.set_nonblocking(true).map_err(|e| e.to_string())?;
 
     let mut guard = STATE.lock().unwrap();
-    let state = &mut guard.0;
-    
-    let id = state.next_id;
-    state.streams.insert(id, stream);
-    state.next_id += 1;
-
-    Ok(Value::Integer(id as i64))
+    add_stream(&mut guard.0, TcpStream::from_std(stream))
 }
 
 // 4. READ (Lecture de N octets)
 fn sock_read(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 { return Err("sock_read attend 2 arguments".into()); }
     let id = args[0].as_int()? as usize;
     let size = args[1].as_int()? as usize; // Nombre d'octets à lire
 
@@ -111,7 +153,7 @@ fn sock_read(args: Vec<Value>) -> Result<Value, String> {
     let stream = state.streams.get_mut(&id).ok_or("Invalid Stream ID")?;
     
     let mut buffer = vec![0; size];
-    let bytes_read = stream.read(&mut buffer).map_err(|e| e.to_string())?;
+    let bytes_read = blocking(|| stream.read(&mut buffer))?;
     
     // On tronque si on a lu moins que prévu
     buffer.truncate(bytes_read);
@@ -120,11 +162,12 @@ fn sock_read(args: Vec<Value>) -> Result<Value, String> {
     // On remplace les caractères invalides pour ne pas crasher
     let s = String::from_utf8_lossy(&buffer).to_string();
     
-    Ok(Value::String(s))
+    Ok(Value::String(s.into()))
 }
 
 // Retourne les données brutes, parfait pour les images ou l'upload
 fn sock_read_bytes(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 { return Err("sock_read_bytes attend 2 arguments".into()); }
     let id = args[0].as_int()? as usize;
     let size = args[1].as_int()? as usize; 
 
@@ -134,7 +177,7 @@ fn sock_read_bytes(args: Vec<Value>) -> Result<Value, String> {
     let stream = state.streams.get_mut(&id).ok_or("Invalid Stream ID")?;
     
     let mut buffer = vec![0; size];
-    let bytes_read = stream.read(&mut buffer).map_err(|e| e.to_string())?;
+    let bytes_read = blocking(|| stream.read(&mut buffer))?;
     
     // On garde uniquement ce qu'on a lu
     buffer.truncate(bytes_read);
@@ -145,39 +188,114 @@ fn sock_read_bytes(args: Vec<Value>) -> Result<Value, String> {
 
 // 5. WRITE
 fn sock_write(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 { return Err("sock_write attend 2 arguments".into()); }
     let id = args[0].as_int()? as usize;
-    let content = &args[1];
+    let data = payload(&args[1]);
 
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
     
     let stream = state.streams.get_mut(&id).ok_or("Invalid Stream ID")?;
     
-    let res = match content {
-        Value::String(s) => stream.write_all(s.as_bytes()),
-        
-        // Support du type Bytes : on écrit le buffer brut
-        Value::Bytes(b) => stream.write_all(&b.borrow()),
-        
-        // Fallback (ex: Integer) -> Convertir en string
-        _ => stream.write_all(content.to_string().as_bytes()),
-    };
-
-    res.map_err(|e| e.to_string())?;
-    stream.flush().map_err(|e| e.to_string())?;
+    let mut written = 0;
+    blocking(|| write_more(stream, &data, &mut written))?;
     
     Ok(Value::Null)
 }
 
 // 6. CLOSE
 fn sock_close(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("sock_close attend 1 argument".into()); }
     let id = args[0].as_int()? as usize;
     let mut guard = STATE.lock().unwrap();
     let state = &mut guard.0;
     
-    // On essaie de retirer des deux maps
-    state.listeners.remove(&id);
-    state.streams.remove(&id);
+    // On essaie de retirer des deux maps (et du réacteur)
+    if let Some(mut listener) = state.listeners.remove(&id) {
+        scheduler::deregister(&mut listener);
+    }
+    if let Some(mut stream) = state.streams.remove(&id) {
+        scheduler::deregister(&mut stream);
+    }
     
     Ok(Value::Null)
 }
+
+// --- ASYNC ---
+// Les versions async renvoient une tâche : la boucle d'événements retente l'opération
+// à chaque événement du réacteur, jusqu'à ce qu'elle ne renvoie plus WouldBlock
+
+// 7. SERVEUR : Accepter une connexion (await Socket.accept_async(server))
+fn sock_accept_async(args: Vec<Value>) -> Result<Value, String> {
+    let id = args.first().ok_or("Args: server_id")?.as_int()? as usize;
+
+    let task = TaskData::poll("sock_accept", Box::new(move || {
+        let mut guard = STATE.lock().unwrap();
+        let state = &mut guard.0;
+
+        let Some(listener) = state.listeners.get(&id) else {
+            return Some(Err("Invalid Listener ID".into()));
+        };
+
+        match listener.accept() {
+            Ok((stream, _addr)) => Some(add_stream(state, stream)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => Some(Err(e.to_string())),
+        }
+    }));
+
+    Ok(Value::Task(Rc::new(RefCell::new(task))))
+}
+
+// 8. READ async : se termine dès que des données arrivent ("" quand la connexion est fermée)
+fn sock_read_async(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() < 2 { return Err("Args: client_id, size".into()); }
+    let id = args[0].as_int()? as usize;
+    let size = args[1].as_int()? as usize;
+
+    let task = TaskData::poll("sock_read", Box::new(move || {
+        let mut guard = STATE.lock().unwrap();
+        let state = &mut guard.0;
+
+        let Some(stream) = state.streams.get_mut(&id) else {
+            return Some(Err("Invalid Stream ID".into()));
+        };
+        let mut buffer = vec![0; size];
+
+        match stream.read(&mut buffer) {
+            Ok(bytes_read) => {
+                buffer.truncate(bytes_read);
+                Some(Ok(Value::String(String::from_utf8_lossy(&buffer).into())))
+            },
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => Some(Err(e.to_string())),
+        }
+    }));
+
+    Ok(Value::Task(Rc::new(RefCell::new(task))))
+}
+
+// 9. WRITE async : se termine quand tout est écrit, sans bloquer les autres tâches si le pair lit lentement
+fn sock_write_async(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() < 2 { return Err("Args: client_id, data".into()); }
+    let id = args[0].as_int()? as usize;
+    let data = payload(&args[1]);
+    let mut written = 0;
+
+    let task = TaskData::poll("sock_write", Box::new(move || {
+        let mut guard = STATE.lock().unwrap();
+        let state = &mut guard.0;
+
+        let Some(stream) = state.streams.get_mut(&id) else {
+            return Some(Err("Invalid Stream ID".into()));
+        };
+
+        match write_more(stream, &data, &mut written) {
+            Ok(()) => Some(Ok(Value::Null)),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => None,
+            Err(e) => Some(Err(e.to_string())),
+        }
+    }));
+
+    Ok(Value::Task(Rc::new(RefCell::new(task))))
+}
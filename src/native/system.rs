@@ -1,12 +1,17 @@
 use crate::ast::Value;
-use std::{collections::HashMap, io::{self, Write}};
+use crate::vm::gc;
+use std::{cell::RefCell, collections::HashMap, io::{self, Write}, rc::Rc};
+use super::{native, Native};
 
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("io_clear".to_string(), io_clear);
-    map.insert("io_write".to_string(), io_write);
-    map.insert("sys_env".to_string(), sys_env);
-    map.insert("sys_fail".to_string(), sys_fail);
-    map.insert("sys_exit".to_string(), sys_exit);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("io_clear".to_string(), native(io_clear, 0));
+    map.insert("io_write".to_string(), native(io_write, 1));
+    map.insert("sys_env".to_string(), native(sys_env, 1));
+    map.insert("sys_fail".to_string(), native(sys_fail, 0));
+    map.insert("sys_exit".to_string(), native(sys_exit, 0));
+    map.insert("sys_executable".to_string(), native(sys_executable, 0));
+    map.insert("sys_gc".to_string(), native(sys_gc, 0));
+    map.insert("sys_gc_stats".to_string(), native(sys_gc_stats, 0));
 }
 
 fn io_clear(_: Vec<Value>) -> Result<Value, String> {
@@ -17,6 +22,7 @@ fn io_clear(_: Vec<Value>) -> Result<Value, String> {
 }
 
 fn io_write(args: Vec<Value>) -> Result<Value, String> {
+    if args.is_empty() { return Err("io_write attend 1 argument".into()); }
     let s = args[0].as_str()?;
     print!("{}", s); // Pas de println!
     std::io::stdout().flush().unwrap();
@@ -31,7 +37,7 @@ fn sys_env(args: Vec<Value>) -> Result<Value, String> {
     let key = args[0].as_str()?;
 
     match std::env::var(key) {
-        Ok(val) => Ok(Value::String(val)),
+        Ok(val) => Ok(Value::String(val.into())),
         Err(_) => Ok(Value::Null)
     }
 }
@@ -45,3 +51,25 @@ fn sys_exit(args: Vec<Value>) -> Result<Value, String> {
     let code = if args.is_empty() { 0 } else { args[0].as_int().unwrap_or(0) as i32 };
     std::process::exit(code);
 }
+
+// Chemin de l'interpréteur en cours d'exécution (ou de l'exécutable produit par `aegis build`)
+fn sys_executable(_: Vec<Value>) -> Result<Value, String> {
+    let path = std::env::current_exe().map_err(|e| format!("sys_executable: {}", e))?;
+    Ok(Value::String(path.to_string_lossy().as_ref().into()))
+}
+
+// Collecte immédiate des cycles : renvoie le nombre d'objets libérés
+fn sys_gc(_: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Integer(gc::collect() as i64))
+}
+
+fn sys_gc_stats(_: Vec<Value>) -> Result<Value, String> {
+    let stats = gc::stats();
+    let mut dict = HashMap::new();
+    dict.insert("collections".to_string(), Value::Integer(stats.collections as i64));
+    dict.insert("freed".to_string(), Value::Integer(stats.freed as i64));
+    dict.insert("last_freed".to_string(), Value::Integer(stats.last_freed as i64));
+    dict.insert("tracked".to_string(), Value::Integer(stats.tracked as i64));
+    dict.insert("threshold".to_string(), Value::Integer(stats.threshold as i64));
+    Ok(Value::Dict(Rc::new(RefCell::new(dict))))
+}
@@ -0,0 +1,40 @@
+use crate::ast::Value;
+use crate::vm::scheduler::{self, TaskData};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+use super::{native, Native};
+
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("spawn".to_string(), native(spawn, 1));
+    map.insert("sleep".to_string(), native(sleep, 1));
+    map.insert("set_timeout".to_string(), native(set_timeout, 2));
+}
+
+fn delay(value: &Value) -> Result<Duration, String> {
+    let ms = value.as_int()?;
+    if ms < 0 { return Err("Delay must be positive (milliseconds)".into()); }
+    Ok(Duration::from_millis(ms as u64))
+}
+
+// Lance une tâche sans l'attendre : elle tournera au prochain `await` (ou à la fin du script)
+fn spawn(args: Vec<Value>) -> Result<Value, String> {
+    match args.first() {
+        Some(Value::Task(task)) => {
+            scheduler::spawn(task.clone());
+            Ok(args[0].clone())
+        },
+        _ => Err("spawn expects a task (the result of an async function call)".into()),
+    }
+}
+
+fn sleep(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("sleep expects 1 argument (milliseconds)".into()); }
+    let task = TaskData::timer("sleep", delay(&args[0])?, None);
+    Ok(Value::Task(Rc::new(RefCell::new(task))))
+}
+
+fn set_timeout(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 { return Err("set_timeout expects 2 arguments (callback, milliseconds)".into()); }
+    let task = Rc::new(RefCell::new(TaskData::timer("set_timeout", delay(&args[1])?, Some(args[0].clone()))));
+    scheduler::spawn(task.clone());
+    Ok(Value::Task(task))
+}
@@ -1,9 +1,10 @@
 use crate::ast::Value;
 use std::{collections::HashMap, thread, time::{self, SystemTime, UNIX_EPOCH}};
+use super::{native, Native};
 
-pub fn register(map: &mut HashMap<String, super::NativeFn>) {
-    map.insert("time_now".to_string(), time_now);
-    map.insert("time_sleep".to_string(), time_sleep);
+pub fn register(map: &mut HashMap<String, Native>) {
+    map.insert("time_now".to_string(), native(time_now, 0));
+    map.insert("time_sleep".to_string(), native(time_sleep, 1));
 }
 
 fn time_now(_: Vec<Value>) -> Result<Value, String> {
@@ -15,6 +16,7 @@ fn time_now(_: Vec<Value>) -> Result<Value, String> {
 }
 
 fn time_sleep(args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 { return Err("time_sleep attend 1 argument".into()); }
     let ms = args[0].as_int()?;
     thread::sleep(time::Duration::from_millis(ms as u64));
     Ok(Value::Null)
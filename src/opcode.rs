@@ -35,13 +35,12 @@ pub enum OpCode {
     // Structures
     MakeList, // operand: u8 (count)
     MakeDict, // operand: u8 (count * 2)
-    MakeEnum,
 
     // OOP
     Class,    // operand: const_idx (nom)
-    SetAttr,  // operand: const_idx (nom attribut)
-    GetAttr,  // operand: const_idx (nom attribut)
-    Method,   // operand: const_idx (nom méthode)
+    SetAttr,  // operands: const_idx (nom attribut), cache_idx
+    GetAttr,  // operands: const_idx (nom attribut), cache_idx
+    Method,   // operands: const_idx (nom méthode), u8 (nombre d'arguments), cache_idx
     Super,
 
     // Scopes / Namespaces
@@ -50,9 +49,13 @@ pub enum OpCode {
     // I/O
     Input,
 
-    MakeClosure,
-    GetFreeVar,
+    MakeClosure,  // crée la closure et ouvre les upvalues listées dans FunctionData.captures
+    GetUpvalue,   // operand: u8 (index de l'upvalue de la closure courante)
+    SetUpvalue,   // operand: u8 ; comme SetLocal, la valeur reste sur la pile
+    CloseUpvalue, // retire la locale au sommet de la pile en fermant les upvalues qui la désignent
+    GetFreeGlobal, // operand: u8 (id) ; comme GetGlobal, mais erreur si la globale n'existe pas
     Dup,
+    Dup2,         // duplique les deux valeurs du sommet (objet et clé d'une assignation composée x[k] += v)
 
     // Exception
     SetupExcept, // Démarre un bloc Try (pousse un handler)
@@ -61,11 +64,93 @@ pub enum OpCode {
 
     Import,
     CheckType,
-    MakeRange
+    MakeRange,
+
+    // Indexation : x[i], x[i] = v, x[a:b]
+    GetIndex,
+    SetIndex,
+    Slice,
+
+    // match : la valeur testée et les variables liées restent sur la pile
+    MatchBegin, // operand: u8 (niveau d'imbrication du match)
+    GetMatch,   // operands: u8 (niveau), u8 (slot, 0 = valeur testée)
+    MatchEnd,   // operand: u8 (niveau)
+    MatchList,  // operands: u8 (nombre d'éléments), u8 (1 si motif ...rest)
+    MatchKey,   // operand: const_idx (clé, null pour le motif {})
+    MatchClass,
+    MatchRange,
+    GetField,   // operand: u8 (position du paramètre dans init)
+
+    // Paramètres par défaut, arguments nommés et `...liste` (préfixes d'un Call/Method/Super)
+    ArgMissing, // operand: u8 (slot du paramètre) ; empile true si l'argument n'a pas été passé
+    CallNames,  // operand: const_idx (liste des noms des derniers arguments)
+    SpreadArgs, // operands: const_idx (positions des `...liste`), u8 (nombre d'arguments écrits)
+
+    // Générateurs et protocole d'itération de `foreach`
+    Yield,      // suspend la frame du générateur et renvoie la valeur au demandeur
+    GetIter,    // remplace l'itérable au sommet de la pile par son itérateur
+    IterNext,   // operand: u16 (saut de sortie) ; empile l'élément suivant (null puis saut si épuisé)
+
+    // async / await
+    Await,      // attend la tâche au sommet de la pile et la remplace par son résultat
+
+    // Préfixe : les opérandes d'un octet de l'instruction suivante sont écrits sur quatre (u32, big-endian).
+    // Les sauts (u16) ne changent pas.
+    Wide
 }
 
-impl From<u8> for OpCode {
-    fn from(b: u8) -> Self {
-        unsafe { std::mem::transmute(b) }
+// Tous les opcodes, rangés par valeur : Wide est le dernier
+const OPCODES: [OpCode; OpCode::Wide as usize + 1] = [
+    OpCode::LoadConst, OpCode::GetGlobal, OpCode::SetGlobal, OpCode::GetLocal, OpCode::SetLocal, OpCode::Add,
+    OpCode::Sub, OpCode::Mul, OpCode::Div, OpCode::Modulo, OpCode::NotEqual, OpCode::Equal,
+    OpCode::Greater, OpCode::GreaterEqual, OpCode::Less, OpCode::LessEqual, OpCode::Not, OpCode::BitAnd,
+    OpCode::BitOr, OpCode::BitXor, OpCode::ShiftLeft, OpCode::ShiftRight, OpCode::JumpIfFalse, OpCode::Jump,
+    OpCode::Loop, OpCode::Print, OpCode::Return, OpCode::Call, OpCode::MakeList, OpCode::MakeDict,
+    OpCode::Class, OpCode::SetAttr, OpCode::GetAttr, OpCode::Method, OpCode::Super, OpCode::Pop,
+    OpCode::Input, OpCode::MakeClosure, OpCode::GetUpvalue, OpCode::SetUpvalue, OpCode::CloseUpvalue, OpCode::GetFreeGlobal,
+    OpCode::Dup, OpCode::Dup2, OpCode::SetupExcept, OpCode::PopExcept, OpCode::Throw, OpCode::Import, OpCode::CheckType,
+    OpCode::MakeRange, OpCode::GetIndex, OpCode::SetIndex, OpCode::Slice, OpCode::MatchBegin, OpCode::GetMatch,
+    OpCode::MatchEnd, OpCode::MatchList, OpCode::MatchKey, OpCode::MatchClass, OpCode::MatchRange, OpCode::GetField,
+    OpCode::ArgMissing, OpCode::CallNames, OpCode::SpreadArgs, OpCode::Yield, OpCode::GetIter, OpCode::IterNext,
+    OpCode::Await, OpCode::Wide,
+];
+
+// Chaque opcode doit être à sa place dans la table (vérifié à la compilation)
+const _: () = {
+    let mut i = 0;
+    while i < OPCODES.len() {
+        assert!(OPCODES[i] as usize == i, "OPCODES n'est pas dans l'ordre de l'enum");
+        i += 1;
+    }
+};
+
+// Conversion vérifiée : un octet qui n'est pas un opcode (bytecode corrompu) est renvoyé en erreur
+impl TryFrom<u8> for OpCode {
+    type Error = u8;
+
+    fn try_from(b: u8) -> Result<Self, u8> {
+        OPCODES.get(b as usize).copied().ok_or(b)
+    }
+}
+
+impl OpCode {
+    /// Nombre d'opérandes d'index ou de compteur : un octet chacun, ou quatre après Wide.
+    pub fn operand_count(self) -> usize {
+        match self {
+            OpCode::LoadConst | OpCode::GetGlobal | OpCode::SetGlobal | OpCode::GetLocal | OpCode::SetLocal
+            | OpCode::Call | OpCode::MakeList | OpCode::MakeDict | OpCode::Class
+            | OpCode::GetUpvalue | OpCode::SetUpvalue | OpCode::GetFreeGlobal
+            | OpCode::CheckType
+            | OpCode::MatchBegin | OpCode::MatchEnd | OpCode::MatchKey | OpCode::GetField
+            | OpCode::ArgMissing | OpCode::CallNames => 1,
+            OpCode::SetAttr | OpCode::GetAttr | OpCode::GetMatch | OpCode::MatchList | OpCode::SpreadArgs => 2,
+            OpCode::Method | OpCode::Super | OpCode::Import => 3,
+            _ => 0,
+        }
+    }
+
+    /// Sauts : un déplacement u16 sur deux octets, que l'instruction soit préfixée par Wide ou non.
+    pub fn is_jump(self) -> bool {
+        matches!(self, OpCode::Jump | OpCode::JumpIfFalse | OpCode::Loop | OpCode::SetupExcept | OpCode::IterNext)
     }
 }
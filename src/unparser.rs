@@ -0,0 +1,514 @@
+use std::collections::HashSet;
+
+use crate::ast::nodes::{ClassDefinition, EnumDefinition, Param};
+use crate::ast::value::Visibility;
+use crate::ast::{Expression, Instruction, MatchArm, Pattern, Statement, Value};
+
+/// Regénère du code source `.aeg` lisible à partir d'un AST
+/// (par exemple un AST JSON chargé avec `loader::parse_program`).
+pub fn unparse(program: &[Statement]) -> String {
+    let mut unparser = Unparser { indent: 0, declared: vec![HashSet::new()] };
+    let mut out = String::new();
+    for stmt in program {
+        out.push_str(&unparser.statement(stmt));
+        out.push('\n');
+    }
+    out
+}
+
+// Niveaux de priorité, du plus faible au plus fort (même découpage que le parser)
+const PREC_TERNARY: u8 = 1;
+const PREC_COALESCE: u8 = 2;
+const PREC_OR: u8 = 3;
+const PREC_AND: u8 = 4;
+const PREC_EQUALITY: u8 = 5;
+const PREC_RELATIONAL: u8 = 6;
+const PREC_BITWISE: u8 = 7;
+const PREC_RANGE: u8 = 8;
+const PREC_ADDITIVE: u8 = 9;
+const PREC_MULTIPLICATIVE: u8 = 10;
+const PREC_UNARY: u8 = 11;
+const PREC_POSTFIX: u8 = 12;
+
+struct Unparser {
+    indent: usize,
+    // Variables déjà déclarées par fonction : la première affectation s'écrit `var x = ...`
+    declared: Vec<HashSet<String>>,
+}
+
+fn visibility_prefix(v: &Visibility) -> &'static str {
+    match v {
+        Visibility::Public => "",
+        Visibility::Protected => "protected ",
+        Visibility::Private => "private ",
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn string_literal(s: &str) -> String {
+    // "${" serait relu comme une interpolation : on coupe la chaîne entre '$' et '{'
+    let parts: Vec<&str> = s.split("${").collect();
+    let last = parts.len() - 1;
+    parts.iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let open = if i > 0 { "{" } else { "" };
+            let dollar = if i < last { "$" } else { "" };
+            quote(&format!("{}{}{}", open, part, dollar))
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+fn float_literal(f: f64) -> String {
+    let s = f.to_string();
+    if s.contains('.') || !f.is_finite() { s } else { format!("{}.0", s) }
+}
+
+fn value_literal(value: &Value) -> String {
+    match value {
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => float_literal(*f),
+        Value::String(s) => string_literal(s),
+        Value::Boolean(b) => b.to_string(),
+        Value::List(list) => format!("[{}]", list.borrow().iter().map(value_literal).collect::<Vec<_>>().join(", ")),
+        Value::Dict(dict) => {
+            let mut entries: Vec<_> = dict.borrow().iter().map(|(k, v)| format!("{}: {}", quote(k), value_literal(v))).collect();
+            entries.sort();
+            format!("{{{}}}", entries.join(", "))
+        },
+        _ => "null".to_string(),
+    }
+}
+
+impl Unparser {
+    fn pad(&self) -> String {
+        "    ".repeat(self.indent)
+    }
+
+    fn block(&mut self, body: &[Statement]) -> String {
+        if body.is_empty() {
+            return "{}".to_string();
+        }
+        self.indent += 1;
+        let mut out = String::from("{\n");
+        for stmt in body {
+            out.push_str(&self.statement(stmt));
+            out.push('\n');
+        }
+        self.indent -= 1;
+        out.push_str(&self.pad());
+        out.push('}');
+        out
+    }
+
+    // Corps de fonction : nouvelle portée de déclarations, paramètres inclus
+    fn params(&mut self, params: &[Param]) -> String {
+        params.iter()
+            .map(|p| {
+                let mut out = if p.is_rest { format!("...{}", p.name) } else { p.name.clone() };
+                if let Some(t) = &p.type_annot {
+                    out.push_str(&format!(": {}", t));
+                }
+                if let Some(default) = &p.default {
+                    out.push_str(&format!(" = {}", self.expr(default, 0)));
+                }
+                out
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn function_body(&mut self, params: &[Param], body: &[Statement]) -> String {
+        self.declared.push(params.iter().map(|p| p.name.clone()).collect());
+        let out = self.block(body);
+        self.declared.pop();
+        out
+    }
+
+    fn statement(&mut self, stmt: &Statement) -> String {
+        let code = match &stmt.kind {
+            Instruction::Set(name, typ, expr) => self.assignment(name, typ.as_deref(), expr),
+            Instruction::SetAttr(obj, attr, expr) => {
+                let target = format!("{}.{}", self.expr(obj, PREC_POSTFIX), attr);
+                match compound(&Expression::GetAttr(obj.clone(), attr.clone()), expr) {
+                    Some((op, r)) => format!("{} {} {}", target, op, self.expr(r, 0)),
+                    None => format!("{} = {}", target, self.expr(expr, 0)),
+                }
+            },
+            Instruction::SetIndex(obj, key, expr) => {
+                let target = format!("{}[{}]", self.expr(obj, PREC_POSTFIX), self.expr(key, 0));
+                match compound(&Expression::Index(obj.clone(), Box::new(key.clone())), expr) {
+                    Some((op, r)) => format!("{} {} {}", target, op, self.expr(r, 0)),
+                    None => format!("{} = {}", target, self.expr(expr, 0)),
+                }
+            },
+            Instruction::Print(expr) => format!("print {}", self.expr(expr, 0)),
+            Instruction::Input(name, prompt) => format!("input {} {}", name, self.expr(prompt, 0)),
+            Instruction::If { .. } => self.if_chain(&stmt.kind),
+            Instruction::While { condition, body } => {
+                format!("while ({}) {}", self.expr(condition, 0), self.block(body))
+            },
+            Instruction::Return(expr) => format!("return {}", self.expr(expr, 0)),
+            Instruction::Yield(expr) => format!("yield {}", self.expr(expr, 0)),
+            Instruction::ExpressionStatement(expr) => self.expr(expr, 0),
+            Instruction::Function { name, params: p, ret_type, body, is_async } => {
+                self.declared.last_mut().unwrap().insert(name.clone());
+                let ret = ret_type.as_ref().map(|t| format!(" -> {}", t)).unwrap_or_default();
+                let prefix = if *is_async { "async " } else { "" };
+                format!("{}func {}({}){} {}", prefix, name, self.params(p), ret, self.function_body(p, body))
+            },
+            Instruction::Class(class) => self.class(class),
+            Instruction::Interface(def) => {
+                let mut out = format!("interface {} {{\n", def.name);
+                for m in &def.methods {
+                    out.push_str(&format!("{}    func {}({})\n", self.pad(), m.name, self.params(&m.params)));
+                }
+                out.push_str(&self.pad());
+                out.push('}');
+                out
+            },
+            Instruction::Enum(def) => self.enum_def(def),
+            Instruction::Import(path, None) => format!("import {}", quote(path)),
+            Instruction::Import(path, Some(alias)) => format!("import {} as {}", quote(path), alias),
+            Instruction::ImportFrom(path, names) => format!("from {} import {}", quote(path), names.join(", ")),
+            Instruction::Export(declaration) => match &declaration.kind {
+                // Toujours sous forme de déclaration, même si le nom a déjà été affecté
+                Instruction::Set(name, typ, expr) => {
+                    self.declared.last_mut().unwrap().insert(name.clone());
+                    let typ = typ.as_ref().map(|t| format!(": {}", t)).unwrap_or_default();
+                    format!("export var {}{} = {}", name, typ, self.expr(expr, 0))
+                },
+                _ => format!("export {}", self.statement(declaration).trim_start()),
+            },
+            Instruction::Switch { value, cases, default } => {
+                let mut out = format!("switch ({}) {{\n", self.expr(value, 0));
+                self.indent += 1;
+                for (case, body) in cases {
+                    out.push_str(&format!("{}case {}:\n", self.pad(), self.expr(case, 0)));
+                    out.push_str(&self.case_body(body));
+                }
+                if !default.is_empty() {
+                    out.push_str(&format!("{}default:\n", self.pad()));
+                    out.push_str(&self.case_body(default));
+                }
+                self.indent -= 1;
+                out.push_str(&self.pad());
+                out.push('}');
+                out
+            },
+            Instruction::TryCatch { try_body, error_var, catch_body } => {
+                format!("try {} catch ({}) {}", self.block(try_body), error_var, self.block(catch_body))
+            },
+            Instruction::Throw(expr) => format!("throw {}", self.expr(expr, 0)),
+            Instruction::Namespace { name, body } => format!("namespace {} {}", name, self.block(body)),
+            Instruction::Break => "break".to_string(),
+            Instruction::Continue => "continue".to_string(),
+            Instruction::Const(name, expr) => format!("const {} = {}", name, self.expr(expr, 0)),
+            Instruction::ForEach(var, iterable, body) => {
+                self.declared.last_mut().unwrap().insert(var.clone());
+                format!("foreach ({} in {}) {}", var, self.expr(iterable, 0), self.block(body))
+            },
+        };
+        format!("{}{}", self.pad(), code)
+    }
+
+    fn assignment(&mut self, name: &str, typ: Option<&str>, expr: &Expression) -> String {
+        let first = self.declared.last_mut().unwrap().insert(name.to_string());
+
+        // x = x + v  =>  x += v
+        if !first && typ.is_none()
+            && let Some((op, r)) = compound(&Expression::Variable(name.to_string()), expr) {
+            return format!("{} {} {}", name, op, self.expr(r, 0));
+        }
+
+        let value = self.expr(expr, 0);
+        match (first, typ) {
+            (_, Some(t)) => format!("var {}: {} = {}", name, t, value),
+            (true, None) => format!("var {} = {}", name, value),
+            (false, None) => format!("{} = {}", name, value),
+        }
+    }
+
+    fn if_chain(&mut self, instr: &Instruction) -> String {
+        let Instruction::If { condition, body, else_body } = instr else { unreachable!() };
+        let mut out = format!("if ({}) {}", self.expr(condition, 0), self.block(body));
+        match else_body.as_slice() {
+            [] => {},
+            [Statement { kind: nested @ Instruction::If { .. }, .. }] => {
+                out.push_str(" else ");
+                out.push_str(&self.if_chain(nested));
+            },
+            _ => {
+                out.push_str(" else ");
+                out.push_str(&self.block(else_body));
+            },
+        }
+        out
+    }
+
+    fn case_body(&mut self, body: &[Statement]) -> String {
+        self.indent += 1;
+        let mut out = String::new();
+        for stmt in body {
+            out.push_str(&self.statement(stmt));
+            out.push('\n');
+        }
+        self.indent -= 1;
+        out
+    }
+
+    fn class(&mut self, class: &ClassDefinition) -> String {
+        let mut header = String::new();
+        if class.is_final { header.push_str("final "); }
+        header.push_str(&format!("class {}", class.name));
+        if let Some(parent) = &class.parent {
+            header.push_str(&format!(" extends {}", parent));
+        }
+        if !class.interfaces.is_empty() {
+            header.push_str(&format!(" implements {}", class.interfaces.join(", ")));
+        }
+
+        let mut members = Vec::new();
+        let mut fields = Vec::new();
+        self.indent += 1;
+        let pad = self.pad();
+
+        for field in &class.fields {
+            let mut line = format!("{}{}", pad, visibility_prefix(&field.visibility));
+            if field.is_static { line.push_str("static "); }
+            line.push_str(&format!("var {}", field.name));
+            if let Some(t) = &field.type_annot {
+                line.push_str(&format!(": {}", t));
+            }
+            if !matches!(field.default_value, Expression::Literal(Value::Null)) {
+                line.push_str(&format!(" = {}", self.expr(&field.default_value, 0)));
+            }
+            fields.push(line);
+        }
+        if !fields.is_empty() {
+            members.push(fields.join("\n"));
+        }
+
+        for prop in &class.properties {
+            let mut line = format!("{}{}", pad, visibility_prefix(&prop.visibility));
+            if prop.is_static { line.push_str("static "); }
+            line.push_str(&format!("prop {} {{\n", prop.name));
+            self.indent += 1;
+            if let Some((_, body)) = &prop.getter {
+                line.push_str(&format!("{}get {}\n", self.pad(), self.function_body(&[], body)));
+            }
+            if let Some((p, body)) = &prop.setter {
+                line.push_str(&format!("{}set({}) {}\n", self.pad(), self.params(p), self.function_body(p, body)));
+            }
+            self.indent -= 1;
+            line.push_str(&pad);
+            line.push('}');
+            members.push(line);
+        }
+
+        // La table des méthodes n'est pas ordonnée : tri par nom pour une sortie stable
+        let mut names: Vec<&String> = class.methods.keys().collect();
+        names.sort();
+        for name in names {
+            let (p, body, is_static, is_final, is_async) = &class.methods[name];
+            let visibility = class.visibilities.get(name).unwrap_or(&Visibility::Public);
+            let mut line = format!("{}{}", pad, visibility_prefix(visibility));
+            if *is_static { line.push_str("static "); }
+            if *is_final { line.push_str("final "); }
+            if *is_async { line.push_str("async "); }
+            line.push_str(&format!("func {}({}) {}", name, self.params(p), self.function_body(p, body)));
+            members.push(line);
+        }
+        self.indent -= 1;
+
+        if members.is_empty() {
+            return format!("{} {{}}", header);
+        }
+        format!("{} {{\n{}\n{}}}", header, members.join("\n\n"), self.pad())
+    }
+
+    fn enum_def(&mut self, def: &EnumDefinition) -> String {
+        let variants: Vec<String> = def.variants.iter()
+            .map(|v| if v.fields.is_empty() { v.name.clone() } else { format!("{}({})", v.name, v.fields.join(", ")) })
+            .collect();
+        if def.methods.is_empty() && def.variants.iter().all(|v| v.fields.is_empty()) {
+            return format!("enum {} {{ {} }}", def.name, variants.join(", "));
+        }
+
+        self.indent += 1;
+        let pad = self.pad();
+        let mut members = vec![variants.iter().map(|v| format!("{}{}", pad, v)).collect::<Vec<_>>().join(",\n")];
+        let mut names: Vec<&String> = def.methods.keys().collect();
+        names.sort();
+        for name in names {
+            let (p, body) = &def.methods[name];
+            members.push(format!("{}func {}({}) {}", pad, name, self.params(p), self.function_body(p, body)));
+        }
+        self.indent -= 1;
+        format!("enum {} {{\n{}\n{}}}", def.name, members.join("\n\n"), self.pad())
+    }
+
+    fn args(&mut self, args: &[Expression]) -> String {
+        args.iter().map(|a| self.expr(a, 0)).collect::<Vec<_>>().join(", ")
+    }
+
+    fn binary(&mut self, op: &str, prec: u8, l: &Expression, r: &Expression) -> (String, u8) {
+        // Associativité à gauche : l'opérande droit doit être strictement plus prioritaire
+        (format!("{} {} {}", self.expr(l, prec), op, self.expr(r, prec + 1)), prec)
+    }
+
+    // Rend l'expression, entre parenthèses si sa priorité est inférieure à `min_prec`
+    fn expr(&mut self, expr: &Expression, min_prec: u8) -> String {
+        let (code, prec) = match expr {
+            Expression::Literal(Value::Integer(i)) if *i < 0 => (i.to_string(), PREC_UNARY),
+            Expression::Literal(Value::Float(f)) if *f < 0.0 => (float_literal(*f), PREC_UNARY),
+            Expression::Literal(v @ Value::String(s)) if s.contains("${") => (value_literal(v), PREC_ADDITIVE),
+            Expression::Literal(v) => (value_literal(v), PREC_POSTFIX),
+            Expression::Variable(name) => (name.clone(), PREC_POSTFIX),
+            Expression::Function { params: p, body, is_async, .. } => {
+                let prefix = if *is_async { "async " } else { "" };
+                (format!("{}func({}) {}", prefix, self.params(p), self.function_body(p, body)), PREC_POSTFIX)
+            },
+
+            // -x est représenté par 0 - x
+            Expression::Sub(l, r) if matches!(l.as_ref(), Expression::Literal(Value::Integer(0))) => {
+                let operand = self.expr(r, PREC_UNARY);
+                // "--x" serait lu comme une décrémentation
+                let operand = if operand.starts_with('-') { format!("({})", operand) } else { operand };
+                (format!("-{}", operand), PREC_UNARY)
+            },
+            Expression::Add(l, r) => self.binary("+", PREC_ADDITIVE, l, r),
+            Expression::Sub(l, r) => self.binary("-", PREC_ADDITIVE, l, r),
+            Expression::Mul(l, r) => self.binary("*", PREC_MULTIPLICATIVE, l, r),
+            Expression::Div(l, r) => self.binary("/", PREC_MULTIPLICATIVE, l, r),
+            Expression::Modulo(l, r) => self.binary("%", PREC_MULTIPLICATIVE, l, r),
+
+            Expression::Equal(l, r) => self.binary("==", PREC_EQUALITY, l, r),
+            Expression::NotEqual(l, r) => self.binary("!=", PREC_EQUALITY, l, r),
+            Expression::LessThan(l, r) => self.binary("<", PREC_RELATIONAL, l, r),
+            Expression::GreaterThan(l, r) => self.binary(">", PREC_RELATIONAL, l, r),
+            Expression::LessEqual(l, r) => self.binary("<=", PREC_RELATIONAL, l, r),
+            Expression::GreaterEqual(l, r) => self.binary(">=", PREC_RELATIONAL, l, r),
+
+            Expression::And(l, r) => self.binary("&&", PREC_AND, l, r),
+            Expression::Or(l, r) => self.binary("||", PREC_OR, l, r),
+            Expression::Not(e) => (format!("!{}", self.expr(e, PREC_UNARY)), PREC_UNARY),
+            Expression::Await(e) => (format!("await {}", self.expr(e, PREC_UNARY)), PREC_UNARY),
+            Expression::NullCoalescing(l, r) => self.binary("??", PREC_COALESCE, l, r),
+            Expression::Ternary(c, t, f) => {
+                let code = format!("{} ? {} : {}", self.expr(c, PREC_COALESCE), self.expr(t, 0), self.expr(f, PREC_TERNARY));
+                (code, PREC_TERNARY)
+            },
+            // '..' n'est pas associatif : les deux bornes sont des expressions additives
+            Expression::Range(l, r) => {
+                (format!("{}..{}", self.expr(l, PREC_ADDITIVE), self.expr(r, PREC_ADDITIVE)), PREC_RANGE)
+            },
+
+            Expression::BitAnd(l, r) => self.binary("&", PREC_BITWISE, l, r),
+            Expression::BitOr(l, r) => self.binary("|", PREC_BITWISE, l, r),
+            Expression::BitXor(l, r) => self.binary("^", PREC_BITWISE, l, r),
+            Expression::ShiftLeft(l, r) => self.binary("<<", PREC_BITWISE, l, r),
+            Expression::ShiftRight(l, r) => self.binary(">>", PREC_BITWISE, l, r),
+
+            Expression::Call(target, args) => {
+                (format!("{}({})", self.expr(target, PREC_POSTFIX), self.args(args)), PREC_POSTFIX)
+            },
+            Expression::CallMethod(obj, name, args) => {
+                (format!("{}.{}({})", self.expr(obj, PREC_POSTFIX), name, self.args(args)), PREC_POSTFIX)
+            },
+            Expression::SuperCall(name, args) => (format!("super.{}({})", name, self.args(args)), PREC_POSTFIX),
+            Expression::GetAttr(obj, name) => (format!("{}.{}", self.expr(obj, PREC_POSTFIX), name), PREC_POSTFIX),
+            Expression::Index(obj, key) => (format!("{}[{}]", self.expr(obj, PREC_POSTFIX), self.expr(key, 0)), PREC_POSTFIX),
+            Expression::Slice(obj, start, end) => {
+                // Une borne nulle est une borne absente : x[:n]
+                let mut bound = |e: &Expression| match e {
+                    Expression::Literal(Value::Null) => String::new(),
+                    _ => self.expr(e, 0),
+                };
+                let (start, end) = (bound(start), bound(end));
+                (format!("{}[{}:{}]", self.expr(obj, PREC_POSTFIX), start, end), PREC_POSTFIX)
+            },
+            Expression::New(class, args) => {
+                (format!("new {}({})", self.expr(class, PREC_POSTFIX), self.args(args)), PREC_POSTFIX)
+            },
+            Expression::List(items) => (format!("[{}]", self.args(items)), PREC_POSTFIX),
+            Expression::Dict(entries) => {
+                let entries: Vec<String> = entries.iter().map(|(k, v)| format!("{}: {}", quote(k), self.expr(v, 0))).collect();
+                (format!("{{{}}}", entries.join(", ")), PREC_POSTFIX)
+            },
+            Expression::Match(subject, arms) => (self.match_expr(subject, arms), PREC_POSTFIX),
+            Expression::NamedArg(name, value) => (format!("{}: {}", name, self.expr(value, 0)), PREC_POSTFIX),
+            Expression::Spread(list) => (format!("...{}", self.expr(list, PREC_POSTFIX)), PREC_POSTFIX),
+        };
+
+        if prec < min_prec { format!("({})", code) } else { code }
+    }
+
+    fn match_expr(&mut self, subject: &Expression, arms: &[MatchArm]) -> String {
+        let subject = self.expr(subject, 0);
+        self.indent += 1;
+        let mut lines = Vec::new();
+        for arm in arms {
+            let guard = match &arm.guard {
+                Some(g) => format!(" if {}", self.expr(g, 0)),
+                None => String::new(),
+            };
+            lines.push(format!("{}{}{} => {},", self.pad(), self.pattern(&arm.pattern), guard, self.expr(&arm.body, 0)));
+        }
+        self.indent -= 1;
+        format!("match ({}) {{\n{}\n{}}}", subject, lines.join("\n"), self.pad())
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) -> String {
+        let join = |this: &mut Self, items: &[Pattern]| items.iter().map(|p| this.pattern(p)).collect::<Vec<_>>();
+        match pattern {
+            Pattern::Wildcard => "_".to_string(),
+            Pattern::Binding(name) => name.clone(),
+            Pattern::Literal(v) => value_literal(v),
+            Pattern::Range(start, end) => format!("{}..{}", value_literal(start), value_literal(end)),
+            Pattern::List(items, rest) => {
+                let mut parts = join(self, items);
+                match rest.as_deref() {
+                    Some("_") => parts.push("...".to_string()),
+                    Some(name) => parts.push(format!("...{}", name)),
+                    None => {},
+                }
+                format!("[{}]", parts.join(", "))
+            },
+            Pattern::Dict(entries) => {
+                let entries: Vec<String> = entries.iter().map(|(k, p)| format!("{}: {}", quote(k), self.pattern(p))).collect();
+                format!("{{{}}}", entries.join(", "))
+            },
+            Pattern::Class(class, args) => format!("{}({})", self.expr(class, PREC_POSTFIX), join(self, args).join(", ")),
+            Pattern::Value(expr) => self.expr(expr, PREC_POSTFIX),
+        }
+    }
+}
+
+// cible = cible + v  =>  ("+=", v)
+fn compound<'a>(target: &Expression, value: &'a Expression) -> Option<(&'static str, &'a Expression)> {
+    let (op, l, r) = match value {
+        Expression::Add(l, r) => ("+=", l, r),
+        Expression::Sub(l, r) => ("-=", l, r),
+        Expression::Mul(l, r) => ("*=", l, r),
+        Expression::Div(l, r) => ("/=", l, r),
+        _ => return None,
+    };
+    (l.as_ref() == target).then_some((op, r.as_ref()))
+}
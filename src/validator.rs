@@ -0,0 +1,632 @@
+use std::fmt;
+
+use serde_json::Value as JsonValue;
+
+use crate::loader::AST_FORMAT_VERSION;
+
+/// Problème de structure dans un AST JSON, localisé par son chemin (ex: `$.body[2][4]`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AstError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for AstError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Vérifie qu'un programme JSON respecte le format d'échange avant de le confier au loader.
+/// Renvoie toutes les erreurs trouvées (vide si l'AST est valide).
+pub fn validate(program: &JsonValue) -> Vec<AstError> {
+    let mut validator = Validator { errors: Vec::new() };
+    validator.program(program);
+    validator.errors
+}
+
+fn kind_of(json: &JsonValue) -> &'static str {
+    match json {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "boolean",
+        JsonValue::Number(_) => "number",
+        JsonValue::String(_) => "string",
+        JsonValue::Array(_) => "array",
+        JsonValue::Object(_) => "object",
+    }
+}
+
+fn index(path: &str, i: usize) -> String {
+    format!("{}[{}]", path, i)
+}
+
+struct Validator {
+    errors: Vec<AstError>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        self.errors.push(AstError { path: path.to_string(), message: message.into() });
+    }
+
+    fn program(&mut self, json: &JsonValue) {
+        match json {
+            // Format historique : tableau d'instructions sans en-tête
+            JsonValue::Array(_) => self.block(json, "$"),
+            JsonValue::Object(map) => {
+                match map.get("version") {
+                    None => self.error("$", "missing 'version' header"),
+                    Some(v) => match v.as_u64() {
+                        Some(AST_FORMAT_VERSION) => {},
+                        Some(n) => self.error("$.version", format!("unsupported format version {} (supported: {})", n, AST_FORMAT_VERSION)),
+                        None => self.error("$.version", format!("expected integer, found {}", kind_of(v))),
+                    }
+                }
+                match map.get("body") {
+                    Some(body) => self.block(body, "$.body"),
+                    None => self.error("$", "missing 'body'"),
+                }
+            },
+            other => self.error("$", format!("expected {{\"version\": {}, \"body\": [...]}}, found {}", AST_FORMAT_VERSION, kind_of(other))),
+        }
+    }
+
+    // --- Valeurs simples ---
+
+    fn string(&mut self, json: &JsonValue, path: &str, what: &str) {
+        if !json.is_string() {
+            self.error(path, format!("expected {} (string), found {}", what, kind_of(json)));
+        }
+    }
+
+    fn opt_string(&mut self, json: &JsonValue, path: &str, what: &str) {
+        if !json.is_string() && !json.is_null() {
+            self.error(path, format!("expected {} (string or null), found {}", what, kind_of(json)));
+        }
+    }
+
+    fn boolean(&mut self, json: &JsonValue, path: &str, what: &str) {
+        if !json.is_boolean() {
+            self.error(path, format!("expected {} (boolean), found {}", what, kind_of(json)));
+        }
+    }
+
+    fn visibility(&mut self, json: &JsonValue, path: &str) {
+        match json.as_str() {
+            Some("public" | "protected" | "private") => {},
+            Some(other) => self.error(path, format!("unknown visibility '{}' (expected public, protected or private)", other)),
+            None => self.error(path, format!("expected visibility (string), found {}", kind_of(json))),
+        }
+    }
+
+    fn array<'a>(&mut self, json: &'a JsonValue, path: &str, what: &str) -> &'a [JsonValue] {
+        match json.as_array() {
+            Some(arr) => arr,
+            None => {
+                self.error(path, format!("expected {} (array), found {}", what, kind_of(json)));
+                &[]
+            }
+        }
+    }
+
+    // Vérifie le nombre d'éléments d'un nœud ; false si trop court pour être inspecté
+    fn arity(&mut self, arr: &[JsonValue], path: &str, name: &str, min: usize, max: usize) -> bool {
+        if arr.len() >= min && arr.len() <= max {
+            return true;
+        }
+        let expected = if min == max { min.to_string() } else if max == usize::MAX { format!("at least {}", min) } else { format!("{} to {}", min, max) };
+        self.error(path, format!("'{}' expects {} elements, found {}", name, expected, arr.len()));
+        arr.len() >= min
+    }
+
+    // --- Paramètres : [nom, type|null] (un nom seul est accepté pour les fonctions et lambdas) ---
+
+    fn params(&mut self, json: &JsonValue, path: &str, allow_bare: bool) {
+        for (i, p) in self.array(json, path, "parameter list").iter().enumerate() {
+            let p_path = index(path, i);
+            match p {
+                JsonValue::String(_) if allow_bare => {},
+                // [name, type] ou [name, type, default|null, is_rest]
+                JsonValue::Array(row) if !row.is_empty() && row.len() <= 4 => {
+                    self.string(&row[0], &index(&p_path, 0), "parameter name");
+                    if let Some(t) = row.get(1) {
+                        self.opt_string(t, &index(&p_path, 1), "parameter type");
+                    }
+                    if let Some(default) = row.get(2) {
+                        self.expression(default, &index(&p_path, 2));
+                    }
+                    if let Some(rest) = row.get(3) {
+                        self.boolean(rest, &index(&p_path, 3), "rest flag");
+                    }
+                },
+                other => self.error(&p_path, format!("expected parameter [name, type, default?, rest?], found {}", kind_of(other))),
+            }
+        }
+    }
+
+    // --- Instructions ---
+
+    fn block(&mut self, json: &JsonValue, path: &str) {
+        for (i, stmt) in self.array(json, path, "block").iter().enumerate() {
+            self.statement(stmt, &index(path, i));
+        }
+    }
+
+    fn statement(&mut self, json: &JsonValue, path: &str) {
+        let Some(arr) = json.as_array() else {
+            return self.error(path, format!("expected statement [command, line, ...], found {}", kind_of(json)));
+        };
+        let Some(cmd) = arr.first().and_then(|c| c.as_str()) else {
+            return self.error(&index(path, 0), "expected statement command (string)");
+        };
+
+        let (min, max) = match cmd {
+            "break" | "continue" => (2, 2),
+            "print" | "return" | "yield" | "throw" | "expr" | "export" => (3, 3),
+            "import" => (3, 4),
+            "input" | "while" | "namespace" | "const" | "interface" | "call" | "super_call" | "from_import" => (4, 4),
+            "enum" => (4, 5),
+            "if" => (4, 5),
+            "set" | "set_attr" | "set_index" | "switch" | "try" | "foreach" | "call_method" => (5, 5),
+            "function" => (6, 7),
+            "class" => (5, 9),
+            _ => return self.error(&index(path, 0), format!("unknown statement '{}'", cmd)),
+        };
+        if !self.arity(arr, path, cmd, min, max) {
+            return;
+        }
+
+        if !arr[1].is_u64() {
+            self.error(&index(path, 1), format!("expected line number, found {}", kind_of(&arr[1])));
+        }
+
+        let at = |i: usize| index(path, i);
+        match cmd {
+            "set" => {
+                self.string(&arr[2], &at(2), "variable name");
+                self.opt_string(&arr[3], &at(3), "type annotation");
+                self.expression(&arr[4], &at(4));
+            },
+            "set_attr" => {
+                self.expression(&arr[2], &at(2));
+                self.string(&arr[3], &at(3), "attribute name");
+                self.expression(&arr[4], &at(4));
+            },
+            "set_index" => {
+                for (i, e) in arr.iter().enumerate().skip(2) { self.expression(e, &at(i)); }
+            },
+            "print" | "return" | "yield" | "throw" | "expr" => self.expression(&arr[2], &at(2)),
+            "input" => {
+                self.string(&arr[2], &at(2), "variable name");
+                self.expression(&arr[3], &at(3));
+            },
+            "if" => {
+                self.expression(&arr[2], &at(2));
+                self.block(&arr[3], &at(3));
+                if let Some(else_body) = arr.get(4) {
+                    self.block(else_body, &at(4));
+                }
+            },
+            "while" => {
+                self.expression(&arr[2], &at(2));
+                self.block(&arr[3], &at(3));
+            },
+            // Appels en instruction : même forme que l'expression, ligne en index 1
+            "call" | "call_method" | "super_call" => self.call(arr, path, cmd, 2),
+            "function" => {
+                self.string(&arr[2], &at(2), "function name");
+                self.params(&arr[3], &at(3), true);
+                self.opt_string(&arr[4], &at(4), "return type");
+                self.block(&arr[5], &at(5));
+                if let Some(is_async) = arr.get(6) {
+                    self.boolean(is_async, &at(6), "is_async");
+                }
+            },
+            "interface" => {
+                self.string(&arr[2], &at(2), "interface name");
+                for (i, m) in self.array(&arr[3], &at(3), "method list").iter().enumerate() {
+                    let m_path = index(&at(3), i);
+                    let m_arr = self.array(m, &m_path, "method [name, params]");
+                    if self.arity(m_arr, &m_path, "interface method", 2, 2) {
+                        self.string(&m_arr[0], &index(&m_path, 0), "method name");
+                        self.params(&m_arr[1], &index(&m_path, 1), false);
+                    }
+                }
+            },
+            "class" => self.class(arr, path),
+            "enum" => self.enum_def(arr, path),
+            "import" => {
+                self.string(&arr[2], &at(2), "module path");
+                if let Some(alias) = arr.get(3) {
+                    self.string(alias, &at(3), "module alias");
+                }
+            },
+            "from_import" => {
+                self.string(&arr[2], &at(2), "module path");
+                for (i, name) in self.array(&arr[3], &at(3), "import name list").iter().enumerate() {
+                    self.string(name, &index(&at(3), i), "imported name");
+                }
+            },
+            "export" => {
+                let declaration = arr[2].as_array().and_then(|d| d.first()).and_then(|c| c.as_str());
+                match declaration {
+                    Some("set" | "const" | "function" | "class" | "enum" | "namespace" | "interface") => self.statement(&arr[2], &at(2)),
+                    _ => self.error(&at(2), "expected a declaration (set, const, function, class, enum, namespace or interface)"),
+                }
+            },
+            "switch" => {
+                self.expression(&arr[2], &at(2));
+                for (i, case) in self.array(&arr[3], &at(3), "case list").iter().enumerate() {
+                    let c_path = index(&at(3), i);
+                    let c_arr = self.array(case, &c_path, "case [value, body]");
+                    if self.arity(c_arr, &c_path, "case", 2, 2) {
+                        self.expression(&c_arr[0], &index(&c_path, 0));
+                        self.block(&c_arr[1], &index(&c_path, 1));
+                    }
+                }
+                self.block(&arr[4], &at(4));
+            },
+            "try" => {
+                self.block(&arr[2], &at(2));
+                self.string(&arr[3], &at(3), "error variable name");
+                self.block(&arr[4], &at(4));
+            },
+            "namespace" => {
+                self.string(&arr[2], &at(2), "namespace name");
+                self.block(&arr[3], &at(3));
+            },
+            "const" => {
+                self.string(&arr[2], &at(2), "constant name");
+                self.expression(&arr[3], &at(3));
+            },
+            "foreach" => {
+                self.string(&arr[2], &at(2), "loop variable name");
+                self.expression(&arr[3], &at(3));
+                self.block(&arr[4], &at(4));
+            },
+            _ => {},
+        }
+    }
+
+    // ["enum", line, name, variants, methods?] : variante "Nom" ou ["Nom", [champs]]
+    fn enum_def(&mut self, arr: &[JsonValue], path: &str) {
+        let at = |i: usize| index(path, i);
+        self.string(&arr[2], &at(2), "enum name");
+
+        for (i, v) in self.array(&arr[3], &at(3), "variant list").iter().enumerate() {
+            let v_path = index(&at(3), i);
+            match v {
+                JsonValue::String(_) => {},
+                JsonValue::Array(pair) => {
+                    if self.arity(pair, &v_path, "variant", 2, 2) {
+                        self.string(&pair[0], &index(&v_path, 0), "variant name");
+                        for (j, field) in self.array(&pair[1], &index(&v_path, 1), "variant field list").iter().enumerate() {
+                            self.string(field, &index(&index(&v_path, 1), j), "variant field name");
+                        }
+                    }
+                },
+                other => self.error(&v_path, format!("expected variant name or [name, fields], found {}", kind_of(other))),
+            }
+        }
+
+        if let Some(methods) = arr.get(4) {
+            match methods {
+                JsonValue::Object(methods) => {
+                    for (name, m) in methods {
+                        let m_path = format!("{}.{}", at(4), name);
+                        let m_arr = self.array(m, &m_path, "method [params, body]");
+                        if self.arity(m_arr, &m_path, "method", 2, 2) {
+                            self.params(&m_arr[0], &index(&m_path, 0), false);
+                            self.block(&m_arr[1], &index(&m_path, 1));
+                        }
+                    }
+                },
+                other => self.error(&at(4), format!("expected method map (object), found {}", kind_of(other))),
+            }
+        }
+    }
+
+    // ["class", line, name, methods, parent, members?, visibilities?, is_final?, interfaces?]
+    fn class(&mut self, arr: &[JsonValue], path: &str) {
+        let at = |i: usize| index(path, i);
+        self.string(&arr[2], &at(2), "class name");
+
+        match &arr[3] {
+            JsonValue::Object(methods) => {
+                for (name, m) in methods {
+                    let m_path = format!("{}.{}", at(3), name);
+                    let m_arr = self.array(m, &m_path, "method [params, body, is_static, is_final, is_async]");
+                    if self.arity(m_arr, &m_path, "method", 2, 5) {
+                        self.params(&m_arr[0], &index(&m_path, 0), false);
+                        self.block(&m_arr[1], &index(&m_path, 1));
+                        for (i, flag) in m_arr.iter().enumerate().skip(2) {
+                            self.boolean(flag, &index(&m_path, i), ["is_static", "is_final", "is_async"][i - 2]);
+                        }
+                    }
+                }
+            },
+            other => self.error(&at(3), format!("expected method map (object), found {}", kind_of(other))),
+        }
+
+        self.opt_string(&arr[4], &at(4), "parent class name");
+
+        if let Some(members) = arr.get(5) {
+            for (i, member) in self.array(members, &at(5), "member list").iter().enumerate() {
+                self.class_member(member, &index(&at(5), i));
+            }
+        }
+
+        if let Some(visibilities) = arr.get(6) {
+            match visibilities {
+                JsonValue::Object(map) => {
+                    for (name, v) in map {
+                        self.visibility(v, &format!("{}.{}", at(6), name));
+                    }
+                },
+                other => self.error(&at(6), format!("expected visibility map (object), found {}", kind_of(other))),
+            }
+        }
+
+        if let Some(is_final) = arr.get(7) {
+            self.boolean(is_final, &at(7), "is_final");
+        }
+
+        if let Some(interfaces) = arr.get(8) {
+            for (i, name) in self.array(interfaces, &at(8), "interface list").iter().enumerate() {
+                self.string(name, &index(&at(8), i), "interface name");
+            }
+        }
+    }
+
+    // ["field", name, vis, default, is_static?, type?] | ["prop", name, vis, is_static, getter, setter]
+    fn class_member(&mut self, json: &JsonValue, path: &str) {
+        let arr = self.array(json, path, "class member");
+        let at = |i: usize| index(path, i);
+        match arr.first().and_then(|k| k.as_str()) {
+            Some("field") => {
+                if !self.arity(arr, path, "field", 4, 6) { return; }
+                self.string(&arr[1], &at(1), "field name");
+                self.visibility(&arr[2], &at(2));
+                self.expression(&arr[3], &at(3));
+                if let Some(is_static) = arr.get(4) {
+                    self.boolean(is_static, &at(4), "is_static");
+                }
+                if let Some(t) = arr.get(5) {
+                    self.opt_string(t, &at(5), "type annotation");
+                }
+            },
+            Some("prop") => {
+                if !self.arity(arr, path, "prop", 6, 6) { return; }
+                self.string(&arr[1], &at(1), "property name");
+                self.visibility(&arr[2], &at(2));
+                self.boolean(&arr[3], &at(3), "is_static");
+                for (i, what) in [(4, "getter"), (5, "setter")] {
+                    if arr[i].is_null() { continue; }
+                    let a_arr = self.array(&arr[i], &at(i), what);
+                    if self.arity(a_arr, &at(i), what, 2, 2) {
+                        self.params(&a_arr[0], &index(&at(i), 0), false);
+                        self.block(&a_arr[1], &index(&at(i), 1));
+                    }
+                }
+            },
+            _ if arr.is_empty() => {},
+            _ => self.error(&at(0), "expected member kind \"field\" or \"prop\""),
+        }
+    }
+
+    // --- Expressions ---
+
+    // Arguments d'appel : expressions, ["named", name, expr] et ["spread", expr]
+    fn expressions(&mut self, json: &JsonValue, path: &str) {
+        for (i, e) in self.array(json, path, "argument list").iter().enumerate() {
+            self.argument(e, &index(path, i));
+        }
+    }
+
+    fn argument(&mut self, json: &JsonValue, path: &str) {
+        let arr = json.as_array().map(|a| a.as_slice()).unwrap_or_default();
+        match arr.first().and_then(|o| o.as_str()) {
+            Some(op @ "named") => {
+                if self.arity(arr, path, op, 3, 3) {
+                    self.string(&arr[1], &index(path, 1), "argument name");
+                    self.expression(&arr[2], &index(path, 2));
+                }
+            },
+            Some(op @ "spread") => {
+                if self.arity(arr, path, op, 2, 2) {
+                    self.expression(&arr[1], &index(path, 1));
+                }
+            },
+            _ => self.expression(json, path),
+        }
+    }
+
+    // call / call_method / super_call, avec ou sans ligne : `first` est l'index du premier opérande
+    fn call(&mut self, arr: &[JsonValue], path: &str, cmd: &str, first: usize) {
+        let at = |i: usize| index(path, i);
+        match cmd {
+            "call" => {
+                self.expression(&arr[first], &at(first));
+                self.expressions(&arr[first + 1], &at(first + 1));
+            },
+            "call_method" => {
+                self.expression(&arr[first], &at(first));
+                self.string(&arr[first + 1], &at(first + 1), "method name");
+                self.expressions(&arr[first + 2], &at(first + 2));
+            },
+            _ => {
+                self.string(&arr[first], &at(first), "method name");
+                self.expressions(&arr[first + 1], &at(first + 1));
+            },
+        }
+    }
+
+    fn expression(&mut self, json: &JsonValue, path: &str) {
+        // Littéraux : scalaires, objets (dictionnaires) et tableaux de données
+        let Some(arr) = json.as_array() else { return };
+        let Some(op) = arr.first().and_then(|o| o.as_str()) else { return };
+
+        let at = |i: usize| index(path, i);
+        match op {
+            "get" => {
+                if self.arity(arr, path, op, 2, 2) {
+                    self.string(&arr[1], &at(1), "variable name");
+                }
+            },
+            "+" | "-" | "*" | "/" | "%" | "==" | "!=" | "<" | ">" | "<=" | ">="
+            | "&&" | "||" | "&" | "|" | "^" | "<<" | ">>" => {
+                if self.arity(arr, path, op, 3, 3) {
+                    self.expression(&arr[1], &at(1));
+                    self.expression(&arr[2], &at(2));
+                }
+            },
+            "!" | "await" => {
+                if self.arity(arr, path, op, 2, 2) {
+                    self.expression(&arr[1], &at(1));
+                }
+            },
+            "?" => {
+                if self.arity(arr, path, op, 4, 4) {
+                    for (i, e) in arr.iter().enumerate().skip(1) { self.expression(e, &at(i)); }
+                }
+            },
+            // [op, line, left, right]
+            "??" | "range" => {
+                if self.arity(arr, path, op, 4, 4) {
+                    self.expression(&arr[2], &at(2));
+                    self.expression(&arr[3], &at(3));
+                }
+            },
+            "make_list" => {
+                for (i, e) in arr.iter().enumerate().skip(1) {
+                    self.expression(e, &at(i));
+                }
+            },
+            "make_dict" => {
+                for (i, entry) in arr.iter().enumerate().skip(1) {
+                    let e_arr = self.array(entry, &at(i), "dictionary entry [key, value]");
+                    if self.arity(e_arr, &at(i), "dictionary entry", 2, 2) {
+                        self.string(&e_arr[0], &index(&at(i), 0), "dictionary key");
+                        self.expression(&e_arr[1], &index(&at(i), 1));
+                    }
+                }
+            },
+            "new" => {
+                if self.arity(arr, path, op, 2, usize::MAX) {
+                    self.expression(&arr[1], &at(1));
+                    for (i, e) in arr.iter().enumerate().skip(2) {
+                        self.argument(e, &at(i));
+                    }
+                }
+            },
+            "index" | "slice" => {
+                let len = if op == "index" { 3 } else { 4 };
+                if self.arity(arr, path, op, len, len) {
+                    for (i, e) in arr.iter().enumerate().skip(1) { self.expression(e, &at(i)); }
+                }
+            },
+            "get_attr" => {
+                if self.arity(arr, path, op, 3, 3) {
+                    self.expression(&arr[1], &at(1));
+                    self.string(&arr[2], &at(2), "attribute name");
+                }
+            },
+            "lambda" => {
+                if self.arity(arr, path, op, 3, 4) {
+                    self.params(&arr[1], &at(1), true);
+                    self.block(&arr[2], &at(2));
+                    if let Some(is_async) = arr.get(3) {
+                        self.boolean(is_async, &at(3), "is_async");
+                    }
+                }
+            },
+            "match" => {
+                if self.arity(arr, path, op, 3, 3) {
+                    self.expression(&arr[1], &at(1));
+                    for (i, arm) in self.array(&arr[2], &at(2), "arm list").iter().enumerate() {
+                        let a_path = index(&at(2), i);
+                        let a_arr = self.array(arm, &a_path, "arm [pattern, guard, body]");
+                        if self.arity(a_arr, &a_path, "match arm", 3, 3) {
+                            self.pattern(&a_arr[0], &index(&a_path, 0));
+                            self.expression(&a_arr[1], &index(&a_path, 1));
+                            self.expression(&a_arr[2], &index(&a_path, 2));
+                        }
+                    }
+                }
+            },
+            // La ligne (index 1) est optionnelle dans les appels
+            "call" | "super_call" => {
+                if self.arity(arr, path, op, 3, 4) {
+                    self.call(arr, path, op, arr.len() - 2);
+                }
+            },
+            "call_method" => {
+                if self.arity(arr, path, op, 4, 5) {
+                    self.call(arr, path, op, arr.len() - 3);
+                }
+            },
+            "named" | "spread" => self.error(&at(0), format!("'{}' is only allowed as a call argument", op)),
+            _ => self.error(&at(0), format!("unknown expression operator '{}' (list literals use [\"make_list\", ...])", op)),
+        }
+    }
+
+    // --- Motifs de match ---
+
+    fn patterns(&mut self, json: &JsonValue, path: &str) {
+        for (i, p) in self.array(json, path, "pattern list").iter().enumerate() {
+            self.pattern(p, &index(path, i));
+        }
+    }
+
+    fn pattern(&mut self, json: &JsonValue, path: &str) {
+        let arr = self.array(json, path, "pattern");
+        let Some(kind) = arr.first().and_then(|k| k.as_str()) else {
+            return self.error(&index(path, 0), "expected pattern kind (string)");
+        };
+
+        let (min, max) = match kind {
+            "_" => (1, 1),
+            "bind" | "lit" | "dict" | "value" => (2, 2),
+            "range" | "list" | "class" => (3, 3),
+            _ => return self.error(&index(path, 0), format!("unknown pattern '{}'", kind)),
+        };
+        if !self.arity(arr, path, kind, min, max) {
+            return;
+        }
+
+        let at = |i: usize| index(path, i);
+        match kind {
+            "bind" => self.string(&arr[1], &at(1), "variable name"),
+            "lit" if arr[1].is_array() || arr[1].is_object() => {
+                self.error(&at(1), format!("expected literal (scalar), found {}", kind_of(&arr[1])));
+            },
+            "range" => {
+                for (i, bound) in arr.iter().enumerate().skip(1) {
+                    if !bound.is_number() {
+                        self.error(&at(i), format!("expected range bound (number), found {}", kind_of(bound)));
+                    }
+                }
+            },
+            "list" => {
+                self.patterns(&arr[1], &at(1));
+                self.opt_string(&arr[2], &at(2), "rest variable name");
+            },
+            "dict" => {
+                for (i, entry) in self.array(&arr[1], &at(1), "entry list").iter().enumerate() {
+                    let e_path = index(&at(1), i);
+                    let e_arr = self.array(entry, &e_path, "entry [key, pattern]");
+                    if self.arity(e_arr, &e_path, "dictionary pattern entry", 2, 2) {
+                        self.string(&e_arr[0], &index(&e_path, 0), "dictionary key");
+                        self.pattern(&e_arr[1], &index(&e_path, 1));
+                    }
+                }
+            },
+            "class" => {
+                self.expression(&arr[1], &at(1));
+                self.patterns(&arr[2], &at(2));
+            },
+            "value" => self.expression(&arr[1], &at(1)),
+            _ => {},
+        }
+    }
+}
@@ -0,0 +1,682 @@
+// Méthodes natives des types de base (listes, chaînes, dictionnaires, octets, ranges,
+// itérateurs et tâches). Chaque type a sa table : un appel `obj.nom(...)` est résolu une fois
+// en (type, index) puis retenu par le cache du site d'appel, et `obj.nom` sans appel donne
+// une méthode liée (Value::BoundMethod).
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::ast::value::{IteratorData, RangeData};
+use crate::ast::Value;
+
+use super::scheduler::TaskState;
+use super::{range_len, VM};
+
+/// Implémentation d'une méthode native : reçoit l'objet et les arguments.
+pub type BuiltinFn = fn(&mut VM, &Value, Vec<Value>) -> Result<Value, String>;
+
+pub struct BuiltinMethod {
+    pub name: &'static str,
+    pub min_args: usize,
+    pub func: BuiltinFn,
+}
+
+const fn method(name: &'static str, min_args: usize, func: BuiltinFn) -> BuiltinMethod {
+    BuiltinMethod { name, min_args, func }
+}
+
+/// Type de base qui a une table de méthodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinType {
+    List,
+    Dict,
+    String,
+    Bytes,
+    Range,
+    Iterator, // Générateurs et itérateurs
+    Task,
+}
+
+impl BuiltinType {
+    pub fn of(value: &Value) -> Option<BuiltinType> {
+        Some(match value {
+            Value::List(_) => BuiltinType::List,
+            Value::Dict(_) => BuiltinType::Dict,
+            Value::String(_) => BuiltinType::String,
+            Value::Bytes(_) => BuiltinType::Bytes,
+            Value::Range(_) => BuiltinType::Range,
+            Value::Generator(_) | Value::Iterator(_) => BuiltinType::Iterator,
+            Value::Task(_) => BuiltinType::Task,
+            _ => return None,
+        })
+    }
+
+    pub fn methods(self) -> &'static [BuiltinMethod] {
+        match self {
+            BuiltinType::List => LIST_METHODS,
+            BuiltinType::Dict => DICT_METHODS,
+            BuiltinType::String => STRING_METHODS,
+            BuiltinType::Bytes => BYTES_METHODS,
+            BuiltinType::Range => RANGE_METHODS,
+            BuiltinType::Iterator => ITERATOR_METHODS,
+            BuiltinType::Task => TASK_METHODS,
+        }
+    }
+
+    /// Index de la méthode `name` dans la table du type.
+    pub fn find(self, name: &str) -> Option<usize> {
+        self.methods().iter().position(|m| m.name == name)
+    }
+
+    pub fn method(self, id: usize) -> &'static BuiltinMethod {
+        &self.methods()[id]
+    }
+}
+
+static LIST_METHODS: &[BuiltinMethod] = &[
+    method("push", 1, list_push),
+    method("pop", 0, list_pop),
+    method("at", 1, list_at),
+    method("len", 0, list_len),
+    method("reverse", 0, list_reverse),
+    method("contains", 1, list_contains),
+    method("join", 0, list_join),
+    method("is_empty", 0, list_is_empty),
+    method("first", 0, list_first),
+    method("last", 0, list_last),
+    method("clear", 0, list_clear),
+    method("reduce", 2, list_reduce),
+    method("index_of", 1, list_index_of),
+    method("find", 1, list_find),
+    method("sort", 0, list_sort),
+    method("slice", 0, list_slice),
+    method("map", 1, list_map),
+    method("filter", 1, list_filter),
+    method("for_each", 1, list_for_each),
+    method("iter", 0, sequence_iter),
+];
+
+static DICT_METHODS: &[BuiltinMethod] = &[
+    method("insert", 2, dict_insert),
+    method("keys", 0, dict_keys),
+    method("get", 1, dict_get),
+    method("is_empty", 0, dict_is_empty),
+    method("remove", 1, dict_remove),
+    method("values", 0, dict_values),
+    method("contains", 1, dict_contains),
+];
+
+static STRING_METHODS: &[BuiltinMethod] = &[
+    method("len", 0, string_len),
+    method("at", 1, string_at),
+    method("index_of", 1, string_index_of),
+    method("slice", 0, string_slice),
+    method("to_bytes", 0, string_to_bytes),
+    method("trim", 0, string_trim),
+    method("upper", 0, string_upper),
+    method("lower", 0, string_lower),
+    method("contains", 1, string_contains),
+    method("starts_with", 1, string_starts_with),
+    method("ends_with", 1, string_ends_with),
+    method("replace", 2, string_replace),
+    method("split", 0, string_split),
+    method("is_empty", 0, string_is_empty),
+    method("pad_start", 1, string_pad_start),
+    method("pad_end", 1, string_pad_end),
+    method("iter", 0, sequence_iter),
+];
+
+static BYTES_METHODS: &[BuiltinMethod] = &[
+    method("len", 0, bytes_len),
+    method("is_empty", 0, bytes_is_empty),
+    method("at", 1, bytes_at),
+    method("slice", 0, bytes_slice),
+    method("to_string", 0, bytes_to_string),
+    method("to_hex", 0, bytes_to_hex),
+    method("iter", 0, sequence_iter),
+];
+
+static RANGE_METHODS: &[BuiltinMethod] = &[
+    method("len", 0, range_len_method),
+    method("at", 1, range_at),
+    method("step", 1, range_step),
+    method("to_list", 0, range_to_list),
+    method("iter", 0, sequence_iter),
+];
+
+static ITERATOR_METHODS: &[BuiltinMethod] = &[
+    method("next", 0, iterator_next),
+    method("iter", 0, iterator_iter),
+    method("map", 0, iterator_map),
+    method("filter", 0, iterator_filter),
+    method("take", 0, iterator_take),
+    method("enumerate", 0, iterator_enumerate),
+    method("zip", 0, iterator_zip),
+    method("to_list", 0, iterator_to_list),
+];
+
+static TASK_METHODS: &[BuiltinMethod] = &[
+    method("done", 0, task_done),
+];
+
+fn new_list(items: Vec<Value>) -> Value {
+    Value::List(Rc::new(RefCell::new(items)))
+}
+
+// Itérateur paresseux sur une séquence : (0..1000).iter().map(f).take(3)
+fn sequence_iter(vm: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    vm.get_iter(this.clone())
+}
+
+// --- Listes ---
+
+fn list(this: &Value) -> &Rc<RefCell<Vec<Value>>> {
+    match this {
+        Value::List(l) => l,
+        _ => unreachable!("méthode de liste appelée sur {}", this.type_name()),
+    }
+}
+
+fn list_push(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    super::gc::note_store(this, &args[0]);
+    list(this).borrow_mut().push(args[0].clone());
+    Ok(Value::Null)
+}
+
+fn list_pop(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(list(this).borrow_mut().pop().unwrap_or(Value::Null))
+}
+
+fn list_at(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let idx = args[0].as_int().unwrap_or(0) as usize;
+    Ok(list(this).borrow().get(idx).cloned().unwrap_or(Value::Null))
+}
+
+fn list_len(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Integer(list(this).borrow().len() as i64))
+}
+
+fn list_reverse(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    list(this).borrow_mut().reverse();
+    Ok(this.clone())
+}
+
+fn list_contains(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    // Value implémente PartialEq
+    Ok(Value::Boolean(list(this).borrow().contains(&args[0])))
+}
+
+fn list_join(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let sep = if args.is_empty() { "".to_string() } else { args[0].as_str().unwrap_or_default() };
+    // On convertit tout en string et on joint
+    let strings: Vec<String> = list(this).borrow().iter().map(|v| v.to_string()).collect();
+    Ok(Value::String(strings.join(&sep).into()))
+}
+
+fn list_is_empty(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Boolean(list(this).borrow().is_empty()))
+}
+
+fn list_first(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(list(this).borrow().first().cloned().unwrap_or(Value::Null))
+}
+
+fn list_last(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(list(this).borrow().last().cloned().unwrap_or(Value::Null))
+}
+
+fn list_clear(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    list(this).borrow_mut().clear();
+    Ok(Value::Null)
+}
+
+// Usage: list.reduce(func(acc, val), initial_value)
+fn list_reduce(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let callback = args[0].clone();
+    let mut accumulator = args[1].clone();
+    let list_data = list(this).borrow().clone();
+
+    for item in list_data {
+        // Le callback prend (acc, item) et retourne le nouvel acc
+        accumulator = vm.run_callable_sync(callback.clone(), vec![accumulator, item], None)?;
+    }
+    Ok(accumulator)
+}
+
+// Usage: list.index_of(value) -> int (ou -1)
+fn list_index_of(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let index = list(this).borrow().iter().position(|x| x == &args[0]);
+    Ok(Value::Integer(index.map_or(-1, |i| i as i64)))
+}
+
+fn list_find(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let callback = args[0].clone();
+    let list_data = list(this).borrow().clone();
+
+    for item in list_data {
+        let res = vm.run_callable_sync(callback.clone(), vec![item.clone()], None)?;
+        let is_found = match res {
+            Value::Boolean(b) => b,
+            Value::Null => false,
+            Value::Integer(i) => i != 0,
+            _ => true,
+        };
+        if is_found {
+            return Ok(item);
+        }
+    }
+    Ok(Value::Null)
+}
+
+fn list_sort(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    // On trie une copie : le callback de comparaison peut lire la liste sans conflit d'emprunt
+    let mut data = list(this).borrow().clone();
+
+    if let Some(comp_fn) = args.first().cloned() {
+        // --- TRI PERSONNALISÉ ---
+        // Une erreur survenue dans le callback Aegis est remontée après le tri
+        let mut sort_error = None;
+
+        data.sort_by(|a, b| {
+            if sort_error.is_some() { return std::cmp::Ordering::Equal; }
+
+            match vm.run_callable_sync(comp_fn.clone(), vec![a.clone(), b.clone()], None) {
+                Ok(res) => {
+                    // Négatif = Less, positif = Greater, 0 = Equal
+                    let n = if let Ok(i) = res.as_int() { i as f64 }
+                            else { res.as_float().unwrap_or(0.0) };
+
+                    if n < 0.0 { std::cmp::Ordering::Less }
+                    else if n > 0.0 { std::cmp::Ordering::Greater }
+                    else { std::cmp::Ordering::Equal }
+                },
+                Err(e) => {
+                    sort_error = Some(e);
+                    std::cmp::Ordering::Equal
+                }
+            }
+        });
+
+        if let Some(e) = sort_error { return Err(e); }
+    } else {
+        // --- TRI PAR DÉFAUT ---
+        // Value n'implémente pas Ord : nombres entre eux, chaînes entre elles, sinon leur affichage
+        data.sort_by(|a, b| {
+            match (a, b) {
+                (Value::Integer(i1), Value::Integer(i2)) => i1.cmp(i2),
+                (Value::Float(f1), Value::Float(f2)) => f1.partial_cmp(f2).unwrap_or(std::cmp::Ordering::Equal),
+                (Value::Integer(i), Value::Float(f)) => (*i as f64).partial_cmp(f).unwrap_or(std::cmp::Ordering::Equal),
+                (Value::Float(f), Value::Integer(i)) => f.partial_cmp(&(*i as f64)).unwrap_or(std::cmp::Ordering::Equal),
+                (Value::String(s1), Value::String(s2)) => s1.cmp(s2),
+                (v1, v2) => v1.to_string().cmp(&v2.to_string())
+            }
+        });
+    }
+
+    // On remplace le contenu de la liste originale par la version triée
+    *list(this).borrow_mut() = data;
+    Ok(Value::Null)
+}
+
+// Usage: list.slice(start, end_exclusive)
+fn list_slice(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let items = list(this).borrow();
+    let (start, end) = clamp_range(&args, items.len());
+    Ok(new_list(items[start..end].to_vec()))
+}
+
+// Bornes de slice(start, end) en éléments, ramenées dans [0, len]
+fn clamp_range(args: &[Value], len: usize) -> (usize, usize) {
+    let start = args.first().and_then(|v| v.as_int().ok()).unwrap_or(0) as usize;
+    let end = args.get(1).and_then(|v| v.as_int().ok()).unwrap_or(len as i64) as usize;
+    let start = start.min(len);
+    (start, end.min(len).max(start))
+}
+
+// --- Programmation fonctionnelle ---
+// On copie la liste avant d'appeler le callback (il peut la modifier)
+
+fn list_map(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let list_data = list(this).borrow().clone();
+    let mut mapped = Vec::new();
+    for item in list_data {
+        mapped.push(vm.run_callable_sync(args[0].clone(), vec![item], None)?);
+    }
+    Ok(new_list(mapped))
+}
+
+fn list_filter(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let list_data = list(this).borrow().clone();
+    let mut kept = Vec::new();
+    for item in list_data {
+        let res = vm.run_callable_sync(args[0].clone(), vec![item.clone()], None)?;
+        // On garde si le résultat est "truthy"
+        if matches!(res, Value::Boolean(true)) || (res.as_int().unwrap_or(0) != 0 && !matches!(res, Value::Null)) {
+            kept.push(item);
+        }
+    }
+    Ok(new_list(kept))
+}
+
+fn list_for_each(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let list_data = list(this).borrow().clone();
+    for item in list_data {
+        // Pour l'effet de bord, on ignore le résultat
+        vm.run_callable_sync(args[0].clone(), vec![item], None)?;
+    }
+    Ok(Value::Null)
+}
+
+// --- Dictionnaires ---
+
+fn dict(this: &Value) -> &Rc<RefCell<std::collections::HashMap<String, Value>>> {
+    match this {
+        Value::Dict(d) => d,
+        _ => unreachable!("méthode de dictionnaire appelée sur {}", this.type_name()),
+    }
+}
+
+fn dict_insert(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let key = args[0].as_str().unwrap_or("?".to_string());
+    super::gc::note_store(this, &args[1]);
+    dict(this).borrow_mut().insert(key, args[1].clone());
+    Ok(Value::Null)
+}
+
+fn dict_keys(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(new_list(dict(this).borrow().keys().map(|k| Value::String(k.as_str().into())).collect()))
+}
+
+fn dict_get(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let key = args[0].as_str().unwrap_or("?".to_string());
+    Ok(dict(this).borrow().get(&key).cloned().unwrap_or(Value::Null))
+}
+
+fn dict_is_empty(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Boolean(dict(this).borrow().is_empty()))
+}
+
+// Retourne la valeur supprimée ou Null
+fn dict_remove(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let key = args[0].as_str().unwrap_or_default();
+    Ok(dict(this).borrow_mut().remove(&key).unwrap_or(Value::Null))
+}
+
+fn dict_values(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(new_list(dict(this).borrow().values().cloned().collect()))
+}
+
+fn dict_contains(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    // Les clés sont des chaînes (HashMap<String, Value>)
+    let key = args[0].as_str().map_err(|_| "Dict key must be a string")?;
+    Ok(Value::Boolean(dict(this).borrow().contains_key(&key)))
+}
+
+// --- Chaînes ---
+
+fn string(this: &Value) -> &str {
+    match this {
+        Value::String(s) => s,
+        _ => unreachable!("méthode de chaîne appelée sur {}", this.type_name()),
+    }
+}
+
+fn string_len(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Integer(string(this).chars().count() as i64))
+}
+
+// Caractère à l'index donné (en caractères, pas en octets : accents et emojis comptent pour un)
+fn string_at(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let idx = args[0].as_int().unwrap_or(0);
+    if idx < 0 {
+        return Ok(Value::Null);
+    }
+    Ok(match string(this).chars().nth(idx as usize) {
+        Some(c) => Value::String(c.to_string().into()),
+        None => Value::Null,
+    })
+}
+
+// Index en octets de la sous-chaîne, -1 si absente
+fn string_index_of(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let sub = args[0].as_str().unwrap_or_default();
+    Ok(Value::Integer(string(this).find(&sub).map_or(-1, |idx| idx as i64)))
+}
+
+// Usage: string.slice(start, end), en caractères
+fn string_slice(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let s = string(this);
+    let (start, end) = clamp_range(&args, s.chars().count());
+    let sub: String = s.chars().skip(start).take(end - start).collect();
+    Ok(Value::String(sub.into()))
+}
+
+fn string_to_bytes(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Bytes(Rc::new(RefCell::new(string(this).as_bytes().to_vec()))))
+}
+
+fn string_trim(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::String(string(this).trim().into()))
+}
+
+fn string_upper(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::String(string(this).to_uppercase().into()))
+}
+
+fn string_lower(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::String(string(this).to_lowercase().into()))
+}
+
+fn string_contains(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Boolean(string(this).contains(&args[0].as_str().unwrap_or_default())))
+}
+
+fn string_starts_with(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Boolean(string(this).starts_with(&args[0].as_str().unwrap_or_default())))
+}
+
+fn string_ends_with(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Boolean(string(this).ends_with(&args[0].as_str().unwrap_or_default())))
+}
+
+fn string_replace(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let old_part = args[0].as_str().unwrap_or("".to_string());
+    let new_part = args[1].as_str().unwrap_or("".to_string());
+    Ok(Value::String(string(this).replace(&old_part, &new_part).into()))
+}
+
+// Découpe sur le séparateur (un espace par défaut)
+fn string_split(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let delim = match args.first() {
+        Some(d) => d.as_str().unwrap_or(" ".to_string()),
+        None => " ".to_string(),
+    };
+    Ok(new_list(string(this).split(&delim).map(|sub| Value::String(sub.into())).collect()))
+}
+
+fn string_is_empty(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Boolean(string(this).is_empty()))
+}
+
+// Args: width, char (optionnel, ' ' par défaut)
+fn padding(this: &Value, args: &[Value]) -> Option<String> {
+    let width = args[0].as_int().unwrap_or(0) as usize;
+    let pad_char = match args.get(1) {
+        Some(c) => c.as_str().unwrap_or(" ".to_string()).chars().next().unwrap_or(' '),
+        None => ' ',
+    };
+    let len = string(this).len();
+    (len < width).then(|| pad_char.to_string().repeat(width - len))
+}
+
+fn string_pad_start(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    Ok(match padding(this, &args) {
+        Some(padding) => Value::String(format!("{}{}", padding, string(this)).into()),
+        None => this.clone(),
+    })
+}
+
+fn string_pad_end(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    Ok(match padding(this, &args) {
+        Some(padding) => Value::String(format!("{}{}", string(this), padding).into()),
+        None => this.clone(),
+    })
+}
+
+// --- Octets ---
+
+fn bytes(this: &Value) -> &Rc<RefCell<Vec<u8>>> {
+    match this {
+        Value::Bytes(b) => b,
+        _ => unreachable!("méthode d'octets appelée sur {}", this.type_name()),
+    }
+}
+
+fn bytes_len(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Integer(bytes(this).borrow().len() as i64))
+}
+
+fn bytes_is_empty(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(Value::Boolean(bytes(this).borrow().is_empty()))
+}
+
+// L'octet sous forme d'entier (0-255)
+fn bytes_at(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let idx = args[0].as_int().unwrap_or(0) as usize;
+    Ok(bytes(this).borrow().get(idx).map_or(Value::Null, |byte| Value::Integer(*byte as i64)))
+}
+
+fn bytes_slice(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let data = bytes(this).borrow();
+    let (start, end) = clamp_range(&args, data.len());
+    Ok(Value::Bytes(Rc::new(RefCell::new(data[start..end].to_vec()))))
+}
+
+// Texte UTF-8 (utile pour un fichier texte lu en binaire), null si les octets ne sont pas valides
+fn bytes_to_string(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    Ok(match String::from_utf8(bytes(this).borrow().clone()) {
+        Ok(s) => Value::String(s.into()),
+        Err(_) => Value::Null,
+    })
+}
+
+// Debug : "1FA2..."
+fn bytes_to_hex(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    let hex: String = bytes(this).borrow().iter().map(|b| format!("{:02X}", b)).collect();
+    Ok(Value::String(hex.into()))
+}
+
+// --- Ranges ---
+
+fn range(this: &Value) -> RangeData {
+    match this {
+        Value::Range(r) => **r,
+        _ => unreachable!("méthode de range appelée sur {}", this.type_name()),
+    }
+}
+
+fn range_len_method(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    let RangeData { start, end, step } = range(this);
+    if step == 0 { return Err("Step cannot be zero".into()); }
+    Ok(Value::Integer(range_len(start, end, step) as i64))
+}
+
+fn range_at(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let RangeData { start, step, .. } = range(this);
+    Ok(Value::Integer(start + args[0].as_int().unwrap_or(0) * step))
+}
+
+// Méthode fluide pour changer le pas : (0..10).step(2)
+fn range_step(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let RangeData { start, end, .. } = range(this);
+    let new_step = args[0].as_int().unwrap_or(1);
+    if new_step == 0 { return Err("Step cannot be 0".into()); }
+    Ok(Value::range(start, end, new_step))
+}
+
+fn range_to_list(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    let RangeData { start, end, step } = range(this);
+    let mut items = Vec::new();
+    let mut current = start;
+    if step > 0 {
+        while current < end {
+            items.push(Value::Integer(current));
+            current += step;
+        }
+    } else {
+        while current > end {
+            items.push(Value::Integer(current));
+            current += step;
+        }
+    }
+    Ok(new_list(items))
+}
+
+// --- Itérateurs et générateurs ---
+
+fn expect_args(this: &Value, name: &str, args: &[Value], count: usize) -> Result<(), String> {
+    if args.len() == count {
+        Ok(())
+    } else {
+        Err(format!("Method '{}' of {} expects {} argument(s), got {}", name, this.type_name(), count, args.len()))
+    }
+}
+
+fn lazy(data: IteratorData) -> Value {
+    Value::Iterator(Rc::new(RefCell::new(data)))
+}
+
+fn iterator_next(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    expect_args(this, "next", &args, 0)?;
+    Ok(vm.iter_next(this)?.unwrap_or(Value::Null))
+}
+
+fn iterator_iter(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    expect_args(this, "iter", &args, 0)?;
+    Ok(this.clone())
+}
+
+fn iterator_map(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    expect_args(this, "map", &args, 1)?;
+    Ok(lazy(IteratorData::Map(this.clone(), args[0].clone())))
+}
+
+fn iterator_filter(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    expect_args(this, "filter", &args, 1)?;
+    Ok(lazy(IteratorData::Filter(this.clone(), args[0].clone())))
+}
+
+fn iterator_take(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    expect_args(this, "take", &args, 1)?;
+    let count = args[0].as_int()?.max(0) as usize;
+    Ok(lazy(IteratorData::Take(this.clone(), count)))
+}
+
+fn iterator_enumerate(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    expect_args(this, "enumerate", &args, 0)?;
+    Ok(lazy(IteratorData::Enumerate(this.clone(), 0)))
+}
+
+fn iterator_zip(_: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    let mut sources = vec![this.clone()];
+    sources.extend(args.into_iter().map(|other| lazy(IteratorData::Source(other))));
+    Ok(lazy(IteratorData::Zip(sources)))
+}
+
+fn iterator_to_list(vm: &mut VM, this: &Value, args: Vec<Value>) -> Result<Value, String> {
+    expect_args(this, "to_list", &args, 0)?;
+    let mut items = Vec::new();
+    while let Some(item) = vm.iter_next(this)? {
+        items.push(item);
+    }
+    Ok(new_list(items))
+}
+
+// --- Tâches ---
+
+fn task_done(_: &mut VM, this: &Value, _: Vec<Value>) -> Result<Value, String> {
+    match this {
+        Value::Task(t) => Ok(Value::Boolean(t.borrow().state == TaskState::Done)),
+        _ => unreachable!("méthode de tâche appelée sur {}", this.type_name()),
+    }
+}
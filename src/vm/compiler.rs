@@ -2,11 +2,119 @@ use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 
-use crate::ast::value::{ClassData, FunctionData, InterfaceData};
-use crate::ast::{Instruction, Expression, Value};
+use crate::ast::value::{Capture, ClassData, EnumData, FunctionData, InterfaceData, Shape};
+use crate::ast::nodes::{Param, Params};
+use crate::ast::{Instruction, Expression, MatchArm, Pattern, Statement, Value};
 use crate::chunk::Chunk;
 use crate::opcode::OpCode;
 
+/// Index des globales dans `VM.globals`, dans les deux sens : nom -> id pour le compilateur,
+/// id -> nom pour les messages d'erreur et la résolution paresseuse des natives.
+#[derive(Debug, Default, Clone)]
+pub struct GlobalTable {
+    ids: HashMap<String, usize>,
+    names: Vec<String>,
+}
+
+impl GlobalTable {
+    /// Table qui commence par les fonctions natives (ids 0, 1, 2... dans l'ordre alphabétique).
+    pub fn with_natives() -> Self {
+        let mut table = GlobalTable::default();
+        for name in crate::native::get_all_names() {
+            table.resolve(&name);
+        }
+        table
+    }
+
+    /// Table relue telle quelle (bytecode compilé) : l'id de chaque nom est sa position.
+    pub fn from_names(names: Vec<String>) -> Self {
+        let ids = names.iter().enumerate().map(|(id, name)| (name.clone(), id)).collect();
+        GlobalTable { ids, names }
+    }
+
+    pub fn get(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.names.get(id).map(String::as_str)
+    }
+
+    /// Id de la globale `name`, ajoutée à la fin si elle est nouvelle.
+    pub fn resolve(&mut self, name: &str) -> usize {
+        if let Some(id) = self.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.ids.insert(name.to_string(), id);
+        self.names.push(name.to_string());
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+// Partagé par le compilateur, les modules importés et la VM
+pub type GlobalNames = Rc<RefCell<GlobalTable>>;
+
+/// Globale où un module range la liste de ses exports (voir Compiler::declare_exports).
+pub const EXPORTS: &str = "__exports__";
+
+/// Nom, dans la table des globales, de la variable `name` du module `module` :
+/// chaque module importé a ses propres globales, préfixées par son identité (voir crate::imports).
+pub fn scoped_name(module: &str, name: &str) -> String {
+    format!("{}::{}", module, name)
+}
+
+/// Nom tel qu'il est écrit dans le source, sans le préfixe du module (messages d'erreur, natives).
+pub fn plain_name(name: &str) -> &str {
+    name.rsplit_once("::").map_or(name, |(_, plain)| plain)
+}
+
+// Les noms `__x__` (comme __ARGS__) sont fournis par la VM et partagés par tous les modules
+fn is_shared(name: &str) -> bool {
+    name.len() > 4 && name.starts_with("__") && name.ends_with("__")
+}
+
+/// Noms qu'un module rend visibles à ceux qui l'importent : ses déclarations `export`,
+/// ou, s'il n'en a aucune, toutes ses déclarations de premier niveau dont le nom ne commence pas par `_`.
+pub fn module_exports(statements: &[Statement]) -> Vec<String> {
+    let explicit = statements.iter().any(|stmt| matches!(stmt.kind, Instruction::Export(_)));
+    let mut names: Vec<String> = Vec::new();
+    for stmt in statements {
+        let name = match &stmt.kind {
+            Instruction::Export(declaration) => declared_name(&declaration.kind),
+            kind if !explicit => declared_name(kind).filter(|name| !name.starts_with('_')),
+            _ => None,
+        };
+        if let Some(name) = name.filter(|name| !names.iter().any(|n| n == name)) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+fn declared_name(instr: &Instruction) -> Option<&str> {
+    match instr {
+        Instruction::Set(name, ..) | Instruction::Const(name, _) | Instruction::Function { name, .. }
+        | Instruction::Namespace { name, .. } => Some(name),
+        Instruction::Class(def) => Some(&def.name),
+        Instruction::Enum(def) => Some(&def.name),
+        Instruction::Interface(def) => Some(&def.name),
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub enum LoopState {
     While { 
@@ -23,15 +131,31 @@ pub enum LoopState {
     }
 }
 
+// Accès depuis la valeur testée par un `match` jusqu'à une sous-valeur du motif
+#[derive(Debug, Clone)]
+enum MatchStep {
+    Index(i64),
+    Rest(i64),
+    Key(String),
+    Field(usize)
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct LocalInfo {
-    index: u8,
+    index: usize,
     is_const: bool
 }
 
+// Ce qu'une fonction imbriquée voit de la fonction qui la contient, au moment où elle est compilée
+struct Enclosing {
+    locals: HashMap<String, LocalInfo>,
+    match_scopes: Vec<Vec<String>>,
+    upvalues: Rc<RefCell<Vec<Capture>>>,
+}
+
 pub struct Compiler {
     pub chunk: Chunk,
-    pub globals: Rc<RefCell<HashMap<String, u8>>>, 
+    pub globals: GlobalNames, 
     pub locals: HashMap<String, LocalInfo>,
     pub global_constants: Vec<String>,
     pub scope_depth: usize,
@@ -40,20 +164,32 @@ pub struct Compiler {
     pub loop_stack: Vec<LoopState>,
     pub context_parent_name: Option<String>,
     pub try_depth: usize,
+    // Variantes des enums connus, pour vérifier l'exhaustivité des `match`
+    pub enums: Rc<RefCell<HashMap<String, Vec<String>>>>,
+    // Variables liées par les `match` en cours (slot = position + 1, le slot 0 est la valeur testée)
+    match_scopes: Vec<Vec<String>>,
+    // Nom donné à la prochaine lambda compilée (var f = func..., @deco func f)
+    function_name: Option<String>,
+    // None hors d'une fonction, Some(true) dès que le corps contient un `yield`
+    generator: Option<bool>,
+    // `await` est permis au niveau du script et dans les fonctions `async`
+    awaitable: bool,
+    // Variables capturées par la fonction en cours (partagé avec ses fonctions imbriquées)
+    upvalues: Rc<RefCell<Vec<Capture>>>,
+    // Fonctions englobantes, de la plus externe à la plus proche
+    enclosing: Vec<Rc<Enclosing>>,
+    // Ce que le bytecode ne sait pas encoder (partagé avec les compilateurs imbriqués)
+    errors: Rc<RefCell<Vec<String>>>,
+    // Ids de globales toujours écrits sur quatre octets : le bytecode d'un module mis en cache
+    // peut ainsi être relié à la table d'une autre VM sans changer de taille (voir crate::bytecode)
+    pub relocatable: bool,
+    // Identité du module importé en cours de compilation (voir scoped_name), aucune pour le script principal
+    module: Option<Rc<str>>,
 }
 
 impl Compiler {
     pub fn new() -> Self {
-        let globals = Rc::new(RefCell::new(HashMap::new()));
-        let natives = crate::native::get_all_names();
-        
-        {
-            let mut g = globals.borrow_mut();
-            for (i, name) in natives.into_iter().enumerate() {
-                // On assigne les ID 0, 1, 2... dans l'ordre alphabétique
-                g.insert(name, i as u8);
-            }
-        }
+        let globals = Rc::new(RefCell::new(GlobalTable::with_natives()));
 
         Self {
             chunk: Chunk::new(),
@@ -65,11 +201,21 @@ impl Compiler {
             current_line: 1,
             loop_stack: Vec::new(),
             context_parent_name: None,
-            try_depth: 0
+            try_depth: 0,
+            enums: Rc::new(RefCell::new(HashMap::new())),
+            match_scopes: Vec::new(),
+            function_name: None,
+            generator: None,
+            awaitable: true,
+            upvalues: Rc::new(RefCell::new(Vec::new())),
+            enclosing: Vec::new(),
+            errors: Rc::new(RefCell::new(Vec::new())),
+            relocatable: false,
+            module: None,
         }
     }
 
-    pub fn new_with_globals(globals: Rc<RefCell<HashMap<String, u8>>>) -> Self {
+    pub fn new_with_globals(globals: GlobalNames) -> Self {
          Self {
             chunk: Chunk::new(),
             globals, 
@@ -80,18 +226,87 @@ impl Compiler {
             current_line: 1,
             loop_stack: Vec::new(),
             context_parent_name: None,
-            try_depth: 0
+            try_depth: 0,
+            enums: Rc::new(RefCell::new(HashMap::new())),
+            match_scopes: Vec::new(),
+            function_name: None,
+            generator: None,
+            awaitable: true,
+            upvalues: Rc::new(RefCell::new(Vec::new())),
+            enclosing: Vec::new(),
+            errors: Rc::new(RefCell::new(Vec::new())),
+            relocatable: false,
+            module: None,
         }
     }
 
-    pub fn compile(mut self, statements: Vec<crate::ast::Statement>) -> (Chunk, Rc<RefCell<HashMap<String, u8>>>) {
+    // Compilateur d'une fonction, d'une méthode ou d'un namespace imbriqué
+    fn nested(&self) -> Compiler {
+        let mut compiler = Compiler::new_with_globals(self.globals.clone());
+        compiler.enums = self.enums.clone();
+        compiler.errors = self.errors.clone();
+        compiler.relocatable = self.relocatable;
+        compiler.module = self.module.clone();
+        compiler
+    }
+
+    // Compilateur d'une fonction, d'une lambda ou d'un namespace : il peut capturer
+    // les variables de `self` et des fonctions qui l'englobent
+    fn closure_compiler(&self) -> Compiler {
+        let mut compiler = self.nested();
+        compiler.enclosing = self.enclosing.clone();
+        compiler.enclosing.push(Rc::new(Enclosing {
+            locals: self.locals.clone(),
+            match_scopes: self.match_scopes.clone(),
+            upvalues: self.upvalues.clone(),
+        }));
+        compiler
+    }
+
+    /// Compilateur d'un module importé : ses globales lui sont propres (voir scoped_name) et
+    /// son bytecode peut être mis en cache.
+    pub fn for_module(module: &str, globals: GlobalNames) -> Self {
+        let mut compiler = Compiler::new_with_globals(globals);
+        compiler.module = Some(module.into());
+        compiler.relocatable = true;
+        compiler
+    }
+
+    /// Range la liste des exports du module dans sa globale EXPORTS : la VM construit
+    /// l'objet module à partir de ces noms une fois le module exécuté.
+    pub fn declare_exports(&mut self, names: Vec<String>) {
+        let Some(module) = self.module.clone() else { return };
+        let names = names.into_iter().map(|name| Value::String(name.into())).collect();
+        self.emit_constant(Value::List(Rc::new(RefCell::new(names))));
+        let id = self.globals.borrow_mut().resolve(&scoped_name(&module, EXPORTS));
+        self.emit_instruction(OpCode::SetGlobal, &[id]);
+    }
+
+    pub fn compile(mut self, statements: Vec<crate::ast::Statement>) -> Result<(Chunk, GlobalNames), String> {
+        // Les enums peuvent être déclarés après les fonctions qui les utilisent
+        self.collect_enums(&statements, "");
+
         for stmt in statements {
-            self.current_line = stmt.line;
-            self.compile_instruction(stmt.kind);
+            self.compile_statement(stmt);
         }
-        (self.chunk, self.globals)
+        self.take_errors()?;
+        Ok((self.chunk, self.globals))
     } 
 
+    // Erreurs relevées depuis le dernier appel, réunies en un seul message
+    pub fn take_errors(&self) -> Result<(), String> {
+        let errors = std::mem::take(&mut *self.errors.borrow_mut());
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    fn error(&self, message: String) {
+        self.errors.borrow_mut().push(format!("[Line {}] Compile error: {}", self.current_line, message));
+    }
+
     fn emit_byte(&mut self, byte: u8) {
         self.chunk.write(byte, self.current_line);
     }
@@ -100,20 +315,69 @@ impl Compiler {
         self.emit_byte(op as u8);
     }
 
+    // Instruction avec opérandes d'index ou de compteur : un octet chacun, ou le préfixe Wide
+    // et quatre octets chacun dès que l'un d'eux dépasse 255
+    fn emit_instruction(&mut self, op: OpCode, operands: &[usize]) {
+        let global = matches!(op, OpCode::GetGlobal | OpCode::SetGlobal | OpCode::GetFreeGlobal);
+        if !(global && self.relocatable) && operands.iter().all(|&operand| operand <= u8::MAX as usize) {
+            self.emit_op(op);
+            for &operand in operands {
+                self.emit_byte(operand as u8);
+            }
+            return;
+        }
+
+        self.emit_op(OpCode::Wide);
+        self.emit_op(op);
+        for &operand in operands {
+            let Ok(wide) = u32::try_from(operand) else {
+                self.error(format!("operand {} of {:?} is too large to be encoded", operand, op));
+                return;
+            };
+            for byte in wide.to_be_bytes() {
+                self.emit_byte(byte);
+            }
+        }
+    }
+
     fn emit_constant(&mut self, val: Value) {
         let idx = self.chunk.add_constant(val);
-        self.emit_op(OpCode::LoadConst);
-        self.emit_byte(idx);
+        self.emit_instruction(OpCode::LoadConst, &[idx]);
     }
 
-    fn resolve_global(&mut self, name: &str) -> u8 {
-        let mut globals = self.globals.borrow_mut();
-        if let Some(&id) = globals.get(name) {
-            return id;
+    fn resolve_global(&mut self, name: &str) -> usize {
+        let name = self.global_name(name);
+        self.globals.borrow_mut().resolve(&name)
+    }
+
+    // Nom de la globale `name` dans la table : préfixé dans un module importé
+    fn global_name(&self, name: &str) -> String {
+        match &self.module {
+            Some(module) if !is_shared(name) => scoped_name(module, name),
+            _ => name.to_string(),
+        }
+    }
+
+    // Troisième opérande de OpCode::Import : le module qui contient l'import (null pour le script principal).
+    // Un import dans une fonction est résolu et lié depuis ce module, quel que soit l'appelant.
+    fn importer_constant(&mut self) -> usize {
+        let module = match &self.module {
+            Some(module) => Value::String(module.clone()),
+            None => Value::Null,
+        };
+        self.chunk.add_constant(module)
+    }
+
+    // La valeur au sommet de la pile devient la variable `name` : une locale dans une fonction
+    // ou un bloc, une globale sinon
+    fn define_variable(&mut self, name: String) {
+        if self.scope_depth > 0 {
+            let idx = self.locals.len();
+            self.locals.insert(name, LocalInfo { index: idx, is_const: false });
+        } else {
+            let id = self.resolve_global(&name);
+            self.emit_instruction(OpCode::SetGlobal, &[id]);
         }
-        let id = globals.len() as u8;
-        globals.insert(name.to_string(), id);
-        id
     }
 
     fn compile_expression(&mut self, expr: Expression) {
@@ -145,20 +409,25 @@ impl Compiler {
                 self.emit_op(OpCode::Div);
             },
             Expression::Variable(name) => {
+                // 0. Variable liée par un motif de `match`
+                if let Some((level, slot)) = self.resolve_match_binding(&name) {
+                    self.emit_instruction(OpCode::GetMatch, &[level, slot]);
+                }
                 // 1. On cherche d'abord dans les locales (si on est dans une fonction)
-                if let Some(info) = self.locals.get(&name) {
+                else if let Some(info) = self.locals.get(&name) {
                     let idx = info.index;
-                    self.emit_op(OpCode::GetLocal);
-                    self.emit_byte(idx);
+                    self.emit_instruction(OpCode::GetLocal, &[idx]);
+                }
+                // 2. Puis dans les fonctions englobantes (closure)
+                else if let Some((idx, _)) = self.resolve_upvalue(&name) {
+                    self.emit_instruction(OpCode::GetUpvalue, &[idx]);
                 } else {
                     if self.scope_depth > 0 {
-                        let name_idx = self.chunk.add_constant(Value::String(name.clone()));
-                        self.emit_op(OpCode::GetFreeVar);
-                        self.emit_byte(name_idx);
+                        let id = self.resolve_global(&name);
+                        self.emit_instruction(OpCode::GetFreeGlobal, &[id]);
                     } else {
                         let id = self.resolve_global(&name);
-                        self.emit_op(OpCode::GetGlobal);
-                        self.emit_byte(id);
+                        self.emit_instruction(OpCode::GetGlobal, &[id]);
                     }
                 }
             },
@@ -178,22 +447,16 @@ impl Compiler {
                 self.emit_op(OpCode::Equal);
             },
             Expression::Call(target, args) => {
-                // 1. On sauvegarde la taille (nécessaire pour le borrow checker)
-                let arg_count = args.len(); 
-    
                 // A. D'abord on compile la fonction (pour qu'elle soit au fond de la pile)
                 self.compile_expression(*target);
 
                 // B. Ensuite on compile les arguments (qui s'empilent par-dessus)
-                for arg in args {
-                    self.compile_expression(arg);
-                }
+                let arg_count = self.compile_arguments(args);
     
                 // ----------------------------------
     
                 // 4. Émettre CALL
-                self.emit_op(OpCode::Call);
-                self.emit_byte(arg_count as u8);
+                self.emit_instruction(OpCode::Call, &[arg_count]);
             }
 
             Expression::Modulo(left, right) => {
@@ -246,6 +509,14 @@ impl Compiler {
                 self.compile_expression(*expr);
                 self.emit_op(OpCode::Not);
             },
+            Expression::Await(expr) => {
+                self.compile_expression(*expr);
+                if self.awaitable {
+                    self.emit_op(OpCode::Await);
+                } else {
+                    self.error("'await' is only allowed in an async function".into());
+                }
+            },
 
             Expression::And(left, right) => {
                 self.compile_expression(*left);
@@ -301,8 +572,7 @@ impl Compiler {
                 
                 // 3. Charger Null et Comparer
                 let null_idx = self.chunk.add_constant(Value::Null);
-                self.emit_op(OpCode::LoadConst);
-                self.emit_byte(null_idx);       // Pile: [val, val, null]
+                self.emit_instruction(OpCode::LoadConst, &[null_idx]); // Pile: [val, val, null]
                 self.emit_op(OpCode::Equal);    // Pile: [val, is_null]
                 
                 // 4. Si c'est FAUX (donc pas null), on saute le bloc "Remplacement"
@@ -337,127 +607,101 @@ impl Compiler {
                 for expr in exprs.iter() {
                     self.compile_expression(expr.clone());
                 }
-                self.emit_op(OpCode::MakeList);
-                self.emit_byte(exprs.len() as u8);
+                self.emit_instruction(OpCode::MakeList, &[exprs.len()]);
             },
             Expression::Dict(items) => {
                 let count = items.len(); // Sauvegarde avant consommation
 
                 for (key, val) in items {
-                    let key_idx = self.chunk.add_constant(Value::String(key.clone()));
-                    self.emit_op(OpCode::LoadConst);
-                    self.emit_byte(key_idx);
+                    let key_idx = self.chunk.add_constant(Value::String(key.as_str().into()));
+                    self.emit_instruction(OpCode::LoadConst, &[key_idx]);
                     self.compile_expression(val.clone());
                 }
-                self.emit_op(OpCode::MakeDict);
-                self.emit_byte((count * 2) as u8); // Utilisation de la variable sauvegardée
+                self.emit_instruction(OpCode::MakeDict, &[count * 2]); // Utilisation de la variable sauvegardée
             },
 
             Expression::GetAttr(obj, name) => {
                 self.compile_expression(*obj);
-                let name_idx = self.chunk.add_constant(Value::String(name));
-                self.emit_op(OpCode::GetAttr);
-                self.emit_byte(name_idx);
+                let name_idx = self.chunk.add_constant(Value::String(name.into()));
+                let cache_idx = self.chunk.add_cache();
+                self.emit_instruction(OpCode::GetAttr, &[name_idx, cache_idx]);
             },
             Expression::CallMethod(obj, name, args) => {
-                let arg_count = args.len(); // Sauvegarde
-
                 // 1. Compiler l'objet
                 self.compile_expression(*obj);
                 
                 // 2. Compiler les arguments
-                for arg in args {
-                    self.compile_expression(arg.clone());
-                }
+                let arg_count = self.compile_arguments(args);
                 
                 // 3. Émettre l'instruction
-                let name_idx = self.chunk.add_constant(Value::String(name));
-                self.emit_op(OpCode::Method);
-                self.emit_byte(name_idx);
-                self.emit_byte(arg_count as u8); // Utilisation
+                let name_idx = self.chunk.add_constant(Value::String(name.into()));
+                let cache_idx = self.chunk.add_cache();
+                self.emit_instruction(OpCode::Method, &[name_idx, arg_count, cache_idx]);
             },
             Expression::New(class_expr, args) => {
-                let arg_count = args.len(); // Sauvegarde
-
                 self.compile_expression(*class_expr);
                 
-                for arg in args {
-                    self.compile_expression(arg.clone());
-                }
+                let arg_count = self.compile_arguments(args);
                 
-                self.emit_op(OpCode::Call); // Ou OpCode::New si tu en as créé un
-                self.emit_byte(arg_count as u8); // Utilisation
+                self.emit_instruction(OpCode::Call, &[arg_count]); // Ou OpCode::New si tu en as créé un
             },
 
             Expression::SuperCall(method, args) => {
                 // 1. Vérification : Est-on dans une classe enfant ?
                 let parent_name = if let Some(p) = &self.context_parent_name {
-                    p.clone()
+                    self.global_name(p)
                 } else {
                     panic!("'super' utilisé hors d'une classe avec héritage.");
                 };
 
                 // 2. On empile 'this' (toujours l'argument 0 d'une méthode)
-                self.emit_op(OpCode::GetLocal);
-                self.emit_byte(0);
+                self.emit_instruction(OpCode::GetLocal, &[0]);
 
                 // 3. On empile les arguments
-                let arg_count = args.len();
-                for arg in args {
-                    self.compile_expression(arg);
-                }
+                let arg_count = self.compile_arguments(args);
 
                 // 4. On émet l'instruction SUPER
-                let name_idx = self.chunk.add_constant(Value::String(method));
-                let parent_idx = self.chunk.add_constant(Value::String(parent_name));
+                let name_idx = self.chunk.add_constant(Value::String(method.into()));
+                let parent_idx = self.chunk.add_constant(Value::String(parent_name.into()));
 
-                self.emit_op(OpCode::Super);
-                self.emit_byte(name_idx);
-                self.emit_byte(arg_count as u8);
-                self.emit_byte(parent_idx);
+                self.emit_instruction(OpCode::Super, &[name_idx, arg_count, parent_idx]);
             },
 
-            Expression::Function { params, ret_type, body } => {
-                let mut func_compiler = Compiler::new_with_globals(self.globals.clone());
-                func_compiler.scope_depth = 1;
-
-                for (i, (param_name, _)) in params.iter().enumerate() {
-                    func_compiler.locals.insert(param_name.clone(), LocalInfo {
-                        index: i as u8,
-                        is_const: false
-                    });
-                }
-                for stmt in body {
-                    func_compiler.compile_instruction(stmt.kind);
-                }
-                func_compiler.emit_op(OpCode::LoadConst);
-                let null_idx = func_compiler.chunk.add_constant(Value::Null);
-                func_compiler.emit_byte(null_idx);
-                func_compiler.emit_op(OpCode::Return);
-
-                for (name, info) in &func_compiler.locals {
-                    func_compiler.chunk.locals_map.insert(info.index, name.clone());
-                }
-
-                let func_chunk = func_compiler.chunk;
-                let compiled_val = Value::Function(Rc::new(FunctionData {
-                    params: params.clone(),
-                    ret_type: ret_type.clone(),
-                    chunk: func_chunk,
-                    env: None
-                }));
+            Expression::Function { params, ret_type, body, is_async } => {
+                let name = self.function_name.take().unwrap_or_default();
+                let compiled_val = Value::Function(Rc::new(self.compile_function(name, params, ret_type, body, is_async)));
                 let const_idx = self.chunk.add_constant(compiled_val);
 
-                self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
+                self.emit_instruction(OpCode::LoadConst, &[const_idx]);
 
                 self.emit_op(OpCode::MakeClosure);
             },
+            Expression::Match(subject, arms) => self.compile_match(*subject, arms),
+            // Possibles seulement depuis un AST JSON : le parseur ne les produit que dans un appel
+            Expression::NamedArg(name, value) => {
+                self.error(format!("named argument '{}' used outside of a call", name));
+                self.compile_expression(*value);
+            },
+            Expression::Spread(list) => {
+                self.error("'...' used outside of a call".into());
+                self.compile_expression(*list);
+            },
             Expression::Range(start, end) => {
                 self.compile_expression(*start);
                 self.compile_expression(*end);
                 self.emit_op(OpCode::MakeRange);
             },
+            Expression::Index(obj, key) => {
+                self.compile_expression(*obj);
+                self.compile_expression(*key);
+                self.emit_op(OpCode::GetIndex);
+            },
+            Expression::Slice(obj, start, end) => {
+                self.compile_expression(*obj);
+                self.compile_expression(*start);
+                self.compile_expression(*end);
+                self.emit_op(OpCode::Slice);
+            },
         }
     }
 
@@ -471,44 +715,62 @@ impl Compiler {
                 self.compile_expression(expr); // 1. Calcule la valeur de retour
 
                 if let Some(ret_type) = &self.current_return_type {
-                    let type_idx = self.chunk.add_constant(Value::String(ret_type.clone()));
-                    self.emit_op(OpCode::CheckType);
-                    self.emit_byte(type_idx);
+                    let type_idx = self.chunk.add_constant(Value::String(ret_type.as_str().into()));
+                    self.emit_instruction(OpCode::CheckType, &[type_idx]);
                 }
 
                 self.emit_op(OpCode::Return);  // 2. Quitte la fonction
             },
+            Instruction::Yield(expr) => {
+                if self.generator.is_none() {
+                    self.error("'yield' used outside of a function".into());
+                } else if self.awaitable {
+                    self.error("'yield' cannot be used in an async function".into());
+                } else {
+                    self.generator = Some(true);
+                }
+                self.compile_expression(expr);
+                self.emit_op(OpCode::Yield);
+            },
             Instruction::Set(var_name, type_annot, expr) => {
                 // A. Check Locals
                 if let Some(info) = self.locals.get(&var_name) {
                     if info.is_const {
-                        panic!("Erreur: Impossible de modifier la constante locale '{}'", var_name);
+                        self.error(format!("cannot assign to local constant '{}'", var_name));
                     }
                 }
                 
                 // B. Check Globals (Scope courant)
                 if self.global_constants.contains(&var_name) {
-                    panic!("Erreur: Impossible de modifier la constante globale '{}'", var_name);
+                    self.error(format!("cannot assign to global constant '{}'", var_name));
                 }
 
+                self.function_name = Self::lambda_name(&var_name, &expr);
                 self.compile_expression(expr); // La valeur calculée est maintenant sur la pile [val]
+                self.function_name = None;
 
                 if let Some(type_name) = type_annot {
-                    let type_idx = self.chunk.add_constant(Value::String(type_name));
-                    self.emit_op(OpCode::CheckType);
-                    self.emit_byte(type_idx);
+                    let type_idx = self.chunk.add_constant(Value::String(type_name.into()));
+                    self.emit_instruction(OpCode::CheckType, &[type_idx]);
                 }
 
                 // CAS 1 : C'est une variable locale DÉJÀ connue (Assignation : x = 5)
                 if let Some(info) = self.locals.get(&var_name) {
                     let idx = info.index;
-                    self.emit_op(OpCode::SetLocal);
-                    self.emit_byte(idx);
+                    self.emit_instruction(OpCode::SetLocal, &[idx]);
                     self.emit_op(OpCode::Pop); // Nettoyage : On retire la valeur car c'est une instruction (statement)
+                }
+                // CAS 1 bis : Variable d'une fonction englobante (closure) : on modifie la variable partagée
+                else if let Some((idx, is_const)) = self.resolve_upvalue(&var_name) {
+                    if is_const {
+                        self.error(format!("cannot assign to constant '{}' captured from an enclosing function", var_name));
+                    }
+                    self.emit_instruction(OpCode::SetUpvalue, &[idx]);
+                    self.emit_op(OpCode::Pop);
                 } 
                 // CAS 2 : On est dans une fonction, c'est une NOUVELLE variable (Déclaration : var res = ...)
                 else if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8; // Le prochain slot libre sur la pile
+                    let idx = self.locals.len(); // Le prochain slot libre sur la pile
                     self.locals.insert(var_name.clone(), LocalInfo {
                         index: idx,
                         is_const: false
@@ -523,8 +785,7 @@ impl Compiler {
                 // CAS 3 : C'est une Globale (Assignation ou Déclaration globale)
                 else {
                     let id = self.resolve_global(&var_name);
-                    self.emit_op(OpCode::SetGlobal); // SetGlobal fait déjà un Pop dans la VM
-                    self.emit_byte(id);
+                    self.emit_instruction(OpCode::SetGlobal, &[id]); // SetGlobal fait déjà un Pop dans la VM
                 }
             },
 
@@ -536,79 +797,36 @@ impl Compiler {
                 self.compile_while(condition, body);
             },
             
-            Instruction::Function { name, params, ret_type, body } => {
-                // 1. Compilation du corps de la fonction (Inchangé)
-                let mut func_compiler = Compiler::new_with_globals(self.globals.clone());
-                func_compiler.scope_depth = 1;
-
-                for (i, (param_name, param_type)) in params.iter().enumerate() {
-                    func_compiler.locals.insert(param_name.clone(), LocalInfo {
-                        index: i as u8,
+            Instruction::Function { name, params, ret_type, body, is_async } => {
+                // 0. Dans une fonction ou un namespace, le nom est une locale déclarée avant le corps :
+                // la fonction peut s'appeler elle-même (upvalue vers son propre slot)
+                if self.scope_depth > 0 {
+                    let idx = self.locals.len();
+                    self.locals.insert(name.clone(), LocalInfo {
+                        index: idx,
                         is_const: false
                     });
-
-                    if let Some(t) = param_type {
-                        // Au début de la fonction, les arguments sont déjà sur la pile (locales).
-                        // On doit les charger, les checker, et les poper (juste pour le check).
-                        
-                        // 1. Lire la variable locale
-                        func_compiler.emit_op(OpCode::GetLocal);
-                        func_compiler.emit_byte(i as u8);
-                        
-                        // 2. Checker
-                        let type_idx = func_compiler.chunk.add_constant(Value::String(t.clone()));
-                        func_compiler.emit_op(OpCode::CheckType);
-                        func_compiler.emit_byte(type_idx);
-                        
-                        // 3. Nettoyer la pile (on a dupliqué via GetLocal)
-                        func_compiler.emit_op(OpCode::Pop);
-                    }
-                }
-
-                for stmt in body {
-                    func_compiler.compile_instruction(stmt.kind);
                 }
 
-                func_compiler.emit_op(OpCode::LoadConst);
-                let null_idx = func_compiler.chunk.add_constant(Value::Null);
-                func_compiler.emit_byte(null_idx);
-                func_compiler.emit_op(OpCode::Return);
-
-                for (name, info) in &func_compiler.locals {
-                    func_compiler.chunk.locals_map.insert(info.index, name.clone());
-                }
-
-                let func_chunk = func_compiler.chunk;
-                let compiled_val = Value::Function(Rc::new(FunctionData {
-                    params: params.clone(),
-                    ret_type: ret_type.clone(),
-                    chunk: func_chunk,
-                    env: None
-                }));
+                // 1. Compilation du corps de la fonction
+                let compiled_val = Value::Function(Rc::new(self.compile_function(name.clone(), params, ret_type, body, is_async)));
 
                 // 2. Chargement de la fonction sur la pile (Inchangé)
                 let const_idx = self.chunk.add_constant(compiled_val);
-                self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
+                self.emit_instruction(OpCode::LoadConst, &[const_idx]);
                 
-                // On la transforme en closure (pour capturer l'env si besoin)
+                // On la transforme en closure (pour capturer les variables des fonctions englobantes)
                 self.emit_op(OpCode::MakeClosure);
 
                 // 3. --- MODIFICATION : Stockage (Global ou Local) ---
                 if self.scope_depth > 0 {
-                    // Cas Namespace ou Fonction imbriquée : C'est une locale
-                    let idx = self.locals.len() as u8;
-                    self.locals.insert(name.clone(), LocalInfo {
-                        index: idx,
-                        is_const: false
-                    });
+                    // Cas Namespace ou Fonction imbriquée : la locale déclarée en 0.
                     // La fonction est déjà sur la pile, elle devient la variable locale 'name'.
                     // On ne fait rien d'autre (comme pour SetLocal implicite).
                 } else {
                     // Cas Script Principal : C'est une globale
                     let global_id = self.resolve_global(&name);
-                    self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(global_id);
+                    self.emit_instruction(OpCode::SetGlobal, &[global_id]);
                 }
             },
 
@@ -655,8 +873,7 @@ impl Compiler {
                 self.emit_op(OpCode::Input); // VM devra gérer l'affichage + lecture
                 // Le résultat de Input est sur la pile, on le stocke
                 let id = self.resolve_global(&var_name); // Ou local
-                self.emit_op(OpCode::SetGlobal);
-                self.emit_byte(id);
+                self.emit_instruction(OpCode::SetGlobal, &[id]);
             },
 
             Instruction::Interface(def) => {
@@ -676,12 +893,10 @@ impl Compiler {
                 let const_idx = self.chunk.add_constant(interface_val);
                 
                 // On utilise LoadConst + SetGlobal pour définir l'interface
-                self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
+                self.emit_instruction(OpCode::LoadConst, &[const_idx]);
                 
                 let global_id = self.resolve_global(&def.name);
-                self.emit_op(OpCode::SetGlobal);
-                self.emit_byte(global_id);
+                self.emit_instruction(OpCode::SetGlobal, &[global_id]);
             },
 
             Instruction::Class(def) => {
@@ -692,62 +907,8 @@ impl Compiler {
 
                 let mut final_methods_set = std::collections::HashSet::new();
 
-                for (m_name, (m_params, m_body, is_static, is_final)) in def.methods {
-                    // Chaque méthode a son propre compilateur (scope isolé)
-                    let mut method_compiler = Compiler::new_with_globals(self.globals.clone());
-                    method_compiler.scope_depth = 1;
-                    
-                    // On transmet le nom du parent (utile pour 'super' qui vérifie context_parent_name)
-                    method_compiler.context_parent_name = def.parent.clone();
-                    
-                    // A. Paramètres de la méthode
-                    // Convention : 'this' est toujours le paramètre implicite 0
-                    let mut actual_params = vec![("this".to_string(), None)];
-                    actual_params.extend(m_params.clone());
-
-                    for (i, (param_name, param_type)) in actual_params.iter().enumerate() {
-                        method_compiler.locals.insert(param_name.clone(), LocalInfo {
-                            index: i as u8,
-                            is_const: false
-                        });
-                        
-                        // Si le paramètre est typé, on ajoute une vérification runtime au début du bytecode
-                        if let Some(t) = param_type {
-                            method_compiler.emit_op(OpCode::GetLocal);
-                            method_compiler.emit_byte(i as u8);
-                            
-                            let type_idx = method_compiler.chunk.add_constant(Value::String(t.clone()));
-                            method_compiler.emit_op(OpCode::CheckType);
-                            method_compiler.emit_byte(type_idx);
-                            
-                            method_compiler.emit_op(OpCode::Pop); // Nettoyage après check
-                        }
-                    }
-
-                    // B. Corps de la méthode
-                    for stmt in m_body {
-                        method_compiler.compile_instruction(stmt.kind);
-                    }
-                    
-                    // C. Retour implicite (Null) si on arrive au bout
-                    method_compiler.emit_op(OpCode::LoadConst);
-                    let null_idx = method_compiler.chunk.add_constant(Value::Null);
-                    method_compiler.emit_byte(null_idx);
-                    method_compiler.emit_op(OpCode::Return);
-
-                    // D. Debug info pour les variables locales
-                    for (name, info) in &method_compiler.locals {
-                        method_compiler.chunk.locals_map.insert(info.index, name.clone());
-                    }
-
-                    // E. Création de la Value::Function
-                    let method_val = Value::Function(Rc::new(FunctionData {
-                        params: actual_params, // On garde la signature complète
-                        ret_type: None, // Tu pourrais ajouter le support du type de retour ici
-                        chunk: method_compiler.chunk,
-                        env: None, // Les méthodes ne capturent pas l'environnement extérieur (pas des closures)
-                        // Note : owner_class sera rempli par la VM ou est implicite via le CallFrame
-                    }));
+                for (m_name, (m_params, m_body, is_static, is_final, is_async)) in def.methods {
+                    let method_val = self.compile_method(format!("{}.{}", def.name, m_name), m_params, m_body, def.parent.clone(), is_async);
 
                     if is_final {
                         final_methods_set.insert(m_name.clone());
@@ -778,7 +939,7 @@ impl Compiler {
                     }
 
                     // On compile l'expression par défaut dans un contexte isolé
-                    let mut field_compiler = Compiler::new_with_globals(self.globals.clone());
+                    let mut field_compiler = self.nested();
                     // Pas de scope depth particulier, c'est comme une fonction statique
                     
                     // On compile l'expression (ex: "10 + 5")
@@ -789,10 +950,16 @@ impl Compiler {
                     
                     // On emballe dans une Value::Function
                     let init_func = Value::Function(Rc::new(FunctionData {
+                        name: format!("{}.{}", def.name, field.name),
                         params: vec![], // 0 arguments
+                        required: 0,
+                        variadic: false,
                         ret_type: None,
-                        chunk: field_compiler.chunk,
-                        env: None,
+                        is_generator: false,
+                        is_async: false,
+                        chunk: Rc::new(field_compiler.chunk),
+                        captures: Vec::new(),
+                        upvalues: Vec::new(),
                     }));
                     
                     if field.is_static {
@@ -810,62 +977,14 @@ impl Compiler {
                     let mut comp_getter = None;
                     let mut comp_setter = None;
 
-                    // A. Compile Getter
+                    // A. Compile Getter (seul paramètre : 'this')
                     if let Some((_, body)) = prop.getter {
-                        let mut c = Compiler::new_with_globals(self.globals.clone());
-                        c.scope_depth = 1;
-                        c.context_parent_name = def.parent.clone();
-                        
-                        // Param 'this' implicite
-                        c.locals.insert("this".to_string(), LocalInfo { index: 0, is_const: false });
-                        
-                        for stmt in body { c.compile_instruction(stmt.kind); }
-                        
-                        // Retour par défaut (Null) si pas de return explicite
-                        c.emit_op(OpCode::LoadConst);
-                        let null_idx = c.chunk.add_constant(Value::Null);
-                        c.emit_byte(null_idx);
-                        c.emit_op(OpCode::Return);
-                        
-                        comp_getter = Some(Value::Function(Rc::new(FunctionData {
-                            params: vec![("this".to_string(), None)],
-                            ret_type: None,
-                            chunk: c.chunk,
-                            env: None
-                        })));
+                        comp_getter = Some(self.compile_method(format!("{}.{}", def.name, prop.name), Vec::new(), body, def.parent.clone(), false));
                     }
 
-                    // B. Compile Setter
+                    // B. Compile Setter (params : 0=this, 1=value)
                     if let Some((params, body)) = prop.setter {
-                        let mut c = Compiler::new_with_globals(self.globals.clone());
-                        c.scope_depth = 1;
-                        c.context_parent_name = def.parent.clone();
-                        
-                        // Params: 0=this, 1=value
-                        c.locals.insert("this".to_string(), LocalInfo { index: 0, is_const: false });
-                        
-                        // On récupère le nom de l'argument du setter (ex: "val")
-                        if let Some((p_name, _)) = params.first() {
-                            c.locals.insert(p_name.clone(), LocalInfo { index: 1, is_const: false });
-                        }
-
-                        for stmt in body { c.compile_instruction(stmt.kind); }
-                        
-                        c.emit_op(OpCode::LoadConst);
-                        let null_idx = c.chunk.add_constant(Value::Null);
-                        c.emit_byte(null_idx);
-                        c.emit_op(OpCode::Return);
-
-                        // Signature de la fonction pour la VM
-                        let mut final_params = vec![("this".to_string(), None)];
-                        final_params.extend(params);
-
-                        comp_setter = Some(Value::Function(Rc::new(FunctionData {
-                            params: final_params,
-                            ret_type: None,
-                            chunk: c.chunk,
-                            env: None
-                        })));
+                        comp_setter = Some(self.compile_method(format!("{}.{}", def.name, prop.name), params, body, def.parent.clone(), false));
                     }
                     
                     let prop_data = crate::ast::value::PropertyData { 
@@ -884,7 +1003,7 @@ impl Compiler {
                 // On utilise la nouvelle structure ClassData enrichie
                 let class_val = Value::Class(Rc::new(ClassData {
                     name: def.name.clone(),
-                    parent: def.parent.clone(),
+                    parent: def.parent.as_deref().map(|parent| self.global_name(parent)),
                     parent_ref: None, // Sera résolu par la VM via OpCode::Class
                     
                     methods: compiled_methods,
@@ -901,10 +1020,11 @@ impl Compiler {
                     final_methods: final_methods_set,
 
                     interfaces: Vec::new(),
-                    interfaces_names: def.interfaces,
+                    interfaces_names: def.interfaces.iter().map(|name| self.global_name(name)).collect(),
                     
                     // Nouveaux champs v0.3.0
                     visibilities: def.visibilities, // HashMap<String, Visibility>
+                    shape: Shape::root(),
                 }));
 
                 // Hack: On injecte les initialiseurs statiques dans static_fields pour le transport
@@ -915,13 +1035,12 @@ impl Compiler {
 
                 // 4. ÉMISSION DU BYTECODE DE CRÉATION
                 let const_idx = self.chunk.add_constant(class_val);
-                self.emit_op(OpCode::Class); // Instruction spéciale qui résout parent_ref
-                self.emit_byte(const_idx);
+                self.emit_instruction(OpCode::Class, &[const_idx]); // Instruction spéciale qui résout parent_ref
                 
                 // 5. ENREGISTREMENT (Global ou Local)
                 // Par défaut, les classes sont souvent globales, mais Aegis permet des classes locales
                 if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8;
+                    let idx = self.locals.len();
                     self.locals.insert(def.name.clone(), LocalInfo {
                         index: idx,
                         is_const: false
@@ -930,8 +1049,7 @@ impl Compiler {
                     // SetLocal implicite (comme pour Function)
                 } else {
                     let global_id = self.resolve_global(&def.name);
-                    self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(global_id);
+                    self.emit_instruction(OpCode::SetGlobal, &[global_id]);
                 }
             },
 
@@ -939,14 +1057,33 @@ impl Compiler {
                 self.compile_expression(*obj); // 1. L'objet
                 self.compile_expression(val);  // 2. La valeur
                 
-                let name_idx = self.chunk.add_constant(Value::String(attr));
-                self.emit_op(OpCode::SetAttr);
-                self.emit_byte(name_idx);
+                let name_idx = self.chunk.add_constant(Value::String(attr.into()));
+                let cache_idx = self.chunk.add_cache();
+                self.emit_instruction(OpCode::SetAttr, &[name_idx, cache_idx]);
                 // SetAttr laisse généralement la valeur sur la pile (comme une assignation),
                 // mais comme c'est une instruction ici, on POP pour nettoyer.
                 self.emit_op(OpCode::Pop); 
             },
 
+            Instruction::SetIndex(obj, key, val) => {
+                // x[k] op= v (le parseur en fait x[k] = x[k] op v) : l'objet et la clé ne sont évalués qu'une fois
+                if let Some((op, operand)) = compound_index(&obj, &key, &val) {
+                    self.compile_expression(*obj);
+                    self.compile_expression(key);
+                    self.emit_op(OpCode::Dup2);      // Pile: [obj, key, obj, key]
+                    self.emit_op(OpCode::GetIndex);  // Pile: [obj, key, x[k]]
+                    self.compile_expression(operand);
+                    self.emit_op(op);                // Pile: [obj, key, x[k] op v]
+                } else {
+                    self.compile_expression(*obj);
+                    self.compile_expression(key);
+                    self.compile_expression(val);
+                }
+                self.emit_op(OpCode::SetIndex);
+                // Comme SetAttr : la valeur reste sur la pile
+                self.emit_op(OpCode::Pop);
+            },
+
             Instruction::TryCatch { try_body, error_var, catch_body } => {
                 // 1. Setup Exception Handler
                 let catch_jump = self.emit_jump(OpCode::SetupExcept);
@@ -967,7 +1104,7 @@ impl Compiler {
                 self.scope_depth += 1;
                 
                 // On déclare que la variable 'e' existe et qu'elle est située au sommet actuel de la pile.
-                let catch_var_idx = self.locals.len() as u8;
+                let catch_var_idx = self.locals.len();
                 self.locals.insert(error_var.clone(), LocalInfo {
                     index: catch_var_idx,
                     is_const: true
@@ -982,7 +1119,7 @@ impl Compiler {
                 
                 // 6. Cleanup (OPTIONNEL MAIS RECOMMANDÉ)
                 // À la fin du catch, on retire la variable 'e' de la pile pour revenir à l'état propre
-                self.emit_op(OpCode::Pop); 
+                self.emit_op(OpCode::CloseUpvalue); 
                 
                 self.locals.remove(&error_var);
                 self.scope_depth -= 1;
@@ -1008,7 +1145,7 @@ impl Compiler {
                 };
 
                 let local_idx = if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8;
+                    let idx = self.locals.len();
                     // On "réserve" le slot local. Attention: la valeur n'y est pas encore !
                     // Mais cela permet à 'resolve_local' de savoir que la variable existe.
                     self.locals.insert(name.clone(), LocalInfo {
@@ -1021,30 +1158,27 @@ impl Compiler {
                 };
 
                 // 2. COMPILATION DU CORPS (IIFE Pattern)
-                let mut ns_compiler = Compiler::new_with_globals(self.globals.clone());
+                let mut ns_compiler = self.closure_compiler();
                 ns_compiler.scope_depth = 1; 
 
                 for stmt in body {
-                    ns_compiler.compile_instruction(stmt.kind);
+                    ns_compiler.compile_statement(stmt);
                 }
 
                 // 3. CONSTRUCTION DU DICTIONNAIRE (Exports)
-                let exports: Vec<(String, u8)> = ns_compiler.locals.iter()
+                let exports: Vec<(String, usize)> = ns_compiler.locals.iter()
                     .map(|(k, info)| (k.clone(), info.index))
                     .collect();
                 
                 let count = exports.len();
 
                 for (var_name, slot_idx) in exports {
-                    let key_idx = ns_compiler.chunk.add_constant(Value::String(var_name));
-                    ns_compiler.emit_op(OpCode::LoadConst);
-                    ns_compiler.emit_byte(key_idx);
-                    ns_compiler.emit_op(OpCode::GetLocal);
-                    ns_compiler.emit_byte(slot_idx);
+                    let key_idx = ns_compiler.chunk.add_constant(Value::String(var_name.into()));
+                    ns_compiler.emit_instruction(OpCode::LoadConst, &[key_idx]);
+                    ns_compiler.emit_instruction(OpCode::GetLocal, &[slot_idx]);
                 }
 
-                ns_compiler.emit_op(OpCode::MakeDict);
-                ns_compiler.emit_byte((count * 2) as u8);
+                ns_compiler.emit_instruction(OpCode::MakeDict, &[count * 2]);
                 ns_compiler.emit_op(OpCode::Return);
 
                 for (name, info) in &ns_compiler.locals {
@@ -1054,43 +1188,90 @@ impl Compiler {
                 // 4. EMBALLAGE (Closure)
                 let ns_chunk = ns_compiler.chunk;
                 let ns_func = Value::Function(Rc::new(FunctionData {
+                    name: name.clone(),
                     params: vec![],
+                    required: 0,
+                    variadic: false,
                     ret_type: None,
-                    chunk: ns_chunk,
-                    env: None
+                    is_generator: false,
+                    is_async: false,
+                    chunk: Rc::new(ns_chunk),
+                    captures: ns_compiler.upvalues.take(),
+                    upvalues: Vec::new()
                 }));
                 
                 let const_idx = self.chunk.add_constant(ns_func);
-                self.emit_op(OpCode::LoadConst);
-                self.emit_byte(const_idx);
+                self.emit_instruction(OpCode::LoadConst, &[const_idx]);
                 self.emit_op(OpCode::MakeClosure);
 
-                self.emit_op(OpCode::Call);
-                self.emit_byte(0);
+                self.emit_instruction(OpCode::Call, &[0]);
 
                 // 5. STOCKAGE FINAL
                 // On utilise les ID calculés à l'étape 1
                 if let Some(id) = global_id {
-                    self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(id);
+                    self.emit_instruction(OpCode::SetGlobal, &[id]);
                 } else if let Some(idx) = local_idx {
                     // Pour une locale, la valeur est maintenant sur le sommet de la pile.
                     // SetLocal la copie dans le slot réservé.
-                    self.emit_op(OpCode::SetLocal);
-                    self.emit_byte(idx);
+                    self.emit_instruction(OpCode::SetLocal, &[idx]);
                     // Namespace est une instruction, pas une expression, donc on pop le résultat de la pile
                     // (La valeur est maintenant en sécurité dans la variable locale)
                     self.emit_op(OpCode::Pop); 
                 }
             },
 
-            Instruction::Import(path) => {
+            Instruction::Import(path, alias) => {
                 // Store the path as a constant string
-                let path_idx = self.chunk.add_constant(Value::String(path));
-                
-                // Emit the IMPORT opcode
-                self.emit_op(OpCode::Import);
-                self.emit_byte(path_idx);
+                let path_idx = self.chunk.add_constant(Value::String(path.into()));
+
+                // Second operand: Null binds every export in our scope, a list of names returns the module object
+                let mode = match alias {
+                    None => Value::Null,
+                    Some(_) => Value::List(Rc::new(RefCell::new(Vec::new()))),
+                };
+                let mode_idx = self.chunk.add_constant(mode);
+                let module_idx = self.importer_constant();
+
+                // Emit the IMPORT opcode (it leaves a value on the stack: Null or the module object)
+                self.emit_instruction(OpCode::Import, &[path_idx, mode_idx, module_idx]);
+                match alias {
+                    Some(alias) => self.define_variable(alias),
+                    None => self.emit_op(OpCode::Pop),
+                }
+            },
+
+            Instruction::ImportFrom(path, names) => {
+                let path_idx = self.chunk.add_constant(Value::String(path.into()));
+                // The VM checks that the module exports each of these names
+                let wanted = names.iter().map(|name| Value::String(name.as_str().into())).collect();
+                let mode_idx = self.chunk.add_constant(Value::List(Rc::new(RefCell::new(wanted))));
+                let module_idx = self.importer_constant();
+                self.emit_instruction(OpCode::Import, &[path_idx, mode_idx, module_idx]);
+
+                // In a block, the module object stays on the stack as a hidden local
+                let module_slot = (self.scope_depth > 0).then(|| {
+                    let idx = self.locals.len();
+                    self.locals.insert(format!("__module_{}", idx), LocalInfo { index: idx, is_const: true });
+                    idx
+                });
+                for name in names {
+                    match module_slot {
+                        Some(idx) => self.emit_instruction(OpCode::GetLocal, &[idx]),
+                        None => self.emit_op(OpCode::Dup),
+                    }
+                    let name_idx = self.chunk.add_constant(Value::String(name.as_str().into()));
+                    let cache_idx = self.chunk.add_cache();
+                    self.emit_instruction(OpCode::GetAttr, &[name_idx, cache_idx]);
+                    self.define_variable(name);
+                }
+                if module_slot.is_none() {
+                    self.emit_op(OpCode::Pop);
+                }
+            },
+
+            Instruction::Export(declaration) => {
+                // Le nom exporté est une globale comme une autre : la VM en lit la valeur après le module
+                self.compile_statement(*declaration);
             },
 
             Instruction::Break => {
@@ -1112,19 +1293,18 @@ impl Compiler {
                     self.emit_op(OpCode::PopExcept);
                 }
 
-                // B. Fermeture des Variables Locales
+                // B. Fermeture des Variables Locales (et des upvalues qui les capturent)
                 let current_locals = self.locals.len();
                 let pop_locals_count = current_locals - start_locals;
                 for _ in 0..pop_locals_count {
-                    self.emit_op(OpCode::Pop);
+                    self.emit_op(OpCode::CloseUpvalue);
                 }
 
                 // C. --- FIX SEGFAULT : Dummy Value ---
                 // La sortie de boucle s'attend à trouver la condition (booléen) sur la pile 
                 // pour faire un POP final. Break doit simuler cette valeur pour garder la pile alignée.
                 let null_idx = self.chunk.add_constant(Value::Null);
-                self.emit_op(OpCode::LoadConst);
-                self.emit_byte(null_idx);
+                self.emit_instruction(OpCode::LoadConst, &[null_idx]);
                 // -------------------------------------
 
                 // D. Saut
@@ -1162,7 +1342,7 @@ impl Compiler {
                 let current_locals = self.locals.len();
                 let pop_locals_count = current_locals - start_locals;
                 for _ in 0..pop_locals_count {
-                    self.emit_op(OpCode::Pop);
+                    self.emit_op(OpCode::CloseUpvalue);
                 }
 
                 // ÉTAPE 3 : SAUT
@@ -1180,36 +1360,34 @@ impl Compiler {
                 }
             },
 
-            Instruction::Enum(name, variants) => {
-                for (i, variant_name) in variants.iter().enumerate() {
-                    // Clé
-                    let key_idx = self.chunk.add_constant(Value::String(variant_name.clone()));
-                    self.emit_op(OpCode::LoadConst);
-                    self.emit_byte(key_idx);
-                    
-                    // Valeur (i)
-                    let val_idx = self.chunk.add_constant(Value::Integer(i as i64));
-                    self.emit_op(OpCode::LoadConst);
-                    self.emit_byte(val_idx);
+            Instruction::Enum(def) => {
+                let name = def.name;
+                self.enums.borrow_mut().insert(name.clone(), def.variants.iter().map(|v| v.name.clone()).collect());
+
+                let mut methods = HashMap::new();
+                for (m_name, (m_params, m_body)) in def.methods {
+                    let method_val = self.compile_method(format!("{}.{}", name, m_name), m_params, m_body, None, false);
+                    methods.insert(m_name, method_val);
                 }
-                
-                // On crée l'enum
-                self.emit_op(OpCode::MakeEnum);
-                self.emit_byte((variants.len() * 2) as u8);
-                
+
+                // Comme une classe, l'enum est une constante construite à la compilation
+                self.emit_constant(Value::Enum(Rc::new(EnumData {
+                    name: name.clone(),
+                    variants: def.variants.into_iter().map(|v| (v.name, v.fields)).collect(),
+                    methods,
+                })));
+
                 // On le stocke dans la variable (Globale ou Locale selon le scope)
                 if self.scope_depth > 0 {
-                    let idx = self.locals.len() as u8;
+                    let idx = self.locals.len();
                     self.locals.insert(name.clone(), LocalInfo {
                         index: idx,
                         is_const: false
                     });
-                    self.emit_op(OpCode::SetLocal);
-                    self.emit_byte(idx);
+                    self.emit_instruction(OpCode::SetLocal, &[idx]);
                 } else {
                     let id = self.resolve_global(&name);
-                    self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(id);
+                    self.emit_instruction(OpCode::SetGlobal, &[id]);
                 }
                 // SetGlobal/SetLocal ne popent pas toujours selon ton implémentation.
                 // Si SetGlobal consomme la valeur (ce qui est le cas dans ta VM v2), c'est bon.
@@ -1218,11 +1396,13 @@ impl Compiler {
                 // C'est bon, la pile est propre.
             },
             Instruction::Const(name, expr) => {
+                self.function_name = Self::lambda_name(&name, &expr);
                 self.compile_expression(expr); // Valeur sur la pile
+                self.function_name = None;
                 
                 if self.scope_depth > 0 {
                     // --- LOCALE ---
-                    let idx = self.locals.len() as u8;
+                    let idx = self.locals.len();
                     self.locals.insert(name.clone(), LocalInfo { 
                         index: idx, 
                         is_const: true 
@@ -1231,8 +1411,7 @@ impl Compiler {
                 } else {
                     // --- GLOBALE ---
                     let id = self.resolve_global(&name);
-                    self.emit_op(OpCode::SetGlobal);
-                    self.emit_byte(id);
+                    self.emit_instruction(OpCode::SetGlobal, &[id]);
                     
                     // On la marque comme constante pour empêcher la modif dans ce fichier
                     self.global_constants.push(name);
@@ -1242,30 +1421,18 @@ impl Compiler {
             Instruction::ForEach(iter_var_name, iterable, body) => {
                 self.scope_depth += 1;
                 
-                // 1. Init __seq et __idx (Code inchangé...)
-                let seq_var = format!("__seq_{}", self.locals.len());
+                // 1. L'itérateur (liste, range, générateur, objet avec iter()/next()...) devient une locale cachée
+                let iter_var = format!("__iter_{}", self.locals.len());
                 self.compile_expression(iterable);
-                let seq_idx = self.locals.len() as u8;
-                self.locals.insert(seq_var.clone(), LocalInfo { index: seq_idx, is_const: true });
-                
-                let idx_var = format!("__idx_{}", self.locals.len());
-                self.emit_op(OpCode::LoadConst);
-                let zero_const = self.chunk.add_constant(Value::Integer(0));
-                self.emit_byte(zero_const);
-                let idx_idx = self.locals.len() as u8;
-                self.locals.insert(idx_var.clone(), LocalInfo { index: idx_idx, is_const: false });
+                self.emit_op(OpCode::GetIter);
+                let iter_idx = self.locals.len();
+                self.locals.insert(iter_var.clone(), LocalInfo { index: iter_idx, is_const: true });
                 
                 let loop_start = self.chunk.code.len();
                 
-                // 2. Condition (Code inchangé...)
-                self.emit_op(OpCode::GetLocal); self.emit_byte(idx_idx);
-                self.emit_op(OpCode::GetLocal); self.emit_byte(seq_idx);
-                let len_str_idx = self.chunk.add_constant(Value::String("len".to_string()));
-                self.emit_op(OpCode::Method); self.emit_byte(len_str_idx); self.emit_byte(0);
-                self.emit_op(OpCode::Less);
-                
-                let exit_jump = self.emit_jump(OpCode::JumpIfFalse);
-                self.emit_op(OpCode::Pop); 
+                // 2. Élément suivant, ou sortie (IterNext empile alors un null, comme la condition d'un while)
+                self.emit_instruction(OpCode::GetLocal, &[iter_idx]);
+                let exit_jump = self.emit_jump(OpCode::IterNext);
                 
                 // 3. PUSH DU LOOP STATE (Nécessaire pour le break !)
                 self.loop_stack.push(LoopState::For { 
@@ -1275,51 +1442,36 @@ impl Compiler {
                     locals_count_at_start: self.locals.len()
                 });
 
-                // 4. Variable utilisateur 'elem'
+                // 4. Variable utilisateur 'elem' : l'élément empilé par IterNext
                 self.scope_depth += 1; 
-                self.emit_op(OpCode::GetLocal); self.emit_byte(seq_idx);
-                self.emit_op(OpCode::GetLocal); self.emit_byte(idx_idx);
-                let at_str_idx = self.chunk.add_constant(Value::String("at".to_string()));
-                self.emit_op(OpCode::Method); self.emit_byte(at_str_idx); self.emit_byte(1);
-                
-                let user_var_idx = self.locals.len() as u8;
+                let user_var_idx = self.locals.len();
                 self.locals.insert(iter_var_name.clone(), LocalInfo { index: user_var_idx, is_const: false });
                 
                 // 5. CORPS DE LA BOUCLE AVEC NETTOYAGE (FIX MEMORY LEAK)
                 let locals_count_before_body = self.locals.len(); // Snapshot
                 
                 for stmt in body {
-                    self.compile_instruction(stmt.kind);
+                    self.compile_statement(stmt);
                 }
                 
                 // --- NETTOYAGE MANUEL DES VARIABLES DU CORPS ---
-                // C'est ce qui manquait et causait le crash !
                 let locals_count_after_body = self.locals.len();
                 let vars_created = locals_count_after_body - locals_count_before_body;
                 for _ in 0..vars_created {
-                    self.emit_op(OpCode::Pop);
+                    self.emit_op(OpCode::CloseUpvalue);
                 }
-                self.locals.retain(|_, info| info.index < locals_count_before_body as u8);
+                self.locals.retain(|_, info| info.index < locals_count_before_body);
                 // ------------------------------------------------
                 
-                // 6. Fin scope utilisateur 'elem'
-                self.emit_op(OpCode::Pop); 
+                // 6. Fin scope utilisateur 'elem' : une closure créée dans le corps garde la valeur de ce tour
+                self.emit_op(OpCode::CloseUpvalue); 
                 self.locals.remove(&iter_var_name);
                 self.scope_depth -= 1;
                 
-                // 7. Patch Continue & Incrément
+                // 7. Patch Continue, puis retour au début
                 if let Some(LoopState::For { continue_patches, break_jumps , ..}) = self.loop_stack.pop() {
                     for patch in continue_patches { self.patch_jump(patch); }
                     
-                    // Increment __idx
-                    self.emit_op(OpCode::GetLocal); self.emit_byte(idx_idx);
-                    self.emit_op(OpCode::LoadConst);
-                    let one_const = self.chunk.add_constant(Value::Integer(1));
-                    self.emit_byte(one_const);
-                    self.emit_op(OpCode::Add);
-                    self.emit_op(OpCode::SetLocal); self.emit_byte(idx_idx);
-                    self.emit_op(OpCode::Pop);
-                    
                     self.emit_loop(loop_start);
                     
                     self.patch_jump(exit_jump); // Sortie normale
@@ -1328,11 +1480,9 @@ impl Compiler {
                     for jump in break_jumps { self.patch_jump(jump); }
                 }
 
-                self.emit_op(OpCode::Pop); // Pop condition
-                self.emit_op(OpCode::Pop); // Pop __idx
-                self.locals.remove(&idx_var);
-                self.emit_op(OpCode::Pop); // Pop __seq
-                self.locals.remove(&seq_var);
+                self.emit_op(OpCode::Pop); // Pop du null de sortie
+                self.emit_op(OpCode::Pop); // Pop __iter
+                self.locals.remove(&iter_var);
                 self.scope_depth -= 1;
             },
         }
@@ -1353,7 +1503,8 @@ impl Compiler {
         let jump = self.chunk.code.len() - offset - 2;
 
         if jump > u16::MAX as usize {
-            panic!("Too much code to jump over!");
+            self.error(format!("too much code to jump over ({} bytes, the limit is {})", jump, u16::MAX));
+            return;
         }
 
         self.chunk.code[offset] = ((jump >> 8) & 0xff) as u8;
@@ -1397,7 +1548,7 @@ impl Compiler {
         let offset = self.chunk.code.len() - loop_start + 2;
         
         if offset > u16::MAX as usize {
-            panic!("Loop body too large!");
+            self.error(format!("loop body too large ({} bytes, the limit is {})", offset, u16::MAX));
         }
 
         self.emit_byte(((offset >> 8) & 0xff) as u8);
@@ -1428,32 +1579,478 @@ impl Compiler {
         // 5. Remonter au début !
         self.emit_loop(loop_start);
 
-        // 6. Patcher le saut de sortie
+        // 6. Patcher le saut de sortie, et les `break` (qui empilent un null à la place de la condition)
         self.patch_jump(exit_jump);
+        if let Some(LoopState::While { break_jumps, .. }) = self.loop_stack.pop() {
+            for jump in break_jumps {
+                self.patch_jump(jump);
+            }
+        }
         self.emit_op(OpCode::Pop); // Nettoyer la condition finale
-
-        self.loop_stack.pop();
     }
 
     // Compile une liste d'instructions en gérant le nettoyage des variables locales (Scope)
+    fn compile_statement(&mut self, stmt: crate::ast::Statement) {
+        self.current_line = stmt.line;
+        self.compile_instruction(stmt.kind);
+    }
+
+    // Compile une méthode : chaque méthode a son propre compilateur (scope isolé)
+    // et reçoit `this` comme paramètre implicite 0
+    fn compile_method(&self, name: String, params: Params, body: Vec<Statement>, parent: Option<String>, is_async: bool) -> Value {
+        let mut method_compiler = self.nested();
+        method_compiler.current_line = self.current_line;
+
+        // On transmet le nom du parent (utile pour 'super' qui vérifie context_parent_name)
+        method_compiler.context_parent_name = parent;
+
+        let mut actual_params = vec![Param::new("this".to_string(), None)];
+        actual_params.extend(params);
+
+        // Les méthodes ne capturent pas l'environnement extérieur (pas des closures)
+        Value::Function(Rc::new(method_compiler.function_body(name, actual_params, None, body, is_async)))
+    }
+
+    fn compile_function(&self, name: String, params: Params, ret_type: Option<String>, body: Vec<Statement>, is_async: bool) -> FunctionData {
+        let mut func_compiler = self.closure_compiler();
+        func_compiler.current_line = self.current_line;
+        func_compiler.function_body(name, params, ret_type, body, is_async)
+    }
+
+    // var f = func(...) {...} et @deco func f(...) {...} (f = deco(func...)) : la lambda prend le nom de f
+    fn lambda_name(var_name: &str, expr: &Expression) -> Option<String> {
+        match expr {
+            Expression::Function { .. } => Some(var_name.to_string()),
+            Expression::Call(_, args) if matches!(args.as_slice(), [Expression::Function { .. }]) => Some(var_name.to_string()),
+            _ => None,
+        }
+    }
+
+    // Corps d'une fonction : les paramètres occupent les premiers slots locaux.
+    // Un argument absent (paramètre avec valeur par défaut) arrive à Null : le prologue
+    // le remplace par sa valeur par défaut, puis les types annotés sont vérifiés.
+    fn function_body(mut self, name: String, params: Params, ret_type: Option<String>, body: Vec<Statement>, is_async: bool) -> FunctionData {
+        self.scope_depth = 1;
+        self.generator = Some(false);
+        self.awaitable = is_async;
+
+        for (i, param) in params.iter().enumerate() {
+            self.locals.insert(param.name.clone(), LocalInfo {
+                index: i,
+                is_const: false
+            });
+        }
+
+        // A. Valeurs par défaut : ArgMissing i ; JumpIfFalse ; <défaut> ; SetLocal i
+        for (i, param) in params.iter().enumerate() {
+            let Some(default) = &param.default else { continue };
+            self.emit_instruction(OpCode::ArgMissing, &[i]);
+            let skip = self.emit_jump(OpCode::JumpIfFalse);
+            self.emit_op(OpCode::Pop);
+            self.compile_expression(default.clone());
+            self.emit_instruction(OpCode::SetLocal, &[i]);
+            self.emit_op(OpCode::Pop);
+            let end = self.emit_jump(OpCode::Jump);
+            self.patch_jump(skip);
+            self.emit_op(OpCode::Pop);
+            self.patch_jump(end);
+        }
+
+        // B. Vérification des types annotés (on charge la locale, on la vérifie, on la retire)
+        for (i, param) in params.iter().enumerate() {
+            if let Some(t) = &param.type_annot {
+                self.emit_instruction(OpCode::GetLocal, &[i]);
+
+                let type_idx = self.chunk.add_constant(Value::String(t.as_str().into()));
+                self.emit_instruction(OpCode::CheckType, &[type_idx]);
+
+                self.emit_op(OpCode::Pop);
+            }
+        }
+
+        // C. Corps, puis retour implicite (Null) si on arrive au bout
+        for stmt in body {
+            self.compile_statement(stmt);
+        }
+
+        self.emit_constant(Value::Null);
+        self.emit_op(OpCode::Return);
+
+        // D. Debug info pour les variables locales
+        for (name, info) in &self.locals {
+            self.chunk.locals_map.insert(info.index, name.clone());
+        }
+
+        FunctionData {
+            name,
+            required: params.iter().take_while(|p| p.default.is_none() && !p.is_rest).count(),
+            variadic: params.last().is_some_and(|p| p.is_rest),
+            params: params.into_iter().map(|p| (p.name, p.type_annot)).collect(),
+            ret_type,
+            is_generator: self.generator == Some(true),
+            is_async,
+            chunk: Rc::new(self.chunk),
+            captures: self.upvalues.take(),
+            upvalues: Vec::new()
+        }
+    }
+
+    // Empile les arguments d'un appel et renvoie le nombre écrit dans l'instruction.
+    // `...liste` (SpreadArgs) et `nom: valeur` (CallNames) sont résolus par la VM juste avant l'appel.
+    fn compile_arguments(&mut self, args: Vec<Expression>) -> usize {
+        let arg_count = args.len();
+        let mut spreads = Vec::new();
+        let mut names = Vec::new();
+
+        for (i, arg) in args.into_iter().enumerate() {
+            match arg {
+                Expression::NamedArg(name, value) => {
+                    names.push(Value::String(name.into()));
+                    self.compile_expression(*value);
+                },
+                Expression::Spread(list) => {
+                    spreads.push(Value::Integer(i as i64));
+                    self.compile_expression(*list);
+                },
+                other => self.compile_expression(other),
+            }
+        }
+
+        // CallNames d'abord : SpreadArgs y ajoute les noms repassés par un `...rest`
+        if !names.is_empty() {
+            let idx = self.chunk.add_constant(Value::List(Rc::new(RefCell::new(names))));
+            self.emit_instruction(OpCode::CallNames, &[idx]);
+        }
+        if !spreads.is_empty() {
+            let idx = self.chunk.add_constant(Value::List(Rc::new(RefCell::new(spreads))));
+            self.emit_instruction(OpCode::SpreadArgs, &[idx, arg_count]);
+        }
+        arg_count
+    }
+
+    fn collect_enums(&self, statements: &[crate::ast::Statement], prefix: &str) {
+        for stmt in statements {
+            match &stmt.kind {
+                Instruction::Enum(def) => {
+                    let variants = def.variants.iter().map(|v| v.name.clone()).collect();
+                    self.enums.borrow_mut().insert(format!("{}{}", prefix, def.name), variants);
+                },
+                Instruction::Namespace { name, body } => {
+                    self.collect_enums(body, &format!("{}{}.", prefix, name));
+                },
+                _ => {}
+            }
+        }
+    }
+
+    fn resolve_match_binding(&self, name: &str) -> Option<(usize, usize)> {
+        Self::find_match_binding(&self.match_scopes, name)
+    }
+
+    fn find_match_binding(match_scopes: &[Vec<String>], name: &str) -> Option<(usize, usize)> {
+        for (level, scope) in match_scopes.iter().enumerate().rev() {
+            if let Some(i) = scope.iter().rposition(|b| b == name) {
+                return Some((level, i + 1));
+            }
+        }
+        None
+    }
+
+    // Variable d'une fonction englobante : (index de l'upvalue, constante ?)
+    fn resolve_upvalue(&self, name: &str) -> Option<(usize, bool)> {
+        Self::capture_in(&self.enclosing, &self.upvalues, name)
+    }
+
+    // Cherche `name` dans la fonction englobante la plus proche, puis (récursivement) dans les
+    // suivantes : chaque fonction traversée capture à son tour la variable. Une variable capturée
+    // deux fois réutilise la même upvalue.
+    fn capture_in(enclosing: &[Rc<Enclosing>], upvalues: &RefCell<Vec<Capture>>, name: &str) -> Option<(usize, bool)> {
+        let (parent, outer) = enclosing.split_last()?;
+        let (capture, is_const) = if let Some((level, slot)) = Self::find_match_binding(&parent.match_scopes, name) {
+            (Capture::Match(level, slot), false)
+        } else if let Some(info) = parent.locals.get(name) {
+            (Capture::Local(info.index), info.is_const)
+        } else {
+            let (index, is_const) = Self::capture_in(outer, &parent.upvalues, name)?;
+            (Capture::Upvalue(index), is_const)
+        };
+
+        let mut upvalues = upvalues.borrow_mut();
+        let index = match upvalues.iter().position(|c| *c == capture) {
+            Some(index) => index,
+            None => {
+                upvalues.push(capture);
+                upvalues.len() - 1
+            }
+        };
+        Some((index, is_const))
+    }
+
+    // La valeur testée reste sur la pile pendant tout le `match` (MatchBegin note sa position),
+    // suivie des variables liées par le bras en cours. MatchEnd remplace le tout par le résultat.
+    fn compile_match(&mut self, subject: Expression, arms: Vec<MatchArm>) {
+        self.check_exhaustive(&arms);
+
+        let level = self.match_scopes.len();
+        self.compile_expression(subject);
+        self.emit_instruction(OpCode::MatchBegin, &[level]);
+        self.match_scopes.push(Vec::new());
+
+        let mut end_jumps = Vec::new();
+        let mut irrefutable = false;
+
+        for arm in arms {
+            let mut fail_jumps = Vec::new();
+            self.compile_pattern_test(&arm.pattern, level, &[], &mut fail_jumps);
+
+            let mut bindings = Vec::new();
+            Self::pattern_bindings(&arm.pattern, &[], &mut bindings);
+            let binding_count = bindings.len();
+            for (name, path) in bindings {
+                self.emit_match_path(level, &path);
+                self.match_scopes[level].push(name);
+            }
+
+            let guard_jump = arm.guard.map(|guard| {
+                self.compile_expression(guard);
+                let jump = self.emit_jump(OpCode::JumpIfFalse);
+                self.emit_op(OpCode::Pop);
+                jump
+            });
+
+            self.compile_expression(arm.body);
+            self.emit_instruction(OpCode::MatchEnd, &[level]);
+            end_jumps.push(self.emit_jump(OpCode::Jump));
+            self.match_scopes[level].clear();
+
+            match guard_jump {
+                Some(jump) => {
+                    // Garde fausse : on retire le booléen puis les variables liées
+                    self.patch_jump(jump);
+                    self.emit_op(OpCode::Pop);
+                    for _ in 0..binding_count {
+                        self.emit_op(OpCode::CloseUpvalue);
+                    }
+                    if !fail_jumps.is_empty() {
+                        let skip = self.emit_jump(OpCode::Jump);
+                        for jump in fail_jumps {
+                            self.patch_jump(jump);
+                        }
+                        self.emit_op(OpCode::Pop);
+                        self.patch_jump(skip);
+                    }
+                },
+                None => {
+                    if fail_jumps.is_empty() {
+                        irrefutable = true;
+                    }
+                    for jump in fail_jumps {
+                        self.patch_jump(jump);
+                    }
+                    if irrefutable {
+                        break;
+                    }
+                    self.emit_op(OpCode::Pop);
+                }
+            }
+        }
+
+        if !irrefutable {
+            self.emit_constant(Value::String("No match arm for value: ".into()));
+            self.emit_match_path(level, &[]);
+            self.emit_op(OpCode::Add);
+            self.emit_op(OpCode::Throw);
+        }
+
+        for jump in end_jumps {
+            self.patch_jump(jump);
+        }
+        self.match_scopes.pop();
+    }
+
+    // Charge la sous-valeur désignée par `path`
+    fn emit_match_path(&mut self, level: usize, path: &[MatchStep]) {
+        self.emit_instruction(OpCode::GetMatch, &[level, 0]);
+        for step in path {
+            match step {
+                MatchStep::Index(i) => {
+                    self.emit_constant(Value::Integer(*i));
+                    self.emit_op(OpCode::GetIndex);
+                },
+                MatchStep::Rest(start) => {
+                    self.emit_constant(Value::Integer(*start));
+                    self.emit_constant(Value::Null);
+                    self.emit_op(OpCode::Slice);
+                },
+                MatchStep::Key(key) => {
+                    let key_idx = self.chunk.add_constant(Value::String(key.as_str().into()));
+                    let cache_idx = self.chunk.add_cache();
+                    self.emit_instruction(OpCode::GetAttr, &[key_idx, cache_idx]);
+                },
+                MatchStep::Field(position) => {
+                    self.emit_instruction(OpCode::GetField, &[*position]);
+                },
+            }
+        }
+    }
+
+    // Chaque test laisse un booléen : s'il est faux on saute vers `fail_jumps` (booléen encore sur la pile)
+    fn compile_pattern_test(&mut self, pattern: &Pattern, level: usize, path: &[MatchStep], fail_jumps: &mut Vec<usize>) {
+        let sub_path = |step: MatchStep| {
+            let mut p = path.to_vec();
+            p.push(step);
+            p
+        };
+
+        match pattern {
+            Pattern::Wildcard | Pattern::Binding(_) => return,
+            Pattern::Literal(val) => {
+                self.emit_match_path(level, path);
+                self.emit_constant(val.clone());
+                self.emit_op(OpCode::Equal);
+            },
+            Pattern::Value(expr) => {
+                self.emit_match_path(level, path);
+                self.compile_expression(expr.clone());
+                self.emit_op(OpCode::Equal);
+            },
+            Pattern::Range(start, end) => {
+                self.emit_match_path(level, path);
+                self.emit_constant(start.clone());
+                self.emit_constant(end.clone());
+                self.emit_op(OpCode::MatchRange);
+            },
+            Pattern::List(items, rest) => {
+                self.emit_match_path(level, path);
+                self.emit_instruction(OpCode::MatchList, &[items.len(), usize::from(rest.is_some())]);
+                fail_jumps.push(self.emit_jump(OpCode::JumpIfFalse));
+                self.emit_op(OpCode::Pop);
+                for (i, item) in items.iter().enumerate() {
+                    self.compile_pattern_test(item, level, &sub_path(MatchStep::Index(i as i64)), fail_jumps);
+                }
+                return;
+            },
+            Pattern::Dict(entries) => {
+                for (key, item) in entries {
+                    self.emit_match_path(level, path);
+                    let key_idx = self.chunk.add_constant(Value::String(key.as_str().into()));
+                    self.emit_instruction(OpCode::MatchKey, &[key_idx]);
+                    fail_jumps.push(self.emit_jump(OpCode::JumpIfFalse));
+                    self.emit_op(OpCode::Pop);
+                    self.compile_pattern_test(item, level, &sub_path(MatchStep::Key(key.clone())), fail_jumps);
+                }
+                if !entries.is_empty() {
+                    return;
+                }
+                // {} accepte n'importe quel dictionnaire ou instance
+                self.emit_match_path(level, path);
+                let null_idx = self.chunk.add_constant(Value::Null);
+                self.emit_instruction(OpCode::MatchKey, &[null_idx]);
+            },
+            Pattern::Class(class, args) => {
+                self.emit_match_path(level, path);
+                self.compile_expression(class.clone());
+                self.emit_op(OpCode::MatchClass);
+                fail_jumps.push(self.emit_jump(OpCode::JumpIfFalse));
+                self.emit_op(OpCode::Pop);
+                for (i, arg) in args.iter().enumerate() {
+                    self.compile_pattern_test(arg, level, &sub_path(MatchStep::Field(i)), fail_jumps);
+                }
+                return;
+            },
+        }
+
+        fail_jumps.push(self.emit_jump(OpCode::JumpIfFalse));
+        self.emit_op(OpCode::Pop);
+    }
+
+    // Variables liées par le motif, dans l'ordre, avec le chemin de leur valeur
+    fn pattern_bindings(pattern: &Pattern, path: &[MatchStep], out: &mut Vec<(String, Vec<MatchStep>)>) {
+        let sub_path = |step: MatchStep| {
+            let mut p = path.to_vec();
+            p.push(step);
+            p
+        };
+
+        match pattern {
+            Pattern::Binding(name) => out.push((name.clone(), path.to_vec())),
+            Pattern::List(items, rest) => {
+                for (i, item) in items.iter().enumerate() {
+                    Self::pattern_bindings(item, &sub_path(MatchStep::Index(i as i64)), out);
+                }
+                if let Some(name) = rest.as_ref().filter(|n| *n != "_") {
+                    out.push((name.clone(), sub_path(MatchStep::Rest(items.len() as i64))));
+                }
+            },
+            Pattern::Dict(entries) => {
+                for (key, item) in entries {
+                    Self::pattern_bindings(item, &sub_path(MatchStep::Key(key.clone())), out);
+                }
+            },
+            Pattern::Class(_, args) => {
+                for (i, arg) in args.iter().enumerate() {
+                    Self::pattern_bindings(arg, &sub_path(MatchStep::Field(i)), out);
+                }
+            },
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Range(..) | Pattern::Value(_) => {},
+        }
+    }
+
+    // Avertit si un `match` sur les variantes d'un enum connu en oublie (sans `_` ni variable pour le reste)
+    fn check_exhaustive(&self, arms: &[MatchArm]) {
+        let mut enum_name = None;
+        let mut covered = Vec::new();
+
+        for arm in arms {
+            match &arm.pattern {
+                Pattern::Wildcard | Pattern::Binding(_) if arm.guard.is_none() => return,
+                Pattern::Value(Expression::GetAttr(base, variant))
+                | Pattern::Class(Expression::GetAttr(base, variant), _) => {
+                    let Some(path) = dotted_path(base) else { return };
+                    if !self.enums.borrow().contains_key(&path) { return; }
+                    if enum_name.get_or_insert_with(|| path.clone()) != &path { return; }
+                    // Circle(r) couvre la variante, Circle(0) seulement une partie
+                    let irrefutable = match &arm.pattern {
+                        Pattern::Class(_, args) => args.iter().all(|a| matches!(a, Pattern::Wildcard | Pattern::Binding(_))),
+                        _ => true,
+                    };
+                    if arm.guard.is_none() && irrefutable {
+                        covered.push(variant.clone());
+                    }
+                },
+                _ => return,
+            }
+        }
+
+        let Some(name) = enum_name else { return };
+        let enums = self.enums.borrow();
+        let missing: Vec<&str> = enums[&name].iter()
+            .filter(|v| !covered.contains(v))
+            .map(|v| v.as_str())
+            .collect();
+        if !missing.is_empty() {
+            eprintln!("warning (line {}): non-exhaustive match on enum '{}', missing: {}", self.current_line, name, missing.join(", "));
+        }
+    }
+
     fn compile_scope(&mut self, statements: Vec<crate::ast::Statement>) {
         let initial_locals_count = self.locals.len();
         
         for stmt in statements {
-            self.compile_instruction(stmt.kind);
+            self.compile_statement(stmt);
         }
         
         let final_locals_count = self.locals.len();
         let vars_created = final_locals_count - initial_locals_count;
         
-        // 1. On nettoie la pile d'exécution (Runtime)
+        // 1. On nettoie la pile d'exécution (Runtime), en fermant les upvalues qui visent ces variables
         for _ in 0..vars_created {
-            self.emit_op(OpCode::Pop);
+            self.emit_op(OpCode::CloseUpvalue);
         }
         
         // 2. On nettoie la table des symboles (Compile-time)
         // On retire toutes les variables qui ont un index >= initial_locals_count
-        self.locals.retain(|_, &mut info| info.index < initial_locals_count as u8);
+        self.locals.retain(|_, &mut info| info.index < initial_locals_count);
     }
 
     // Tente de réduire une expression constante
@@ -1467,7 +2064,7 @@ impl Compiler {
                 match (self.evaluate_constant(left), self.evaluate_constant(right)) {
                     (Some(Value::Integer(a)), Some(Value::Integer(b))) => Some(Value::Integer(a + b)),
                     (Some(Value::Float(a)), Some(Value::Float(b))) => Some(Value::Float(a + b)),
-                    (Some(Value::String(a)), Some(Value::String(b))) => Some(Value::String(format!("{}{}", a, b))),
+                    (Some(Value::String(a)), Some(Value::String(b))) => Some(Value::String(format!("{}{}", a, b).into())),
                     _ => None
                 }
             },
@@ -1569,4 +2166,28 @@ impl Compiler {
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+
+// "Color" ou "Geo.Color" pour l'expression d'un chemin pointé
+fn dotted_path(expr: &Expression) -> Option<String> {
+    match expr {
+        Expression::Variable(name) => Some(name.clone()),
+        Expression::GetAttr(base, member) => Some(format!("{}.{}", dotted_path(base)?, member)),
+        _ => None,
+    }
+}
+
+// x[k] = x[k] op v  =>  (opcode de op, v) ; même forme que celle que l'unparser réécrit en x[k] op= v
+fn compound_index(obj: &Expression, key: &Expression, value: &Expression) -> Option<(OpCode, Expression)> {
+    let (op, l, r) = match value {
+        Expression::Add(l, r) => (OpCode::Add, l, r),
+        Expression::Sub(l, r) => (OpCode::Sub, l, r),
+        Expression::Mul(l, r) => (OpCode::Mul, l, r),
+        Expression::Div(l, r) => (OpCode::Div, l, r),
+        _ => return None,
+    };
+    match l.as_ref() {
+        Expression::Index(o, k) if o.as_ref() == obj && k.as_ref() == key => Some((op, r.as_ref().clone())),
+        _ => None,
+    }
+}
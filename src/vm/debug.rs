@@ -13,7 +13,24 @@ pub fn disassemble_chunk(chunk: &Chunk, name: &str) {
 pub fn disassemble_instruction(chunk: &Chunk, offset: usize) -> usize {
     print!("{:04} ", offset); // Affiche l'adresse (ex: 0000)
 
-    let instruction: OpCode = chunk.code[offset].into();
+    let Ok(mut instruction) = OpCode::try_from(chunk.code[offset]) else {
+        println!("UNKNOWN {}", chunk.code[offset]);
+        return offset + 1;
+    };
+
+    // Préfixe Wide : on affiche l'instruction qui suit, dont les opérandes font quatre octets
+    let mut offset = offset;
+    let mut width = 1;
+    if matches!(instruction, OpCode::Wide) {
+        print!("WIDE ");
+        offset += 1;
+        width = 4;
+        let Ok(next) = OpCode::try_from(chunk.code[offset]) else {
+            println!("UNKNOWN {}", chunk.code[offset]);
+            return offset + 1;
+        };
+        instruction = next;
+    }
 
     match instruction {
         OpCode::Return => simple_instruction("RETURN", offset),
@@ -26,18 +43,18 @@ pub fn disassemble_instruction(chunk: &Chunk, offset: usize) -> usize {
         OpCode::Pop => simple_instruction("POP", offset),
         
         // Instructions avec opérandes (1 octet de plus)
-        OpCode::LoadConst => constant_instruction("LOAD_CONST", chunk, offset),
+        OpCode::LoadConst => constant_instruction("LOAD_CONST", chunk, offset, width),
 
         // --- Affichage des Globales ---
-        OpCode::GetGlobal => byte_instruction("GET_GLOBAL", chunk, offset),
-        OpCode::SetGlobal => byte_instruction("SET_GLOBAL", chunk, offset),
-        OpCode::GetLocal => byte_instruction("GET_LOCAL", chunk, offset),
-        OpCode::SetLocal => byte_instruction("SET_LOCAL", chunk, offset),
+        OpCode::GetGlobal => byte_instruction("GET_GLOBAL", chunk, offset, width),
+        OpCode::SetGlobal => byte_instruction("SET_GLOBAL", chunk, offset, width),
+        OpCode::GetLocal => byte_instruction("GET_LOCAL", chunk, offset, width),
+        OpCode::SetLocal => byte_instruction("SET_LOCAL", chunk, offset, width),
 
         OpCode::Jump => jump_instruction("JUMP", 1, chunk, offset),
         OpCode::JumpIfFalse => jump_instruction("JUMP_IF_FALSE", 1, chunk, offset),
         OpCode::Loop => jump_instruction("LOOP", -1, chunk, offset), // -1 pour indiquer arrière
-        OpCode::Call => byte_instruction("CALL", chunk, offset),
+        OpCode::Call => byte_instruction("CALL", chunk, offset, width),
 
         OpCode::Modulo => simple_instruction("MOD", offset),
         OpCode::Equal => simple_instruction("EQUAL", offset),
@@ -54,42 +71,87 @@ pub fn disassemble_instruction(chunk: &Chunk, offset: usize) -> usize {
         OpCode::ShiftLeft => simple_instruction("SHIFT_LEFT", offset),
         OpCode::ShiftRight => simple_instruction("SHIFT_RIGHT", offset),
 
-        OpCode::MakeList => byte_instruction("MAKE_LIST", chunk, offset),
-        OpCode::MakeDict => byte_instruction("MAKE_DICT", chunk, offset),
+        OpCode::MakeList => byte_instruction("MAKE_LIST", chunk, offset, width),
+        OpCode::MakeDict => byte_instruction("MAKE_DICT", chunk, offset, width),
         
-        OpCode::Class => constant_instruction("CLASS", chunk, offset),
-        OpCode::MakeEnum => byte_instruction("ENUM", chunk, offset),
+        OpCode::Class => constant_instruction("CLASS", chunk, offset, width),
         OpCode::MakeRange => simple_instruction("MAKE_RANGE", offset),
-        OpCode::Method => constant_instruction("METHOD", chunk, offset),
-        OpCode::GetAttr => constant_instruction("GET_ATTR", chunk, offset),
-        OpCode::SetAttr => constant_instruction("SET_ATTR", chunk, offset),
+        OpCode::GetIndex => simple_instruction("GET_INDEX", offset),
+        OpCode::SetIndex => simple_instruction("SET_INDEX", offset),
+        OpCode::Slice => simple_instruction("SLICE", offset),
+        OpCode::MatchBegin => byte_instruction("MATCH_BEGIN", chunk, offset, width),
+        OpCode::GetMatch => two_byte_instruction("GET_MATCH", chunk, offset, width),
+        OpCode::MatchEnd => byte_instruction("MATCH_END", chunk, offset, width),
+        OpCode::MatchList => two_byte_instruction("MATCH_LIST", chunk, offset, width),
+        OpCode::MatchKey => constant_instruction("MATCH_KEY", chunk, offset, width),
+        OpCode::MatchClass => simple_instruction("MATCH_CLASS", offset),
+        OpCode::MatchRange => simple_instruction("MATCH_RANGE", offset),
+        OpCode::GetField => byte_instruction("GET_FIELD", chunk, offset, width),
+        OpCode::ArgMissing => byte_instruction("ARG_MISSING", chunk, offset, width),
+        OpCode::CallNames => constant_instruction("CALL_NAMES", chunk, offset, width),
+        OpCode::SpreadArgs => two_byte_instruction("SPREAD_ARGS", chunk, offset, width),
+        OpCode::Yield => simple_instruction("YIELD", offset),
+        OpCode::GetIter => simple_instruction("GET_ITER", offset),
+        OpCode::IterNext => jump_instruction("ITER_NEXT", 1, chunk, offset),
+        OpCode::Await => simple_instruction("AWAIT", offset),
+        OpCode::Method => {
+            // operands: const_idx (nom), u8 (nombre d'arguments), cache_idx
+            let name_idx = operand(chunk, offset + 1, width);
+            let arg_count = operand(chunk, offset + 1 + width, width);
+            let cache_idx = operand(chunk, offset + 1 + 2 * width, width);
+            println!("{:<16} {:4} '{}' ({} args) cache {}", "METHOD", name_idx, chunk.constants[name_idx], arg_count, cache_idx);
+            offset + 1 + 3 * width
+        },
+        OpCode::GetAttr => cached_instruction("GET_ATTR", chunk, offset, width),
+        OpCode::SetAttr => cached_instruction("SET_ATTR", chunk, offset, width),
         OpCode::Super => {
-            let method_idx = chunk.code[offset + 1];
-            let arg_count = chunk.code[offset + 2];
-            let parent_idx = chunk.code[offset + 3];
+            let method_idx = operand(chunk, offset + 1, width);
+            let arg_count = operand(chunk, offset + 1 + width, width);
+            let parent_idx = operand(chunk, offset + 1 + 2 * width, width);
 
-            let method_name = &chunk.constants[method_idx as usize];
-            let parent_name = &chunk.constants[parent_idx as usize];
+            let method_name = &chunk.constants[method_idx];
+            let parent_name = &chunk.constants[parent_idx];
 
             println!("{:-16} '{}' ({} args) super-> '{}'", "SUPER", method_name, arg_count, parent_name);
             
             // On avance de 4 (1 OpCode + 3 Args)
-            offset + 4
+            offset + 1 + 3 * width
         },
         
         OpCode::Input => simple_instruction("INPUT", offset),
 
         OpCode::MakeClosure => simple_instruction("MAKE_CLOSURE", offset),
-        OpCode::GetFreeVar => { constant_instruction("GET_FREE_VAR", chunk, offset) },
+        OpCode::GetUpvalue => byte_instruction("GET_UPVALUE", chunk, offset, width),
+        OpCode::SetUpvalue => byte_instruction("SET_UPVALUE", chunk, offset, width),
+        OpCode::CloseUpvalue => simple_instruction("CLOSE_UPVALUE", offset),
+        OpCode::GetFreeGlobal => byte_instruction("GET_FREE_GLOBAL", chunk, offset, width),
         OpCode::Dup => simple_instruction("DUP", offset),
+        OpCode::Dup2 => simple_instruction("DUP2", offset),
 
         OpCode::SetupExcept => jump_instruction("SETUP_EXCEPT", 1, chunk, offset),
         OpCode::PopExcept => simple_instruction("POP_EXCEPT", offset),
         OpCode::Throw => simple_instruction("THROW", offset),
 
-        OpCode::Import => constant_instruction("IMPORT", chunk, offset),
-        OpCode::CheckType => constant_instruction("CHECK_TYPE", chunk, offset),
+        OpCode::Import => {
+            // operands: const_idx (chemin), const_idx (null : tous les exports, sinon les noms importés),
+            // const_idx (module qui importe, null pour le script principal)
+            let path_idx = operand(chunk, offset + 1, width);
+            let mode_idx = operand(chunk, offset + 1 + width, width);
+            let module_idx = operand(chunk, offset + 1 + 2 * width, width);
+            println!("{:<16} {:4} '{}' {} {}", "IMPORT", path_idx, chunk.constants[path_idx], chunk.constants[mode_idx], chunk.constants[module_idx]);
+            offset + 1 + 3 * width
+        },
+        OpCode::CheckType => constant_instruction("CHECK_TYPE", chunk, offset, width),
+        OpCode::Wide => simple_instruction("WIDE", offset),
+    }
+}
+
+// Opérande d'index ou de compteur : un octet, ou quatre (u32) après Wide
+fn operand(chunk: &Chunk, at: usize, width: usize) -> usize {
+    if width == 1 {
+        return chunk.code[at] as usize;
     }
+    u32::from_be_bytes([chunk.code[at], chunk.code[at + 1], chunk.code[at + 2], chunk.code[at + 3]]) as usize
 }
 
 fn simple_instruction(name: &str, offset: usize) -> usize {
@@ -97,19 +159,34 @@ fn simple_instruction(name: &str, offset: usize) -> usize {
     offset + 1
 }
 
-fn constant_instruction(name: &str, chunk: &Chunk, offset: usize) -> usize {
-    // L'octet suivant contient l'index de la constante
-    let constant_idx = chunk.code[offset + 1];
+fn constant_instruction(name: &str, chunk: &Chunk, offset: usize, width: usize) -> usize {
+    // L'opérande suivant contient l'index de la constante
+    let constant_idx = operand(chunk, offset + 1, width);
     print!("{:<16} {:4} '", name, constant_idx);
-    print!("{}", chunk.constants[constant_idx as usize]);
+    print!("{}", chunk.constants[constant_idx]);
     println!("'");
-    offset + 2 // On a lu l'opcode + l'index
+    offset + 1 + width // On a lu l'opcode + l'index
+}
+
+fn cached_instruction(name: &str, chunk: &Chunk, offset: usize, width: usize) -> usize {
+    // operands: const_idx (nom), cache_idx
+    let constant_idx = operand(chunk, offset + 1, width);
+    let cache_idx = operand(chunk, offset + 1 + width, width);
+    println!("{:<16} {:4} '{}' cache {}", name, constant_idx, chunk.constants[constant_idx], cache_idx);
+    offset + 1 + 2 * width
 }
 
-fn byte_instruction(name: &str, chunk: &Chunk, offset: usize) -> usize {
-    let slot = chunk.code[offset + 1];
+fn byte_instruction(name: &str, chunk: &Chunk, offset: usize, width: usize) -> usize {
+    let slot = operand(chunk, offset + 1, width);
     println!("{:<16} {:4}", name, slot);
-    offset + 2
+    offset + 1 + width
+}
+
+fn two_byte_instruction(name: &str, chunk: &Chunk, offset: usize, width: usize) -> usize {
+    let a = operand(chunk, offset + 1, width);
+    let b = operand(chunk, offset + 1 + width, width);
+    println!("{:<16} {:4} {:4}", name, a, b);
+    offset + 1 + 2 * width
 }
 
 fn jump_instruction(name: &str, sign: i8, chunk: &Chunk, offset: usize) -> usize {
@@ -0,0 +1,287 @@
+// Ramasse-miettes des cycles. Les valeurs sont comptées par référence (Rc) : une structure
+// qui se référence elle-même (parent <-> enfant, closure qui se capture) n'est jamais libérée.
+//
+// On procède par suppression d'essai, comme CPython : pour chaque conteneur suivi, on retire
+// de son compteur de références celles qui viennent d'autres conteneurs suivis. Ce qui reste
+// vient de l'extérieur (pile, globales, frames, tâches...) : ces conteneurs sont vivants, ainsi
+// que tout ce qu'ils atteignent. Les autres ne sont atteignables que par des cycles : on les vide,
+// ce qui casse les cycles et laisse les Rc libérer la mémoire.
+//
+// Un cycle ne peut se former qu'en rangeant un conteneur dans un autre après leur création :
+// la VM signale ces écritures (note_store, note_upvalue) et seuls ces conteneurs sont suivis.
+// Les générateurs, tâches, itérateurs et classes ne sont pas parcourus : ce qu'ils contiennent
+// compte comme une référence extérieure (jamais libéré à tort, mais ces cycles restent).
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
+use crate::ast::value::{BoundMethod, FunctionData, InstanceData, Upvalue, VariantData};
+use crate::ast::Value;
+
+// Nombre minimal de nouveaux conteneurs suivis entre deux collectes automatiques
+const THRESHOLD_MIN: usize = 10_000;
+
+/// Statistiques du ramasse-miettes (System.gc_stats()).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GcStats {
+    pub collections: usize,
+    pub freed: usize,      // Total des objets libérés depuis le lancement
+    pub last_freed: usize, // Objets libérés par la dernière collecte
+    pub tracked: usize,    // Conteneurs actuellement suivis
+    pub threshold: usize,  // Nouveaux conteneurs suivis avant la prochaine collecte automatique
+}
+
+// Conteneur suivi, sans le garder en vie
+enum Tracked {
+    List(Weak<RefCell<Vec<Value>>>),
+    Dict(Weak<RefCell<HashMap<String, Value>>>),
+    Instance(Weak<RefCell<InstanceData>>),
+    Upvalue(Weak<RefCell<Upvalue>>),
+}
+
+// Objet parcouru pendant une collecte
+enum Node {
+    List(Rc<RefCell<Vec<Value>>>),
+    Dict(Rc<RefCell<HashMap<String, Value>>>),
+    Instance(Rc<RefCell<InstanceData>>),
+    Upvalue(Rc<RefCell<Upvalue>>),
+    // Immuables : parcourus, mais leurs cycles passent forcément par un conteneur modifiable
+    Function(Rc<FunctionData>),
+    Variant(Rc<VariantData>),
+    Bound(Rc<BoundMethod>),
+}
+
+#[derive(Default)]
+struct Heap {
+    tracked: HashMap<*const (), Tracked>,
+    since_last: usize,
+    stats: GcStats,
+}
+
+thread_local! {
+    static HEAP: RefCell<Heap> = RefCell::new(Heap {
+        stats: GcStats { threshold: THRESHOLD_MIN, ..GcStats::default() },
+        ..Heap::default()
+    });
+    static PENDING: Cell<bool> = const { Cell::new(false) };
+}
+
+fn node_of(value: &Value) -> Option<Node> {
+    match value {
+        Value::List(l) => Some(Node::List(l.clone())),
+        Value::Dict(d) => Some(Node::Dict(d.clone())),
+        Value::Instance(i) => Some(Node::Instance(i.clone())),
+        Value::Function(f) if !f.upvalues.is_empty() => Some(Node::Function(f.clone())),
+        Value::Variant(v) if !v.values.is_empty() => Some(Node::Variant(v.clone())),
+        Value::BoundMethod(m) if node_of(&m.receiver).is_some() => Some(Node::Bound(m.clone())),
+        _ => None,
+    }
+}
+
+impl Node {
+    fn ptr(&self) -> *const () {
+        match self {
+            Node::List(l) => Rc::as_ptr(l) as *const (),
+            Node::Dict(d) => Rc::as_ptr(d) as *const (),
+            Node::Instance(i) => Rc::as_ptr(i) as *const (),
+            Node::Upvalue(u) => Rc::as_ptr(u) as *const (),
+            Node::Function(f) => Rc::as_ptr(f) as *const (),
+            Node::Variant(v) => Rc::as_ptr(v) as *const (),
+            Node::Bound(m) => Rc::as_ptr(m) as *const (),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            Node::List(l) => Rc::strong_count(l),
+            Node::Dict(d) => Rc::strong_count(d),
+            Node::Instance(i) => Rc::strong_count(i),
+            Node::Upvalue(u) => Rc::strong_count(u),
+            Node::Function(f) => Rc::strong_count(f),
+            Node::Variant(v) => Rc::strong_count(v),
+            Node::Bound(m) => Rc::strong_count(m),
+        }
+    }
+
+    // Objets référencés directement (une entrée par référence), None si le contenu est emprunté
+    fn children(&self) -> Option<Vec<Node>> {
+        Some(match self {
+            Node::List(l) => l.try_borrow().ok()?.iter().filter_map(node_of).collect(),
+            Node::Dict(d) => d.try_borrow().ok()?.values().filter_map(node_of).collect(),
+            Node::Instance(i) => i.try_borrow().ok()?.slots.iter().filter_map(node_of).collect(),
+            Node::Upvalue(u) => match &*u.try_borrow().ok()? {
+                Upvalue::Closed(value) => node_of(value).into_iter().collect(),
+                Upvalue::Open(_) => Vec::new(),
+            },
+            Node::Function(f) => f.upvalues.iter().map(|u| Node::Upvalue(u.clone())).collect(),
+            Node::Variant(v) => v.values.iter().filter_map(node_of).collect(),
+            Node::Bound(m) => node_of(&m.receiver).into_iter().collect(),
+        })
+    }
+
+    // Vide un objet mort : ses références sortantes sont rendues pour être libérées après la collecte
+    fn clear(&self, doomed: &mut Vec<Value>) {
+        match self {
+            Node::List(l) => if let Ok(mut items) = l.try_borrow_mut() {
+                doomed.append(&mut items);
+            },
+            Node::Dict(d) => if let Ok(mut entries) = d.try_borrow_mut() {
+                doomed.extend(entries.drain().map(|(_, v)| v));
+            },
+            Node::Instance(i) => if let Ok(mut inst) = i.try_borrow_mut() {
+                doomed.append(&mut inst.slots);
+            },
+            Node::Upvalue(u) => if let Ok(mut cell) = u.try_borrow_mut()
+                && let Upvalue::Closed(value) = std::mem::replace(&mut *cell, Upvalue::Closed(Value::Null)) {
+                doomed.push(value);
+            },
+            Node::Function(_) | Node::Variant(_) | Node::Bound(_) => {}
+        }
+    }
+
+    fn downgrade(&self) -> Option<Tracked> {
+        match self {
+            Node::List(l) => Some(Tracked::List(Rc::downgrade(l))),
+            Node::Dict(d) => Some(Tracked::Dict(Rc::downgrade(d))),
+            Node::Instance(i) => Some(Tracked::Instance(Rc::downgrade(i))),
+            Node::Upvalue(u) => Some(Tracked::Upvalue(Rc::downgrade(u))),
+            Node::Function(_) | Node::Variant(_) | Node::Bound(_) => None,
+        }
+    }
+}
+
+impl Tracked {
+    fn upgrade(&self) -> Option<Node> {
+        match self {
+            Tracked::List(l) => l.upgrade().map(Node::List),
+            Tracked::Dict(d) => d.upgrade().map(Node::Dict),
+            Tracked::Instance(i) => i.upgrade().map(Node::Instance),
+            Tracked::Upvalue(u) => u.upgrade().map(Node::Upvalue),
+        }
+    }
+}
+
+fn track(node: Node) {
+    let Some(weak) = node.downgrade() else { return };
+    HEAP.with_borrow_mut(|heap| {
+        if heap.tracked.insert(node.ptr(), weak).is_none() {
+            heap.since_last += 1;
+            if heap.since_last >= heap.stats.threshold {
+                PENDING.set(true);
+            }
+        }
+    });
+}
+
+/// `value` a été rangé dans `target` (liste, dictionnaire ou instance) : un cycle peut s'être formé.
+pub fn note_store(target: &Value, value: &Value) {
+    if node_of(value).is_some()
+        && let Some(node) = node_of(target) {
+        track(node);
+    }
+}
+
+/// L'upvalue fermée `cell` contient désormais `value`.
+pub fn note_upvalue(cell: &Rc<RefCell<Upvalue>>, value: &Value) {
+    if node_of(value).is_some() {
+        track(Node::Upvalue(cell.clone()));
+    }
+}
+
+/// Lance une collecte si assez de conteneurs ont été suivis depuis la dernière.
+pub fn collect_if_needed() {
+    if PENDING.get() {
+        collect();
+    }
+}
+
+/// Libère les cycles inaccessibles et renvoie le nombre d'objets libérés.
+pub fn collect() -> usize {
+    PENDING.set(false);
+
+    // 1. Les conteneurs suivis encore en vie, puis tout ce qu'ils atteignent
+    let mut nodes: Vec<Node> = Vec::new();
+    let mut index: HashMap<*const (), usize> = HashMap::new();
+    HEAP.with_borrow_mut(|heap| {
+        heap.tracked.retain(|ptr, tracked| match tracked.upgrade() {
+            Some(node) => {
+                index.insert(*ptr, nodes.len());
+                nodes.push(node);
+                true
+            }
+            None => false,
+        });
+    });
+
+    let mut edges: Vec<Vec<usize>> = Vec::new();
+    let mut pinned: Vec<bool> = Vec::new();
+    let mut i = 0;
+    while i < nodes.len() {
+        let children = nodes[i].children();
+        pinned.push(children.is_none());
+        let mut targets = Vec::new();
+        for child in children.unwrap_or_default() {
+            let ptr = child.ptr();
+            let target = *index.entry(ptr).or_insert_with(|| {
+                nodes.push(child);
+                nodes.len() - 1
+            });
+            targets.push(target);
+        }
+        edges.push(targets);
+        i += 1;
+    }
+
+    // 2. Références extérieures : le compteur, moins notre propre clone, moins les références internes
+    let mut external: Vec<usize> = nodes.iter().map(|n| n.strong_count() - 1).collect();
+    for targets in &edges {
+        for &target in targets {
+            external[target] -= 1;
+        }
+    }
+
+    // 3. Tout ce qui est atteignable depuis l'extérieur est vivant
+    let mut alive = vec![false; nodes.len()];
+    let mut work: Vec<usize> = (0..nodes.len()).filter(|&n| external[n] > 0 || pinned[n]).collect();
+    while let Some(n) = work.pop() {
+        if !alive[n] {
+            alive[n] = true;
+            work.extend(edges[n].iter().copied().filter(|&t| !alive[t]));
+        }
+    }
+
+    // 4. Le reste n'est retenu que par des cycles : on le vide
+    let mut doomed = Vec::new();
+    let mut freed = 0;
+    for (n, node) in nodes.iter().enumerate() {
+        if !alive[n] {
+            node.clear(&mut doomed);
+            freed += 1;
+        }
+    }
+
+    HEAP.with_borrow_mut(|heap| {
+        for (n, node) in nodes.iter().enumerate() {
+            if !alive[n] {
+                heap.tracked.remove(&node.ptr());
+            }
+        }
+        heap.since_last = 0;
+        let stats = &mut heap.stats;
+        stats.collections += 1;
+        stats.freed += freed;
+        stats.last_freed = freed;
+        stats.tracked = heap.tracked.len();
+        stats.threshold = THRESHOLD_MIN.max(heap.tracked.len());
+    });
+
+    // Les objets morts sont libérés ici, une fois plus aucun emprunt en cours
+    drop(nodes);
+    drop(doomed);
+    freed
+}
+
+pub fn stats() -> GcStats {
+    HEAP.with_borrow(|heap| GcStats { tracked: heap.tracked.len(), ..heap.stats })
+}
@@ -1,20 +1,30 @@
+pub mod builtins;
 pub mod compiler;
 pub mod debug;
+pub mod gc;
+pub mod scheduler;
+pub mod verifier;
 
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
+use std::path::{Path, PathBuf};
+use std::rc::{Rc, Weak};
 
-use crate::ast::value::{ClassData, FunctionData, Visibility};
+use crate::ast::value::{BoundMethod, Capture, ClassData, EnumData, FunctionData, GeneratorData, GeneratorState, IteratorData, NamedRest, NativeData, Shape, Upvalue, VariantData, Visibility};
 use crate::ast::{InstanceData, Value};
-use crate::chunk::Chunk;
+use crate::chunk::{Chunk, InlineCache};
 use crate::opcode::OpCode;
-use crate::ast::environment::Environment;
+use builtins::BuiltinType;
+use compiler::{plain_name, scoped_name, GlobalNames};
 
-const STACK_MAX: usize = 4096;
+// Limites par défaut (réglables avec VM::set_limits, ou --max-frames / --max-stack) :
+// au-delà, l'appel lève une erreur "Stack overflow" qu'un try/catch peut attraper
+pub const FRAMES_MAX: usize = 10_000;
+pub const STACK_MAX: usize = 1 << 20;
 
-#[allow(dead_code)]
-const FRAMES_MAX: usize = 64;
+// Exécutions imbriquées (callbacks de map/filter, générateurs, méthodes spéciales) : chacune
+// consomme de la pile native, le thread qui fait tourner la VM doit en avoir assez pour ce nombre
+pub const NESTED_MAX: usize = 1_000;
 
 #[derive(Debug, Clone)]
 struct CallFrame {
@@ -22,6 +32,10 @@ struct CallFrame {
     ip: usize,          // Où on en est dans CETTE fonction
     slot_offset: usize, // Où commencent ses variables locales dans la pile globale (Base Pointer)
     class_context: Option<Rc<ClassData>>, // La classe dans laquelle on s'exécute (pour private/protected)
+    match_bases: Vec<usize>, // Position sur la pile de la valeur testée par chaque `match` en cours
+    missing_args: u64,       // Bit i : le paramètre i n'a pas été passé (sa valeur par défaut s'applique)
+    named_rest: Option<NamedRest>, // Arguments nommés reçus dans le `...rest`, repassés par `f(...rest)`
+    generator: Option<Rc<RefCell<GeneratorData>>>, // Générateur repris dans cette frame (cible de Yield)
 }
 
 impl CallFrame {
@@ -45,18 +59,190 @@ pub struct VM {
     frames: Vec<CallFrame>,
     stack: Vec<Value>,
     globals: Vec<Value>,
-    global_names: Rc<RefCell<HashMap<String, u8>>>,
+    global_names: GlobalNames,
     handlers: Vec<ExceptionHandler>,
-    modules: HashMap<String, Value>,
+    modules: HashMap<String, Value>, // Identité du module -> objet module (le dictionnaire de ses exports)
+    imported: HashMap<usize, String>, // Globale -> module qui l'a définie par un `import` simple
+    importing: Vec<ModuleFrame>, // Modules dont le code s'exécute, du script principal au dernier import
+    module_dirs: HashMap<String, Option<PathBuf>>, // Module chargé -> dossier de ses imports relatifs
+    project_root: Option<PathBuf>, // Où chercher `packages/` : voir imports::project_root
+    bundle: Option<crate::bundle::Bundle>, // Programme embarqué par `aegis build` : ses imports ne lisent aucun fichier
+    call_names: Vec<String>,     // Noms des derniers arguments du prochain appel (CallNames)
+    spread_count: Option<usize>, // Nombre d'arguments après dépliage des `...liste` (SpreadArgs)
+    frame_floor: usize,          // Première frame de l'exécution imbriquée en cours (run_nested)
+    scheduler: scheduler::Scheduler,
+    open_upvalues: Vec<Rc<RefCell<Upvalue>>>, // Upvalues qui désignent encore la pile, triées par slot
+    wide: bool,                  // L'instruction en cours est préfixée par Wide
+    max_frames: usize,
+    max_stack: usize,
+    nested_depth: usize,         // Nombre d'exécutions imbriquées en cours (run_nested)
+    error_line: Option<usize>,   // Ligne d'une erreur sortie d'une exécution imbriquée sans être rattrapée
+}
+
+/// Source d'un module importé -> bytecode relogeable, ses globales étant résolues dans `globals`
+/// sous le préfixe du module (voir compiler::scoped_name).
+pub fn compile_module(module: &crate::imports::Module, globals: GlobalNames) -> Result<Chunk, String> {
+    // FRONTEND (Source -> AST)
+    // We reuse the v1 compiler pipeline to get instructions
+    let statements = crate::compiler::compile(&module.source)
+        .map_err(|errors| errors.into_iter()
+            .map(|d| d.with_file(&module.name).to_string())
+            .collect::<Vec<_>>()
+            .join("\n"))?;
+    let exports = crate::vm::compiler::module_exports(&statements);
+    let instructions: Vec<crate::ast::Instruction> = statements.into_iter().map(|s| s.kind).collect();
+
+    // BACKEND (AST -> Bytecode)
+    // The compiler SHARES the global_names with the importer, but the module's own globals
+    // are prefixed by its identity: two modules may both declare 'helper' without clashing.
+    // Global ids are written on four bytes, so that the cached bytecode can be relinked by another VM.
+    let mut module_compiler = crate::vm::compiler::Compiler::for_module(&module.id, globals);
+
+    // CRITICAL: We force GLOBAL scope (0) so 'var' and 'func' become SET_GLOBAL
+    module_compiler.scope_depth = 0;
+    module_compiler.declare_exports(exports);
+
+    for instr in instructions {
+        module_compiler.compile_instruction(instr);
+    }
+    module_compiler.take_errors()
+        .map_err(|e| format!("Failed to import '{}': {}", module.name, e))?;
+    Ok(module_compiler.chunk)
+}
+
+// Module en cours d'exécution : son identité (voir crate::imports), son nom dans les erreurs
+// et le dossier de ses imports relatifs
+struct ModuleFrame {
+    id: String,
+    name: String,
+    dir: Option<PathBuf>,
+}
+
+// Status.Idle : variante simple ; Shape.Circle : constructeur à appeler
+fn enum_variant(e: &Rc<EnumData>, name: &str) -> Result<Value, String> {
+    let index = e.variants.iter().position(|(v_name, _)| v_name == name)
+        .ok_or_else(|| format!("Enum '{}' has no variant '{}'", e.name, name))?;
+    Ok(Value::Variant(Rc::new(VariantData { enum_data: e.clone(), index, values: Vec::new() })))
+}
+
+// Slot désigné par une upvalue de `open_upvalues` (toujours ouverte)
+fn open_slot(upvalue: &Rc<RefCell<Upvalue>>) -> usize {
+    match *upvalue.borrow() {
+        Upvalue::Open(slot) => slot,
+        Upvalue::Closed(_) => unreachable!("closed upvalue in the open list"),
+    }
+}
+
+// Les méthodes reçoivent `this` en paramètre 0, qui n'est pas compté dans les messages d'arité
+fn is_method(f: &FunctionData) -> bool {
+    f.params.first().is_some_and(|(name, _)| name == "this")
+}
+
+fn callable_label(f: &FunctionData) -> String {
+    if f.name.is_empty() {
+        "Anonymous function".to_string()
+    } else if is_method(f) {
+        format!("Method '{}'", f.name)
+    } else {
+        format!("Function '{}'", f.name)
+    }
+}
+
+// "Function 'get' expects 1 to 2 arguments, got 3"
+fn arity_error(f: &FunctionData, arg_count: usize) -> String {
+    let offset = usize::from(is_method(f));
+    let min = f.required.saturating_sub(offset);
+    let max = f.params.len().saturating_sub(offset + usize::from(f.variadic));
+    let (expected, plural) = if f.variadic {
+        (format!("at least {}", min), min != 1)
+    } else if min == max {
+        (min.to_string(), min != 1)
+    } else {
+        (format!("{} to {}", min, max), true)
+    };
+    format!("{} expects {} argument{}, got {}", callable_label(f), expected, if plural { "s" } else { "" }, arg_count.saturating_sub(offset))
+}
+
+// Range les arguments d'un appel dans l'ordre des paramètres : positionnels d'abord, puis
+// nommés, le surplus dans le `...rest`. Un paramètre absent vaut Null et son bit est levé
+// dans le masque renvoyé : le prologue de la fonction lui donnera sa valeur par défaut.
+// Les arguments nommés sans paramètre du même nom terminent le `...rest`, avec leurs noms.
+fn bind_arguments(f: &FunctionData, mut args: Vec<Value>, names: Vec<String>) -> Result<(Vec<Value>, u64, Option<NamedRest>), String> {
+    let arg_count = args.len();
+    let fixed = f.params.len() - usize::from(f.variadic);
+    let named = args.split_off(args.len() - names.len());
+
+    let mut slots: Vec<Option<Value>> = vec![None; fixed];
+    let mut rest = Vec::new();
+    for (i, value) in args.into_iter().enumerate() {
+        if i < fixed {
+            slots[i] = Some(value);
+        } else if f.variadic {
+            rest.push(value);
+        } else {
+            return Err(arity_error(f, arg_count));
+        }
+    }
+
+    let mut rest_names: Vec<String> = Vec::new();
+    for (name, value) in names.into_iter().zip(named) {
+        let Some(index) = f.params[..fixed].iter().position(|(p_name, _)| *p_name == name && name != "this") else {
+            if !f.variadic {
+                return Err(format!("{} has no parameter named '{}'", callable_label(f), name));
+            }
+            if rest_names.contains(&name) {
+                return Err(format!("{} got multiple values for argument '{}'", callable_label(f), name));
+            }
+            rest_names.push(name);
+            rest.push(value);
+            continue;
+        };
+        if slots[index].is_some() {
+            return Err(format!("{} got multiple values for argument '{}'", callable_label(f), name));
+        }
+        slots[index] = Some(value);
+    }
+
+    let mut missing = 0u64;
+    let mut bound = Vec::with_capacity(f.params.len());
+    for (i, slot) in slots.into_iter().enumerate() {
+        match slot {
+            Some(value) => bound.push(value),
+            None if i < f.required => {
+                return Err(format!("{} is missing argument '{}'", callable_label(f), f.params[i].0));
+            },
+            None if i < 64 => {
+                missing |= 1 << i;
+                bound.push(Value::Null);
+            },
+            None => return Err(format!("{}: too many parameters with default values", callable_label(f))),
+        }
+    }
+    let mut named_rest = None;
+    if f.variadic {
+        let len = rest.len();
+        let list = Rc::new(RefCell::new(rest));
+        if !rest_names.is_empty() {
+            named_rest = Some(NamedRest { list: list.clone(), len, names: rest_names });
+        }
+        bound.push(Value::List(list));
+    }
+    Ok((bound, missing, named_rest))
 }
 
 impl VM {
-    pub fn new(main_chunk: Chunk, global_names: Rc<RefCell<HashMap<String, u8>>>, args: Vec<String>) -> Self {
+    pub fn new(main_chunk: Chunk, global_names: GlobalNames, args: Vec<String>) -> Self {
         let main_func = Value::Function(Rc::new(FunctionData {
+            name: "<script>".to_string(),
             params: vec![],
+            required: 0,
+            variadic: false,
             ret_type: None,
-            chunk: main_chunk,
-            env: None
+            is_generator: false,
+            is_async: false,
+            chunk: Rc::new(main_chunk),
+            captures: Vec::new(),
+            upvalues: Vec::new()
         }));
 
         // Le script principal est la première "fonction" exécutée
@@ -64,7 +250,11 @@ impl VM {
             closure: main_func, // Utilise la closure
             ip: 0,
             slot_offset: 0,
-            class_context: None
+            class_context: None,
+            match_bases: Vec::new(),
+            missing_args: 0,
+            named_rest: None,
+            generator: None
         };
 
         // 1. On détermine la taille nécessaire
@@ -77,51 +267,94 @@ impl VM {
 
         let mut vm = VM {
             frames: Vec::with_capacity(64),
-            stack: Vec::with_capacity(STACK_MAX),
+            stack: Vec::with_capacity(4096),
             globals: vec![Value::Null; initial_size],
             global_names,
             handlers: Vec::new(),
-            modules: HashMap::new()
+            modules: HashMap::new(),
+            importing: Vec::new(),
+            module_dirs: HashMap::new(),
+            imported: HashMap::new(),
+            project_root: None,
+            bundle: None,
+            call_names: Vec::new(),
+            spread_count: None,
+            frame_floor: 0,
+            scheduler: scheduler::Scheduler::default(),
+            open_upvalues: Vec::new(),
+            wide: false,
+            max_frames: FRAMES_MAX,
+            max_stack: STACK_MAX,
+            nested_depth: 0,
+            error_line: None
         };
 
         vm.frames.push(main_frame);
 
-        let natives = crate::native::get_all_names();
-
-        // Sécurité : On ne peut pas avoir plus de 256 globales avec des ID sur u8
-        if natives.len() > 256 {
-            panic!("Trop de fonctions natives pour la VM v2 (>256)");
-        }
-
-        for (i, name) in natives.into_iter().enumerate() {
-            vm.globals[i] = Value::Native(name);
+        // Les natives sont résolues une fois ici : un appel passe ensuite directement par le pointeur
+        for (name, native) in crate::native::entries() {
+            let id = vm.global_names.borrow_mut().resolve(&name);
+            if id >= vm.globals.len() {
+                vm.globals.resize(id + 1, Value::Null);
+            }
+            vm.globals[id] = Value::Native(Rc::new(NativeData { name: name.into(), func: native.func, min_args: native.min_args }));
         }
 
-        let args_values: Vec<Value> = args.iter().map(|s| Value::String(s.clone())).collect();
+        let args_values: Vec<Value> = args.iter().map(|s| Value::String(s.as_str().into())).collect();
         let args_list = Value::List(Rc::new(RefCell::new(args_values)));
 
-        // On doit trouver l'ID de "_ARGS" (ou un nom réservé)
-        // Astuce : On l'ajoute manuellement à global_names et globals
-        {
-            let mut names = vm.global_names.borrow_mut();
-            if !names.contains_key("__ARGS__") {
-                let id = names.len() as u8;
-                names.insert("__ARGS__".to_string(), id);
-                // Si jamais on dépasse la taille initiale (peu probable avec le max(..., 256))
-                if id as usize >= vm.globals.len() {
-                    vm.globals.resize((id + 1) as usize, Value::Null);
-                }
-                vm.globals[id as usize] = args_list;
-            } else {
-                // Si __ARGS__ existe déjà (compilé), on récupère son ID
-                let id = *names.get("__ARGS__").unwrap();
-                vm.globals[id as usize] = args_list;
-            }
+        // Nom réservé, ajouté à global_names s'il n'a pas été compilé
+        let id = vm.global_names.borrow_mut().resolve("__ARGS__");
+        if id >= vm.globals.len() {
+            vm.globals.resize(id + 1, Value::Null);
         }
+        vm.globals[id] = args_list;
 
         vm
     }
 
+    /// Nombre maximal de frames d'appel et de valeurs sur la pile.
+    pub fn set_limits(&mut self, max_frames: usize, max_stack: usize) {
+        self.max_frames = max_frames;
+        self.max_stack = max_stack;
+    }
+
+    /// Fichier du script principal : ses imports sont cherchés à partir de son dossier.
+    pub fn set_script_path(&mut self, path: &Path) {
+        let id = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let dir = path.parent().map(|dir| if dir.as_os_str().is_empty() { Path::new(".") } else { dir });
+        self.importing = vec![ModuleFrame {
+            id: id.display().to_string(),
+            name: path.display().to_string(),
+            dir: dir.map(Path::to_path_buf),
+        }];
+        self.project_root = dir.map(crate::imports::project_root);
+    }
+
+    /// Programme d'un exécutable produit par `aegis build` : les imports sont servis par le bundle.
+    pub fn set_bundle(&mut self, bundle: crate::bundle::Bundle) {
+        self.bundle = Some(bundle);
+    }
+
+    // Vérifié avant chaque nouvelle frame : une récursion infinie devient une erreur attrapable
+    fn check_limits(&self) -> Result<(), String> {
+        if self.frames.len() >= self.max_frames {
+            return Err(format!("Stack overflow: more than {} nested calls", self.max_frames));
+        }
+        if self.stack.len() > self.max_stack {
+            return Err(format!("Stack overflow: more than {} values on the stack", self.max_stack));
+        }
+        Ok(())
+    }
+
+    // Nom (attribut, méthode, type) rangé dans les constantes : partagé, sans copie
+    fn constant_name(&mut self, idx: usize) -> Rc<str> {
+        match &self.current_frame().chunk().constants[idx] {
+            Value::String(s) => s.clone(),
+            other => other.to_string().into(),
+        }
+    }
+
     // Helper pour récupérer la frame courante sans se battre avec le borrow checker
     fn current_frame(&mut self) -> &mut CallFrame {
         self.frames.last_mut().expect("No code to execute")
@@ -144,54 +377,82 @@ impl VM {
         // On vérifie d'abord si l'IP est au bout du code de la frame actuelle
         if self.current_frame().ip >= self.current_frame().chunk().code.len() {
             if self.frames.len() > 1 {
-                self.frames.pop();
+                let frame = self.frames.pop().expect("No frame to pop");
+                self.close_upvalues(frame.slot_offset);
                 return Ok(true); // On continue sur la frame parente
             } else {
                 return Ok(false); // Plus de frames, fin du programme
             }
         }
 
-        // 2. FETCH
-        let byte = self.read_byte();
-        let op: OpCode = byte.into();
+        // 2. FETCH (Wide : les opérandes de l'instruction suivante sont sur quatre octets)
+        let mut op = self.read_opcode()?;
+        self.wide = matches!(op, OpCode::Wide);
+        if self.wide {
+            op = self.read_opcode()?;
+        }
 
         // EXECUTE WITH INTERCEPTION
         let result = self.execute_op(op);
 
-        match result {
-            Ok(keep_going) => Ok(keep_going),
-            Err(msg) => {
-                if let Some(handler) = self.handlers.pop() {
-                    // 1. Unwind frames
-                    while self.frames.len() > handler.frame_index + 1 {
-                        self.frames.pop();
-                    }
-                    
-                    // 2. Restore Stack - C'EST LA CLÉ
-                    // On coupe brutalement la pile à la hauteur enregistrée lors du 'try'
-                    if handler.stack_height <= self.stack.len() {
-                        self.stack.truncate(handler.stack_height);
-                    } else {
-                        // Corruption grave : la pile est plus petite qu'au début du try !
-                        return Err("Critical VM Error: Stack corrupted during unwind".into());
-                    }
-                    
-                    // 3. Push Error
-                    self.push(Value::String(msg));
-                    
-                    // 4. Jump
-                    self.current_frame().ip = handler.catch_ip;
-                    Ok(true) 
-                } else {
-                    Err(msg)
+        result.or_else(|msg| self.throw(msg))
+    }
+
+    // Lève une erreur : saut au catch le plus proche, ou Err si aucun try ne l'attrape
+    fn throw(&mut self, msg: String) -> Result<bool, String> {
+        // Un appel interrompu ne doit pas laisser ses arguments nommés au suivant
+        self.call_names.clear();
+        self.spread_count = None;
+
+        // Une exécution imbriquée (callback, générateur) ne rattrape que ses propres try :
+        // les autres erreurs remontent à l'instruction qui l'a lancée
+        let floor = self.frame_floor;
+        if let Some(handler) = self.handlers.pop_if(|h| h.frame_index >= floor) {
+            // 1. Unwind frames
+            while self.frames.len() > handler.frame_index + 1 {
+                // Un générateur interrompu par une exception ne peut plus reprendre
+                if let Some(generator) = self.frames.pop().and_then(|f| f.generator) {
+                    generator.borrow_mut().state = GeneratorState::Done;
                 }
             }
+            
+            // 2. Restore Stack - C'EST LA CLÉ
+            // On coupe brutalement la pile à la hauteur enregistrée lors du 'try'
+            if handler.stack_height <= self.stack.len() {
+                self.close_upvalues(handler.stack_height);
+                self.stack.truncate(handler.stack_height);
+            } else {
+                // Corruption grave : la pile est plus petite qu'au début du try !
+                return Err("Critical VM Error: Stack corrupted during unwind".into());
+            }
+            
+            // 3. Push Error
+            self.push(Value::String(msg.into()));
+            
+            // 4. Jump
+            self.current_frame().ip = handler.catch_ip;
+            self.error_line = None;
+            Ok(true) 
+        } else {
+            Err(msg)
         }
     }
 
     pub fn run(&mut self) -> Result<(), String> {
+        let result = self.run_loop();
+        let unhandled = self.finish_loop();
+        result.and(unhandled)
+    }
+
+    fn run_loop(&mut self) -> Result<(), String> {
         loop {
-            match self.step() {
+            let result = match self.step() {
+                // Fin du script principal : la boucle d'événements termine les tâches lancées
+                Ok(false) => self.finish_main().or_else(|e| self.throw(e)),
+                other => other,
+            };
+
+            match result.or_else(|e| self.recover(e)) {
                 Ok(true) => continue, // Continue loop
                 Ok(false) => break,   // End of program
                 Err(e) => {
@@ -213,15 +474,42 @@ impl VM {
             self.push(arg.clone());
         }
 
-        // 2. On prépare la Frame (comme OpCode::Call)
+        // 2. On note la profondeur de la frame que l'appel va empiler
+        // (une native ou un générateur n'en empile pas : le résultat est déjà sur la pile)
+        let start_depth = self.frames.len() + 1;
+
+        // 3. On prépare la Frame (comme OpCode::Call)
         // Note: call_value empile la nouvelle frame
         self.call_value(callable, args.len(), context)?;
 
-        // 3. On note la profondeur actuelle de la pile de frames
-        let start_depth = self.frames.len();
-
         // 4. BOUCLE SECONDAIRE : On exécute tant qu'on n'est pas revenu au niveau d'avant
-        // C'est ici la magie : on fait tourner la VM "manuellement" pour ce callback
+        self.run_nested(start_depth)?;
+
+        // 5. Le résultat est sur la pile (la valeur de retour du callback)
+        // Normalement, `OpCode::Return` a laissé la valeur de retour sur la pile
+        if self.stack.is_empty() {
+             return Ok(Value::Null); // Sécurité
+        }
+
+        Ok(self.pop())
+    }
+
+    // C'est ici la magie : on fait tourner la VM "manuellement" jusqu'au retour de la frame `start_depth`
+    fn run_nested(&mut self, start_depth: usize) -> Result<(), String> {
+        if self.nested_depth >= NESTED_MAX {
+            return Err(format!("Stack overflow: more than {} nested callbacks", NESTED_MAX));
+        }
+        let outer_floor = std::mem::replace(&mut self.frame_floor, start_depth - 1);
+        let wide = self.wide;
+        self.nested_depth += 1;
+        let result = self.run_frames(start_depth);
+        self.nested_depth -= 1;
+        self.frame_floor = outer_floor;
+        self.wide = wide;
+        result
+    }
+
+    fn run_frames(&mut self, start_depth: usize) -> Result<(), String> {
         while self.frames.len() >= start_depth {
             if self.frames.is_empty() {
                 return Err("VM Panic: Call stack exhausted during sync execution".into());
@@ -233,18 +521,15 @@ impl VM {
                 Err(e) => {
                     // Si une erreur survient et n'est pas attrapée par un try/catch interne,
                     // elle remonte ici. On doit propager l'erreur et arrêter la mini-VM.
-                    return Err(self.runtime_error(e));
+                    // Elle reste brute (un catch plus haut reçoit le message d'origine) : seule sa ligne est notée
+                    if self.error_line.is_none() {
+                        self.error_line = Some(self.current_line());
+                    }
+                    return Err(e);
                 }
             }
         }
-
-        // 5. Le résultat est sur la pile (la valeur de retour du callback)
-        // Normalement, `OpCode::Return` a laissé la valeur de retour sur la pile
-        if self.stack.is_empty() {
-             return Ok(Value::Null); // Sécurité
-        }
-
-        Ok(self.pop())
+        Ok(())
     }
 
     #[inline(always)]
@@ -254,12 +539,28 @@ impl VM {
             OpCode::Return => {
                 let result = self.pop(); // La valeur de retour
 
-                // On détruit la frame
+                // On détruit la frame : les closures qui capturent ses variables en gardent la valeur
                 let frame = self.frames.pop().expect("No frame to return from");
+                self.close_upvalues(frame.slot_offset);
+
+                // Un `return` dans un try : ses handlers disparaissent avec la frame
+                let frame_index = self.frames.len();
+                while self.handlers.pop_if(|h| h.frame_index >= frame_index).is_some() {}
+
+                // Fin d'un générateur : les next() suivants ne renvoient plus rien
+                if let Some(generator) = &frame.generator {
+                    generator.borrow_mut().state = GeneratorState::Done;
+                }
+
+                // `return` au niveau d'un script (principal ou ligne du REPL, seules frames en 0) :
+                // il s'arrête comme arrivé au bout de son code
+                if frame.slot_offset == 0 {
+                    return Ok(!self.frames.is_empty());
+                }
 
+                // Fin d'une fonction async : la tâche est terminée
                 if self.frames.is_empty() {
-                    // Fin du script principal
-                    return Ok(true);
+                    return self.finish_current(result);
                 }
 
                 // Nettoyage de la pile : on enlève les arguments et les variables locales de la fonction
@@ -267,8 +568,27 @@ impl VM {
                 self.stack.truncate(frame.slot_offset - 1);
                 self.push(result);
             }
+            OpCode::Yield => self.op_yield()?,
+            OpCode::Await => return self.op_await(),
+            OpCode::Wide => return Err("Invalid bytecode: Wide prefix followed by Wide".into()),
+            OpCode::GetIter => {
+                let iterable = self.pop();
+                let iterator = self.get_iter(iterable)?;
+                self.push(iterator);
+            },
+            OpCode::IterNext => {
+                let offset = self.read_short() as usize;
+                let iterator = self.pop();
+                match self.iter_next(&iterator)? {
+                    Some(value) => self.push(value),
+                    None => {
+                        self.push(Value::Null);
+                        self.current_frame().ip += offset;
+                    }
+                }
+            },
             OpCode::Call => {
-                let arg_count = self.read_byte() as usize;
+                let arg_count = self.read_arg_count();
                 
                 // SÉCURITÉ : Vérifier qu'on a assez d'éléments sur la pile
                 if self.stack.len() < 1 + arg_count {
@@ -287,8 +607,8 @@ impl VM {
                 println!("{}", val);
             }
             OpCode::LoadConst => {
-                let idx = self.read_byte();
-                let val = self.current_frame().chunk().constants[idx as usize].clone();
+                let idx = self.read_operand();
+                let val = self.current_frame().chunk().constants[idx].clone();
                 self.push(val);
             }
             OpCode::Add => {
@@ -312,25 +632,28 @@ impl VM {
                     let a = self.pop();
 
                     match (a, b) {
-                        // Float + Float
-                        (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 + v2)),
-                        // Float + Int (Coercition)
-                        (Value::Float(v1), Value::Integer(v2)) => {
-                            self.push(Value::Float(v1 + v2 as f64))
-                        }
-                        (Value::Integer(v1), Value::Float(v2)) => {
-                            self.push(Value::Float(v1 as f64 + v2))
-                        }
-
                         // String + N'importe quoi
                         (Value::String(s1), val2) => {
-                            self.push(Value::String(format!("{}{}", s1, val2)));
+                            self.push(Value::String(format!("{}{}", s1, val2).into()));
                         }
                         (val1, Value::String(s2)) => {
-                            self.push(Value::String(format!("{}{}", val1, s2)));
+                            self.push(Value::String(format!("{}{}", val1, s2).into()));
                         }
 
-                        _ => return Err("Type error in ADD".into()),
+                        (a, b) => match (a.numeric(), b.numeric()) {
+                            // Variante simple + Int (ordinal)
+                            (Value::Integer(v1), Value::Integer(v2)) => self.push(Value::Integer(v1 + v2)),
+                            // Float + Float
+                            (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 + v2)),
+                            // Float + Int (Coercition)
+                            (Value::Float(v1), Value::Integer(v2)) => {
+                                self.push(Value::Float(v1 + v2 as f64))
+                            }
+                            (Value::Integer(v1), Value::Float(v2)) => {
+                                self.push(Value::Float(v1 as f64 + v2))
+                            }
+                            _ => return Err("Type error in ADD".into()),
+                        },
                     }
                 }
             }
@@ -349,9 +672,10 @@ impl VM {
                 }
                 // SLOW PATH : Le reste (Float...)
                 else {
-                    let b = self.pop();
-                    let a = self.pop();
+                    let b = self.pop().numeric();
+                    let a = self.pop().numeric();
                     match (a, b) {
+                        (Value::Integer(v1), Value::Integer(v2)) => self.push(Value::Integer(v1 - v2)),
                         (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 - v2)),
                         (Value::Integer(v1), Value::Float(v2)) => self.push(Value::Float(v1 as f64 - v2)),
                         (Value::Float(v1), Value::Integer(v2)) => self.push(Value::Float(v1 - v2 as f64)),
@@ -360,8 +684,8 @@ impl VM {
                 }
             },
             OpCode::Mul => {
-                let b = self.pop();
-                let a = self.pop();
+                let b = self.pop().numeric();
+                let a = self.pop().numeric();
                 match (a, b) {
                     (Value::Integer(v1), Value::Integer(v2)) => self.push(Value::Integer(v1 * v2)),
                     (Value::Float(v1), Value::Float(v2)) => self.push(Value::Float(v1 * v2)),
@@ -371,8 +695,8 @@ impl VM {
                 }
             },
             OpCode::Div => {
-                let b = self.pop();
-                let a = self.pop();
+                let b = self.pop().numeric();
+                let a = self.pop().numeric();
                 match (a, b) {
                     (Value::Integer(v1), Value::Integer(v2)) => {
                         if v2 == 0 { return Err("Division by zero".into()); }
@@ -385,7 +709,7 @@ impl VM {
                 }
             },
             OpCode::SetGlobal => {
-                let idx = self.read_byte() as usize;
+                let idx = self.read_operand();
                 let val = self.pop();
 
                 // Si l'index est plus grand que le tableau, on agrandit (sécurité)
@@ -396,7 +720,7 @@ impl VM {
                 self.globals[idx] = val;
             }
             OpCode::GetGlobal => {
-                let idx = self.read_byte() as usize;
+                let idx = self.read_operand();
     
                 // 1. On récupère la valeur brute. 
                 // Si l'index est hors limite (ne devrait pas arriver si le compilateur est bon), on met Null.
@@ -418,7 +742,7 @@ impl VM {
                 self.push(val);
             },
             OpCode::GetLocal => {
-                let slot_idx = self.read_byte() as usize;
+                let slot_idx = self.read_operand();
                 let abs_index = self.current_frame().slot_offset + slot_idx;
                 
                 // VERSION SAFE
@@ -430,7 +754,7 @@ impl VM {
                 }
             }
             OpCode::SetLocal => {
-                let slot_idx = self.read_byte() as usize;
+                let slot_idx = self.read_operand();
                 let abs_index = self.current_frame().slot_offset + slot_idx;
 
                 let val = self.stack.last().expect("Stack empty").clone(); // Peek
@@ -464,13 +788,14 @@ impl VM {
                 let offset = self.read_short();
                 // On soustrait l'offset à l'IP (on recule)
                 self.current_frame().ip -= offset as usize;
+                gc::collect_if_needed();
             }
             OpCode::Pop => {
                 self.pop();
             }
             OpCode::Modulo => {
-                let b = self.pop();
-                let a = self.pop();
+                let b = self.pop().numeric();
+                let a = self.pop().numeric();
                 match (a, b) {
                     (Value::Integer(v1), Value::Integer(v2)) => self.push(Value::Integer(v1 % v2)),
                     _ => return Err("Type error %".into()),
@@ -479,16 +804,16 @@ impl VM {
             OpCode::Equal => {
                 let b = self.pop();
                 let a = self.pop();
-                self.push(Value::Boolean(a == b));
+                self.push(Value::Boolean(a.equals(&b)));
             }
             OpCode::NotEqual => {
                 let b = self.pop();
                 let a = self.pop();
-                self.push(Value::Boolean(a != b));
+                self.push(Value::Boolean(!a.equals(&b)));
             }
             OpCode::Greater => {
-                let b = self.pop();
-                let a = self.pop();
+                let b = self.pop().numeric();
+                let a = self.pop().numeric();
                 if let (Value::Integer(v1), Value::Integer(v2)) = (a, b) {
                     self.push(Value::Boolean(v1 > v2));
                 } else {
@@ -496,8 +821,8 @@ impl VM {
                 }
             }
             OpCode::GreaterEqual => {
-                let b = self.pop();
-                let a = self.pop();
+                let b = self.pop().numeric();
+                let a = self.pop().numeric();
                 if let (Value::Integer(v1), Value::Integer(v2)) = (a, b) {
                     self.push(Value::Boolean(v1 >= v2));
                 } else {
@@ -519,8 +844,8 @@ impl VM {
                 } 
                 // SLOW PATH
                 else {
-                    let b = self.pop();
-                    let a = self.pop();
+                    let b = self.pop().numeric();
+                    let a = self.pop().numeric();
                     if let (Value::Integer(v1), Value::Integer(v2)) = (&a, &b) {
                         self.push(Value::Boolean(v1 < v2));
                     } else if let (Value::Float(v1), Value::Float(v2)) = (&a, &b) {
@@ -533,8 +858,8 @@ impl VM {
                 }
             }
             OpCode::LessEqual => {
-                let b = self.pop();
-                let a = self.pop();
+                let b = self.pop().numeric();
+                let a = self.pop().numeric();
                 if let (Value::Integer(v1), Value::Integer(v2)) = (a, b) {
                     self.push(Value::Boolean(v1 <= v2));
                 } else {
@@ -577,7 +902,7 @@ impl VM {
                 self.push(Value::Integer(a >> b));
             }
             OpCode::MakeList => {
-                let count = self.read_byte() as usize;
+                let count = self.read_operand();
                 let mut items = Vec::new();
                 // On dépile dans l'ordre inverse pour retrouver l'ordre initial
                 for _ in 0..count {
@@ -589,8 +914,108 @@ impl VM {
                 ))));
             }
             OpCode::Method => self.op_method()?,
+            OpCode::GetIndex => self.op_get_index()?,
+            OpCode::SetIndex => self.op_set_index()?,
+            OpCode::Slice => self.op_slice()?,
+            OpCode::MatchBegin => {
+                let level = self.read_operand();
+                let base = self.stack.len() - 1;
+                let frame = self.current_frame();
+                // Un `throw` rattrapé dans la même frame peut laisser des niveaux périmés
+                frame.match_bases.truncate(level);
+                frame.match_bases.push(base);
+            },
+            OpCode::GetMatch => {
+                let level = self.read_operand();
+                let slot = self.read_operand();
+                let base = self.current_frame().match_bases[level];
+                let val = self.stack[base + slot].clone();
+                self.push(val);
+            },
+            OpCode::MatchEnd => {
+                let level = self.read_operand();
+                let result = self.pop();
+                let base = self.current_frame().match_bases[level];
+                self.current_frame().match_bases.truncate(level);
+                self.close_upvalues(base);
+                self.stack.truncate(base);
+                self.push(result);
+            },
+            OpCode::MatchList => {
+                let count = self.read_operand();
+                let has_rest = self.read_operand() == 1;
+                let matched = match self.pop() {
+                    Value::List(l) => {
+                        let len = l.borrow().len();
+                        if has_rest { len >= count } else { len == count }
+                    },
+                    _ => false,
+                };
+                self.push(Value::Boolean(matched));
+            },
+            OpCode::MatchKey => {
+                let key_idx = self.read_operand();
+                // Clé null : motif {} (n'importe quel dictionnaire ou instance)
+                let key = match &self.current_frame().chunk().constants[key_idx] {
+                    Value::Null => None,
+                    k => Some(k.to_string()),
+                };
+                let matched = match (self.pop(), key) {
+                    (Value::Dict(d), Some(key)) => d.borrow().contains_key(&key),
+                    (Value::Instance(inst), Some(key)) => inst.borrow().contains(&key),
+                    (Value::Variant(v), Some(key)) => v.get(&key).is_some(),
+                    (Value::Dict(_) | Value::Instance(_) | Value::Variant(_), None) => true,
+                    _ => false,
+                };
+                self.push(Value::Boolean(matched));
+            },
+            OpCode::MatchClass => {
+                let class = self.pop();
+                let val = self.pop();
+                let matched = match (&class, &val) {
+                    // Shape.Circle(r) : même enum et même variante
+                    (Value::Variant(variant), Value::Variant(v)) => Rc::ptr_eq(&v.enum_data, &variant.enum_data) && v.index == variant.index,
+                    (Value::Variant(_), _) => false,
+                    (Value::Class(class_rc), Value::Instance(inst)) => {
+                        let mut lookup = Some(inst.borrow().class.clone());
+                        let mut found = false;
+                        while let Some(c) = lookup {
+                            if Rc::ptr_eq(&c, class_rc) {
+                                found = true;
+                                break;
+                            }
+                            lookup = c.parent_ref.clone();
+                        }
+                        found
+                    },
+                    (Value::Class(_), _) => false,
+                    _ => return Err(format!("Invalid class pattern: '{}' is not a class or an enum variant", class)),
+                };
+                self.push(Value::Boolean(matched));
+            },
+            OpCode::MatchRange => {
+                let end = self.pop();
+                let start = self.pop();
+                let val = self.pop();
+                let as_float = |v: &Value| match v {
+                    Value::Integer(n) => Some(*n as f64),
+                    Value::Float(f) => Some(*f),
+                    _ => None,
+                };
+                let matched = match (as_float(&val), as_float(&start), as_float(&end)) {
+                    (Some(v), Some(a), Some(b)) => a <= v && v < b,
+                    _ => false,
+                };
+                self.push(Value::Boolean(matched));
+            },
+            OpCode::GetField => {
+                let position = self.read_operand();
+                let obj = self.pop();
+                let val = self.op_get_field(&obj, position)?;
+                self.push(val);
+            },
             OpCode::MakeDict => {
-                let count = self.read_byte() as usize; // Nombre d'éléments total sur la pile (clés + valeurs)
+                let count = self.read_operand(); // Nombre d'éléments total sur la pile (clés + valeurs)
                 let num_pairs = count / 2;
                 let mut dict = HashMap::new();
 
@@ -607,8 +1032,17 @@ impl VM {
                 self.push(Value::Dict(Rc::new(RefCell::new(dict))));
             }
             OpCode::GetAttr => {
-                let name_idx = self.read_byte();
-                let attr_name = self.current_frame().chunk().constants[name_idx as usize].to_string();
+                let name_idx = self.read_operand();
+                let cache_idx = self.read_operand();
+
+                // Champ déjà vu à ce site pour cette forme : lecture directe
+                if let Some((inst, slot)) = self.cached_field(cache_idx, 1) {
+                    let val = inst.borrow().slots[slot].clone();
+                    *self.stack.last_mut().expect("Stack underflow") = val;
+                    return Ok(true);
+                }
+
+                let attr_name = self.constant_name(name_idx);
                 let obj = self.pop();
 
                 match obj {
@@ -622,7 +1056,7 @@ impl VM {
                         let mut found_prop = None;
                         
                         while let Some(c) = lookup_class {
-                            if let Some(prop) = c.properties.get(&attr_name) {
+                            if let Some(prop) = c.properties.get(&*attr_name) {
                                 found_prop = Some((prop.clone(), c.clone()));
                                 break;
                             }
@@ -642,7 +1076,15 @@ impl VM {
                         }
 
                         // 2. Champs classiques
-                        let val = inst.borrow().fields.get(&attr_name).cloned().unwrap_or(Value::Null);
+                        let data = inst.borrow();
+                        let val = match data.shape.slot(&attr_name) {
+                            Some(slot) => {
+                                self.cache_field(cache_idx, &data.shape, slot);
+                                data.slots.get(slot).cloned().unwrap_or(Value::Null)
+                            }
+                            None => Value::Null,
+                        };
+                        drop(data);
                         self.push(val);
                     }
                     Value::Class(class_rc) => {
@@ -650,7 +1092,7 @@ impl VM {
 
                         // 1. Check Static Properties
                         // Pour l'instant on cherche juste dans la classe elle-même (pas d'héritage statique complexe)
-                        if let Some(prop) = class_rc.static_properties.get(&attr_name) {
+                        if let Some(prop) = class_rc.static_properties.get(&*attr_name) {
                             if let Some(getter) = &prop.getter {
                                 // 'this' pour un statique est la Classe elle-même
                                 self.push(getter.clone());
@@ -663,23 +1105,36 @@ impl VM {
                         }
 
                         // 2. Static Fields
-                        if let Some(val) = class_rc.static_fields.borrow().get(&attr_name) {
+                        if let Some(val) = class_rc.static_fields.borrow().get(&*attr_name) {
                             self.push(val.clone());
                         } 
                         // 3. Static Methods
-                        else if let Some(method) = class_rc.static_methods.get(&attr_name) {
+                        else if let Some(method) = class_rc.static_methods.get(&*attr_name) {
                             self.push(method.clone());
                         } else {
                             return Err(format!("Unknown static member '{}'", attr_name));
                         }
                     }
                     Value::Dict(d) => {
-                        let val = d.borrow().get(&attr_name).cloned().unwrap_or(Value::Null);
+                        let val = d.borrow().get(&*attr_name).cloned();
+                        let val = match val {
+                            Some(val) => val,
+                            None => bind_builtin(Value::Dict(d), &attr_name).unwrap_or(Value::Null),
+                        };
                         self.push(val);
                     }
                     Value::Enum(e) => {
-                        // Accès direct sans borrow() car pas de RefCell
-                        let val = e.get(&attr_name).cloned().unwrap_or(Value::Null);
+                        self.push(enum_variant(&e, &attr_name)?);
+                    }
+                    Value::Variant(v) => {
+                        let val = v.get(&attr_name).cloned()
+                            .ok_or_else(|| format!("Variant '{}.{}' has no field '{}'", v.enum_data.name, v.name(), attr_name))?;
+                        self.push(val);
+                    }
+                    // Méthode native sans appel : `var push = list.push`
+                    other if BuiltinType::of(&other).is_some() => {
+                        let val = bind_builtin(other.clone(), &attr_name)
+                            .ok_or_else(|| format!("Unknown {} method '{}'", other.type_name(), attr_name))?;
                         self.push(val);
                     }
                     // On pourrait ajouter d'autres types (ex: Module)
@@ -693,8 +1148,15 @@ impl VM {
                 }
             }
             OpCode::SetAttr => {
-                let name_idx = self.read_byte();
-                let attr_name = self.current_frame().chunk().constants[name_idx as usize].to_string();
+                let name_idx = self.read_operand();
+                let cache_idx = self.read_operand();
+
+                // Champ existant ou ajout déjà vu à ce site pour cette forme : écriture directe
+                if self.cached_store(cache_idx) {
+                    return Ok(true);
+                }
+
+                let attr_name = self.constant_name(name_idx);
 
                 let val = self.pop(); // La valeur à assigner
                 let obj = self.pop(); // L'objet
@@ -708,7 +1170,7 @@ impl VM {
                         let mut lookup_class = Some(class_rc.clone());
                         let mut found_prop = None;
                         while let Some(c) = lookup_class {
-                            if let Some(prop) = c.properties.get(&attr_name) {
+                            if let Some(prop) = c.properties.get(&*attr_name) {
                                 found_prop = Some((prop.clone(), c.clone()));
                                 break;
                             }
@@ -731,14 +1193,22 @@ impl VM {
                         }
 
                         // 2. Champs classiques
-                        inst.borrow_mut().fields.insert(attr_name, val.clone());
+                        gc::note_store(&Value::Instance(inst.clone()), &val);
+                        let before = inst.borrow().shape.clone();
+                        let slot = inst.borrow_mut().set(&attr_name, val.clone());
+                        let after = inst.borrow().shape.clone();
+                        if Rc::ptr_eq(&before, &after) {
+                            self.cache_field(cache_idx, &after, slot);
+                        } else if before.transition(&attr_name).is_some_and(|next| Rc::ptr_eq(&next, &after)) {
+                            self.cache_transition(cache_idx, &before, &after);
+                        }
                         self.push(val);
                     }
                     Value::Class(class_rc) => {
                         self.check_access(&class_rc, &attr_name)?;
 
                         // 1. Check Static Properties
-                        if let Some(prop) = class_rc.static_properties.get(&attr_name) {
+                        if let Some(prop) = class_rc.static_properties.get(&*attr_name) {
                             if let Some(setter) = &prop.setter {
                                 self.push(setter.clone());
                                 self.push(Value::Class(class_rc.clone())); // arg 0: this (Class)
@@ -751,14 +1221,15 @@ impl VM {
                         }
 
                         // 2. Static Fields
-                        class_rc.static_fields.borrow_mut().insert(attr_name, val.clone());
+                        class_rc.static_fields.borrow_mut().insert(attr_name.to_string(), val.clone());
                         self.push(val);
                     }
                     Value::Dict(d) => {
-                        d.borrow_mut().insert(attr_name, val.clone());
+                        gc::note_store(&Value::Dict(d.clone()), &val);
+                        d.borrow_mut().insert(attr_name.to_string(), val.clone());
                         self.push(val);
                     }
-                    Value::Enum(_) => {
+                    Value::Enum(_) | Value::Variant(_) => {
                         return Err("Cannot modify an Enum member (Enums are immutable)".into());
                     },
                     _ => return Err("Impossible d'assigner un attribut sur ce type".into()),
@@ -777,12 +1248,12 @@ impl VM {
                 std::io::stdin().read_line(&mut buffer).unwrap();
                 let input = buffer.trim().to_string();
 
-                self.push(Value::String(input));
+                self.push(Value::String(input.into()));
             }
 
             OpCode::Class => {
-                let idx = self.read_byte();
-                let template_val = self.current_frame().chunk().constants[idx as usize].clone();
+                let idx = self.read_operand();
+                let template_val = self.current_frame().chunk().constants[idx].clone();
                 
                 if let Value::Class(template_data) = template_val {
                     // ---------------------------------------------------------
@@ -794,10 +1265,10 @@ impl VM {
                             if let Value::Class(parent_rc) = parent_val {
                                 final_parent_ref = Some(parent_rc.clone());
                             } else {
-                                return Err(format!("Parent '{}' is not a class", parent_name));
+                                return Err(format!("Parent '{}' is not a class", plain_name(parent_name)));
                             }
                         } else {
-                            return Err(format!("Parent class '{}' not found", parent_name));
+                            return Err(format!("Parent class '{}' not found", plain_name(parent_name)));
                         }
                     }
 
@@ -817,10 +1288,10 @@ impl VM {
                             if let Value::Interface(iface_rc) = val {
                                 resolved_interfaces.push(iface_rc.clone());
                             } else {
-                                return Err(format!("'{}' is not an interface", iface_name));
+                                return Err(format!("'{}' is not an interface", plain_name(iface_name)));
                             }
                         } else {
-                            return Err(format!("Interface '{}' not found", iface_name));
+                            return Err(format!("Interface '{}' not found", plain_name(iface_name)));
                         }
                     }
 
@@ -848,6 +1319,9 @@ impl VM {
                         // On injecte les interfaces résolues
                         interfaces: resolved_interfaces.clone(),
                         interfaces_names: template_data.interfaces_names.clone(),
+
+                        // Chaque classe créée a ses propres formes
+                        shape: Shape::root(),
                     });
 
                     // ---------------------------------------------------------
@@ -920,139 +1394,72 @@ impl VM {
                 }
             },
 
-            OpCode::MakeEnum => {
-                let count = self.read_byte() as usize; // Nombre total d'éléments sur la pile (clés + valeurs)
-                let num_pairs = count / 2;
-                let mut map = HashMap::new();
-
-                // On dépile dans l'ordre inverse
-                for _ in 0..num_pairs {
-                    let val = self.pop();
-                    let key_val = self.pop();
-                    let key = key_val.as_str().unwrap_or("?".to_string());
-                    map.insert(key, val);
-                }
-
-                // On crée un Value::Enum SANS RefCell
-                self.push(Value::Enum(Rc::new(map)));
-            }
-
             OpCode::MakeClosure => {
                 let function_val = self.pop();
-                
-                if let Value::Function(rc_fn) = function_val {
-                    let env_rc = Environment::new_global();
-                    
-                    // 1. Extraction (Attention : il faut accéder aux champs du Rc)
-                    let (parent_params, parent_locals_map, slot_offset) = {
-                        let frame = self.current_frame();
-                        
-                        let pp = if let Value::Function(parent_rc) = &frame.closure {
-                            Some(parent_rc.params.clone()) // On clone le Vec<Params>
-                        } else {
-                            None
-                        };
-                        
-                        let locals = frame.chunk().locals_map.clone();
-                        (pp, locals, frame.slot_offset)
-                    };
-
-                    // 2. Population Phase (Fill the environment)
-                    // SCOPE START: We create a block to contain the mutable borrow
-                    {
-                        let mut env_inner = env_rc.borrow_mut();
-
-                        // A. Capture Arguments
-                        if let Some(parent_params) = parent_params {
-                            for (i, (name, _)) in parent_params.iter().enumerate() {
-                                if slot_offset + i < self.stack.len() {
-                                    let val = self.stack[slot_offset + i].clone();
-                                    env_inner.variables.insert(name.clone(), val);
-                                }
-                            }
-                        }
-
-                        // B. Capture Locals (The fix for your "line" variable)
-                        for (idx, name) in parent_locals_map {
-                            let abs_index = slot_offset + (idx as usize);
-                            if abs_index < self.stack.len() {
-                                let val = self.stack[abs_index].clone();
-                                // We insert into the closure environment
-                                env_inner.variables.insert(name, val);
-                            }
-                        }
-                    } 
-                    // SCOPE END: 'env_inner' is dropped here. 'env_rc' is now free!
+                let Value::Function(rc_fn) = function_val else {
+                    return Err("MakeClosure on non-function value".into());
+                };
 
-                    // 3. Creation (On doit créer un NOUVEAU FunctionData)
-                    // Note: rc_fn.chunk est un clone couteux ici ? 
-                    // Non, Chunk contient des Vec. Idéalement Chunk devrait être dans un Rc aussi,
-                    // mais FunctionData est déjà un gros progrès.
-                    
-                    let new_data = FunctionData {
-                        params: rc_fn.params.clone(),
-                        ret_type: rc_fn.ret_type.clone(),
-                        chunk: rc_fn.chunk.clone(), // On clone le chunk (lourd, mais nécessaire pour l'instant)
-                        env: Some(env_rc)
-                    };
+                // Chaque variable capturée devient une upvalue partagée avec la frame qui la possède
+                let upvalues = rc_fn.captures.iter().map(|capture| match *capture {
+                    Capture::Local(slot) => {
+                        let abs_index = self.current_frame().slot_offset + slot;
+                        self.capture_upvalue(abs_index)
+                    },
+                    Capture::Match(level, slot) => {
+                        let abs_index = self.current_frame().match_bases[level] + slot;
+                        self.capture_upvalue(abs_index)
+                    },
+                    Capture::Upvalue(index) => match &self.current_frame().closure {
+                        Value::Function(parent) => parent.upvalues[index].clone(),
+                        _ => unreachable!("CallFrame closure is not a function"),
+                    },
+                }).collect();
 
-                    let closure = Value::Function(Rc::new(new_data));
-                    self.push(closure);
-                } else {
-                    panic!("MakeClosure on non-function value");
-                }
+                // Le chunk est partagé (Rc) : créer une closure ne copie pas le code
+                let closure = FunctionData { upvalues, ..(*rc_fn).clone() };
+                self.push(Value::Function(Rc::new(closure)));
             },
-
-            OpCode::GetFreeVar => {
-                let name_idx = self.read_byte();
-                // Récupération du nom
-                let name = {
-                    let frame = self.current_frame();
-                    if let Value::Function(rc_fn) = &frame.closure {
-                        rc_fn.chunk.constants[name_idx as usize].to_string()
-                    } else {
-                        panic!("Frame sans closure fonctionnelle ?");
-                    }
+            OpCode::GetUpvalue => {
+                let index = self.read_operand();
+                let upvalue = self.frame_upvalue(index);
+                let val = match &*upvalue.borrow() {
+                    Upvalue::Open(slot) => self.stack[*slot].clone(),
+                    Upvalue::Closed(val) => val.clone(),
                 };
-
-                let mut val_to_push = None;
-
-                // 1. Essai : Closure Environment
-                {
-                    let frame = self.current_frame();
-                    // On match le Rc
-                    if let Value::Function(rc_fn) = &frame.closure {
-                        if let Some(env) = &rc_fn.env { // on accède au champ .env du struct
-                            if let Some(val) = env.borrow().variables.get(&name) {
-                                val_to_push = Some(val.clone());
-                            }
-                        }
-                    }
+                self.push(val);
+            },
+            OpCode::SetUpvalue => {
+                let index = self.read_operand();
+                let val = self.stack.last().expect("Stack underflow in SET_UPVALUE").clone();
+                let upvalue = self.frame_upvalue(index);
+                match &mut *upvalue.borrow_mut() {
+                    Upvalue::Open(slot) => self.stack[*slot] = val,
+                    Upvalue::Closed(closed) => {
+                        gc::note_upvalue(&upvalue, &val);
+                        *closed = val
+                    },
                 }
-
-                // 2. Essai : Global Environment (Fallback)
-                if val_to_push.is_none() {
-                    let global_id_opt = self.global_names.borrow().get(&name).cloned();
-                    
-                    if let Some(id) = global_id_opt {
-                        let idx = id as usize;
-                        
-                        // Même logique que GetGlobal
-                        if idx < self.globals.len() && !matches!(self.globals[idx], Value::Null) {
-                            val_to_push = Some(self.globals[idx].clone());
-                        } else {
-                            // Tentative de résolution native tardive
-                            val_to_push = self.resolve_lazy_native(idx);
+            },
+            OpCode::CloseUpvalue => {
+                self.close_upvalues(self.stack.len() - 1);
+                self.pop();
+            },
+            OpCode::GetFreeGlobal => {
+                // Variable inconnue du compilateur dans une fonction : c'est une globale,
+                // qui doit exister au moment de l'appel
+                let idx = self.read_operand();
+                let mut val = self.globals.get(idx).cloned().unwrap_or(Value::Null);
+                if matches!(val, Value::Null) {
+                    match self.resolve_lazy_native(idx) {
+                        Some(native_val) => val = native_val,
+                        None => {
+                            let name = plain_name(self.global_names.borrow().name(idx).unwrap_or_default()).to_string();
+                            return Err(format!("Variable introuvable (ni locale, ni globale) : '{}'", name));
                         }
                     }
                 }
-
-                // 3. Résultat
-                if let Some(val) = val_to_push {
-                    self.push(val);
-                } else {
-                    return Err(format!("Variable introuvable (ni locale, ni globale) : '{}'", name));
-                }
+                self.push(val);
             },
 
             OpCode::Dup => {
@@ -1060,6 +1467,10 @@ impl VM {
                 let val = self.stack.last().expect("Stack underflow in DUP").clone();
                 self.push(val);
             },
+            OpCode::Dup2 => {
+                let start = self.stack.len() - 2;
+                self.stack.extend_from_within(start..);
+            },
 
             OpCode::SetupExcept => {
                 let offset = self.read_short();
@@ -1079,69 +1490,120 @@ impl VM {
             },
 
             OpCode::Import => {
-                let path_idx = self.read_byte();
-                let path = self.current_frame().chunk().constants[path_idx as usize].to_string();
-
-                // 1. CACHE CHECK
-                // If module is already loaded, we don't re-execute it (prevents side-effect duplication)
-                if self.modules.contains_key(&path) {
-                    self.push(Value::Null); // Import returns Null
-                } else {
-                    // 2. LOAD FILE
-                    // Reads relative to CWD. You might want to handle absolute paths or include paths later.
-                    let source = std::fs::read_to_string(&path)
-                        .map_err(|e| format!("Failed to import '{}': {}", path, e))?;
-
-                    // 3. FRONTEND (Source -> AST)
-                    // We reuse the v1 compiler pipeline to get instructions
-                    let json_ast = crate::compiler::compile(&source)?;
-                    let statements = crate::loader::parse_block(&json_ast)?;
-                    let instructions: Vec<crate::ast::Instruction> = statements.into_iter().map(|s| s.kind).collect();
-
-                    // 4. BACKEND (AST -> Bytecode)
-                    // CRITICAL: We create a compiler that SHARES the global_names with the main VM.
-                    // This ensures that 'namespace System' in the module gets the same Global ID 
-                    // as 'System' in the main script.
-                    let mut module_compiler = crate::vm::compiler::Compiler::new_with_globals(self.global_names.clone());
-                    
-                    // CRITICAL: We force GLOBAL scope (0) so 'var' and 'func' become SET_GLOBAL
-                    module_compiler.scope_depth = 0; 
+                let path_idx = self.read_operand();
+                let mode_idx = self.read_operand();
+                let module_idx = self.read_operand();
+                let path = self.current_frame().chunk().constants[path_idx].to_string();
+                let mode = self.current_frame().chunk().constants[mode_idx].clone();
+                // The module containing the statement, not the one running: an import in a function
+                // resolves and binds the same way wherever the function is called from
+                let importer = match &self.current_frame().chunk().constants[module_idx] {
+                    Value::String(id) => Some(id.to_string()),
+                    _ => None,
+                };
 
-                    for instr in instructions {
-                        module_compiler.compile_instruction(instr);
+                // 1. RESOLUTION
+                // Bundled program: the build already resolved every import. Otherwise, see crate::imports
+                let (module, module_chunk) = match &self.bundle {
+                    Some(bundle) => {
+                        let (id, bytes) = bundle.module(importer.as_deref().unwrap_or(""), &path)
+                            .ok_or_else(|| format!("Failed to import '{}': module not found in the executable", path))?;
+                        let module = ModuleFrame { id: id.to_string(), name: id.to_string(), dir: None };
+                        if self.modules.contains_key(&module.id) {
+                            (module, None)
+                        } else {
+                            let chunk = crate::bytecode::load_module(bytes, &mut self.global_names.borrow_mut())
+                                .map_err(|e| format!("Failed to import '{}': {}", path, e))?;
+                            (module, Some(chunk))
+                        }
                     }
-                    
-                    // 5. EXECUTION
-                    let module_chunk = module_compiler.chunk;
-                    
-                    // Wrap module code in a function to execute it
-                    let module_func = Value::Function(Rc::new(FunctionData {
-                        params: vec![],
-                        ret_type: None,
-                        chunk: module_chunk,
-                        env: None
-                    }));
-                    
-                    // Run the module synchronously.
-                    // Its instructions (SET_GLOBAL) will write directly to 'self.globals'.
-                    let module_result = self.run_callable_sync(module_func, vec![], None)?;
+                    None => {
+                        let dir = match (&importer, self.importing.first()) {
+                            (Some(id), _) => self.module_dirs.get(id).cloned().flatten(),
+                            (None, Some(script)) => script.dir.clone(),
+                            (None, None) => std::env::current_dir().ok(), // REPL
+                        };
+                        let root = self.project_root.clone()
+                            .or_else(|| std::env::current_dir().ok().map(|dir| crate::imports::project_root(&dir)));
+                        let found = crate::imports::resolve(&path, dir.as_deref(), root.as_deref())?;
+                        let module = ModuleFrame { id: found.id.clone(), name: found.name.clone(), dir: found.dir.clone() };
+                        if self.modules.contains_key(&module.id) || self.importing.iter().any(|frame| frame.id == module.id) {
+                            (module, None)
+                        } else {
+                            // 2. BYTECODE CACHE
+                            // An unchanged module was already compiled: its bytecode is relinked to our globals
+                            let cached = crate::bytecode::cached_module(&found.id, &found.source, &mut self.global_names.borrow_mut());
+                            let chunk = match cached {
+                                Some(chunk) => chunk,
+                                None => {
+                                    let chunk = compile_module(&found, self.global_names.clone())?;
+                                    crate::bytecode::cache_module(&found.id, &found.source, &chunk, &self.global_names.borrow());
+                                    chunk
+                                }
+                            };
+                            (module, Some(chunk))
+                        }
+                    }
+                };
 
-                    // 6. UPDATE CACHE
-                    self.modules.insert(path.clone(), Value::Boolean(true));
-                    
-                    // 7. RETURN
-                    self.push(module_result);
+                // 3. CIRCULAR IMPORTS
+                // A module that is still running is half initialised: importing it again is an error
+                if let Some(start) = self.importing.iter().position(|frame| frame.id == module.id) {
+                    let cycle: Vec<&str> = self.importing[start..].iter().map(|frame| frame.name.as_str())
+                        .chain(std::iter::once(module.name.as_str()))
+                        .collect();
+                    return Err(format!("Circular import: {}", cycle.join(" -> ")));
                 }
+
+                // 4. LOADED ONCE
+                // If module is already loaded, we don't re-execute it (prevents side-effect duplication)
+                let Some(module_chunk) = module_chunk else {
+                    let exports = self.modules.get(&module.id).cloned().unwrap_or(Value::Null);
+                    self.bind_module(&path, &module.id, exports, &mode, importer.as_deref())?;
+                    return Ok(true);
+                };
+
+                // 5. EXECUTION
+                // Wrap module code in a function to execute it
+                let module_func = Value::Function(Rc::new(FunctionData {
+                    name: "<module>".to_string(),
+                    params: vec![],
+                    required: 0,
+                    variadic: false,
+                    ret_type: None,
+                    is_generator: false,
+                    is_async: false,
+                    chunk: Rc::new(module_chunk),
+                    captures: Vec::new(),
+                    upvalues: Vec::new()
+                }));
+
+                // Run the module synchronously.
+                // Its instructions (SET_GLOBAL) will write directly to 'self.globals'.
+                let id = module.id.clone();
+                self.module_dirs.insert(id.clone(), module.dir.clone());
+                self.importing.push(module);
+                let module_result = self.run_callable_sync(module_func, vec![], None);
+                self.importing.pop();
+                module_result?;
+
+                // 6. UPDATE CACHE
+                // The module object holds the values of its exports once it has run
+                let exports = self.module_exports(&id);
+                self.modules.insert(id.clone(), exports.clone());
+
+                // 7. RETURN
+                self.bind_module(&path, &id, exports, &mode, importer.as_deref())?;
             },
             OpCode::CheckType => {
-                let type_name_idx = self.read_byte();
-                let expected_type = self.current_frame().chunk().constants[type_name_idx as usize].to_string();
+                let type_name_idx = self.read_operand();
+                let expected_type = self.constant_name(type_name_idx);
                 
                 // On regarde la valeur sur le sommet de la pile (sans la pop)
                 let val = self.stack.last().expect("Stack underflow in CheckType");
                 
                 // Vérification
-                let is_valid = match (val, expected_type.as_str()) {
+                let is_valid = match (val, &*expected_type) {
                     (Value::Integer(_), "int") => true,
                     (Value::Float(_), "float") => true,
                     (Value::String(_), "string") => true,
@@ -1164,13 +1626,13 @@ impl VM {
             },
 
             OpCode::Super => {
-                let method_idx = self.read_byte();
-                let arg_count = self.read_byte() as usize;
-                let parent_idx = self.read_byte(); // Le 3ème argument
+                let method_idx = self.read_operand();
+                let arg_count = self.read_arg_count();
+                let parent_idx = self.read_operand(); // Le 3ème argument
 
                 let chunk = self.current_frame().chunk();
-                let method_name = chunk.constants[method_idx as usize].to_string();
-                let parent_name = chunk.constants[parent_idx as usize].to_string();
+                let method_name = chunk.constants[method_idx].to_string();
+                let parent_name = chunk.constants[parent_idx].to_string();
 
                 // L'objet 'this' est sur la pile, juste avant les args
                 let obj_idx = self.stack.len() - 1 - arg_count;
@@ -1187,7 +1649,7 @@ impl VM {
                     // On convertit Value::Class -> Rc<ClassData> tout de suite
                     let mut current_class_rc = match parent_class_val {
                         Value::Class(c) => c,
-                        _ => return Err(format!("'{}' n'est pas une classe", parent_name)),
+                        _ => return Err(format!("'{}' n'est pas une classe", plain_name(&parent_name))),
                     };
 
                     loop {
@@ -1210,9 +1672,59 @@ impl VM {
                         return Err(format!("Méthode '{}' introuvable dans super", method_name));
                     }
                 } else {
-                    return Err(format!("Classe parente '{}' introuvable", parent_name));
+                    return Err(format!("Classe parente '{}' introuvable", plain_name(&parent_name)));
                 }
             },
+            OpCode::ArgMissing => {
+                let slot = self.read_operand();
+                let missing = slot < 64 && self.current_frame().missing_args & (1 << slot) != 0;
+                self.push(Value::Boolean(missing));
+            },
+            OpCode::CallNames => {
+                let idx = self.read_operand();
+                let names: Vec<String> = match &self.current_frame().chunk().constants[idx] {
+                    Value::List(names) => names.borrow().iter().map(|n| n.to_string()).collect(),
+                    _ => return Err("CallNames: invalid names constant".into()),
+                };
+                self.call_names.extend(names);
+            },
+            OpCode::SpreadArgs => {
+                let idx = self.read_operand();
+                let written = self.read_operand();
+                let positions: Vec<usize> = match &self.current_frame().chunk().constants[idx] {
+                    Value::List(list) => list.borrow().iter().filter_map(|p| p.as_int().ok()).map(|p| p as usize).collect(),
+                    _ => return Err("SpreadArgs: invalid positions constant".into()),
+                };
+
+                // Les listes marquées sont remplacées par leurs éléments, à leur place.
+                // CallNames est déjà passé : les `call_names` sont les derniers arguments écrits.
+                let args_start = self.stack.len() - written;
+                let last_positional = written.checked_sub(self.call_names.len() + 1);
+                let args: Vec<Value> = self.stack.drain(args_start..).collect();
+                for (i, arg) in args.into_iter().enumerate() {
+                    if !positions.contains(&i) {
+                        self.push(arg);
+                        continue;
+                    }
+                    let Value::List(items) = arg else {
+                        return Err(format!("Cannot spread a '{}' value into arguments", arg.type_name()));
+                    };
+                    self.stack.extend(items.borrow().iter().cloned());
+
+                    // Le `...rest` de la fonction courante repasse ses arguments nommés avec leurs noms
+                    let forwarded = self.current_frame().named_rest.as_ref()
+                        .filter(|rest| Rc::ptr_eq(&rest.list, &items) && rest.len == items.borrow().len())
+                        .map(|rest| rest.names.clone());
+                    if let Some(mut names) = forwarded {
+                        if Some(i) != last_positional {
+                            return Err("A '...' list that holds named arguments must be the last positional argument".into());
+                        }
+                        names.append(&mut self.call_names);
+                        self.call_names = names;
+                    }
+                }
+                self.spread_count = Some(self.stack.len() - args_start);
+            },
             OpCode::MakeRange => {
                 let end_val = self.pop();
                 let start_val = self.pop();
@@ -1221,7 +1733,7 @@ impl VM {
                 let end = end_val.as_int().unwrap_or(0);
                 
                 // Par défaut, le step est 1
-                self.push(Value::Range(start, end, 1));
+                self.push(Value::range(start, end, 1));
             },
         }
 
@@ -1229,30 +1741,39 @@ impl VM {
     }
 
     fn op_method(&mut self) -> Result<(), String> {
-        let name_idx = self.read_byte();
-        let arg_count = self.read_byte() as usize;
-
-        // Name resolution
-        let method_name_val = &self.current_frame().chunk().constants[name_idx as usize];
-        let method_name = match method_name_val {
-            Value::String(s) => s.clone(),
-            _ => method_name_val.to_string(),
-        };
+        let name_idx = self.read_operand();
+        let arg_count = self.read_arg_count();
+        let cache_idx = self.read_operand();
 
         let obj_idx = self.stack.len() - 1 - arg_count;
         let obj = self.stack[obj_idx].clone();
 
+        // Méthode déjà trouvée à ce site pour cette classe : ni recherche ni check_access
+        if let Some((method, owner)) = self.cached_method(cache_idx, &obj) {
+            self.stack[obj_idx] = method.clone();
+            self.stack.insert(obj_idx + 1, obj);
+            return self.call_value(method, arg_count + 1, Some(owner));
+        }
+        // Méthode native déjà résolue (sauf dictionnaire : une clé du même nom passe avant)
+        if !matches!(obj, Value::Dict(_))
+            && let Some(ty) = BuiltinType::of(&obj)
+            && let Some(id) = self.cached_builtin(cache_idx, ty) {
+            return self.call_builtin(ty, id, obj_idx);
+        }
+
+        // Name resolution
+        let method_name = self.constant_name(name_idx);
+
         // 1. Instance Methods (POO)
         if let Value::Instance(inst) = &obj {
             // --- 1. REFLECTION (MÉTHODES NATIVES) ---
             // On vérifie si c'est une méthode d'introspection avant de chercher dans les classes
-            let handled = match method_name.as_str() {
+            let handled = match &*method_name {
                 
                 "get_properties" => {
-                    // Retourne la liste des clés du dictionnaire interne 'fields'
-                    let fields = &inst.borrow().fields;
-                    let keys: Vec<Value> = fields.keys()
-                        .map(|k| Value::String(k.clone()))
+                    // Retourne les noms des champs, dans l'ordre d'ajout
+                    let keys: Vec<Value> = inst.borrow().shape.names().iter()
+                        .map(|k| Value::String(k.as_str().into()))
                         .collect();
                     
                     // Résultat sur la stack à la place de l'objet
@@ -1268,7 +1789,7 @@ impl VM {
                     // L'argument 'name' est juste après l'objet sur la stack
                     let prop_name = self.stack[obj_idx + 1].as_str().map_err(|_| "Property name must be a string")?;
                     
-                    let val = if let Some(v) = inst.borrow().fields.get(&prop_name) {
+                    let val = if let Some(v) = inst.borrow().get(&prop_name) {
                         v.clone()
                     } else {
                         Value::Null
@@ -1286,7 +1807,8 @@ impl VM {
                     let val = self.stack[obj_idx + 2].clone();
 
                     // On insère directement dans le stockage interne
-                    inst.borrow_mut().fields.insert(prop_name, val);
+                    gc::note_store(&self.stack[obj_idx], &val);
+                    inst.borrow_mut().set(&prop_name, val);
 
                     self.stack[obj_idx] = Value::Null;
                     self.stack.truncate(obj_idx + 1); // On retire les 2 arguments
@@ -1306,8 +1828,11 @@ impl VM {
             
             loop {
                 // A. Méthode présente ?
-                if let Some(method_val) = current_class_rc.methods.get(&method_name) {
+                if let Some(method_val) = current_class_rc.methods.get(&*method_name) {
                     self.check_access(&current_class_rc, &method_name)?;
+                    if let Value::Function(f) = method_val {
+                        self.cache_method(cache_idx, &inst.borrow().class, f, &current_class_rc);
+                    }
                     self.stack[obj_idx] = method_val.clone();
                     self.stack.insert(obj_idx + 1, obj.clone()); 
                     self.call_value(
@@ -1334,7 +1859,7 @@ impl VM {
             let mut current_static_lookup = inst.borrow().class.clone();
     
             loop {
-                if let Some(method_val) = current_static_lookup.static_methods.get(&method_name) {
+                if let Some(method_val) = current_static_lookup.static_methods.get(&*method_name) {
                     // A. Vérification de sécurité
                     self.check_access(&current_static_lookup, &method_name)?;
 
@@ -1371,11 +1896,11 @@ impl VM {
             // To support static inheritance: Loop on parent_ref like in Instance.
 
             // --- REFLECTION STATIQUE ---
-            let handled = match method_name.as_str() {
+            let handled = match &*method_name {
                 "get_static_properties" => {
                     // Retourne les propriétés statiques
                     let keys: Vec<Value> = class_rc.static_properties.keys()
-                        .map(|k| Value::String(k.clone()))
+                        .map(|k| Value::String(k.as_str().into()))
                         .collect();
                     self.stack[obj_idx] = Value::List(Rc::new(RefCell::new(keys)));
                     self.stack.truncate(obj_idx + 1);
@@ -1391,7 +1916,7 @@ impl VM {
             
             let mut current_lookup = class_rc.clone();
             loop {
-                if let Some(method_val) = current_lookup.static_methods.get(&method_name) {
+                if let Some(method_val) = current_lookup.static_methods.get(&*method_name) {
                     // A. Security Check
                     self.check_access(&current_lookup, &method_name)?;
 
@@ -1419,13 +1944,29 @@ impl VM {
             return Err(format!("Static method '{}' not found on class '{}'", method_name, class_rc.name).into());
         }
 
+        // Shape.Circle(2) : appel du constructeur de la variante
+        if let Value::Enum(e) = &obj {
+            let constructor = enum_variant(e, &method_name)?;
+            self.stack[obj_idx] = constructor.clone();
+            return self.call_value(constructor, arg_count, None);
+        }
+
+        // Méthodes déclarées dans le corps de l'enum : la variante devient 'this'
+        if let Value::Variant(v) = &obj {
+            let method_val = v.enum_data.methods.get(&*method_name).cloned()
+                .ok_or_else(|| format!("Method '{}' not found on enum '{}'", method_name, v.enum_data.name))?;
+            self.stack[obj_idx] = method_val.clone();
+            self.stack.insert(obj_idx + 1, obj.clone());
+            return self.call_value(method_val, arg_count + 1, None);
+        }
+
         if let Value::Dict(d) = &obj {
             // On regarde si la clé existe dans le dictionnaire
-            let field_val = d.borrow().get(&method_name).cloned();
+            let field_val = d.borrow().get(&*method_name).cloned();
 
             if let Some(val) = field_val {
                 // Si la valeur trouvée est une fonction (ou native), on l'exécute
-                if matches!(val, Value::Function(..) | Value::Native(..)) {
+                if matches!(val, Value::Function(..) | Value::Native(..) | Value::BoundMethod(..)) {
                     
                     // On remplace le Dictionnaire sur la pile par la Fonction trouvée
                     // Stack avant : [Dict, Arg1, Arg2...]
@@ -1441,530 +1982,477 @@ impl VM {
             }
         }
 
-        // 2. Native Methods
+        // 2. Méthodes natives des types de base
+        if let Some(ty) = BuiltinType::of(&obj) {
+            let id = match self.cached_builtin(cache_idx, ty) {
+                Some(id) => id,
+                None => {
+                    let id = ty.find(&method_name)
+                        .ok_or_else(|| format!("Unknown {} method '{}'", obj.type_name(), method_name))?;
+                    self.set_cache(cache_idx, |_| InlineCache::Builtin { ty, id });
+                    id
+                }
+            };
+            return self.call_builtin(ty, id, obj_idx);
+        }
+
+        match obj {
+            Value::Instance(_) => Err(format!("Instance has no method '{}'", method_name)),
+            _ => Err(format!("Method '{}' not supported on {:?}", method_name, obj)),
+        }
+    }
+
+    // Appel de la méthode native `id` du type `ty` : pile [.., objet, args...] -> [.., résultat]
+    fn call_builtin(&mut self, ty: BuiltinType, id: usize, obj_idx: usize) -> Result<(), String> {
+        let method = ty.method(id);
+        if !self.call_names.is_empty() {
+            self.call_names.clear();
+            return Err(format!("Method '{}' of {} does not accept named arguments", method.name, self.stack[obj_idx].type_name()));
+        }
         let args: Vec<Value> = self.stack.drain((obj_idx + 1)..).collect();
-        let _obj_popped = self.pop(); // Pop object
-
-        let result = match obj {
-            Value::List(l) => match method_name.as_str() {
-                "push" => { l.borrow_mut().push(args[0].clone()); Value::Null },
-                "pop" => l.borrow_mut().pop().unwrap_or(Value::Null),
-                "at" => { 
-                    let idx = args[0].as_int().unwrap_or(0) as usize;
-                    l.borrow().get(idx).cloned().unwrap_or(Value::Null) 
-                },
-                "len" => Value::Integer(l.borrow().len() as i64),
+        let obj = self.pop();
+        if args.len() < method.min_args {
+            return Err(format!("Method '{}' of {} expects at least {} argument(s), got {}", method.name, obj.type_name(), method.min_args, args.len()));
+        }
+        let result = (method.func)(self, &obj, args)?;
+        self.push(result);
+        Ok(())
+    }
 
-                "reverse" => {
-                    l.borrow_mut().reverse();
-                    Value::List(l.clone())
-                },
+    // x[i] : index négatif compté depuis la fin, null hors limites (comme `at`)
+    fn op_get_index(&mut self) -> Result<(), String> {
+        let key = self.pop();
+        let obj = self.pop();
 
-                "contains" => {
-                    let target = &args[0];
-                    let exists = l.borrow().contains(target); // Nécessite que Value implémente PartialEq (c'est le cas)
-                    Value::Boolean(exists)
-                },
+        let result = match &obj {
+            Value::List(l) => {
+                let list = l.borrow();
+                resolve_index(&key, list.len())?.map(|i| list[i].clone()).unwrap_or(Value::Null)
+            },
+            Value::String(s) => {
+                resolve_index(&key, s.chars().count())?
+                    .and_then(|i| s.chars().nth(i))
+                    .map(|c| Value::String(c.to_string().into()))
+                    .unwrap_or(Value::Null)
+            },
+            Value::Bytes(b) => {
+                let bytes = b.borrow();
+                resolve_index(&key, bytes.len())?.map(|i| Value::Integer(bytes[i] as i64)).unwrap_or(Value::Null)
+            },
+            Value::Range(r) => {
+                resolve_index(&key, range_len(r.start, r.end, r.step))?
+                    .map(|i| Value::Integer(r.start + i as i64 * r.step))
+                    .unwrap_or(Value::Null)
+            },
+            Value::Dict(d) => d.borrow().get(&dict_key(&key)?).cloned().unwrap_or(Value::Null),
+            Value::Instance(inst) => return self.call_index_method(inst.clone(), "get_index", vec![key]),
+            _ => return Err(format!("Type '{}' is not indexable", obj.type_name())),
+        };
 
-                "join" => {
-                    let sep = if args.is_empty() { "".to_string() } else { args[0].as_str().unwrap_or_default() };
-                    
-                    let list_borrow = l.borrow();
-                    // On convertit tout en string et on joint
-                    let strings: Vec<String> = list_borrow.iter().map(|v| v.to_string()).collect();
-                    
-                    Value::String(strings.join(&sep))
-                },
+        self.push(result);
+        Ok(())
+    }
 
-                "is_empty" => Value::Boolean(l.borrow().is_empty()),
+    // x[i] = v : laisse la valeur sur la pile, comme SetAttr
+    fn op_set_index(&mut self) -> Result<(), String> {
+        let val = self.pop();
+        let key = self.pop();
+        let obj = self.pop();
+
+        match &obj {
+            Value::List(l) => {
+                let len = l.borrow().len();
+                let i = resolve_index(&key, len)?
+                    .ok_or_else(|| format!("List index {} out of range (length {})", key, len))?;
+                gc::note_store(&obj, &val);
+                l.borrow_mut()[i] = val.clone();
+            },
+            Value::Bytes(b) => {
+                let len = b.borrow().len();
+                let i = resolve_index(&key, len)?
+                    .ok_or_else(|| format!("Bytes index {} out of range (length {})", key, len))?;
+                let byte = match val {
+                    Value::Integer(n) if (0..=255).contains(&n) => n as u8,
+                    _ => return Err(format!("Byte value must be an integer between 0 and 255, got {}", val)),
+                };
+                b.borrow_mut()[i] = byte;
+            },
+            Value::Dict(d) => {
+                gc::note_store(&obj, &val);
+                d.borrow_mut().insert(dict_key(&key)?, val.clone());
+            },
+            Value::Instance(inst) => return self.call_index_method(inst.clone(), "set_index", vec![key, val]),
+            Value::String(_) | Value::Range(..) => return Err(format!("Type '{}' is immutable", obj.type_name())),
+            _ => return Err(format!("Type '{}' does not support index assignment", obj.type_name())),
+        }
 
-                "first" => l.borrow().first().cloned().unwrap_or(Value::Null),
+        self.push(val);
+        Ok(())
+    }
 
-                "last" => l.borrow().last().cloned().unwrap_or(Value::Null),
+    // x[start:end] : mêmes règles que `slice` (bornes ramenées dans les limites), bornes négatives depuis la fin
+    fn op_slice(&mut self) -> Result<(), String> {
+        let end = self.pop();
+        let start = self.pop();
+        let obj = self.pop();
+
+        let result = match &obj {
+            Value::List(l) => {
+                let list = l.borrow();
+                let (from, to) = slice_bounds(&start, &end, list.len())?;
+                Value::List(Rc::new(RefCell::new(list[from..to].to_vec())))
+            },
+            Value::String(s) => {
+                let (from, to) = slice_bounds(&start, &end, s.chars().count())?;
+                Value::String(s.chars().skip(from).take(to - from).collect::<String>().into())
+            },
+            Value::Bytes(b) => {
+                let bytes = b.borrow();
+                let (from, to) = slice_bounds(&start, &end, bytes.len())?;
+                Value::Bytes(Rc::new(RefCell::new(bytes[from..to].to_vec())))
+            },
+            Value::Range(r) => {
+                let (from, to) = slice_bounds(&start, &end, range_len(r.start, r.end, r.step))?;
+                Value::range(r.start + from as i64 * r.step, r.start + to as i64 * r.step, r.step)
+            },
+            Value::Instance(inst) => return self.call_index_method(inst.clone(), "slice", vec![start, end]),
+            _ => return Err(format!("Type '{}' cannot be sliced", obj.type_name())),
+        };
 
-                "clear" => {
-                    l.borrow_mut().clear();
-                    Value::Null
-                },
+        self.push(result);
+        Ok(())
+    }
 
-                "reduce" => {
-                    // Usage: list.reduce(func(acc, val), initial_value)
-                    if args.len() < 2 { return Err("reduce expects (callback, initial)".into()); }
-                    
-                    let callback = args[0].clone();
-                    let mut accumulator = args[1].clone();
-                    let list_data = l.borrow().clone();
+    // Motif Point(x, y) : le champ qui porte le nom du n-ième paramètre de `init`
+    fn op_get_field(&mut self, obj: &Value, position: usize) -> Result<Value, String> {
+        if let Value::Variant(v) = obj {
+            return v.values.get(position).cloned()
+                .ok_or_else(|| format!("Variant '{}.{}' has {} field(s), the pattern expects more", v.enum_data.name, v.name(), v.values.len()));
+        }
+        let Value::Instance(inst) = obj else {
+            return Err(format!("Class pattern used on a '{}' value", obj.type_name()));
+        };
+        let class_rc = inst.borrow().class.clone();
 
-                    for item in list_data {
-                        // Le callback prend (acc, item) et retourne le nouvel acc
-                        accumulator = self.run_callable_sync(callback.clone(), vec![accumulator, item], None)?;
-                    }
-                    
-                    accumulator
-                },
+        let mut lookup = Some(class_rc.clone());
+        let mut init_params = None;
+        while let Some(c) = lookup {
+            if let Some(Value::Function(init)) = c.methods.get("init") {
+                init_params = Some(init.params.clone());
+                break;
+            }
+            lookup = c.parent_ref.clone();
+        }
 
-                "index_of" => {
-                    // Usage: list.index_of(value) -> int (ou -1)
-                    if args.len() < 1 { return Err("index_of attend 1 argument".into()); }
-                    let target = &args[0];
-                    
-                    let list = l.borrow();
-                    let index = list.iter().position(|x| x == target); // PartialEq fait le travail
-                    
-                    match index {
-                        Some(i) => Value::Integer(i as i64),
-                        None => Value::Integer(-1),
-                    }
-                },
+        // params[0] est `this`
+        let field = init_params
+            .and_then(|params| params.get(position + 1).map(|(name, _)| name.clone()))
+            .ok_or_else(|| format!("Class '{}' has no init parameter at position {} for this pattern", class_rc.name, position))?;
 
-                "find" => {
-                    if args.len() < 1 { return Err("find attend 1 callback".into()); }
-                    let callback = args[0].clone();
-                    
-                    let list_data = l.borrow().clone();
-                    
-                    // 1. Variable pour stocker le résultat (par défaut Null)
-                    let mut found_item = Value::Null;
-                    
-                    for item in list_data {
-                        let res = self.run_callable_sync(callback.clone(), vec![item.clone()], None)?;
-                        
-                        let is_found = match res {
-                            Value::Boolean(b) => b,
-                            Value::Null => false,
-                            Value::Integer(i) => i != 0,
-                            _ => true,
-                        };
-                        
-                        if is_found {
-                            found_item = item; // On stocke
-                            break;             // On arrête la recherche
-                        }
-                    }
-                    
-                    // 2. On retourne la valeur trouvée (ou Null) comme résultat du match
-                    found_item 
-                },
+        self.check_access(&class_rc, &field)?;
+        let val = inst.borrow().get(&field).cloned();
+        val.ok_or_else(|| format!("Class pattern: instance of '{}' has no field '{}' (init parameters must be stored in fields of the same name)", class_rc.name, field))
+    }
 
-                "sort" => {
-                    // 1. On clone le vecteur pour pouvoir le trier sans violer les règles d'emprunt (RefCell)
-                    // Cela permet aussi au callback de comparaison de lire la liste si nécessaire sans crash.
-                    let mut data = l.borrow().clone();
-                    
-                    // 2. Récupération du comparateur optionnel (fonction de callback)
-                    let comparator = if args.len() > 0 { Some(args[0].clone()) } else { None };
-                    
-                    // 3. Logique de Tri
-                    if let Some(comp_fn) = comparator {
-                        // --- CAS A : TRI PERSONNALISÉ ---
-                        // On utilise une variable pour capturer une erreur éventuelle survenue dans le callback Aegis
-                        let mut sort_error = None;
-                        
-                        data.sort_by(|a, b| {
-                            // Si une erreur a déjà eu lieu, on arrête de calculer (on renvoie Equal)
-                            if sort_error.is_some() { return std::cmp::Ordering::Equal; }
-                            
-                            // Appel Synchrone de la VM : on exécute la fonction Aegis
-                            match self.run_callable_sync(comp_fn.clone(), vec![a.clone(), b.clone()], None) {
-                                Ok(res) => {
-                                    // La convention standard : négatif = Less, positif = Greater, 0 = Equal
-                                    // On essaie de convertir en entier, sinon en float
-                                    let n = if let Ok(i) = res.as_int() { i as f64 } 
-                                            else { res.as_float().unwrap_or(0.0) };
-
-                                    if n < 0.0 { std::cmp::Ordering::Less }
-                                    else if n > 0.0 { std::cmp::Ordering::Greater }
-                                    else { std::cmp::Ordering::Equal }
-                                },
-                                Err(e) => {
-                                    // On capture l'erreur pour la remonter après le sort
-                                    sort_error = Some(e);
-                                    std::cmp::Ordering::Equal
-                                }
-                            }
-                        });
-                        
-                        // Si le tri a échoué à cause d'une erreur script, on la propage
-                        if let Some(e) = sort_error { return Err(e); }
-                        
-                    } else {
-                        // --- CAS B : TRI PAR DÉFAUT ---
-                        // Rust ne sait pas trier nativement nos Values sans implémenter Ord.
-                        // On implémente une logique "best effort".
-                        data.sort_by(|a, b| {
-                             match (a, b) {
-                                 // Comparaison d'entiers
-                                 (Value::Integer(i1), Value::Integer(i2)) => i1.cmp(i2),
-                                 // Comparaison de floats (partial_cmp peut renvoyer None pour NaN, on gère)
-                                 (Value::Float(f1), Value::Float(f2)) => f1.partial_cmp(f2).unwrap_or(std::cmp::Ordering::Equal),
-                                 // Mixte Int/Float
-                                 (Value::Integer(i), Value::Float(f)) => (*i as f64).partial_cmp(f).unwrap_or(std::cmp::Ordering::Equal),
-                                 (Value::Float(f), Value::Integer(i)) => f.partial_cmp(&(*i as f64)).unwrap_or(std::cmp::Ordering::Equal),
-                                 // Chaînes de caractères
-                                 (Value::String(s1), Value::String(s2)) => s1.cmp(s2),
-                                 // Fallback : Comparaison via représentation string (ex: "true" > "false")
-                                 (v1, v2) => v1.to_string().cmp(&v2.to_string())
-                             }
-                        });
-                    }
-                    
-                    // 4. On remplace le contenu de la liste originale par la version triée
-                    *l.borrow_mut() = data;
-                    
-                    Value::Null
-                },
+    // Surcharge de l'indexation par une classe : get_index(key), set_index(key, value), slice(start, end)
+    fn call_index_method(&mut self, inst: Rc<RefCell<InstanceData>>, name: &str, args: Vec<Value>) -> Result<(), String> {
+        let mut lookup = Some(inst.borrow().class.clone());
+        while let Some(class_rc) = lookup {
+            if let Some(method) = class_rc.methods.get(name) {
+                self.check_access(&class_rc, name)?;
+                let arg_count = args.len() + 1;
+                self.push(method.clone());
+                self.push(Value::Instance(inst.clone())); // arg 0: this
+                for arg in args {
+                    self.push(arg);
+                }
+                return self.call_value(method.clone(), arg_count, Some(class_rc.clone()));
+            }
+            lookup = class_rc.parent_ref.clone();
+        }
 
-                // --- Utility ---
+        let class_name = inst.borrow().class.name.clone();
+        Err(format!("Class '{}' does not support this operation (define a '{}' method)", class_name, name))
+    }
 
-                "slice" => {
-                    // Usage: list.slice(start, end_exclusive)
-                    let len = l.borrow().len();
-                    let start = args.get(0).and_then(|v| v.as_int().ok()).unwrap_or(0) as usize;
-                    let end = args.get(1).and_then(|v| v.as_int().ok()).unwrap_or(len as i64) as usize;
+    // --- UPVALUES ---
 
-                    // Clamping pour éviter les crashs
-                    let start = start.min(len);
-                    let end = end.min(len).max(start);
+    fn frame_upvalue(&mut self, index: usize) -> Rc<RefCell<Upvalue>> {
+        match &self.current_frame().closure {
+            Value::Function(rc_fn) => rc_fn.upvalues[index].clone(),
+            _ => panic!("CallFrame closure is not a function"),
+        }
+    }
 
-                    let list_borrow = l.borrow();
-                    // On crée une nouvelle liste avec la tranche
-                    let new_vec = list_borrow[start..end].to_vec();
-                    
-                    Value::List(Rc::new(RefCell::new(new_vec)))
-                },
-                
-                // --- FUNCTIONAL PROGRAMMING ---
-                
-                "map" => {
-                    let callback = args[0].clone();
-                    let list_data = l.borrow().clone(); // Clone to avoid RefCell borrow conflict during callback
-                    let mut new_list = Vec::new();
-
-                    for item in list_data {
-                        // On appelle la VM récursivement pour chaque élément !
-                        let res = self.run_callable_sync(callback.clone(), vec![item], None)?;
-                        new_list.push(res);
-                    }
-                    Value::List(Rc::new(RefCell::new(new_list)))
-                },
+    // Upvalue ouverte sur le slot `abs_index` : deux closures qui capturent la même variable
+    // partagent la même upvalue
+    fn capture_upvalue(&mut self, abs_index: usize) -> Rc<RefCell<Upvalue>> {
+        let position = self.open_upvalues.partition_point(|u| open_slot(u) < abs_index);
+        if let Some(existing) = self.open_upvalues.get(position)
+            && open_slot(existing) == abs_index {
+            return existing.clone();
+        }
+        let upvalue = Rc::new(RefCell::new(Upvalue::Open(abs_index)));
+        self.open_upvalues.insert(position, upvalue.clone());
+        upvalue
+    }
 
-                "filter" => {
-                    let callback = args[0].clone();
-                    let list_data = l.borrow().clone();
-                    let mut new_list = Vec::new();
+    // Les variables à partir de `from` quittent la pile : leurs upvalues gardent la dernière valeur
+    fn close_upvalues(&mut self, from: usize) {
+        while let Some(upvalue) = self.open_upvalues.pop_if(|u| open_slot(u) >= from) {
+            let slot = open_slot(&upvalue);
+            let val = self.stack.get(slot).cloned().unwrap_or(Value::Null);
+            gc::note_upvalue(&upvalue, &val);
+            *upvalue.borrow_mut() = Upvalue::Closed(val);
+        }
+    }
 
-                    for item in list_data {
-                        let res = self.run_callable_sync(callback.clone(), vec![item.clone()], None)?;
-                        // On garde si le résultat est "truthy"
-                        if matches!(res, Value::Boolean(true)) || (res.as_int().unwrap_or(0) != 0 && !matches!(res, Value::Null)) {
-                            new_list.push(item);
-                        }
-                    }
-                    Value::List(Rc::new(RefCell::new(new_list)))
-                },
+    // Frame mise de côté (yield, changement de tâche) : ses upvalues sont fermées le temps
+    // de la suspension et renvoyées avec leur slot relatif à `from`
+    fn detach_upvalues(&mut self, from: usize) -> Vec<(usize, Rc<RefCell<Upvalue>>)> {
+        let position = self.open_upvalues.partition_point(|u| open_slot(u) < from);
+        let detached = self.open_upvalues.split_off(position);
+        detached.into_iter().map(|upvalue| {
+            let slot = open_slot(&upvalue);
+            *upvalue.borrow_mut() = Upvalue::Closed(self.stack[slot].clone());
+            (slot - from, upvalue)
+        }).collect()
+    }
 
-                "for_each" => {
-                    let callback = args[0].clone();
-                    let list_data = l.borrow().clone();
-                    
-                    for item in list_data {
-                        // On exécute juste pour l'effet de bord, on ignore le résultat
-                        self.run_callable_sync(callback.clone(), vec![item], None)?;
-                    }
-                    Value::Null
-                },
+    // Reprise : la valeur (peut-être modifiée entre-temps) retourne sur la pile et l'upvalue est rouverte
+    fn attach_upvalues(&mut self, base: usize, upvalues: Vec<(usize, Rc<RefCell<Upvalue>>)>) {
+        for (offset, upvalue) in upvalues {
+            let slot = base + offset;
+            if let Upvalue::Closed(val) = std::mem::replace(&mut *upvalue.borrow_mut(), Upvalue::Open(slot)) {
+                self.stack[slot] = val;
+            }
+            self.open_upvalues.push(upvalue);
+        }
+    }
 
-                _ => return Err(format!("Unknown list method '{}'", method_name).into())
-            },
-            
-            // ... Dict methods (insert, keys, get...) inchangés ...
-            Value::Dict(d) => match method_name.as_str() {
-                "insert" => {
-                    if args.len() < 2 { return Err("insert needs 2 args".into()); }
-                    let key = args[0].as_str().unwrap_or("?".to_string());
-                    d.borrow_mut().insert(key, args[1].clone());
-                    Value::Null
-                },
-                "keys" => {
-                    let keys: Vec<Value> = d.borrow().keys().map(|k| Value::String(k.clone())).collect();
-                    Value::List(Rc::new(RefCell::new(keys)))
-                },
-                "get" => {
-                     let key = args[0].as_str().unwrap_or("?".to_string());
-                     d.borrow().get(&key).cloned().unwrap_or(Value::Null)
-                },
+    // --- GÉNÉRATEURS ET ITÉRATEURS ---
 
-                "is_empty" => Value::Boolean(d.borrow().is_empty()),
+    // Yield : la frame du générateur quitte la pile d'appels avec ses valeurs,
+    // la valeur produite prend la place du générateur sur la pile
+    fn op_yield(&mut self) -> Result<(), String> {
+        let Some(generator) = self.current_frame().generator.clone() else {
+            return Err("'yield' outside of a generator".into());
+        };
+        let value = self.pop();
+        let frame = self.frames.pop().expect("No frame to yield from");
+
+        // Les try ouverts dans le générateur seront restaurés à la reprise
+        let frame_index = self.frames.len();
+        let mut handlers = Vec::new();
+        while let Some(handler) = self.handlers.pop_if(|h| h.frame_index == frame_index) {
+            handlers.push((handler.catch_ip, handler.stack_height - frame.slot_offset));
+        }
+        handlers.reverse();
 
-                "remove" => {
-                    let key = args[0].as_str().unwrap_or_default();
-                    // Retourne la valeur supprimée ou Null
-                    d.borrow_mut().remove(&key).unwrap_or(Value::Null)
-                },
+        let upvalues = self.detach_upvalues(frame.slot_offset);
+        let stack = self.stack.split_off(frame.slot_offset);
+        self.stack.truncate(frame.slot_offset - 1);
+        {
+            let mut g = generator.borrow_mut();
+            g.upvalues = upvalues;
+            g.ip = frame.ip;
+            g.stack = stack;
+            g.class_context = frame.class_context;
+            g.match_bases = frame.match_bases.iter().map(|base| base - frame.slot_offset).collect();
+            g.handlers = handlers;
+            g.missing_args = frame.missing_args;
+            g.named_rest = frame.named_rest;
+            g.state = GeneratorState::Suspended;
+        }
 
-                "values" => {
-                    // Retourne une liste des valeurs
-                    let vals: Vec<Value> = d.borrow().values().cloned().collect();
-                    Value::List(Rc::new(RefCell::new(vals)))
-                },
+        self.push(value);
+        Ok(())
+    }
 
-                "contains" => {
-                    if args.is_empty() { 
-                        return Err("Usage: dict.contains(key)".into()); 
-                    }
-                    
-                    // On s'attend à ce que la clé soit une String (car HashMap<String, Value>)
-                    let key = args[0].as_str().map_err(|_| "Dict key must be a string")?;
-                    
-                    let exists = d.borrow().contains_key(&key);
-                    Value::Boolean(exists)
-                }
+    // Reprend un générateur jusqu'au prochain `yield` (None quand la fonction est terminée)
+    fn resume_generator(&mut self, generator: &Rc<RefCell<GeneratorData>>) -> Result<Option<Value>, String> {
+        self.check_limits()?;
+        let mut g = generator.borrow_mut();
+        match g.state {
+            GeneratorState::Done => return Ok(None),
+            GeneratorState::Running => return Err(format!("Generator '{}' is already running", g.function.name)),
+            GeneratorState::Suspended => g.state = GeneratorState::Running,
+        }
 
-                _ => return Err(format!("Unknown dict method '{}'", method_name).into())
-            },
+        // Le générateur occupe le slot de l'appelé, sa frame est remise au-dessus
+        self.push(Value::Generator(generator.clone()));
+        let slot_offset = self.stack.len();
+        self.stack.append(&mut g.stack);
+        let upvalues = std::mem::take(&mut g.upvalues);
+        self.attach_upvalues(slot_offset, upvalues);
 
-            Value::Bytes(b) => match method_name.as_str() {
-                "len" => Value::Integer(b.borrow().len() as i64),
-                
-                "is_empty" => Value::Boolean(b.borrow().is_empty()),
-                
-                "at" => {
-                    // Retourne l'octet sous forme d'entier (0-255)
-                    let idx = args[0].as_int().unwrap_or(0) as usize;
-                    if let Some(byte) = b.borrow().get(idx) {
-                        Value::Integer(*byte as i64)
-                    } else {
-                        Value::Null
-                    }
-                },
+        let frame_index = self.frames.len();
+        for (catch_ip, height) in g.handlers.drain(..) {
+            self.handlers.push(ExceptionHandler { frame_index, catch_ip, stack_height: slot_offset + height });
+        }
 
-                "slice" => {
-                    // Extrait une sous-partie des octets
-                    let len = b.borrow().len();
-                    let start = args.get(0).and_then(|v| v.as_int().ok()).unwrap_or(0) as usize;
-                    let end = args.get(1).and_then(|v| v.as_int().ok()).unwrap_or(len as i64) as usize;
-                    
-                    let start = start.min(len);
-                    let end = end.min(len).max(start);
-                    
-                    let slice = b.borrow()[start..end].to_vec();
-                    Value::Bytes(Rc::new(RefCell::new(slice)))
-                },
+        self.frames.push(CallFrame {
+            closure: Value::Function(g.function.clone()),
+            ip: g.ip,
+            slot_offset,
+            class_context: g.class_context.clone(),
+            match_bases: g.match_bases.iter().map(|base| base + slot_offset).collect(),
+            missing_args: g.missing_args,
+            named_rest: g.named_rest.clone(),
+            generator: Some(generator.clone()),
+        });
+        drop(g);
+
+        if let Err(e) = self.run_nested(frame_index + 1) {
+            generator.borrow_mut().state = GeneratorState::Done;
+            return Err(e);
+        }
 
-                "to_string" => {
-                    // Tente de convertir les octets en String UTF-8
-                    // Utile pour lire un fichier texte chargé en mode binaire
-                    let bytes = b.borrow();
-                    match String::from_utf8(bytes.clone()) {
-                        Ok(s) => Value::String(s),
-                        Err(_) => Value::Null, // Ou erreur, ou string partielle
-                    }
-                },
-                
-                "to_hex" => {
-                    // Debug : Affiche en hexadécimal "1F A2 ..."
-                    let bytes = b.borrow();
-                    let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
-                    Value::String(hex)
-                },
+        // Yield (ou Return) a laissé sa valeur à la place du générateur
+        let value = self.pop();
+        let done = generator.borrow().state == GeneratorState::Done;
+        Ok(if done { None } else { Some(value) })
+    }
 
-                _ => return Err(format!("Unknown bytes method '{}'", method_name).into())
-            },
+    // Appel synchrone d'une méthode d'instance du protocole d'itération (iter, next, len, at)
+    fn call_method_sync(&mut self, inst: &Rc<RefCell<InstanceData>>, name: &str, args: Vec<Value>) -> Result<Value, String> {
+        let mut lookup = Some(inst.borrow().class.clone());
+        while let Some(class_rc) = lookup {
+            if let Some(method) = class_rc.methods.get(name).cloned() {
+                self.check_access(&class_rc, name)?;
+                let mut full_args = vec![Value::Instance(inst.clone())]; // arg 0: this
+                full_args.extend(args);
+                return self.run_callable_sync(method, full_args, Some(class_rc));
+            }
+            lookup = class_rc.parent_ref.clone();
+        }
 
-            Value::Range(start, end, step) => match method_name.as_str() {
-                // Pour que foreach sache combien de tours faire
-                "len" => {
-                    if step == 0 { return Err("Step cannot be zero".into()); }
-                    
-                    // Calcul mathématique du nombre d'éléments
-                    let diff = end - start;
-                    let count = if (diff > 0 && step > 0) || (diff < 0 && step < 0) {
-                        (diff as f64 / step as f64).ceil() as i64
-                    } else {
-                        0
-                    };
-                    Value::Integer(count.max(0))
-                },
-                
-                // Pour que foreach récupère l'élément courant
-                "at" => {
-                    let idx = args[0].as_int().unwrap_or(0);
-                    let val = start + (idx * step);
-                    Value::Integer(val)
-                },
-                
-                // Méthode fluide pour changer le pas : (0..10).step(2)
-                "step" => {
-                    let new_step = args[0].as_int().unwrap_or(1);
-                    if new_step == 0 { return Err("Step cannot be 0".into()); }
-                    Value::Range(start, end, new_step)
-                },
-                
-                // Bonus : Conversion en liste réelle
-                "to_list" => {
-                    let mut list = Vec::new();
-                    let mut current = start;
-                    // Logique simplifiée (attention aux boucles infinies si step est mauvais)
-                    if step > 0 {
-                        while current < end {
-                            list.push(Value::Integer(current));
-                            current += step;
-                        }
-                    } else {
-                        while current > end {
-                            list.push(Value::Integer(current));
-                            current += step;
-                        }
-                    }
-                    Value::List(Rc::new(RefCell::new(list)))
-                },
-                
-                _ => return Err(format!("Unknown range method '{}'", method_name).into())
-            },
+        let class_name = inst.borrow().class.name.clone();
+        Err(format!("Class '{}' has no method '{}'", class_name, name))
+    }
 
-            Value::String(s) => match method_name.as_str() {
-                "len" => Value::Integer(s.chars().count() as i64),
-                "at" => {
-                    // Récupération de l'index
-                    let idx = args[0].as_int().unwrap_or(0);
-                    
-                    if idx < 0 {
-                        Value::Null
-                    } else {
-                        // On utilise chars().nth() pour gérer correctement l'UTF-8 (accents, emojis)
-                        match s.chars().nth(idx as usize) {
-                            Some(c) => Value::String(c.to_string()),
-                            None => Value::Null,
-                        }
-                    }
-                },
-                "index_of" => {
-                    // Récupère la sous-chaîne à chercher
-                    let sub = args[0].as_str().unwrap_or_default();
-                    
-                    // s.find retourne un Option<usize> (l'index en octets)
-                    match s.find(&sub) {
-                        Some(idx) => Value::Integer(idx as i64),
-                        None => Value::Integer(-1), // Retourne -1 si non trouvé
+    // Itérateur parcouru par `foreach` : un générateur ou un itérateur est renvoyé tel quel
+    fn get_iter(&mut self, iterable: Value) -> Result<Value, String> {
+        let data = match &iterable {
+            Value::Generator(_) | Value::Iterator(_) => return Ok(iterable),
+            Value::List(_) | Value::String(_) | Value::Bytes(_) | Value::Range(..) => IteratorData::Sequence(iterable, 0),
+            Value::Instance(inst) => {
+                let class = inst.borrow().class.clone();
+                if self.find_method(&class, "iter").is_some() {
+                    let result = self.call_method_sync(inst, "iter", vec![])?;
+                    match &result {
+                        // iter() peut renvoyer l'objet lui-même ou un autre objet avec next()
+                        Value::Instance(other) if self.find_method(&other.borrow().class, "next").is_some() => IteratorData::Object(result),
+                        Value::Instance(other) if Rc::ptr_eq(inst, other) => {
+                            return Err(format!("Method '{}.iter' returned an object without a 'next' method", class.name));
+                        },
+                        _ => return self.get_iter(result),
                     }
+                } else if self.find_method(&class, "next").is_some() {
+                    IteratorData::Object(iterable)
+                } else if self.find_method(&class, "len").is_some() && self.find_method(&class, "at").is_some() {
+                    IteratorData::Sequence(iterable, 0)
+                } else {
+                    return Err(format!("Value of type '{}' is not iterable (define 'iter' or 'next')", class.name));
                 }
+            },
+            other => return Err(format!("Value of type '{}' is not iterable", other.type_name())),
+        };
+        Ok(Value::Iterator(Rc::new(RefCell::new(data))))
+    }
 
-                "slice" => {
-                    // Usage: string.slice(start, end)
-                    let len = s.chars().count();
-                    let start = args.get(0).and_then(|v| v.as_int().ok()).unwrap_or(0) as usize;
-                    // Par défaut jusqu'à la fin
-                    let end = args.get(1).and_then(|v| v.as_int().ok()).unwrap_or(len as i64) as usize;
-
-                    // Clamping
-                    let start = start.min(len);
-                    let end = end.min(len).max(start);
-
-                    // Attention : le slicing en Rust se fait sur les octets, mais ici on veut logique "caractères"
-                    // Pour supporter l'UTF-8 correctement, on itère sur chars()
-                    let sub: String = s.chars()
-                        .skip(start)
-                        .take(end - start)
-                        .collect();
-                    
-                    Value::String(sub)
-                },
+    // Élément suivant d'un itérateur ou d'un générateur (None quand il est épuisé)
+    fn iter_next(&mut self, iterator: &Value) -> Result<Option<Value>, String> {
+        let it = match iterator {
+            Value::Generator(g) => return self.resume_generator(g),
+            Value::Iterator(it) => it,
+            other => return Err(format!("Value of type '{}' is not an iterator", other.type_name())),
+        };
 
-                "to_bytes" => {
-                    Value::Bytes(Rc::new(RefCell::new(s.as_bytes().to_vec())))
+        // On travaille sur une copie : les callbacks relancent la VM et peuvent toucher ce même itérateur
+        let data = it.borrow().clone();
+        match data {
+            IteratorData::Source(source) => {
+                let opened = self.get_iter(source)?;
+                *it.borrow_mut() = IteratorData::Source(opened.clone());
+                self.iter_next(&opened)
+            },
+            IteratorData::Sequence(seq, index) => {
+                let item = self.sequence_at(&seq, index)?;
+                if item.is_some() {
+                    *it.borrow_mut() = IteratorData::Sequence(seq, index + 1);
                 }
-                
-                // --- Transformation ---
-                "trim" => {
-                    // Rust fait ça très bien nativement
-                    Value::String(s.trim().to_string())
-                },
-                "upper" => Value::String(s.to_uppercase()),
-                "lower" => Value::String(s.to_lowercase()),
-
-                // --- Analyse ---
-                "contains" => { // NOUVEAU
-                    let sub = args[0].as_str().unwrap_or_default();
-                    Value::Boolean(s.contains(&sub))
-                },
-                "starts_with" => { // NOUVEAU
-                    let sub = args[0].as_str().unwrap_or_default();
-                    Value::Boolean(s.starts_with(&sub))
-                },
-                "ends_with" => { // NOUVEAU
-                    let sub = args[0].as_str().unwrap_or_default();
-                    Value::Boolean(s.ends_with(&sub))
-                },
-
-                // --- Modification ---
-                "replace" => {
-                    if args.len() < 2 { return Err("String.replace attend 2 arguments (old, new)".into()); }
-                    
-                    // On sécurise la récupération des chaînes
-                    let old_part = args[0].as_str().unwrap_or("".to_string());
-                    let new_part = args[1].as_str().unwrap_or("".to_string());
-                    
-                    Value::String(s.replace(&old_part, &new_part))
-                },
-
-                "split" => {
-                    let delim = if args.is_empty() { 
-                        " ".to_string() 
-                    } else { 
-                        args[0].as_str().unwrap_or(" ".to_string()) 
-                    };
-
-                    // On découpe et on convertit chaque morceau en Value::String
-                    let parts: Vec<Value> = s.split(&delim)
-                        .map(|sub| Value::String(sub.to_string()))
-                        .collect();
-                    
-                    // On retourne une Value::List
-                    Value::List(Rc::new(RefCell::new(parts)))
-                },
-
-                "is_empty" => Value::Boolean(s.is_empty()),
-
-                "pad_start" => {
-                    // Args: width, char (optionnel, defaut ' ')
-                    let width = args[0].as_int().unwrap_or(0) as usize;
-                    let pad_char = if args.len() > 1 { 
-                        args[1].as_str().unwrap_or(" ".to_string()).chars().next().unwrap_or(' ') 
-                    } else { ' ' };
-
-                    if s.len() >= width {
-                        Value::String(s.clone())
-                    } else {
-                        let padding = pad_char.to_string().repeat(width - s.len());
-                        Value::String(format!("{}{}", padding, s))
+                Ok(item)
+            },
+            IteratorData::Object(obj) => {
+                let Value::Instance(inst) = &obj else { return Ok(None) };
+                let value = self.call_method_sync(inst, "next", vec![])?;
+                Ok(if matches!(value, Value::Null) { None } else { Some(value) })
+            },
+            IteratorData::Map(source, callback) => match self.iter_next(&source)? {
+                Some(item) => Ok(Some(self.run_callable_sync(callback, vec![item], None)?)),
+                None => Ok(None),
+            },
+            IteratorData::Filter(source, callback) => {
+                while let Some(item) = self.iter_next(&source)? {
+                    let res = self.run_callable_sync(callback.clone(), vec![item.clone()], None)?;
+                    // Même règle que list.filter
+                    if matches!(res, Value::Boolean(true)) || (res.as_int().unwrap_or(0) != 0 && !matches!(res, Value::Null)) {
+                        return Ok(Some(item));
                     }
-                },
-
-                "pad_end" => {
-                    let width = args[0].as_int().unwrap_or(0) as usize;
-                    let pad_char = if args.len() > 1 { 
-                        args[1].as_str().unwrap_or(" ".to_string()).chars().next().unwrap_or(' ') 
-                    } else { ' ' };
-
-                    if s.len() >= width {
-                        Value::String(s.clone())
-                    } else {
-                        let padding = pad_char.to_string().repeat(width - s.len());
-                        Value::String(format!("{}{}", s, padding))
+                }
+                Ok(None)
+            },
+            IteratorData::Take(source, remaining) => {
+                // La source n'est plus sollicitée une fois le compte atteint (générateurs infinis)
+                if remaining == 0 {
+                    return Ok(None);
+                }
+                *it.borrow_mut() = IteratorData::Take(source.clone(), remaining - 1);
+                self.iter_next(&source)
+            },
+            IteratorData::Zip(sources) => {
+                let mut items = Vec::with_capacity(sources.len());
+                for source in &sources {
+                    match self.iter_next(source)? {
+                        Some(item) => items.push(item),
+                        None => return Ok(None),
                     }
-                },
-
-                _ => return Err(format!("Méthode string inconnue '{}'", method_name).into())
+                }
+                Ok(Some(Value::List(Rc::new(RefCell::new(items)))))
             },
+            IteratorData::Enumerate(source, index) => {
+                let Some(item) = self.iter_next(&source)? else { return Ok(None) };
+                *it.borrow_mut() = IteratorData::Enumerate(source, index + 1);
+                Ok(Some(Value::List(Rc::new(RefCell::new(vec![Value::Integer(index), item])))))
+            },
+        }
+    }
 
-            Value::Instance(_) => return Err(format!("Instance has no method '{}'", method_name).into()),
-            _ => return Err(format!("Method '{}' not supported on {:?}", method_name, obj).into())
-        };
-
-        self.push(result);
-        Ok(())
+    // Élément `index` d'une séquence parcourue par foreach (None après le dernier)
+    fn sequence_at(&mut self, seq: &Value, index: usize) -> Result<Option<Value>, String> {
+        Ok(match seq {
+            Value::List(l) => l.borrow().get(index).cloned(),
+            Value::String(s) => s.chars().nth(index).map(|c| Value::String(c.to_string().into())),
+            Value::Bytes(b) => b.borrow().get(index).map(|byte| Value::Integer(*byte as i64)),
+            Value::Range(r) => {
+                if r.step == 0 { return Err("Step cannot be zero".into()); }
+                (index < range_len(r.start, r.end, r.step)).then(|| Value::Integer(r.start + index as i64 * r.step))
+            },
+            // Objet avec len() et at()
+            Value::Instance(inst) => {
+                let len = self.call_method_sync(inst, "len", vec![])?.as_int()?;
+                if (index as i64) < len {
+                    Some(self.call_method_sync(inst, "at", vec![Value::Integer(index as i64)])?)
+                } else {
+                    None
+                }
+            },
+            _ => None,
+        })
     }
 
+    // Méthodes communes aux générateurs et aux itérateurs : next() et les adaptateurs paresseux
     // Helper pour lire l'octet suivant et avancer IP
     #[inline(always)]
     fn read_byte(&mut self) -> u8 {
@@ -1978,6 +2466,23 @@ impl VM {
         b
     }
 
+    fn read_opcode(&mut self) -> Result<OpCode, String> {
+        let byte = self.read_byte();
+        OpCode::try_from(byte).map_err(|b| format!("Invalid bytecode: unknown opcode {}", b))
+    }
+
+    // Opérande d'index ou de compteur : un octet, ou quatre après le préfixe Wide
+    fn read_operand(&mut self) -> usize {
+        if !self.wide {
+            return self.read_byte() as usize;
+        }
+        let frame = self.current_frame();
+        let ip = frame.ip;
+        frame.ip += 4;
+        let bytes = &frame.chunk().code[ip..ip + 4];
+        u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+    }
+
     fn read_short(&mut self) -> u16 {
         let frame = self.current_frame();
         let ip = frame.ip;
@@ -1985,35 +2490,84 @@ impl VM {
         ((frame.chunk().code[ip] as u16) << 8) | frame.chunk().code[ip + 1] as u16
     }
 
+    // Nombre d'arguments d'un appel : l'opérande, sauf si SpreadArgs vient de déplier des listes
+    fn read_arg_count(&mut self) -> usize {
+        let written = self.read_operand();
+        self.spread_count.take().unwrap_or(written)
+    }
+
     fn call_value(&mut self, target: Value, arg_count: usize, context: Option<Rc<ClassData>>) -> Result<(), String> {
         let func_idx = self.stack.len() - 1 - arg_count;
 
         match &target {
             // CAS 1 : Fonction Aegis
             Value::Function(rc_fn) => { 
-                 // On accède aux champs via rc_fn
-                 if arg_count != rc_fn.params.len() { 
-                    return Err(format!("Arity mismatch: attendu {}, reçu {}", rc_fn.params.len(), arg_count)); 
+                 let names = std::mem::take(&mut self.call_names);
+                 let mut missing_args = 0;
+                 let mut named_rest = None;
+
+                 // Cas simple : ni nom, ni défaut, ni ...rest, les arguments sont déjà en place
+                 if names.is_empty() && rc_fn.required == rc_fn.params.len() {
+                    if arg_count != rc_fn.params.len() { 
+                        return Err(arity_error(rc_fn, arg_count)); 
+                    }
+                 } else {
+                    let args: Vec<Value> = self.stack.drain((func_idx + 1)..).collect();
+                    let (bound, missing, rest) = bind_arguments(rc_fn, args, names)?;
+                    self.stack.extend(bound);
+                    missing_args = missing;
+                    named_rest = rest;
+                 }
+
+                 // Fonction avec `yield` : la frame est mise de côté, l'appel renvoie le générateur
+                 if rc_fn.is_generator {
+                    let stack = self.stack.split_off(func_idx + 1);
+                    self.stack[func_idx] = Value::Generator(Rc::new(RefCell::new(GeneratorData {
+                        function: rc_fn.clone(),
+                        ip: 0,
+                        stack,
+                        class_context: context,
+                        match_bases: Vec::new(),
+                        handlers: Vec::new(),
+                        missing_args,
+                        named_rest,
+                        upvalues: Vec::new(),
+                        state: GeneratorState::Suspended,
+                    })));
+                    return Ok(());
                  }
                  
                  let frame = CallFrame {
                     closure: target.clone(), // Clone le Rc (rapide !)
                     ip: 0,
                     slot_offset: func_idx + 1,
-                    class_context: context
+                    class_context: context,
+                    match_bases: Vec::new(),
+                    missing_args,
+                    named_rest,
+                    generator: None
                  };
+
+                 // Fonction async : l'appel renvoie une tâche, lancée par `await` ou spawn()
+                 if rc_fn.is_async {
+                    let task = self.make_task(rc_fn.clone(), func_idx, frame);
+                    self.push(task);
+                    return Ok(());
+                 }
                  
+                 self.check_limits()?;
+                 gc::collect_if_needed();
                  self.frames.push(frame);
                  Ok(())
             },
 
             // CAS 2 : Classe
             Value::Class(rc_class) => {
+                // Les arguments nommés sont pour `init` : on les met de côté pendant l'initialisation des champs
+                let names = std::mem::take(&mut self.call_names);
+
                 // 1. Création de l'instance vide (avec le bon type Rc<ClassData>)
-                let instance_rc = Rc::new(RefCell::new(InstanceData {
-                    class: rc_class.clone(),
-                    fields: HashMap::new()
-                }));
+                let instance_rc = Rc::new(RefCell::new(InstanceData::new(rc_class.clone())));
 
                 // 2. On crée la Value pour la VM
                 let instance = Value::Instance(instance_rc.clone());
@@ -2047,13 +2601,13 @@ impl VM {
                             ) {
                                 Ok(val) => {
                                     // On insère dans l'instance
-                                    instance_rc.borrow_mut().fields.insert(field_name.clone(), val);
+                                    instance_rc.borrow_mut().set(field_name, val);
                                 },
                                 Err(e) => return Err(format!("Erreur initialisation champ '{}': {}", field_name, e)),
                             }
                         } else {
                             // Cas théorique (si on stockait des constantes brutes)
-                            instance_rc.borrow_mut().fields.insert(field_name.clone(), init_val_or_func.clone());
+                            instance_rc.borrow_mut().set(field_name, init_val_or_func.clone());
                         }
                     }
                 }
@@ -2083,6 +2637,7 @@ impl VM {
                     let mut call_args = vec![instance.clone()];
                     call_args.extend(args);
 
+                    self.call_names = names;
                     self.run_callable_sync(method_val, call_args, Some(rc_class.clone()))?;
                 } else {
                     if arg_count > 0 {
@@ -2095,15 +2650,43 @@ impl VM {
                 Ok(())
             },
 
-            // CAS 3 : Fonction Native
-            Value::Native(name) => {
-                let func_ptr = crate::native::find(&name)
-                    .ok_or(format!("Fonction native '{}' introuvable", name))?;
+            // CAS 3 : Constructeur de variante (Shape.Circle(2))
+            Value::Variant(v) if v.is_constructor() => {
+                if !self.call_names.is_empty() {
+                    self.call_names.clear();
+                    return Err(format!("Variant '{}.{}' does not accept named arguments", v.enum_data.name, v.name()));
+                }
+                if arg_count != v.fields().len() {
+                    return Err(format!("Variant '{}.{}' expects {} argument(s), got {}", v.enum_data.name, v.name(), v.fields().len(), arg_count));
+                }
+                let values: Vec<Value> = self.stack.drain((func_idx + 1)..).collect();
+                self.stack[func_idx] = Value::Variant(Rc::new(VariantData { enum_data: v.enum_data.clone(), index: v.index, values }));
+                Ok(())
+            },
+
+            // CAS 4 : Méthode native liée à sa valeur : l'objet reprend la place de la fonction
+            Value::BoundMethod(m) => {
+                self.stack[func_idx] = m.receiver.clone();
+                self.call_builtin(m.ty, m.id, func_idx)
+            },
+
+            // CAS 5 : Fonction Native
+            Value::Native(native) => {
+                if !self.call_names.is_empty() {
+                    self.call_names.clear();
+                    return Err(format!("Native function '{}' does not accept named arguments", native.name));
+                }
 
                 let args_start = func_idx + 1;
+                let arg_count = self.stack.len() - args_start;
+                if let Some(min_args) = native.min_args
+                    && arg_count < min_args
+                {
+                    return Err(format!("Native function '{}' expects at least {} argument(s), got {}", native.name, min_args, arg_count));
+                }
                 let args: Vec<Value> = self.stack.drain(args_start..).collect();
 
-                let result = func_ptr(args)?;
+                let result = (native.func)(args)?;
 
                 self.stack.pop(); // Pop la fonction native
                 self.push(result);
@@ -2118,40 +2701,37 @@ impl VM {
     }
 
     fn resolve_lazy_native(&mut self, global_id: usize) -> Option<Value> {
-        // 1. Retrouver le nom à partir de l'ID
-        let name = {
-            let names = self.global_names.borrow();
-            names.iter()
-                // CORRECTION ICI : On déstructure explicitement la référence externe
-                .find(|&(_, &id)| id as usize == global_id)
-                .map(|(k, _)| k.clone())
-        }?; 
-
-        // 2. Chercher dans le registre natif
-        // on veut juste savoir si 'find' retourne Some(...)
-        if let Some(_) = crate::native::find(&name) {
-            let val = Value::Native(name);
-            
-            // 3. Mettre en cache dans les globales
-            if global_id >= self.globals.len() {
-                self.globals.resize(global_id + 1, Value::Null);
-            }
-            self.globals[global_id] = val.clone();
-            
-            return Some(val);
+        // 1. Retrouver le nom à partir de l'ID (table inverse)
+        // (dans un module importé, la globale `len` s'appelle `<module>::len`)
+        let name = plain_name(self.global_names.borrow().name(global_id)?).to_string();
+
+        // 2. Chercher dans le registre natif, une seule fois
+        let native = crate::native::find(&name)?;
+        let val = Value::Native(Rc::new(NativeData { name: name.into(), func: native.func, min_args: native.min_args }));
+
+        // 3. Mettre en cache dans les globales
+        if global_id >= self.globals.len() {
+            self.globals.resize(global_id + 1, Value::Null);
         }
+        self.globals[global_id] = val.clone();
 
-        None
+        Some(val)
     }
 
     /// Injecte et exécute un nouveau Chunk dans la VM existante (pour le REPL)
     pub fn execute_chunk(&mut self, chunk: Chunk) -> Result<(), String> {
         // On crée une fonction fictive pour emballer ce chunk
         let script_func = Value::Function(Rc::new(crate::ast::value::FunctionData {
+            name: "<script>".to_string(),
             params: vec![],
+            required: 0,
+            variadic: false,
             ret_type: None,
-            chunk,
-            env: None
+            is_generator: false,
+            is_async: false,
+            chunk: Rc::new(chunk),
+            captures: Vec::new(),
+            upvalues: Vec::new()
         }));
 
         // On crée une nouvelle Frame au niveau 0 (comme le main)
@@ -2160,6 +2740,10 @@ impl VM {
             ip: 0,
             slot_offset: 0,
             class_context: None,
+            match_bases: Vec::new(),
+            missing_args: 0,
+            named_rest: None,
+            generator: None,
         };
 
         // On l'ajoute à la pile d'appels
@@ -2169,7 +2753,14 @@ impl VM {
         self.run()
     }
 
-    fn runtime_error(&self, message: String) -> String {
+    // Erreur non rattrapée : préfixée par la ligne où elle est née (dans une exécution imbriquée, s'il y a lieu)
+    fn runtime_error(&mut self, message: String) -> String {
+        let line = self.error_line.take().unwrap_or_else(|| self.current_line());
+        format!("[Line {}] Error: {}", line, message)
+    }
+
+    // Ligne de l'instruction en cours dans la frame courante
+    fn current_line(&self) -> usize {
         let frame = self.frames.last().expect("No frame for error");
         let chunk = frame.chunk();
         
@@ -2177,21 +2768,173 @@ impl VM {
         let ip = if frame.ip > 0 { frame.ip - 1 } else { 0 };
         
         // On récupère la ligne
-        let line = if ip < chunk.lines.len() {
+        if ip < chunk.lines.len() {
             chunk.lines[ip]
         } else {
             0
+        }
+    }
+
+    // Objet module : les valeurs des noms listés dans la globale EXPORTS du module `id`
+    fn module_exports(&self, id: &str) -> Value {
+        let mut exports = HashMap::new();
+        let names = self.global_names.borrow().get(&scoped_name(id, compiler::EXPORTS))
+            .and_then(|global_id| self.globals.get(global_id).cloned());
+        if let Some(Value::List(names)) = names {
+            for name in names.borrow().iter() {
+                let name = name.to_string();
+                let value = self.get_global_by_name(&scoped_name(id, &name)).unwrap_or(Value::Null);
+                exports.insert(name, value);
+            }
+        }
+        Value::Dict(Rc::new(RefCell::new(exports)))
+    }
+
+    // Résultat d'un import, selon le second opérande de OpCode::Import : Null copie chaque export
+    // dans les globales du module qui importe, `importer` (sans en écraser une autre), une liste de noms (vérifiés) empile l'objet module
+    fn bind_module(&mut self, path: &str, module: &str, exports: Value, mode: &Value, importer: Option<&str>) -> Result<(), String> {
+        let Value::Dict(exports) = exports else {
+            return Err(format!("Failed to import '{}': the module has not finished loading", path));
         };
 
-        format!("[Line {}] Error: {}", line, message)
+        let Value::List(wanted) = mode else {
+            // Toutes les collisions sont vérifiées avant de lier quoi que ce soit
+            let mut bindings = Vec::new();
+            for (name, value) in exports.borrow().iter() {
+                let global = match importer {
+                    Some(module) => scoped_name(module, name),
+                    None => name.clone(),
+                };
+                let global_id = self.global_names.borrow_mut().resolve(&global);
+                let current = match self.globals.get(global_id) {
+                    Some(Value::Null) | None => self.resolve_lazy_native(global_id),
+                    Some(current) => Some(current.clone()),
+                };
+                // Un nouvel import du même module, ou la même valeur reçue par un autre chemin, ne masque rien
+                let shadows = current.is_some_and(|current| current != *value)
+                    && self.imported.get(&global_id).map(String::as_str) != Some(module);
+                if shadows {
+                    return Err(format!(
+                        "Import of '{}' would overwrite the existing global '{}' (use `import \"{}\" as name` or `from \"{}\" import ...` instead)",
+                        path, name, path, path
+                    ));
+                }
+                bindings.push((global_id, value.clone()));
+            }
+
+            for (global_id, value) in bindings {
+                if global_id >= self.globals.len() {
+                    self.globals.resize(global_id + 1, Value::Null);
+                }
+                self.globals[global_id] = value;
+                self.imported.insert(global_id, module.to_string());
+            }
+            self.push(Value::Null);
+            return Ok(());
+        };
+
+        for name in wanted.borrow().iter() {
+            let name = name.to_string();
+            if !exports.borrow().contains_key(&name) {
+                return Err(format!("Module '{}' has no export '{}'", path, name));
+            }
+        }
+        self.push(Value::Dict(exports));
+        Ok(())
     }
 
     fn get_global_by_name(&self, name: &str) -> Option<Value> {
-        let global_id = self.global_names.borrow().get(name).cloned()?;
-        let val = self.globals.get(global_id as usize)?;
+        let global_id = self.global_names.borrow().get(name)?;
+        let val = self.globals.get(global_id)?;
         if matches!(val, Value::Null) { None } else { Some(val.clone()) }
     }
 
+    // --- Caches des sites d'accès (voir chunk::InlineCache) ---
+    // Un cache n'est rempli qu'après un check_access réussi : il n'est valable que pour la même
+    // classe appelante. Les classes ne changent pas une fois créées, seules les formes varient.
+
+    // Instance à `depth` places du sommet de la pile et position du champ, si le cache du site la connaît
+    fn cached_field(&self, cache_idx: usize, depth: usize) -> Option<(Rc<RefCell<InstanceData>>, usize)> {
+        let Value::Instance(inst) = &self.stack[self.stack.len() - depth] else { return None };
+        let frame = self.frames.last()?;
+        let InlineCache::Field { shape, context, slot } = &*frame.chunk().caches.get(cache_idx) else { return None };
+        let data = inst.borrow();
+        let hit = std::ptr::eq(shape.as_ptr(), Rc::as_ptr(&data.shape))
+            && *slot < data.slots.len()
+            && same_context(context, &frame.class_context);
+        hit.then(|| (inst.clone(), *slot))
+    }
+
+    // SET_ATTR par le cache : pile [.., objet, valeur] -> [.., valeur]. Renvoie false si le cache ne s'applique pas.
+    fn cached_store(&mut self, cache_idx: usize) -> bool {
+        let inst = if let Some((inst, slot)) = self.cached_field(cache_idx, 2) {
+            inst.borrow_mut().slots[slot] = self.stack[self.stack.len() - 1].clone();
+            inst
+        } else {
+            let Value::Instance(inst) = &self.stack[self.stack.len() - 2] else { return false };
+            let Some(frame) = self.frames.last() else { return false };
+            let InlineCache::Transition { from, to, context } = &*frame.chunk().caches.get(cache_idx) else { return false };
+            let mut data = inst.borrow_mut();
+            if !std::ptr::eq(from.as_ptr(), Rc::as_ptr(&data.shape))
+                || data.slots.len() != data.shape.len()
+                || !same_context(context, &frame.class_context) {
+                return false;
+            }
+            let Some(next) = to.upgrade() else { return false };
+            data.shape = next;
+            data.slots.push(self.stack[self.stack.len() - 1].clone());
+            drop(data);
+            inst.clone()
+        };
+        let val = self.pop();
+        gc::note_store(&Value::Instance(inst), &val);
+        *self.stack.last_mut().expect("Stack underflow") = val;
+        true
+    }
+
+    // Méthode (et classe propriétaire) retenue par le cache du site pour la classe de `obj`
+    fn cached_method(&self, cache_idx: usize, obj: &Value) -> Option<(Value, Rc<ClassData>)> {
+        let Value::Instance(inst) = obj else { return None };
+        let frame = self.frames.last()?;
+        let InlineCache::Method { class, context, method, owner } = &*frame.chunk().caches.get(cache_idx) else { return None };
+        if !std::ptr::eq(class.as_ptr(), Rc::as_ptr(&inst.borrow().class)) || !same_context(context, &frame.class_context) {
+            return None;
+        }
+        Some((Value::Function(method.upgrade()?), owner.upgrade()?))
+    }
+
+    fn cached_builtin(&self, cache_idx: usize, ty: BuiltinType) -> Option<usize> {
+        let frame = self.frames.last()?;
+        match &*frame.chunk().caches.get(cache_idx) {
+            InlineCache::Builtin { ty: cached, id } if *cached == ty => Some(*id),
+            _ => None,
+        }
+    }
+
+    fn set_cache(&self, cache_idx: usize, cache: impl FnOnce(Option<Weak<ClassData>>) -> InlineCache) {
+        if let Some(frame) = self.frames.last() {
+            let context = frame.class_context.as_ref().map(Rc::downgrade);
+            frame.chunk().caches.set(cache_idx, cache(context));
+        }
+    }
+
+    fn cache_field(&self, cache_idx: usize, shape: &Rc<Shape>, slot: usize) {
+        self.set_cache(cache_idx, |context| InlineCache::Field { shape: Rc::downgrade(shape), context, slot });
+    }
+
+    fn cache_transition(&self, cache_idx: usize, from: &Rc<Shape>, to: &Rc<Shape>) {
+        self.set_cache(cache_idx, |context| InlineCache::Transition { from: Rc::downgrade(from), to: Rc::downgrade(to), context });
+    }
+
+    fn cache_method(&self, cache_idx: usize, class: &Rc<ClassData>, method: &Rc<FunctionData>, owner: &Rc<ClassData>) {
+        self.set_cache(cache_idx, |context| InlineCache::Method {
+            class: Rc::downgrade(class),
+            context,
+            method: Rc::downgrade(method),
+            owner: Rc::downgrade(owner),
+        });
+    }
+
     fn check_access(&mut self, target_class: &Rc<ClassData>, member_name: &str) -> Result<(), String> {
         // 1. Récupérer la visibilité (Public par défaut)
         let visibility = target_class.visibilities.get(member_name).unwrap_or(&Visibility::Public);
@@ -2256,3 +2999,59 @@ impl VM {
         None
     }
 }
+
+// Index d'un accès x[i] : négatif = depuis la fin, None si hors limites
+// `value.name` sans appel : méthode native liée, si le type en a une de ce nom
+fn bind_builtin(value: Value, name: &str) -> Option<Value> {
+    let ty = BuiltinType::of(&value)?;
+    let id = ty.find(name)?;
+    Some(Value::BoundMethod(Rc::new(BoundMethod { receiver: value, ty, id })))
+}
+
+// La classe appelante est-elle celle pour laquelle le cache a été rempli ?
+fn same_context(cached: &Option<Weak<ClassData>>, current: &Option<Rc<ClassData>>) -> bool {
+    match (cached, current) {
+        (None, None) => true,
+        (Some(cached), Some(current)) => std::ptr::eq(cached.as_ptr(), Rc::as_ptr(current)),
+        _ => false,
+    }
+}
+
+fn resolve_index(key: &Value, len: usize) -> Result<Option<usize>, String> {
+    let Value::Integer(i) = key else {
+        return Err(format!("Index must be an integer, got {}", key.type_name()));
+    };
+    let i = if *i < 0 { *i + len as i64 } else { *i };
+    Ok(if i >= 0 && (i as usize) < len { Some(i as usize) } else { None })
+}
+
+// Bornes d'une tranche x[start:end] : null = début/fin, négatif = depuis la fin, ramenées dans [0, len]
+fn slice_bounds(start: &Value, end: &Value, len: usize) -> Result<(usize, usize), String> {
+    let bound = |v: &Value, default: usize| -> Result<usize, String> {
+        match v {
+            Value::Null => Ok(default),
+            Value::Integer(i) if *i < 0 => Ok((*i + len as i64).max(0) as usize),
+            Value::Integer(i) => Ok((*i as usize).min(len)),
+            other => Err(format!("Slice bounds must be integers, got {}", other.type_name())),
+        }
+    };
+    let from = bound(start, 0)?;
+    let to = bound(end, len)?.max(from);
+    Ok((from, to))
+}
+
+fn dict_key(key: &Value) -> Result<String, String> {
+    match key {
+        Value::String(s) => Ok(s.to_string()),
+        other => Err(format!("Dict key must be a string, got {}", other.type_name())),
+    }
+}
+
+// Nombre d'éléments d'un range (0 si le pas ne va pas dans le bon sens)
+fn range_len(start: i64, end: i64, step: i64) -> usize {
+    let diff = end - start;
+    if step == 0 || (diff > 0) != (step > 0) || diff == 0 {
+        return 0;
+    }
+    (diff as f64 / step as f64).ceil() as usize
+}
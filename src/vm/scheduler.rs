@@ -0,0 +1,494 @@
+// Boucle d'événements de la VM : tâches async, minuteurs et E/S non bloquantes.
+// Tout tourne sur un seul thread : une tâche ne rend la main qu'à un `await`
+// (ou quand elle se termine), la suivante reprend alors là où elle s'était arrêtée.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::io::ErrorKind;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use mio::event::Source;
+use mio::{Events, Interest, Token, Waker};
+
+use crate::ast::value::{FunctionData, Upvalue};
+use crate::ast::Value;
+use crate::chunk::Chunk;
+use crate::opcode::OpCode;
+
+use super::{CallFrame, ExceptionHandler, VM};
+
+pub type Poll = Box<dyn FnMut() -> Option<Result<Value, String>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    New,       // créée, pas encore lancée (ni attendue, ni passée à spawn)
+    Scheduled, // prête à (re)prendre, ou minuteur / E/S en cours
+    Waiting,   // suspendue sur un `await`
+    Running,
+    Done,
+}
+
+enum TaskKind {
+    // Appel d'une fonction async (ou callback d'un set_timeout)
+    Coroutine,
+    // Le script principal, dès qu'il est suspendu par un `await` au niveau global
+    Main,
+    // sleep / set_timeout : échéance et callback éventuel
+    Timer(Instant, Option<Value>),
+    // Opération d'E/S : la closure renvoie Some(résultat) quand elle est terminée
+    Poll(Poll),
+}
+
+/// Tâche de la boucle d'événements. Chaque tâche garde sa propre pile d'appels,
+/// échangée avec celle de la VM quand elle prend la main.
+pub struct TaskData {
+    pub name: String,
+    pub state: TaskState,
+    kind: TaskKind,
+    frames: Vec<CallFrame>,
+    stack: Vec<Value>,
+    handlers: Vec<ExceptionHandler>,
+    // Upvalues qui désignent la pile de la tâche, fermées pendant qu'elle est suspendue
+    upvalues: Vec<(usize, Rc<RefCell<Upvalue>>)>,
+    // Valeur (ou erreur) renvoyée par l'`await` qui a suspendu la tâche
+    resume: Option<Result<Value, String>>,
+    result: Option<Result<Value, String>>,
+    waiters: Vec<Rc<RefCell<TaskData>>>,
+    // Quelqu'un a lu le résultat : une erreur ne sera pas signalée à la fin du programme
+    observed: bool,
+}
+
+impl TaskData {
+    fn new(name: String, kind: TaskKind) -> Self {
+        TaskData {
+            name,
+            state: TaskState::New,
+            kind,
+            frames: Vec::new(),
+            stack: Vec::new(),
+            handlers: Vec::new(),
+            upvalues: Vec::new(),
+            resume: None,
+            result: None,
+            waiters: Vec::new(),
+            observed: false,
+        }
+    }
+
+    /// Tâche qui se termine (avec null, ou la valeur du callback) après `delay`.
+    pub fn timer(name: &str, delay: Duration, callback: Option<Value>) -> Self {
+        TaskData::new(name.to_string(), TaskKind::Timer(Instant::now() + delay, callback))
+    }
+
+    /// Tâche d'E/S : `poll` est rappelée par la boucle jusqu'à ce qu'elle renvoie un résultat.
+    /// La boucle ne la rappelle qu'après un événement du réacteur : la source de l'opération doit y être
+    /// enregistrée (voir register), ou la réveiller par son Waker (voir waker).
+    pub fn poll(name: &str, poll: Poll) -> Self {
+        TaskData::new(name.to_string(), TaskKind::Poll(poll))
+    }
+
+    fn is_main(&self) -> bool {
+        matches!(self.kind, TaskKind::Main)
+    }
+}
+
+// Deux tâches ne sont égales que si c'est la même
+impl PartialEq for TaskData {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl fmt::Debug for TaskData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskData").field("name", &self.name).field("state", &self.state).finish()
+    }
+}
+
+// Tâches passées à spawn() / set_timeout() : les natives n'ont pas accès à la VM,
+// la boucle les récupère au prochain changement de tâche
+thread_local! {
+    static SPAWNED: RefCell<Vec<Rc<RefCell<TaskData>>>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn spawn(task: Rc<RefCell<TaskData>>) {
+    SPAWNED.with(|spawned| spawned.borrow_mut().push(task));
+}
+
+// Réacteur de la boucle : les sockets y sont enregistrés une fois pour toutes, et les threads
+// d'une E/S (proc_spawn...) le réveillent par son Waker. Ses événements ne disent pas quelle tâche
+// reprendre : la boucle retente toutes les E/S en cours, qui renvoient WouldBlock tant qu'elles ne peuvent pas avancer.
+struct Reactor {
+    poll: mio::Poll,
+    events: Events,
+    waker: Arc<Waker>,
+}
+
+const WAKER: Token = Token(usize::MAX);
+
+thread_local! {
+    static REACTOR: RefCell<Option<Reactor>> = const { RefCell::new(None) };
+}
+
+fn with_reactor<T>(f: impl FnOnce(&mut Reactor) -> std::io::Result<T>) -> Result<T, String> {
+    REACTOR.with(|reactor| {
+        let mut reactor = reactor.borrow_mut();
+        if reactor.is_none() {
+            let poll = mio::Poll::new().map_err(|e| e.to_string())?;
+            let waker = Arc::new(Waker::new(poll.registry(), WAKER).map_err(|e| e.to_string())?);
+            *reactor = Some(Reactor { poll, events: Events::with_capacity(64), waker });
+        }
+        f(reactor.as_mut().expect("Reactor initialised above")).map_err(|e| e.to_string())
+    })
+}
+
+/// Enregistre un socket auprès du réacteur : il reste non bloquant, et chaque changement
+/// de son état réveille la boucle (ou une opération synchrone, voir wait_io).
+pub fn register(source: &mut impl Source, id: usize, interests: Interest) -> Result<(), String> {
+    with_reactor(|reactor| reactor.poll.registry().register(source, Token(id), interests))
+}
+
+/// Retire un socket du réacteur, avant de le fermer.
+pub fn deregister(source: &mut impl Source) {
+    let _ = with_reactor(|reactor| reactor.poll.registry().deregister(source));
+}
+
+/// Waker du réacteur, à appeler depuis un autre thread quand le résultat d'une E/S est prêt.
+pub fn waker() -> Result<Arc<Waker>, String> {
+    with_reactor(|reactor| Ok(reactor.waker.clone()))
+}
+
+/// Attend le prochain événement du réacteur, au plus `timeout` (None : sans limite).
+/// Une opération synchrone sur un socket l'appelle quand elle reçoit WouldBlock, puis réessaie.
+pub fn wait_io(timeout: Option<Duration>) -> Result<(), String> {
+    with_reactor(|reactor| match reactor.poll.poll(&mut reactor.events, timeout) {
+        Err(e) if e.kind() == ErrorKind::Interrupted => Ok(()),
+        result => result,
+    })
+}
+
+#[derive(Default)]
+pub(super) struct Scheduler {
+    // None tant que le script principal n'a jamais été suspendu
+    current: Option<Rc<RefCell<TaskData>>>,
+    main: Option<Rc<RefCell<TaskData>>>,
+    ready: VecDeque<Rc<RefCell<TaskData>>>,
+    timers: Vec<Rc<RefCell<TaskData>>>,
+    polls: Vec<Rc<RefCell<TaskData>>>,
+    // Tâches terminées en erreur sans que personne ne les attende
+    failed: Vec<Rc<RefCell<TaskData>>>,
+}
+
+// Corps exécuté par le callback d'un set_timeout : `return await callback()`
+fn trampoline() -> Rc<FunctionData> {
+    let mut chunk = Chunk::new();
+    for byte in [OpCode::GetLocal as u8, 0, OpCode::Call as u8, 0, OpCode::Await as u8, OpCode::Return as u8] {
+        chunk.write(byte, 0);
+    }
+    Rc::new(FunctionData {
+        name: "set_timeout".to_string(),
+        params: vec![("callback".to_string(), None)],
+        required: 1,
+        variadic: false,
+        ret_type: None,
+        is_generator: false,
+        is_async: true,
+        chunk: Rc::new(chunk),
+        captures: Vec::new(),
+        upvalues: Vec::new(),
+    })
+}
+
+impl VM {
+    // Appel d'une fonction async : la frame est préparée mais pas lancée, l'appel renvoie la tâche
+    pub(super) fn make_task(&mut self, function: Rc<FunctionData>, func_idx: usize, frame: CallFrame) -> Value {
+        let mut task = TaskData::new(function.name.clone(), TaskKind::Coroutine);
+        task.stack = self.stack.split_off(func_idx);
+        task.frames.push(CallFrame { slot_offset: 1, ..frame });
+        Value::Task(Rc::new(RefCell::new(task)))
+    }
+
+    // await : le résultat si la tâche est finie, sinon la tâche courante attend et une autre prend la main
+    pub(super) fn op_await(&mut self) -> Result<bool, String> {
+        let target = self.pop();
+        let Value::Task(task) = target else {
+            // `await` sur une valeur ordinaire la renvoie telle quelle
+            self.push(target);
+            return Ok(true);
+        };
+
+        let state = task.borrow().state;
+        match state {
+            TaskState::Done => {
+                let mut t = task.borrow_mut();
+                t.observed = true;
+                return match t.result.clone().unwrap_or(Ok(Value::Null)) {
+                    Ok(value) => {
+                        drop(t);
+                        self.push(value);
+                        Ok(true)
+                    },
+                    Err(e) => Err(e),
+                };
+            },
+            TaskState::Running => return Err(format!("Task '{}' cannot await itself", task.borrow().name)),
+            _ => {}
+        }
+
+        // Une exécution imbriquée (callback de map, générateur...) ne peut pas être mise de côté
+        if self.frame_floor > 0 {
+            return Err("await cannot suspend inside a callback".into());
+        }
+
+        self.schedule(&task);
+        let current = self.current_task();
+        current.borrow_mut().state = TaskState::Waiting;
+        task.borrow_mut().waiters.push(current);
+        self.next_task()
+    }
+
+    // Fin d'une fonction async (Return de sa dernière frame)
+    pub(super) fn finish_current(&mut self, result: Value) -> Result<bool, String> {
+        if let Some(task) = self.scheduler.current.clone() {
+            self.complete(&task, Ok(result));
+        }
+        self.next_task()
+    }
+
+    // La tâche courante est-elle une tâche async (et pas le script principal) ?
+    pub(super) fn in_task(&self) -> bool {
+        self.scheduler.current.as_ref().is_some_and(|t| !t.borrow().is_main())
+    }
+
+    // Erreur non rattrapée dans une tâche : elle termine la tâche, les autres continuent.
+    // Dans le script principal, l'erreur arrête le programme.
+    pub(super) fn recover(&mut self, mut error: String) -> Result<bool, String> {
+        while self.in_task() {
+            let task = self.scheduler.current.clone().expect("No current task");
+            self.complete(&task, Err(error));
+            self.error_line = None; // L'erreur est gardée par la tâche, sans sa ligne
+            match self.next_task().or_else(|e| self.throw(e)) {
+                Err(e) => error = e,
+                ok => return ok,
+            }
+        }
+        Err(error)
+    }
+
+    // Le script principal est arrivé au bout : la boucle termine les tâches en cours
+    pub(super) fn finish_main(&mut self) -> Result<bool, String> {
+        if let Some(main) = &self.scheduler.main {
+            main.borrow_mut().state = TaskState::Done;
+        }
+        self.next_task()
+    }
+
+    // Fin de la boucle : on signale les erreurs des tâches que personne n'a attendues,
+    // et le programme échoue s'il y en a eu
+    pub(super) fn finish_loop(&mut self) -> Result<(), String> {
+        self.scheduler.current = None;
+        self.scheduler.main = None;
+        let mut unhandled = 0;
+        for task in std::mem::take(&mut self.scheduler.failed) {
+            let t = task.borrow();
+            if let (false, Some(Err(e))) = (t.observed, &t.result) {
+                eprintln!("Unhandled error in task '{}': {}", t.name, e);
+                unhandled += 1;
+            }
+        }
+        match unhandled {
+            0 => Ok(()),
+            n => Err(format!("{} task(s) ended with an unhandled error", n)),
+        }
+    }
+
+    fn current_task(&mut self) -> Rc<RefCell<TaskData>> {
+        if let Some(task) = &self.scheduler.current {
+            return task.clone();
+        }
+        let main = Rc::new(RefCell::new(TaskData::new("<script>".to_string(), TaskKind::Main)));
+        main.borrow_mut().state = TaskState::Running;
+        self.scheduler.main = Some(main.clone());
+        self.scheduler.current = Some(main.clone());
+        main
+    }
+
+    // Lance une tâche qui ne l'est pas encore
+    fn schedule(&mut self, task: &Rc<RefCell<TaskData>>) {
+        let mut t = task.borrow_mut();
+        if t.state != TaskState::New {
+            return;
+        }
+        t.state = TaskState::Scheduled;
+        match t.kind {
+            TaskKind::Coroutine | TaskKind::Main => self.scheduler.ready.push_back(task.clone()),
+            TaskKind::Timer(..) => self.scheduler.timers.push(task.clone()),
+            TaskKind::Poll(_) => self.scheduler.polls.push(task.clone()),
+        }
+    }
+
+    // Termine une tâche et réveille celles qui l'attendaient
+    fn complete(&mut self, task: &Rc<RefCell<TaskData>>, result: Result<Value, String>) {
+        let mut t = task.borrow_mut();
+        t.state = TaskState::Done;
+        let waiters = std::mem::take(&mut t.waiters);
+        if !waiters.is_empty() {
+            t.observed = true;
+        } else if result.is_err() {
+            self.scheduler.failed.push(task.clone());
+        }
+        t.result = Some(result.clone());
+        drop(t);
+
+        for waiter in waiters {
+            let mut w = waiter.borrow_mut();
+            w.resume = Some(result.clone());
+            w.state = TaskState::Scheduled;
+            drop(w);
+            self.scheduler.ready.push_back(waiter);
+        }
+    }
+
+    // Donne la main à la prochaine tâche prête, en attendant minuteurs et E/S si besoin.
+    // Ok(false) : plus rien à exécuter, le programme est terminé.
+    fn next_task(&mut self) -> Result<bool, String> {
+        loop {
+            for task in SPAWNED.with(|spawned| std::mem::take(&mut *spawned.borrow_mut())) {
+                self.schedule(&task);
+            }
+
+            if let Some(task) = self.scheduler.ready.pop_front() {
+                return self.switch_to(task);
+            }
+
+            if self.scheduler.timers.is_empty() && self.scheduler.polls.is_empty() {
+                let main = self.scheduler.main.clone();
+                if let Some(main) = main.filter(|m| m.borrow().state == TaskState::Waiting) {
+                    // Le script attend une tâche qui ne pourra jamais se terminer
+                    main.borrow_mut().resume = Some(Err("Deadlock: every task is waiting for another one".into()));
+                    return self.switch_to(main);
+                }
+                return Ok(false);
+            }
+
+            if !self.poll_events() {
+                self.wait_for_events()?;
+            }
+        }
+    }
+
+    // Minuteurs échus et E/S terminées ; renvoie true si une tâche a été réveillée
+    fn poll_events(&mut self) -> bool {
+        let now = Instant::now();
+        let mut woke = false;
+
+        for task in std::mem::take(&mut self.scheduler.timers) {
+            let fired = matches!(task.borrow().kind, TaskKind::Timer(deadline, _) if deadline <= now);
+            if !fired {
+                self.scheduler.timers.push(task);
+                continue;
+            }
+            woke = true;
+            let callback = match &mut task.borrow_mut().kind {
+                TaskKind::Timer(_, callback) => callback.take(),
+                _ => None,
+            };
+            match callback {
+                None => self.complete(&task, Ok(Value::Null)),
+                // Le callback s'exécute comme une fonction async dans la tâche du minuteur
+                Some(callback) => {
+                    let function = trampoline();
+                    let mut t = task.borrow_mut();
+                    t.kind = TaskKind::Coroutine;
+                    t.stack = vec![Value::Function(function.clone()), callback];
+                    t.frames = vec![CallFrame {
+                        closure: Value::Function(function),
+                        ip: 0,
+                        slot_offset: 1,
+                        class_context: None,
+                        match_bases: Vec::new(),
+                        missing_args: 0,
+                        named_rest: None,
+                        generator: None,
+                    }];
+                    drop(t);
+                    self.scheduler.ready.push_back(task);
+                },
+            }
+        }
+
+        for task in std::mem::take(&mut self.scheduler.polls) {
+            let result = match &mut task.borrow_mut().kind {
+                TaskKind::Poll(poll) => poll(),
+                _ => None,
+            };
+            match result {
+                Some(result) => {
+                    woke = true;
+                    self.complete(&task, result);
+                },
+                None => self.scheduler.polls.push(task),
+            }
+        }
+
+        woke
+    }
+
+    // Rien n'est prêt : on attend un événement du réacteur, ou le prochain minuteur
+    fn wait_for_events(&self) -> Result<(), String> {
+        let now = Instant::now();
+        let next_timer = self.scheduler.timers.iter().filter_map(|task| match task.borrow().kind {
+            TaskKind::Timer(deadline, _) => Some(deadline.saturating_duration_since(now)),
+            _ => None,
+        }).min();
+
+        if self.scheduler.polls.is_empty() {
+            // Que des minuteurs (la boucle s'arrête quand il n'y a plus rien)
+            std::thread::sleep(next_timer.unwrap_or_default());
+            return Ok(());
+        }
+        wait_io(next_timer)
+    }
+
+    // Range le contexte de la tâche courante et installe celui de `task`
+    fn switch_to(&mut self, task: Rc<RefCell<TaskData>>) -> Result<bool, String> {
+        let current = self.current_task();
+        {
+            let mut c = current.borrow_mut();
+            if c.state == TaskState::Done {
+                // Son contexte ne servira plus
+                self.close_upvalues(0);
+                self.frames.clear();
+                self.stack.clear();
+                self.handlers.clear();
+            } else {
+                c.upvalues = self.detach_upvalues(0);
+                c.frames = std::mem::take(&mut self.frames);
+                c.stack = std::mem::take(&mut self.stack);
+                c.handlers = std::mem::take(&mut self.handlers);
+            }
+        }
+
+        let mut t = task.borrow_mut();
+        t.state = TaskState::Running;
+        self.frames = std::mem::take(&mut t.frames);
+        self.stack = std::mem::take(&mut t.stack);
+        self.handlers = std::mem::take(&mut t.handlers);
+        let upvalues = std::mem::take(&mut t.upvalues);
+        self.attach_upvalues(0, upvalues);
+        let resume = t.resume.take();
+        drop(t);
+        self.scheduler.current = Some(task);
+
+        // L'await qui a suspendu la tâche renvoie le résultat attendu (ou lève son erreur)
+        match resume {
+            Some(Ok(value)) => self.push(value),
+            Some(Err(e)) => return Err(e),
+            None => {}
+        }
+        Ok(true)
+    }
+}
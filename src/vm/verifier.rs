@@ -0,0 +1,385 @@
+//! Vérification d'un chunk avant exécution (bytecode relu d'un fichier .aegc ou du cache des modules).
+//!
+//! La VM fait confiance au bytecode qu'elle exécute : un opcode inconnu, un index hors limites
+//! ou une pile déséquilibrée la feraient paniquer en plein milieu du programme.
+//! Le vérificateur refuse ces chunks avant la première instruction :
+//! - opcodes valides, opérandes complets ;
+//! - index de constantes, de caches et de globales dans les bornes ;
+//! - sauts qui tombent au début d'une instruction ;
+//! - hauteur de pile, `match` et `try` ouverts connus et identiques sur tous les chemins qui mènent à une instruction ;
+//! - upvalues et variables capturées par chaque closure qui existent dans la fonction qui la crée.
+
+use crate::ast::value::Capture;
+use crate::ast::Value;
+use crate::chunk::Chunk;
+use crate::opcode::OpCode;
+
+/// Vérifie le chunk principal (et, récursivement, les fonctions de ses constantes).
+/// `globals` est la taille de la table des globales que le bytecode désigne.
+pub fn verify(chunk: &Chunk, globals: usize) -> Result<(), String> {
+    verify_function("<script>", chunk, 0, 0, globals)
+}
+
+// `upvalues` : nombre de variables que la fonction capture (ses GetUpvalue/SetUpvalue restent en dessous)
+fn verify_function(name: &str, chunk: &Chunk, params: usize, upvalues: usize, globals: usize) -> Result<(), String> {
+    let context = |e: String| format!("Invalid bytecode in '{}': {}", name, e);
+    let instructions = decode(chunk, globals, upvalues).map_err(context)?;
+    check_stack(&instructions, chunk, params, upvalues).map_err(context)?;
+
+    for constant in &chunk.constants {
+        verify_constant(constant, globals)?;
+    }
+    Ok(())
+}
+
+// Fonctions portées par une constante : corps des fonctions, méthodes et initialiseurs des classes et des enums
+fn verify_constant(value: &Value, globals: usize) -> Result<(), String> {
+    match value {
+        Value::Function(f) => verify_function(&f.name, &f.chunk, f.params.len(), f.captures.len(), globals),
+        Value::Class(c) => {
+            let properties = c.properties.values().chain(c.static_properties.values())
+                .flat_map(|p| p.getter.iter().chain(p.setter.iter()));
+            for member in c.methods.values().chain(c.fields.values()).chain(c.static_methods.values()).chain(properties) {
+                verify_member(member, globals)?;
+            }
+            for init in c.static_fields.borrow().values() {
+                verify_member(init, globals)?;
+            }
+            Ok(())
+        }
+        Value::Enum(e) => e.methods.values().try_for_each(|m| verify_member(m, globals)),
+        _ => Ok(()),
+    }
+}
+
+// Les membres des classes et des enums sont appelés tels quels, sans MakeClosure : ils ne capturent rien
+fn verify_member(value: &Value, globals: usize) -> Result<(), String> {
+    if let Value::Function(f) = value
+        && !f.captures.is_empty()
+    {
+        return Err(format!("Invalid bytecode in '{}': a method or field initializer cannot capture variables", f.name));
+    }
+    verify_constant(value, globals)
+}
+
+// Instruction décodée : opérandes (l'adresse absolue de la cible pour un saut) et adresse suivante
+struct Instr {
+    at: usize,
+    op: OpCode,
+    operands: [usize; 3],
+    next: usize,
+}
+
+fn decode(chunk: &Chunk, globals: usize, upvalues: usize) -> Result<Vec<Instr>, String> {
+    let code = &chunk.code;
+    if chunk.lines.len() != code.len() {
+        return Err(format!("{} line entries for {} bytes of code", chunk.lines.len(), code.len()));
+    }
+
+    let mut instructions = Vec::new();
+    let mut at = 0;
+    while at < code.len() {
+        let start = at;
+        let mut width = 1;
+        let mut op = OpCode::try_from(code[at]).map_err(|b| format!("unknown opcode {} at {:04}", b, at))?;
+        if matches!(op, OpCode::Wide) {
+            at += 1;
+            let next = code.get(at).ok_or_else(|| format!("WIDE at {:04} is the last byte", start))?;
+            op = OpCode::try_from(*next).map_err(|b| format!("unknown opcode {} at {:04}", b, at))?;
+            if op.operand_count() == 0 {
+                return Err(format!("WIDE at {:04} before {:?}, which has no operand", start, op));
+            }
+            width = 4;
+        }
+
+        let mut operands = [0; 3];
+        let mut next = at + 1;
+        if op.is_jump() {
+            let bytes = code.get(next..next + 2).ok_or_else(|| format!("truncated {:?} at {:04}", op, start))?;
+            let offset = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+            next += 2;
+            operands[0] = if matches!(op, OpCode::Loop) {
+                next.checked_sub(offset).ok_or_else(|| format!("{:?} at {:04} jumps before the start of the code", op, start))?
+            } else {
+                next + offset
+            };
+        } else {
+            for operand in operands.iter_mut().take(op.operand_count()) {
+                let bytes = code.get(next..next + width).ok_or_else(|| format!("truncated {:?} at {:04}", op, start))?;
+                *operand = bytes.iter().fold(0, |acc, &b| (acc << 8) | b as usize);
+                next += width;
+            }
+        }
+
+        check_operands(chunk, globals, upvalues, op, &operands).map_err(|e| format!("{:?} at {:04}: {}", op, start, e))?;
+        instructions.push(Instr { at: start, op, operands, next });
+        at = next;
+    }
+    Ok(instructions)
+}
+
+fn check_operands(chunk: &Chunk, globals: usize, upvalues: usize, op: OpCode, operands: &[usize; 3]) -> Result<(), String> {
+    let constant = |idx: usize| match chunk.constants.get(idx) {
+        Some(value) => Ok(value),
+        None => Err(format!("constant {} out of range ({} constants)", idx, chunk.constants.len())),
+    };
+    let cache = |idx: usize| match idx < chunk.caches.len() {
+        true => Ok(()),
+        false => Err(format!("cache {} out of range ({} caches)", idx, chunk.caches.len())),
+    };
+
+    match op {
+        OpCode::LoadConst | OpCode::CheckType | OpCode::MatchKey => {
+            constant(operands[0])?;
+        }
+        OpCode::Import => {
+            constant(operands[0])?;
+            let is_mode = matches!(constant(operands[1])?, Value::Null | Value::List(_));
+            if !is_mode {
+                return Err("the import mode is neither null nor a list of names".into());
+            }
+            let is_module = matches!(constant(operands[2])?, Value::Null | Value::String(_));
+            if !is_module {
+                return Err("the importing module is neither null nor a module identity".into());
+            }
+        }
+        OpCode::Class => {
+            let is_class = matches!(constant(operands[0])?, Value::Class(_));
+            if !is_class {
+                return Err("the constant is not a class".into());
+            }
+        }
+        OpCode::CallNames | OpCode::SpreadArgs => {
+            let is_list = matches!(constant(operands[0])?, Value::List(_));
+            if !is_list {
+                return Err("the constant is not a list".into());
+            }
+        }
+        OpCode::GetAttr | OpCode::SetAttr => {
+            constant(operands[0])?;
+            cache(operands[1])?;
+        }
+        OpCode::Method => {
+            constant(operands[0])?;
+            cache(operands[2])?;
+        }
+        OpCode::Super => {
+            constant(operands[0])?;
+            constant(operands[2])?;
+        }
+        OpCode::GetGlobal | OpCode::SetGlobal | OpCode::GetFreeGlobal if operands[0] >= globals => {
+            return Err(format!("global {} out of range ({} globals)", operands[0], globals));
+        }
+        OpCode::GetUpvalue | OpCode::SetUpvalue if operands[0] >= upvalues => {
+            return Err(format!("upvalue {} out of range ({} captured variables)", operands[0], upvalues));
+        }
+        OpCode::MakeDict if !operands[0].is_multiple_of(2) => {
+            return Err(format!("odd number of keys and values ({})", operands[0]));
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+// Pile vue depuis la frame : hauteur au-dessus du premier paramètre, bases des `match` en cours
+// et nombre de `try` ouverts (leurs handlers sont retirés par PopExcept, un throw ou le retour de la frame)
+#[derive(Clone, PartialEq)]
+struct State {
+    depth: usize,
+    matches: Vec<usize>,
+    tries: usize,
+}
+
+// Parcours de tous les chemins : chaque instruction atteinte reçoit une hauteur de pile,
+// qui doit être la même quel que soit le chemin
+fn check_stack(instructions: &[Instr], chunk: &Chunk, params: usize, upvalues: usize) -> Result<(), String> {
+    let len = chunk.code.len();
+    let index_of = |target: usize, from: &Instr| -> Result<Option<usize>, String> {
+        if target == len {
+            return Ok(None); // Fin du code : retour implicite de la frame
+        }
+        instructions.binary_search_by_key(&target, |i| i.at)
+            .map(Some)
+            .map_err(|_| format!("{:?} at {:04} jumps to {:04}, which is not the start of an instruction", from.op, from.at, target))
+    };
+
+    // MakeClosure lit les captures de la fonction chargée juste avant : il ne peut pas être la cible d'un saut
+    let mut targets = vec![false; instructions.len()];
+    for instr in instructions.iter().filter(|i| i.op.is_jump()) {
+        if let Some(idx) = index_of(instr.operands[0], instr)? {
+            targets[idx] = true;
+        }
+    }
+
+    let mut states: Vec<Option<State>> = vec![None; instructions.len()];
+    let mut pending = Vec::new();
+    if !instructions.is_empty() {
+        enter(&mut states, &mut pending, instructions, Some(0), State { depth: params, matches: Vec::new(), tries: 0 })?;
+    }
+
+    while let Some(idx) = pending.pop() {
+        let instr = &instructions[idx];
+        let mut state = states[idx].clone().expect("pending instruction without a state");
+        let [a, b, _] = instr.operands;
+        let underflow = || format!("{:?} at {:04} pops more values than the frame holds", instr.op, instr.at);
+        let pop = |state: &mut State, n: usize| -> Result<(), String> {
+            state.depth = state.depth.checked_sub(n).ok_or_else(underflow)?;
+            Ok(())
+        };
+        let next = if instr.next == len { None } else { Some(idx + 1) };
+
+        match instr.op {
+            OpCode::Return | OpCode::Throw => {
+                pop(&mut state, 1)?;
+                continue;
+            }
+            OpCode::Jump | OpCode::Loop => {
+                enter(&mut states, &mut pending, instructions, index_of(a, instr)?, state)?;
+                continue;
+            }
+            OpCode::JumpIfFalse | OpCode::IterNext => {
+                // La condition (ou l'élément suivant, null en fin d'itération) reste sur la pile
+                pop(&mut state, 1)?;
+                state.depth += 1;
+                enter(&mut states, &mut pending, instructions, index_of(a, instr)?, state.clone())?;
+            }
+            OpCode::SetupExcept => {
+                // Le catch reprend à la hauteur du try, plus le message d'erreur (le handler est déjà retiré)
+                let mut handler = state.clone();
+                handler.depth += 1;
+                enter(&mut states, &mut pending, instructions, index_of(a, instr)?, handler)?;
+                state.tries += 1;
+            }
+            OpCode::PopExcept => {
+                state.tries = state.tries.checked_sub(1)
+                    .ok_or_else(|| format!("POP_EXCEPT at {:04} closes a try that is not open", instr.at))?;
+            }
+            OpCode::LoadConst => {
+                // Une fonction qui capture des variables n'a ses upvalues qu'une fois passée par MakeClosure
+                let captures = match &chunk.constants[a] {
+                    Value::Function(f) => !f.captures.is_empty(),
+                    _ => false,
+                };
+                let closed = instructions.get(idx + 1).is_some_and(|i| matches!(i.op, OpCode::MakeClosure));
+                if captures && !closed {
+                    return Err(format!("LOAD_CONST at {:04} loads a function that captures variables without MAKE_CLOSURE", instr.at));
+                }
+                state.depth += 1;
+            }
+            OpCode::MakeClosure => {
+                let function = idx.checked_sub(1)
+                    .filter(|_| !targets[idx])
+                    .map(|prev| &instructions[prev])
+                    .filter(|prev| matches!(prev.op, OpCode::LoadConst))
+                    .and_then(|prev| match &chunk.constants[prev.operands[0]] {
+                        Value::Function(f) => Some(f),
+                        _ => None,
+                    })
+                    .ok_or_else(|| format!("MAKE_CLOSURE at {:04} does not directly follow the LOAD_CONST of a function", instr.at))?;
+                // La fonction est au sommet : une locale capturée est en dessous, ou à sa place (fonction récursive)
+                for capture in &function.captures {
+                    let valid = match *capture {
+                        Capture::Local(slot) => slot < state.depth,
+                        Capture::Match(level, slot) => state.matches.get(level).is_some_and(|base| base + slot + 1 < state.depth),
+                        Capture::Upvalue(index) => index < upvalues,
+                    };
+                    if !valid {
+                        return Err(format!("MAKE_CLOSURE at {:04} captures {:?}, which does not exist here", instr.at, capture));
+                    }
+                }
+                pop(&mut state, 1)?;
+                state.depth += 1;
+            }
+            OpCode::GetLocal | OpCode::SetLocal => {
+                if a >= state.depth {
+                    return Err(format!("{:?} at {:04} uses slot {} with only {} values in the frame", instr.op, instr.at, a, state.depth));
+                }
+                if matches!(instr.op, OpCode::GetLocal) {
+                    state.depth += 1;
+                }
+            }
+            OpCode::MatchBegin => {
+                if a > state.matches.len() || state.depth == 0 {
+                    return Err(format!("MATCH_BEGIN at {:04} opens level {} out of order", instr.at, a));
+                }
+                state.matches.truncate(a);
+                state.matches.push(state.depth - 1);
+            }
+            OpCode::GetMatch => {
+                let base = *state.matches.get(a).ok_or_else(|| format!("GET_MATCH at {:04} reads match level {}, which is not open", instr.at, a))?;
+                if base + b >= state.depth {
+                    return Err(format!("GET_MATCH at {:04} reads slot {} past the top of the stack", instr.at, b));
+                }
+                state.depth += 1;
+            }
+            OpCode::MatchEnd => {
+                let base = *state.matches.get(a).ok_or_else(|| format!("MATCH_END at {:04} closes match level {}, which is not open", instr.at, a))?;
+                if state.depth <= base {
+                    return Err(underflow());
+                }
+                // Le résultat remplace la valeur testée et les variables liées
+                state.matches.truncate(a);
+                state.depth = base + 1;
+            }
+            OpCode::SpreadArgs => {
+                // Les listes dépliées changent le nombre d'arguments lu par l'appel qui suit, pas la pile écrite
+                if b > state.depth {
+                    return Err(underflow());
+                }
+            }
+            op => {
+                let (pops, pushes) = stack_effect(op, &instr.operands);
+                pop(&mut state, pops)?;
+                state.depth += pushes;
+            }
+        }
+
+        enter(&mut states, &mut pending, instructions, next, state)?;
+    }
+    Ok(())
+}
+
+// Première visite : l'instruction reçoit la hauteur du chemin. Visite suivante : elle doit être identique.
+fn enter(states: &mut [Option<State>], pending: &mut Vec<usize>, instructions: &[Instr], idx: Option<usize>, state: State) -> Result<(), String> {
+    let Some(idx) = idx else { return Ok(()) };
+    match &states[idx] {
+        None => {
+            states[idx] = Some(state);
+            pending.push(idx);
+            Ok(())
+        }
+        Some(known) if *known == state => Ok(()),
+        Some(known) if known.tries != state.tries => Err(format!(
+            "{} open try block(s) at {:04} on one path and {} on another",
+            known.tries, instructions[idx].at, state.tries
+        )),
+        Some(known) => Err(format!(
+            "stack height at {:04} is {} on one path and {} on another",
+            instructions[idx].at, known.depth, state.depth
+        )),
+    }
+}
+
+// Valeurs retirées puis ajoutées par les instructions sans saut ni effet particulier sur la frame
+fn stack_effect(op: OpCode, operands: &[usize; 3]) -> (usize, usize) {
+    match op {
+        OpCode::GetGlobal | OpCode::GetFreeGlobal | OpCode::GetUpvalue
+        | OpCode::Class | OpCode::Import | OpCode::ArgMissing => (0, 1),
+        OpCode::SetGlobal | OpCode::Pop | OpCode::Print | OpCode::CloseUpvalue | OpCode::Yield => (1, 0),
+        OpCode::Add | OpCode::Sub | OpCode::Mul | OpCode::Div | OpCode::Modulo
+        | OpCode::Equal | OpCode::NotEqual | OpCode::Greater | OpCode::GreaterEqual | OpCode::Less | OpCode::LessEqual
+        | OpCode::BitAnd | OpCode::BitOr | OpCode::BitXor | OpCode::ShiftLeft | OpCode::ShiftRight
+        | OpCode::SetAttr | OpCode::GetIndex | OpCode::MakeRange | OpCode::MatchClass => (2, 1),
+        OpCode::Not | OpCode::GetAttr | OpCode::Input | OpCode::SetUpvalue
+        | OpCode::CheckType | OpCode::MatchList | OpCode::MatchKey | OpCode::GetField
+        | OpCode::GetIter | OpCode::Await => (1, 1),
+        OpCode::SetIndex | OpCode::Slice | OpCode::MatchRange => (3, 1),
+        OpCode::Dup => (1, 2),
+        OpCode::Dup2 => (2, 4),
+        OpCode::MakeList | OpCode::MakeDict => (operands[0], 1),
+        // La fonction (ou l'objet) et les arguments écrits sont remplacés par le résultat
+        OpCode::Call => (operands[0] + 1, 1),
+        OpCode::Method | OpCode::Super => (operands[1] + 1, 1),
+        _ => (0, 0),
+    }
+}